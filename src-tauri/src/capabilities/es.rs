@@ -8,6 +8,8 @@ use data_studio_agent::capabilities::types::{
     Capability, CapabilityHandler, RiskLevel, SourceKind,
 };
 
+use crate::common::es::EsFlavor;
+
 // ---------------------------------------------------------------------------
 // ES capability handlers
 // ---------------------------------------------------------------------------
@@ -20,8 +22,29 @@ pub(crate) async fn execute_es_http(
     config: &Value,
     extra_root_certs: Option<Vec<reqwest::Certificate>>,
 ) -> Result<String, String> {
+    let result = execute_es_json(method, path, body, config, extra_root_certs).await?;
+    Ok(crate::common::format::truncate_tool_output(
+        result.to_string(),
+    ))
+}
+
+/// Like `execute_es_http`, but returns the untruncated `{status, data,
+/// message}` envelope so handlers can post-process large responses before
+/// they hit the tool output limit.
+pub(crate) async fn execute_es_json(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    config: &Value,
+    extra_root_certs: Option<Vec<reqwest::Certificate>>,
+) -> Result<Value, String> {
     let ssl = crate::common::es::get_es_ssl_flag(config);
-    let headers = crate::common::es::build_es_headers(config);
+    let mut headers = crate::common::es::build_es_headers(config);
+    let connection_tls = crate::common::es::es_connection_tls(config)?;
+    let server_name = connection_tls.server_name.clone();
+    let mut tls = connection_tls.http_options()?;
+    // Test servers pass their self-signed CA here on top of the connection's.
+    tls.root_certs.extend(extra_root_certs.unwrap_or_default());
     let tunnel_original_host = config
         .get("tunnelOriginalHost")
         .and_then(|v| v.as_str())
@@ -30,7 +53,8 @@ pub(crate) async fn execute_es_http(
     // Tunneled connections keep the original hostname for SNI / certificate
     // validation and only redirect TCP to the local tunnel endpoint via a
     // DNS override (issue #472). Proxy bypass ("none") so a system proxy
-    // cannot hijack tunnel traffic.
+    // cannot hijack tunnel traffic. A `tlsServerName` override takes the
+    // original hostname's place.
     let (base_url, client) = match tunnel_original_host {
         Some(original_host) => {
             let local_port = config
@@ -38,9 +62,12 @@ pub(crate) async fn execute_es_http(
                 .and_then(|v| v.as_u64())
                 .ok_or("Missing port in connection config")? as u16;
             let trimmed = original_host.trim();
-            let host = trimmed
-                .trim_start_matches("http://")
-                .trim_start_matches("https://");
+            let host = match server_name.as_deref() {
+                Some(name) => name,
+                None => trimmed
+                    .trim_start_matches("http://")
+                    .trim_start_matches("https://"),
+            };
             let protocol = if trimmed.starts_with("https://") {
                 "https"
             } else if trimmed.starts_with("http://") {
@@ -60,6 +87,11 @@ pub(crate) async fn execute_es_http(
                 .and_then(|v| v.as_str())
                 .filter(|s| !s.is_empty());
             let client = match socks5_proxy {
+                // The SOCKS5 proxy resolves the URL host remotely, so it
+                // cannot be swapped for a different TLS name.
+                Some(_) if server_name.is_some() => {
+                    return Err("tlsServerName is not supported with SOCKS5 tunnels".to_string())
+                }
                 // Socks5 mode: route through the local SOCKS5 proxy, TLS keeps
                 // the real hostname (no DNS override needed).
                 Some(proxy) => crate::common::http_client::create_http_client(
@@ -68,7 +100,7 @@ pub(crate) async fn execute_es_http(
                     Some(ssl),
                     None,
                     None,
-                    extra_root_certs,
+                    tls,
                 ),
                 None => crate::common::http_client::create_http_client(
                     "none",
@@ -79,26 +111,44 @@ pub(crate) async fn execute_es_http(
                         host.to_string(),
                         SocketAddr::from(([127, 0, 0, 1], local_port)),
                     )),
-                    extra_root_certs,
+                    tls,
                 ),
             };
             (base, client)
         }
         None => {
             let base = crate::common::es::build_es_base_url(config)?;
+            let (base, dns_override) = match server_name.as_deref() {
+                Some(name) => {
+                    let (url, dns_override) =
+                        crate::common::tls::server_name_override(&base, name).await?;
+                    (url.trim_end_matches('/').to_string(), Some(dns_override))
+                }
+                None => (base, None),
+            };
             let client = crate::common::http_client::create_http_client(
                 "system",
                 None,
                 Some(ssl),
                 None,
-                None,
-                extra_root_certs,
+                dns_override,
+                tls,
             );
             (base, client)
         }
     };
 
     let url = format!("{}{}", base_url, path);
+    if crate::common::es::es_uses_sigv4(config) {
+        crate::common::es::sign_es_request(
+            config,
+            method,
+            &url,
+            &mut headers,
+            body.unwrap_or("").as_bytes(),
+        )
+        .await?;
+    }
     let method =
         reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| format!("Bad method: {}", e))?;
 
@@ -137,9 +187,7 @@ pub(crate) async fn execute_es_http(
         result["message"] = serde_json::json!(message);
     }
 
-    Ok(crate::common::format::truncate_tool_output(
-        result.to_string(),
-    ))
+    Ok(result)
 }
 
 pub(crate) struct EsSearch;
@@ -166,6 +214,74 @@ pub(crate) struct EsCatNodes;
 pub(crate) struct EsCatShards;
 pub(crate) struct EsListSnapshots;
 pub(crate) struct EsRestoreSnapshot;
+pub(crate) struct EsListLifecyclePolicies;
+pub(crate) struct EsGetLifecyclePolicy;
+pub(crate) struct EsPutLifecyclePolicy;
+pub(crate) struct EsDeleteLifecyclePolicy;
+pub(crate) struct EsAttachLifecyclePolicy;
+pub(crate) struct EsExplainLifecycle;
+pub(crate) struct EsRetryLifecycle;
+pub(crate) struct EsListPipelines;
+pub(crate) struct EsGetPipeline;
+pub(crate) struct EsPutPipeline;
+pub(crate) struct EsDeletePipeline;
+pub(crate) struct EsSimulatePipeline;
+pub(crate) struct EsListSnapshotRepositories;
+pub(crate) struct EsGetSnapshotRepository;
+pub(crate) struct EsPutSnapshotRepository;
+pub(crate) struct EsDeleteSnapshotRepository;
+pub(crate) struct EsVerifySnapshotRepository;
+pub(crate) struct EsCreateSnapshot;
+pub(crate) struct EsSnapshotStatus;
+pub(crate) struct EsDeleteSnapshot;
+pub(crate) struct EsListTasks;
+pub(crate) struct EsGetTask;
+pub(crate) struct EsCancelTask;
+pub(crate) struct EsRethrottle;
+pub(crate) struct EsUpdateByQuery;
+pub(crate) struct EsMultiSearch;
+pub(crate) struct EsProfileSearch;
+pub(crate) struct EsExplainDocument;
+pub(crate) struct EsAnalyze;
+pub(crate) struct EsGetIndexSettings;
+pub(crate) struct EsPutIndexSettings;
+pub(crate) struct EsOpenIndex;
+pub(crate) struct EsCloseIndex;
+pub(crate) struct EsRollover;
+pub(crate) struct EsShrinkIndex;
+pub(crate) struct EsSplitIndex;
+pub(crate) struct EsCloneIndex;
+pub(crate) struct EsForceMerge;
+pub(crate) struct EsRefreshIndex;
+pub(crate) struct EsFlushIndex;
+pub(crate) struct EsClearCache;
+pub(crate) struct EsListDataStreams;
+pub(crate) struct EsCreateDataStream;
+pub(crate) struct EsDeleteDataStream;
+pub(crate) struct EsRolloverDataStream;
+pub(crate) struct EsDataStreamStats;
+pub(crate) struct EsAllocationExplain;
+pub(crate) struct EsNodesStats;
+pub(crate) struct EsHotThreads;
+pub(crate) struct EsPendingTasks;
+pub(crate) struct EsCatThreadPool;
+pub(crate) struct EsCatRecovery;
+pub(crate) struct EsDiagnoseCluster;
+pub(crate) struct EsListUsers;
+pub(crate) struct EsPutUser;
+pub(crate) struct EsDeleteUser;
+pub(crate) struct EsListRoles;
+pub(crate) struct EsPutRole;
+pub(crate) struct EsDeleteRole;
+pub(crate) struct EsListRoleMappings;
+pub(crate) struct EsPutRoleMapping;
+pub(crate) struct EsDeleteRoleMapping;
+pub(crate) struct EsCreateApiKey;
+pub(crate) struct EsListApiKeys;
+pub(crate) struct EsInvalidateApiKey;
+pub(crate) struct EsHasPrivileges;
+pub(crate) struct EsKnnSearch;
+pub(crate) struct EsCopyIndex;
 
 macro_rules! impl_es_handler {
     ($struct:ty, $method:expr, $path_fn:expr, $has_body:expr) => {
@@ -191,8 +307,37 @@ macro_rules! impl_es_handler {
     };
 }
 
+/// `(method, path, body)` of a request built by a flavored handler.
+type EsRequest = (&'static str, String, Option<String>);
+
+/// Like `impl_es_handler!`, but the request is built from the args and the
+/// connection's cluster flavor, for APIs that live under different paths
+/// (and sometimes take different bodies) on Elasticsearch and OpenSearch.
+macro_rules! impl_es_flavored_handler {
+    ($struct:ty, $request_fn:expr) => {
+        #[async_trait::async_trait]
+        impl CapabilityHandler for $struct {
+            async fn handle(
+                &self,
+                args: &Value,
+                connection_config: Option<&Value>,
+            ) -> Result<String, String> {
+                let config = connection_config
+                    .ok_or_else(|| "ES requires a connection config".to_string())?;
+                let flavor = EsFlavor::from_config(config);
+                let request_builder = $request_fn;
+                let (method, path, body): EsRequest = request_builder(args, flavor)?;
+                execute_es_http(method, &path, body.as_deref(), config, None).await
+            }
+        }
+    };
+}
+
 // ---- Handlers ----
 
+/// Values accepted by the `expand_wildcards` search parameter.
+const EXPAND_WILDCARDS: &[&str] = &["open", "closed", "hidden", "all", "none"];
+
 impl_es_handler!(
     EsSearch,
     "POST",
@@ -201,11 +346,26 @@ impl_es_handler!(
             .get("index")
             .and_then(|v| v.as_str())
             .ok_or_else(|| "Missing index".to_string())?;
-        crate::common::validation::validate_index_name(index, true)?;
-        Ok(format!(
+        crate::common::validation::validate_search_targets(index)?;
+        let mut path = format!(
             "/{}/_search",
             crate::common::validation::url_encode_segment(index)
-        ))
+        );
+        if let Some(expand) = args.get("expand_wildcards").and_then(|v| v.as_str()) {
+            if let Some(bad) = expand
+                .split(',')
+                .find(|v| !EXPAND_WILDCARDS.contains(&v.trim()))
+            {
+                return Err(format!(
+                    "Invalid expand_wildcards value '{}': expected one of {}",
+                    bad,
+                    EXPAND_WILDCARDS.join(", ")
+                ));
+            }
+            path.push_str("?expand_wildcards=");
+            path.push_str(&crate::common::validation::url_encode_segment(expand));
+        }
+        Ok(path)
     },
     true
 );
@@ -310,8 +470,9 @@ impl_es_handler!(
             .ok_or_else(|| "Missing index".to_string())?;
         crate::common::validation::validate_index_name(index, false)?;
         Ok(format!(
-            "/{}/_delete_by_query",
-            crate::common::validation::url_encode_segment(index)
+            "/{}/_delete_by_query{}",
+            crate::common::validation::url_encode_segment(index),
+            wait_for_completion_query(args)
         ))
     },
     true
@@ -580,28 +741,47 @@ impl_es_handler!(
 impl_es_handler!(
     EsReindex,
     "POST",
-    |_args: &Value| -> Result<String, String> { Ok("/_reindex".to_string()) },
+    |args: &Value| -> Result<String, String> {
+        Ok(format!("/_reindex{}", wait_for_completion_query(args)))
+    },
     true
 );
 
+const CLUSTER_HEALTH_PATH: &str = "/_cluster/health";
+const CAT_NODES_PATH: &str = "/_cat/nodes?format=json&bytes=b";
+
+/// `_cat/shards` as JSON, optionally narrowed to one (already encoded) index
+/// and to a set of columns.
+fn cat_shards_path(index: Option<&str>, columns: Option<&str>) -> String {
+    let mut path = match index {
+        Some(index) => format!("/_cat/shards/{}?format=json", index),
+        None => "/_cat/shards?format=json".to_string(),
+    };
+    if let Some(columns) = columns {
+        path.push_str("&h=");
+        path.push_str(columns);
+    }
+    path
+}
+
 impl_es_handler!(
     EsClusterHealth,
     "GET",
-    |_args: &Value| -> Result<String, String> { Ok("/_cluster/health".to_string()) },
+    |_args: &Value| -> Result<String, String> { Ok(CLUSTER_HEALTH_PATH.to_string()) },
     false
 );
 
 impl_es_handler!(
     EsCatNodes,
     "GET",
-    |_args: &Value| -> Result<String, String> { Ok("/_cat/nodes?format=json&bytes=b".to_string()) },
+    |_args: &Value| -> Result<String, String> { Ok(CAT_NODES_PATH.to_string()) },
     false
 );
 
 impl_es_handler!(
     EsCatShards,
     "GET",
-    |_args: &Value| -> Result<String, String> { Ok("/_cat/shards?format=json".to_string()) },
+    |_args: &Value| -> Result<String, String> { Ok(cat_shards_path(None, None)) },
     false
 );
 
@@ -633,208 +813,3606 @@ impl_es_handler!(
     true
 );
 
-// ---------------------------------------------------------------------------
-// Registration
-// ---------------------------------------------------------------------------
+// ---- Lifecycle policies (ILM on Elasticsearch, ISM on OpenSearch) ----
 
-pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
-    // Each entry: (name, description, json_schema_type, is_required).
-    // json_schema_type is the OpenAPI type string ("string", "object", "integer", etc.).
-    let es_schema = |props: &[(&str, &str, &str, bool)]| -> Value {
-        let mut properties = serde_json::Map::new();
-        properties.insert(
-            "connection_id".to_string(),
-            serde_json::json!({"type": "string", "description": "ID of the target connection from the session"}),
-        );
-        for (name, desc, type_str, _required) in props {
-            properties.insert(
-                name.to_string(),
-                serde_json::json!({"type": type_str, "description": desc}),
-            );
-        }
-        let required: Vec<String> = std::iter::once("connection_id".to_string())
-            .chain(
-                props
-                    .iter()
-                    .filter(|(_, _, _, r)| *r)
-                    .map(|(n, _, _, _)| n.to_string()),
-            )
-            .collect();
-        serde_json::json!({
-            "type": "object",
-            "properties": properties,
-            "required": required,
-        })
+fn lifecycle_policy_path(flavor: EsFlavor, policy: Option<&str>) -> String {
+    let base = match flavor {
+        EsFlavor::Elasticsearch => "/_ilm/policy",
+        EsFlavor::OpenSearch => "/_plugins/_ism/policies",
     };
-
-    macro_rules! reg {
-        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr, $parallel_ok:expr) => {
-            registry.register(Capability {
-                name: $name,
-                description: $desc,
-                handler: Arc::new($handler),
-                input_schema: $schema,
-                risk_level: $risk,
-                required_permission: $perm,
-                source_kind: SourceKind::Database("ELASTICSEARCH"),
-                tags: $tags,
-                parallel_ok: $parallel_ok,
-            });
-        };
-        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr) => {
-            reg!($name, $desc, $handler, $schema, $risk, $perm, $tags, false)
-        };
+    match policy {
+        Some(policy) => format!(
+            "{}/{}",
+            base,
+            crate::common::validation::url_encode_segment(policy)
+        ),
+        None => base.to_string(),
     }
+}
 
-    reg!("es__search", "Execute an Elasticsearch search query using Query DSL and return matching documents with scores.\n\nUse when a task needs data from Elasticsearch/OpenSearch (document counts, content search, aggregations) — instead of shelling out to curl or a local client.\n\nExample: {\"index\": \"orders*\", \"body\": {\"query\": {\"match\": {\"status\": \"shipped\"}}}}.", EsSearch,
-         es_schema(&[("index", "Target index name", "string", true), ("body", "Elasticsearch Query DSL body", "object", true)]),
-         RiskLevel::Safe, "read", &["agent"], true);
+fn list_lifecycle_policies_request(_args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    Ok(("GET", lifecycle_policy_path(flavor, None), None))
+}
 
-    reg!(
-        "es__get_document",
-        "Get a single document by its ID from an Elasticsearch index.",
-        EsGetDocument,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            ("id", "Document ID", "string", true)
-        ]),
-        RiskLevel::Safe,
-        "read",
-        &["agent"],
-        true
-    );
+impl_es_flavored_handler!(EsListLifecyclePolicies, list_lifecycle_policies_request);
 
-    reg!(
-        "es__index_document",
-        "Create or replace a document in an Elasticsearch index. Omit id to auto-generate one.",
-        EsIndexDocument,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            (
-                "id",
-                "Optional document ID; omit to auto-generate",
-                "string",
-                false
-            ),
-            ("body", "Document body to index", "object", true)
-        ]),
-        RiskLevel::Elevated,
-        "create",
-        &["agent"]
-    );
+fn get_lifecycle_policy_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let policy = args
+        .get("policy")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing policy".to_string())?;
+    Ok(("GET", lifecycle_policy_path(flavor, Some(policy)), None))
+}
 
-    reg!(
-        "es__update_document",
-        "Partially update an existing document in an Elasticsearch index using the Update API.",
-        EsUpdateDocument,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            ("id", "Document ID to update", "string", true),
-            ("body", "Update body", "object", true)
-        ]),
-        RiskLevel::Elevated,
-        "update",
-        &["agent"]
-    );
+impl_es_flavored_handler!(EsGetLifecyclePolicy, get_lifecycle_policy_request);
 
-    reg!(
-        "es__delete_document",
-        "Delete a single document by ID from an Elasticsearch index.",
-        EsDeleteDocument,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            ("id", "Document ID to delete", "string", true)
-        ]),
-        RiskLevel::Destructive,
-        "delete",
-        &["agent"]
-    );
+fn put_lifecycle_policy_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let policy = args
+        .get("policy")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing policy".to_string())?;
+    let body = args.get("body").ok_or_else(|| "Missing body".to_string())?;
+    let mut path = lifecycle_policy_path(flavor, Some(policy));
+    // ISM rejects updates to an existing policy unless the caller proves
+    // it has seen the latest version via optimistic concurrency params.
+    if flavor == EsFlavor::OpenSearch {
+        if let (Some(seq_no), Some(primary_term)) = (
+            args.get("if_seq_no").and_then(|v| v.as_u64()),
+            args.get("if_primary_term").and_then(|v| v.as_u64()),
+        ) {
+            path = format!(
+                "{}?if_seq_no={}&if_primary_term={}",
+                path, seq_no, primary_term
+            );
+        }
+    }
+    Ok(("PUT", path, Some(body.to_string())))
+}
 
-    reg!(
-        "es__delete_by_query",
-        "Delete ALL documents matching a query. WARNING: bulk destructive operation.",
-        EsDeleteByQuery,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            (
-                "body",
-                "Query DSL to match documents for deletion",
-                "object",
-                true
-            )
-        ]),
-        RiskLevel::Destructive,
-        "delete",
-        &["agent"]
-    );
+impl_es_flavored_handler!(EsPutLifecyclePolicy, put_lifecycle_policy_request);
 
-    reg!("es__cat_indices", "List user indices with health status, document count, and storage size. Results are sorted alphabetically. System/hidden indices (starting with . or _) are ONLY included when the user explicitly asks for them — pass include_system=true. NEVER include system indices in routine listing. First step for any Elasticsearch task: list indices, then get_mapping to inspect structure, then search. Report results in the user's language (中文/English).", EsCatIndices,
-         es_schema(&[("include_system", "ONLY set to true when the user explicitly asks for system indices or hidden indices. Default false — system indices are excluded.", "boolean", false)]),
-         RiskLevel::Safe, "read", &["agent", "ui"]);
+fn delete_lifecycle_policy_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let policy = args
+        .get("policy")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing policy".to_string())?;
+    Ok(("DELETE", lifecycle_policy_path(flavor, Some(policy)), None))
+}
 
-    reg!("es__get_mapping", "Get the field mapping (schema) for an Elasticsearch index, showing field names and data types. Use this to understand an index's structure before querying. Report results in the user's language (中文/English).", EsGetMapping,
-         es_schema(&[("index", "Target index name", "string", true)]),
-         RiskLevel::Safe, "read", &["agent"], true);
+impl_es_flavored_handler!(EsDeleteLifecyclePolicy, delete_lifecycle_policy_request);
 
-    reg!(
-        "es__create_index",
-        "Create a new Elasticsearch index with optional custom mappings and settings.",
-        EsCreateIndex,
-        es_schema(&[
-            ("index", "Name for the new index", "string", true),
-            (
-                "body",
-                "Optional index body with settings and mappings",
-                "object",
-                false
-            )
-        ]),
-        RiskLevel::Elevated,
-        "create",
-        &["agent"]
-    );
+fn attach_lifecycle_policy_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let index = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing index".to_string())?;
+    crate::common::validation::validate_index_name(index, true)?;
+    let policy = args
+        .get("policy")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing policy".to_string())?;
+    let index = crate::common::validation::url_encode_segment(index);
+    match flavor {
+        // ILM is driven by the index.lifecycle.name setting.
+        EsFlavor::Elasticsearch => Ok((
+            "PUT",
+            format!("/{}/_settings", index),
+            Some(serde_json::json!({"index": {"lifecycle": {"name": policy}}}).to_string()),
+        )),
+        EsFlavor::OpenSearch => Ok((
+            "POST",
+            format!("/_plugins/_ism/add/{}", index),
+            Some(serde_json::json!({"policy_id": policy}).to_string()),
+        )),
+    }
+}
 
-    reg!("es__delete_index", "Delete an entire Elasticsearch index and all its data permanently. This action is IRREVERSIBLE.", EsDeleteIndex,
-         es_schema(&[("index", "Name of the index to delete", "string", true)]),
-         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+impl_es_flavored_handler!(EsAttachLifecyclePolicy, attach_lifecycle_policy_request);
 
-    reg!(
-        "es__put_mapping",
-        "Add or update field mappings in an existing Elasticsearch index.",
-        EsPutMapping,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            ("body", "Mapping body", "object", true)
-        ]),
-        RiskLevel::Elevated,
-        "update",
-        &["agent"]
-    );
+fn explain_lifecycle_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let index = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing index".to_string())?;
+    crate::common::validation::validate_index_name(index, true)?;
+    let index = crate::common::validation::url_encode_segment(index);
+    let only_errors = args
+        .get("only_errors")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    match flavor {
+        EsFlavor::Elasticsearch if only_errors => Ok((
+            "GET",
+            format!("/{}/_ilm/explain?only_errors=true", index),
+            None,
+        )),
+        EsFlavor::Elasticsearch => Ok(("GET", format!("/{}/_ilm/explain", index), None)),
+        EsFlavor::OpenSearch => Ok(("GET", format!("/_plugins/_ism/explain/{}", index), None)),
+    }
+}
 
-    reg!(
-        "es__cat_aliases",
-        "List all index aliases, their target indices, and routing configuration.",
-        EsCatAliases,
-        es_schema(&[]),
-        RiskLevel::Safe,
-        "read",
-        &["agent", "ui"]
-    );
+impl_es_flavored_handler!(EsExplainLifecycle, explain_lifecycle_request);
 
-    reg!(
-        "es__get_alias",
-        "Get the aliases defined on a specific index.",
-        EsGetAlias,
-        es_schema(&[("index", "Target index name", "string", true)]),
-        RiskLevel::Safe,
-        "read",
-        &["agent"]
-    );
+fn retry_lifecycle_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    let index = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing index".to_string())?;
+    crate::common::validation::validate_index_name(index, true)?;
+    let index = crate::common::validation::url_encode_segment(index);
+    match flavor {
+        EsFlavor::Elasticsearch => Ok(("POST", format!("/{}/_ilm/retry", index), None)),
+        // ISM can optionally resume from a named state instead of the failed one.
+        EsFlavor::OpenSearch => Ok((
+            "POST",
+            format!("/_plugins/_ism/retry/{}", index),
+            args.get("state")
+                .and_then(|v| v.as_str())
+                .map(|state| serde_json::json!({"state": state}).to_string()),
+        )),
+    }
+}
 
-    reg!(
-        "es__put_alias",
-        "Create or update an alias pointing to a specific index.",
-        EsPutAlias,
+impl_es_flavored_handler!(EsRetryLifecycle, retry_lifecycle_request);
+
+// ---- Ingest pipelines ----
+
+impl_es_handler!(
+    EsListPipelines,
+    "GET",
+    |_args: &Value| -> Result<String, String> { Ok("/_ingest/pipeline".to_string()) },
+    false
+);
+
+impl_es_handler!(
+    EsGetPipeline,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing id".to_string())?;
+        Ok(format!(
+            "/_ingest/pipeline/{}",
+            crate::common::validation::url_encode_segment(id)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsPutPipeline,
+    "PUT",
+    |args: &Value| -> Result<String, String> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing id".to_string())?;
+        if args.get("body").is_none() {
+            return Err("Missing body".to_string());
+        }
+        Ok(format!(
+            "/_ingest/pipeline/{}",
+            crate::common::validation::url_encode_segment(id)
+        ))
+    },
+    true
+);
+
+impl_es_handler!(
+    EsDeletePipeline,
+    "DELETE",
+    |args: &Value| -> Result<String, String> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing id".to_string())?;
+        Ok(format!(
+            "/_ingest/pipeline/{}",
+            crate::common::validation::url_encode_segment(id)
+        ))
+    },
+    false
+);
+
+const DEFAULT_SIMULATE_SAMPLE_SIZE: u64 = 5;
+const MAX_SIMULATE_SAMPLE_SIZE: u64 = 100;
+
+/// Wrap bare sources as `{"_source": ...}`; documents that already carry
+/// `_source` (e.g. search hits) keep their `_index`/`_id` metadata.
+fn to_simulate_docs(docs: &[Value]) -> Vec<Value> {
+    docs.iter()
+        .map(|doc| {
+            if doc.get("_source").is_some() {
+                let mut wrapped = serde_json::Map::new();
+                for key in ["_index", "_id", "_routing", "_source"] {
+                    if let Some(v) = doc.get(key) {
+                        wrapped.insert(key.to_string(), v.clone());
+                    }
+                }
+                Value::Object(wrapped)
+            } else {
+                serde_json::json!({"_source": doc})
+            }
+        })
+        .collect()
+}
+
+/// Pull sample documents for a pipeline simulation from an index. The
+/// search goes through `execute_es_json` rather than `es__search`, whose
+/// output cap would cut large documents short.
+async fn sample_docs_from_index(args: &Value, config: &Value) -> Result<Vec<Value>, String> {
+    let index = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Provide either docs or an index to sample documents from".to_string())?;
+    crate::common::validation::validate_search_targets(index)?;
+    let size = args
+        .get("sample_size")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_SIMULATE_SAMPLE_SIZE)
+        .clamp(1, MAX_SIMULATE_SAMPLE_SIZE);
+    let query = args
+        .get("query")
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({"match_all": {}}));
+    let body = serde_json::json!({"size": size, "query": query});
+    let path = format!(
+        "/{}/_search",
+        crate::common::validation::url_encode_segment(index)
+    );
+    let parsed = execute_es_json("POST", &path, Some(&body.to_string()), config, None).await?;
+    if let Some(message) = parsed.get("message").and_then(|v| v.as_str()) {
+        return Err(format!(
+            "Failed to sample documents from {}: {}",
+            index, message
+        ));
+    }
+    let hits = parsed
+        .pointer("/data/hits/hits")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    if hits.is_empty() {
+        return Err(format!(
+            "No documents in {} matched the sample query",
+            index
+        ));
+    }
+    Ok(hits)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsSimulatePipeline {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+
+        let docs = match args.get("docs").and_then(|v| v.as_array()) {
+            Some(docs) if !docs.is_empty() => to_simulate_docs(docs),
+            _ => to_simulate_docs(&sample_docs_from_index(args, config).await?),
+        };
+
+        let mut body = serde_json::json!({ "docs": docs });
+        let base = match (
+            args.get("id").and_then(|v| v.as_str()),
+            args.get("pipeline"),
+        ) {
+            // An inline definition wins so edits can be tested before put_pipeline.
+            (_, Some(pipeline)) => {
+                body["pipeline"] = pipeline.clone();
+                "/_ingest/pipeline/_simulate".to_string()
+            }
+            (Some(id), None) => format!(
+                "/_ingest/pipeline/{}/_simulate",
+                crate::common::validation::url_encode_segment(id)
+            ),
+            (None, None) => return Err("Provide either id or an inline pipeline".to_string()),
+        };
+        let verbose = args
+            .get("verbose")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let path = if verbose {
+            format!("{}?verbose=true", base)
+        } else {
+            base
+        };
+
+        execute_es_http("POST", &path, Some(&body.to_string()), config, None).await
+    }
+}
+
+// ---- Snapshot repositories and snapshot lifecycle ----
+
+impl_es_handler!(
+    EsListSnapshotRepositories,
+    "GET",
+    |_args: &Value| -> Result<String, String> { Ok("/_snapshot".to_string()) },
+    false
+);
+
+impl_es_handler!(
+    EsGetSnapshotRepository,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        Ok(format!(
+            "/_snapshot/{}",
+            crate::common::validation::url_encode_segment(repo)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsPutSnapshotRepository,
+    "PUT",
+    |args: &Value| -> Result<String, String> {
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        if args.get("body").and_then(|b| b.get("type")).is_none() {
+            return Err("Missing body.type (e.g. fs, s3, gcs, azure, url)".to_string());
+        }
+        Ok(format!(
+            "/_snapshot/{}",
+            crate::common::validation::url_encode_segment(repo)
+        ))
+    },
+    true
+);
+
+impl_es_handler!(
+    EsDeleteSnapshotRepository,
+    "DELETE",
+    |args: &Value| -> Result<String, String> {
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        Ok(format!(
+            "/_snapshot/{}",
+            crate::common::validation::url_encode_segment(repo)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsVerifySnapshotRepository,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        Ok(format!(
+            "/_snapshot/{}/_verify",
+            crate::common::validation::url_encode_segment(repo)
+        ))
+    },
+    false
+);
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsCreateSnapshot {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        let snap = args
+            .get("snap")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing snap".to_string())?;
+
+        let mut body = args
+            .get("body")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let Some(indices) = args.get("indices").and_then(|v| v.as_str()) {
+            for pattern in indices.split(',') {
+                // Exclusions ("-logs-old*") are valid in snapshot index lists.
+                crate::common::validation::validate_index_name(
+                    pattern.trim().trim_start_matches('-'),
+                    true,
+                )?;
+            }
+            body["indices"] = serde_json::json!(indices);
+        }
+
+        // Snapshots of large indices outlive the HTTP call; return at once and
+        // let the caller poll es__snapshot_status.
+        let wait = args
+            .get("wait_for_completion")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let path = format!(
+            "/_snapshot/{}/{}?wait_for_completion={}",
+            crate::common::validation::url_encode_segment(repo),
+            crate::common::validation::url_encode_segment(snap),
+            wait
+        );
+        execute_es_http("PUT", &path, Some(&body.to_string()), config, None).await
+    }
+}
+
+/// Condense `_snapshot/{repo}/{snap}/_status` into one progress line per
+/// snapshot so polling stays readable when per-shard details are large.
+fn summarize_snapshot_status(data: &Value) -> Vec<Value> {
+    // A zero total means the size is not known yet (or the snapshot is of
+    // empty indices); only a finished snapshot counts as complete then.
+    let percent = |done: u64, total: u64, state: Option<&str>| -> Option<f64> {
+        if total > 0 {
+            Some(((done as f64 / total as f64) * 1000.0).round() / 10.0)
+        } else if state == Some("SUCCESS") {
+            Some(100.0)
+        } else {
+            None
+        }
+    };
+    data.get("snapshots")
+        .and_then(|v| v.as_array())
+        .map(|snapshots| {
+            snapshots
+                .iter()
+                .map(|snap| {
+                    let shards = snap.get("shards_stats");
+                    let shards_done = shards
+                        .and_then(|s| s.get("done"))
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0);
+                    let shards_total = shards
+                        .and_then(|s| s.get("total"))
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0);
+                    let bytes_done = snap
+                        .pointer("/stats/processed/size_in_bytes")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0);
+                    let bytes_total = snap
+                        .pointer("/stats/total/size_in_bytes")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0);
+                    serde_json::json!({
+                        "snapshot": snap.get("snapshot"),
+                        "repository": snap.get("repository"),
+                        "state": snap.get("state"),
+                        "shards_done": shards_done,
+                        "shards_total": shards_total,
+                        "shards_failed": shards.and_then(|s| s.get("failed")),
+                        "bytes_processed": bytes_done,
+                        "bytes_total": bytes_total,
+                        "percent_complete": percent(
+                            bytes_done,
+                            bytes_total,
+                            snap.get("state").and_then(|v| v.as_str()),
+                        ),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsSnapshotStatus {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let path = match (
+            args.get("repo").and_then(|v| v.as_str()),
+            args.get("snap").and_then(|v| v.as_str()),
+        ) {
+            (Some(repo), Some(snap)) => format!(
+                "/_snapshot/{}/{}/_status",
+                crate::common::validation::url_encode_segment(repo),
+                crate::common::validation::url_encode_segment(snap)
+            ),
+            (Some(repo), None) => format!(
+                "/_snapshot/{}/_status",
+                crate::common::validation::url_encode_segment(repo)
+            ),
+            // Without a repo, _status lists only the snapshots currently running.
+            (None, _) => "/_snapshot/_status".to_string(),
+        };
+
+        let mut result = execute_es_json("GET", &path, None, config, None).await?;
+        let progress = result
+            .get("data")
+            .map(summarize_snapshot_status)
+            .unwrap_or_default();
+        if !progress.is_empty() {
+            result["progress"] = serde_json::json!(progress);
+        }
+        let include_details = args
+            .get("include_details")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if !include_details {
+            // Per-index/per-shard stats dominate the payload; keep them opt-in.
+            if let Some(snapshots) = result
+                .pointer_mut("/data/snapshots")
+                .and_then(|v| v.as_array_mut())
+            {
+                for snap in snapshots.iter_mut().filter_map(|s| s.as_object_mut()) {
+                    snap.remove("indices");
+                }
+            }
+        }
+        Ok(crate::common::format::truncate_tool_output(
+            result.to_string(),
+        ))
+    }
+}
+
+impl_es_handler!(
+    EsDeleteSnapshot,
+    "DELETE",
+    |args: &Value| -> Result<String, String> {
+        let repo = args
+            .get("repo")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing repo".to_string())?;
+        let snap = args
+            .get("snap")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing snap".to_string())?;
+        Ok(format!(
+            "/_snapshot/{}/{}",
+            crate::common::validation::url_encode_segment(repo),
+            crate::common::validation::url_encode_segment(snap)
+        ))
+    },
+    false
+);
+
+// ---- Tasks ----
+
+/// `?wait_for_completion=false` when the caller asked to run a long
+/// operation as a background task; ES then answers `{"task": "<node>:<id>"}`
+/// immediately and progress is tracked through es__get_task.
+fn wait_for_completion_query(args: &Value) -> &'static str {
+    match args.get("wait_for_completion").and_then(|v| v.as_bool()) {
+        Some(false) => "?wait_for_completion=false",
+        _ => "",
+    }
+}
+
+impl_es_handler!(
+    EsListTasks,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("detailed", "true");
+        for key in ["actions", "nodes", "parent_task_id"] {
+            if let Some(v) = args
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+            {
+                query.append_pair(key, v);
+            }
+        }
+        if let Some(group_by) = args.get("group_by").and_then(|v| v.as_str()) {
+            if !matches!(group_by, "nodes" | "parents" | "none") {
+                return Err(format!(
+                    "Invalid group_by '{}': expected nodes, parents or none",
+                    group_by
+                ));
+            }
+            query.append_pair("group_by", group_by);
+        }
+        Ok(format!("/_tasks?{}", query.finish()))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsGetTask,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let task_id = args
+            .get("task_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing task_id".to_string())?;
+        crate::common::validation::validate_task_id(task_id)?;
+        Ok(format!("/_tasks/{}", task_id))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsCancelTask,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let task_id = args
+            .get("task_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing task_id".to_string())?;
+        crate::common::validation::validate_task_id(task_id)?;
+        Ok(format!("/_tasks/{}/_cancel", task_id))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsRethrottle,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let task_id = args
+            .get("task_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing task_id".to_string())?;
+        crate::common::validation::validate_task_id(task_id)?;
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .unwrap_or("reindex");
+        if !matches!(operation, "reindex" | "update_by_query" | "delete_by_query") {
+            return Err(format!(
+                "Invalid operation '{}': expected reindex, update_by_query or delete_by_query",
+                operation
+            ));
+        }
+        // -1 removes throttling entirely.
+        let rps = args
+            .get("requests_per_second")
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| "Missing requests_per_second".to_string())?;
+        Ok(format!(
+            "/_{}/{}/_rethrottle?requests_per_second={}",
+            operation, task_id, rps
+        ))
+    },
+    false
+);
+
+// ---- Update by query and multi-search ----
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsUpdateByQuery {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, true)?;
+        let body = args.get("body").ok_or_else(|| "Missing body".to_string())?;
+        let index = crate::common::validation::url_encode_segment(index);
+
+        let dry_run = args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if dry_run {
+            // Count what the update would touch without running the script.
+            let count_body = match body.get("query") {
+                Some(query) => serde_json::json!({ "query": query }),
+                None => serde_json::json!({}),
+            };
+            let result = execute_es_json(
+                "POST",
+                &format!("/{}/_count", index),
+                Some(&count_body.to_string()),
+                config,
+                None,
+            )
+            .await?;
+            if let Some(message) = result.get("message") {
+                return Ok(serde_json::json!({
+                    "dry_run": true,
+                    "status": result.get("status"),
+                    "message": message,
+                })
+                .to_string());
+            }
+            return Ok(serde_json::json!({
+                "dry_run": true,
+                "matching_documents": result.pointer("/data/count"),
+                "query": body.get("query"),
+                "script": body.get("script"),
+            })
+            .to_string());
+        }
+
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if args.get("wait_for_completion").and_then(|v| v.as_bool()) == Some(false) {
+            query.append_pair("wait_for_completion", "false");
+        }
+        if let Some(conflicts) = args.get("conflicts").and_then(|v| v.as_str()) {
+            if !matches!(conflicts, "abort" | "proceed") {
+                return Err(format!(
+                    "Invalid conflicts '{}': expected abort or proceed",
+                    conflicts
+                ));
+            }
+            query.append_pair("conflicts", conflicts);
+        }
+        let query = query.finish();
+        let path = if query.is_empty() {
+            format!("/{}/_update_by_query", index)
+        } else {
+            format!("/{}/_update_by_query?{}", index, query)
+        };
+        execute_es_http("POST", &path, Some(&body.to_string()), config, None).await
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsMultiSearch {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let searches = args
+            .get("searches")
+            .and_then(|v| v.as_array())
+            .filter(|a| !a.is_empty())
+            .ok_or_else(|| "Missing searches (non-empty array of {index, body})".to_string())?;
+
+        let mut ndjson = String::new();
+        for (i, search) in searches.iter().enumerate() {
+            let index = search
+                .get("index")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("searches[{}]: missing index", i))?;
+            crate::common::validation::validate_index_name(index, true)
+                .map_err(|e| format!("searches[{}]: {}", i, e))?;
+            let body = search
+                .get("body")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({"query": {"match_all": {}}}));
+            ndjson.push_str(&serde_json::json!({ "index": index }).to_string());
+            ndjson.push('\n');
+            ndjson.push_str(&body.to_string());
+            ndjson.push('\n');
+        }
+        execute_es_http("POST", "/_msearch", Some(&ndjson), config, None).await
+    }
+}
+
+// ---- Query profiling and explain ----
+
+const PROFILE_TOP_N: usize = 10;
+const EXPLAIN_MAX_LINES: usize = 60;
+
+fn nanos_to_ms(nanos: u64) -> f64 {
+    (nanos as f64 / 1_000.0).round() / 1_000.0
+}
+
+fn time_in_nanos(node: &Value) -> u64 {
+    node.get("time_in_nanos")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+/// Depth-first walk of a profile tree (`query`, `collector` or
+/// `aggregations` nodes), recording every node with its shard ID.
+fn collect_profile_nodes<'a>(
+    nodes: Option<&'a Value>,
+    shard: &'a str,
+    out: &mut Vec<(&'a str, &'a Value)>,
+) {
+    for node in nodes.and_then(|v| v.as_array()).into_iter().flatten() {
+        out.push((shard, node));
+        collect_profile_nodes(node.get("children"), shard, out);
+    }
+}
+
+fn truncate_description(desc: &str) -> String {
+    const MAX_CHARS: usize = 200;
+    if desc.chars().count() <= MAX_CHARS {
+        desc.to_string()
+    } else {
+        format!("{}…", desc.chars().take(MAX_CHARS).collect::<String>())
+    }
+}
+
+/// The `PROFILE_TOP_N` most expensive profile nodes; `label` is the field
+/// naming the node (`type` for queries/aggregations, `name` for collectors).
+fn top_profile_nodes(nodes: &mut [(&str, &Value)], label: &str) -> Vec<Value> {
+    nodes.sort_by_key(|(_, n)| std::cmp::Reverse(time_in_nanos(n)));
+    nodes
+        .iter()
+        .take(PROFILE_TOP_N)
+        .map(|(shard, n)| {
+            serde_json::json!({
+                "shard": shard,
+                "type": n.get(label),
+                "description": n
+                    .get("description")
+                    .or_else(|| n.get("reason"))
+                    .and_then(|v| v.as_str())
+                    .map(truncate_description),
+                "time_ms": nanos_to_ms(time_in_nanos(n)),
+            })
+        })
+        .collect()
+}
+
+/// Condense a `profile: true` search response into the slowest shards and
+/// the most expensive query, collector and aggregation nodes.
+fn summarize_profile(data: &Value) -> Value {
+    let shards: &[Value] = data
+        .pointer("/profile/shards")
+        .and_then(|v| v.as_array())
+        .map(|v| v.as_slice())
+        .unwrap_or(&[]);
+
+    let mut shard_times = Vec::new();
+    let mut queries = Vec::new();
+    let mut collectors = Vec::new();
+    let mut aggregations = Vec::new();
+
+    for shard in shards {
+        let id = shard.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        let mut query_nanos = 0;
+        let mut collector_nanos = 0;
+        let mut rewrite_nanos = 0;
+        for search in shard
+            .get("searches")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            query_nanos += search
+                .get("query")
+                .and_then(|v| v.as_array())
+                .map(|roots| roots.iter().map(time_in_nanos).sum::<u64>())
+                .unwrap_or(0);
+            collector_nanos += search
+                .get("collector")
+                .and_then(|v| v.as_array())
+                .map(|roots| roots.iter().map(time_in_nanos).sum::<u64>())
+                .unwrap_or(0);
+            rewrite_nanos += search
+                .get("rewrite_time")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            collect_profile_nodes(search.get("query"), id, &mut queries);
+            collect_profile_nodes(search.get("collector"), id, &mut collectors);
+        }
+        let agg_nanos = shard
+            .get("aggregations")
+            .and_then(|v| v.as_array())
+            .map(|roots| roots.iter().map(time_in_nanos).sum::<u64>())
+            .unwrap_or(0);
+        collect_profile_nodes(shard.get("aggregations"), id, &mut aggregations);
+        shard_times.push((id, query_nanos, collector_nanos, rewrite_nanos, agg_nanos));
+    }
+
+    shard_times.sort_by_key(|(_, q, c, r, a)| std::cmp::Reverse(q + c + r + a));
+    let slowest_shards: Vec<Value> = shard_times
+        .iter()
+        .take(PROFILE_TOP_N)
+        .map(|(id, q, c, r, a)| {
+            serde_json::json!({
+                "shard": id,
+                "total_ms": nanos_to_ms(q + c + r + a),
+                "query_ms": nanos_to_ms(*q),
+                "collector_ms": nanos_to_ms(*c),
+                "rewrite_ms": nanos_to_ms(*r),
+                "aggregations_ms": nanos_to_ms(*a),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "took_ms": data.get("took"),
+        "total_hits": data.pointer("/hits/total"),
+        "shards_profiled": shards.len(),
+        "slowest_shards": slowest_shards,
+        "expensive_queries": top_profile_nodes(&mut queries, "type"),
+        "expensive_collectors": top_profile_nodes(&mut collectors, "name"),
+        "expensive_aggregations": top_profile_nodes(&mut aggregations, "type"),
+    })
+}
+
+/// Render an `_explain` explanation tree as indented lines, e.g.
+/// `2.2 = weight(title:rust in 3) [PerFieldSimilarity], result of:`.
+fn render_explanation(node: &Value, depth: usize, lines: &mut Vec<String>) {
+    if lines.len() >= EXPLAIN_MAX_LINES {
+        return;
+    }
+    let value = node.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let description = node
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    lines.push(format!(
+        "{}{} = {}",
+        "  ".repeat(depth),
+        (value * 10_000.0).round() / 10_000.0,
+        truncate_description(description)
+    ));
+    for child in node
+        .get("details")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        render_explanation(child, depth + 1, lines);
+    }
+}
+
+/// Lay out `{"status", "summary", "data"}` with the summary ahead of the raw
+/// response, so truncation at the tool output limit only cuts the raw part.
+fn summary_then_raw(result: &Value, summary: &Value) -> String {
+    let mut out = format!(
+        "{{\"status\":{},\"summary\":{}",
+        result.get("status").unwrap_or(&Value::Null),
+        summary
+    );
+    if let Some(message) = result.get("message") {
+        out.push_str(&format!(",\"message\":{}", message));
+    }
+    out.push_str(&format!(
+        ",\"data\":{}}}",
+        result.get("data").unwrap_or(&Value::Null)
+    ));
+    crate::common::format::truncate_tool_output(out)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsProfileSearch {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, true)?;
+        let mut body = args
+            .get("body")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({"query": {"match_all": {}}}));
+        if !body.is_object() {
+            return Err("body must be a Query DSL object".to_string());
+        }
+        body["profile"] = serde_json::json!(true);
+
+        let path = format!(
+            "/{}/_search",
+            crate::common::validation::url_encode_segment(index)
+        );
+        let result = execute_es_json("POST", &path, Some(&body.to_string()), config, None).await?;
+        let summary = result
+            .get("data")
+            .filter(|d| d.get("profile").is_some())
+            .map(summarize_profile)
+            .unwrap_or(Value::Null);
+        Ok(summary_then_raw(&result, &summary))
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsExplainDocument {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, false)?;
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing id".to_string())?;
+        let body = args
+            .get("body")
+            .filter(|b| b.get("query").is_some())
+            .ok_or_else(|| "Missing body.query".to_string())?;
+
+        let path = format!(
+            "/{}/_explain/{}",
+            crate::common::validation::url_encode_segment(index),
+            crate::common::validation::url_encode_segment(id)
+        );
+        let result = execute_es_json("POST", &path, Some(&body.to_string()), config, None).await?;
+        let summary = match result.get("data").and_then(|d| d.get("explanation")) {
+            Some(explanation) => {
+                let mut lines = Vec::new();
+                render_explanation(explanation, 0, &mut lines);
+                serde_json::json!({
+                    "matched": result.pointer("/data/matched"),
+                    "score": explanation.get("value"),
+                    "breakdown": lines,
+                })
+            }
+            None => serde_json::json!({ "matched": result.pointer("/data/matched") }),
+        };
+        Ok(summary_then_raw(&result, &summary))
+    }
+}
+
+// ---- Analyzer explorer ----
+
+/// Keys of an `_analyze` request that pick the analysis chain.
+const ANALYZE_SPEC_KEYS: [&str; 6] = [
+    "field",
+    "analyzer",
+    "tokenizer",
+    "filter",
+    "char_filter",
+    "normalizer",
+];
+
+/// Run `text` through one analysis chain and return the token stream
+/// (token, position, offsets, type).
+async fn run_analyze(
+    index: Option<&str>,
+    text: &Value,
+    spec: &Value,
+    config: &Value,
+) -> Result<Value, String> {
+    let mut body = serde_json::json!({ "text": text });
+    for key in ANALYZE_SPEC_KEYS {
+        if let Some(v) = spec.get(key) {
+            body[key] = v.clone();
+        }
+    }
+    let path = match index {
+        Some(index) => format!(
+            "/{}/_analyze",
+            crate::common::validation::url_encode_segment(index)
+        ),
+        None if spec.get("field").is_some() => {
+            return Err("field analysis requires an index".to_string())
+        }
+        None => "/_analyze".to_string(),
+    };
+    let result = execute_es_json("POST", &path, Some(&body.to_string()), config, None).await?;
+    if let Some(message) = result.get("message").and_then(|v| v.as_str()) {
+        return Err(format!("Analyze failed: {}", message));
+    }
+    Ok(result
+        .pointer("/data/tokens")
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!([])))
+}
+
+fn token_terms(tokens: &Value) -> Vec<&str> {
+    tokens
+        .as_array()
+        .map(|tokens| {
+            tokens
+                .iter()
+                .filter_map(|t| t.get("token").and_then(|v| v.as_str()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The analysis chain described by a spec, for labelling comparison output.
+fn analyze_spec_label(spec: &Value) -> Value {
+    let mut label = serde_json::Map::new();
+    for key in ANALYZE_SPEC_KEYS {
+        if let Some(v) = spec.get(key) {
+            label.insert(key.to_string(), v.clone());
+        }
+    }
+    if label.is_empty() {
+        label.insert("analyzer".to_string(), serde_json::json!("standard"));
+    }
+    Value::Object(label)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsAnalyze {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let text = args
+            .get("text")
+            .filter(|t| t.is_string() || t.is_array())
+            .ok_or_else(|| "Missing text (string or array of strings)".to_string())?;
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty());
+        if let Some(index) = index {
+            crate::common::validation::validate_index_name(index, false)?;
+        }
+
+        let tokens = run_analyze(index, text, args, config).await?;
+        let result = match args.get("compare_with") {
+            None => serde_json::json!({
+                "analysis": analyze_spec_label(args),
+                "tokens": tokens,
+            }),
+            Some(other) => {
+                let other_tokens = run_analyze(index, text, other, config).await?;
+                let left = token_terms(&tokens);
+                let right = token_terms(&other_tokens);
+                let only_left: Vec<&str> = left
+                    .iter()
+                    .filter(|t| !right.contains(t))
+                    .copied()
+                    .collect();
+                let only_right: Vec<&str> = right
+                    .iter()
+                    .filter(|t| !left.contains(t))
+                    .copied()
+                    .collect();
+                serde_json::json!({
+                    "left": {"analysis": analyze_spec_label(args), "terms": left, "tokens": tokens},
+                    "right": {"analysis": analyze_spec_label(other), "terms": right, "tokens": other_tokens},
+                    "only_in_left": only_left,
+                    "only_in_right": only_right,
+                })
+            }
+        };
+        Ok(crate::common::format::truncate_tool_output(
+            result.to_string(),
+        ))
+    }
+}
+
+// ---- Index settings and maintenance ----
+
+/// `/{index}{suffix}` when an index (or pattern) is given, otherwise the
+/// cluster-wide `{suffix}` endpoint.
+fn optional_index_path(args: &Value, suffix: &str) -> Result<String, String> {
+    match args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+    {
+        Some(index) => {
+            crate::common::validation::validate_index_name(index, true)?;
+            Ok(format!(
+                "/{}{}",
+                crate::common::validation::url_encode_segment(index),
+                suffix
+            ))
+        }
+        None => Ok(suffix.to_string()),
+    }
+}
+
+impl_es_handler!(
+    EsGetIndexSettings,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, true)?;
+        let include_defaults = args
+            .get("include_defaults")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Ok(format!(
+            "/{}/_settings?flat_settings=true{}",
+            crate::common::validation::url_encode_segment(index),
+            if include_defaults {
+                "&include_defaults=true"
+            } else {
+                ""
+            }
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsPutIndexSettings,
+    "PUT",
+    |args: &Value| -> Result<String, String> {
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, true)?;
+        if args.get("body").is_none() {
+            return Err("Missing body".to_string());
+        }
+        Ok(format!(
+            "/{}/_settings",
+            crate::common::validation::url_encode_segment(index)
+        ))
+    },
+    true
+);
+
+impl_es_handler!(
+    EsOpenIndex,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_index_name(index, true)?;
+        Ok(format!(
+            "/{}/_open",
+            crate::common::validation::url_encode_segment(index)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsCloseIndex,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        // Closing takes every matched index offline, so only exact names
+        // are accepted.
+        crate::common::validation::validate_index_name(index, false)?;
+        if index == "_all" {
+            return Err("Close one index at a time; _all is not accepted".to_string());
+        }
+        Ok(format!(
+            "/{}/_close",
+            crate::common::validation::url_encode_segment(index)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsRollover,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let alias = args
+            .get("alias")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing alias".to_string())?;
+        crate::common::validation::validate_index_name(alias, false)?;
+        let mut path = format!(
+            "/{}/_rollover",
+            crate::common::validation::url_encode_segment(alias)
+        );
+        if let Some(new_index) = args.get("new_index").and_then(|v| v.as_str()) {
+            crate::common::validation::validate_index_name(new_index, false)?;
+            path.push('/');
+            path.push_str(&crate::common::validation::url_encode_segment(new_index));
+        }
+        if args.get("dry_run").and_then(|v| v.as_bool()) == Some(true) {
+            path.push_str("?dry_run=true");
+        }
+        Ok(path)
+    },
+    true
+);
+
+/// Check the documented prerequisites of `_shrink`/`_split`/`_clone` up
+/// front, so the caller gets every missing step at once instead of one
+/// ES rejection at a time.
+async fn resize_preflight(op: &str, source: &str, config: &Value) -> Result<(), String> {
+    let encoded = crate::common::validation::url_encode_segment(source);
+    let settings = execute_es_json(
+        "GET",
+        &format!("/{}/_settings?flat_settings=true", encoded),
+        None,
+        config,
+        None,
+    )
+    .await?;
+    if let Some(message) = settings.get("message").and_then(|v| v.as_str()) {
+        return Err(format!(
+            "Failed to read settings of {}: {}",
+            source, message
+        ));
+    }
+    let index_settings = settings
+        .get("data")
+        .and_then(|d| d.get(source))
+        .and_then(|i| i.get("settings"));
+    let is_true = |key: &str| {
+        index_settings
+            .and_then(|s| s.get(key))
+            .and_then(|v| v.as_str())
+            == Some("true")
+    };
+
+    let mut problems = Vec::new();
+    if !is_true("index.blocks.write") && !is_true("index.blocks.read_only") {
+        problems.push(format!(
+            "{} must be write-blocked first: put_index_settings {{\"index.blocks.write\": true}}",
+            source
+        ));
+    }
+
+    if op == "shrink" {
+        // Every shard needs a started copy on one common node.
+        let shards = execute_es_json(
+            "GET",
+            &cat_shards_path(Some(&encoded), Some("shard,state,node")),
+            None,
+            config,
+            None,
+        )
+        .await?;
+        let mut nodes_per_shard: std::collections::BTreeMap<&str, Vec<&str>> =
+            std::collections::BTreeMap::new();
+        for shard in shards
+            .get("data")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if shard.get("state").and_then(|v| v.as_str()) != Some("STARTED") {
+                continue;
+            }
+            if let (Some(number), Some(node)) = (
+                shard.get("shard").and_then(|v| v.as_str()),
+                shard.get("node").and_then(|v| v.as_str()),
+            ) {
+                nodes_per_shard.entry(number).or_default().push(node);
+            }
+        }
+        let mut groups = nodes_per_shard.values();
+        let common_node = groups.next().and_then(|first| {
+            first
+                .iter()
+                .find(|node| groups.clone().all(|nodes| nodes.contains(*node)))
+        });
+        if common_node.is_none() {
+            problems.push(format!(
+                "a copy of every shard of {} must be on one node: put_index_settings {{\"index.routing.allocation.require._name\": \"<node>\"}} and wait for relocation",
+                source
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Cannot {} {}: {}", op, source, problems.join("; ")))
+    }
+}
+
+async fn resize_index(op: &str, args: &Value, config: &Value) -> Result<String, String> {
+    let source = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing index".to_string())?;
+    crate::common::validation::validate_index_name(source, false)?;
+    let target = args
+        .get("target")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing target".to_string())?;
+    crate::common::validation::validate_index_name(target, false)?;
+
+    resize_preflight(op, source, config).await?;
+
+    let path = format!(
+        "/{}/_{}/{}",
+        crate::common::validation::url_encode_segment(source),
+        op,
+        crate::common::validation::url_encode_segment(target)
+    );
+    let body = args.get("body").map(|b| b.to_string());
+    execute_es_http("POST", &path, body.as_deref(), config, None).await
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsShrinkIndex {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        resize_index("shrink", args, config).await
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsSplitIndex {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        resize_index("split", args, config).await
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsCloneIndex {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        resize_index("clone", args, config).await
+    }
+}
+
+impl_es_handler!(
+    EsForceMerge,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let mut path = optional_index_path(args, "/_forcemerge")?;
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(n) = args.get("max_num_segments").and_then(|v| v.as_u64()) {
+            query.append_pair("max_num_segments", &n.to_string());
+        }
+        if args.get("only_expunge_deletes").and_then(|v| v.as_bool()) == Some(true) {
+            query.append_pair("only_expunge_deletes", "true");
+        }
+        let query = query.finish();
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(&query);
+        }
+        Ok(path)
+    },
+    false
+);
+
+impl_es_handler!(
+    EsRefreshIndex,
+    "POST",
+    |args: &Value| -> Result<String, String> { optional_index_path(args, "/_refresh") },
+    false
+);
+
+impl_es_handler!(
+    EsFlushIndex,
+    "POST",
+    |args: &Value| -> Result<String, String> { optional_index_path(args, "/_flush") },
+    false
+);
+
+impl_es_handler!(
+    EsClearCache,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let mut path = optional_index_path(args, "/_cache/clear")?;
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        // No flags clears every cache type.
+        for key in ["query", "fielddata", "request"] {
+            if args.get(key).and_then(|v| v.as_bool()) == Some(true) {
+                query.append_pair(key, "true");
+            }
+        }
+        let query = query.finish();
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(&query);
+        }
+        Ok(path)
+    },
+    false
+);
+
+// ---- Data streams ----
+
+fn data_stream_name(args: &Value, allow_wildcard: bool) -> Result<&str, String> {
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing name".to_string())?;
+    crate::common::validation::validate_index_name(name, allow_wildcard)?;
+    Ok(name)
+}
+
+/// Reduce a `GET /_data_stream` entry to what is needed to reason about it:
+/// the backing indices by name (newest last), not their UUIDs and per-index
+/// ILM flags.
+fn summarize_data_stream(stream: &Value) -> Value {
+    let backing: Vec<&str> = stream
+        .get("indices")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|i| i.get("index_name").and_then(|v| v.as_str()))
+        .collect();
+    serde_json::json!({
+        "name": stream.get("name"),
+        "status": stream.get("status"),
+        "generation": stream.get("generation"),
+        "template": stream.get("template"),
+        "ilm_policy": stream.get("ilm_policy"),
+        "write_index": backing.last(),
+        "backing_indices": backing,
+    })
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsListDataStreams {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let path = match args.get("name").and_then(|v| v.as_str()) {
+            Some(_) => format!(
+                "/_data_stream/{}",
+                crate::common::validation::url_encode_segment(data_stream_name(args, true)?)
+            ),
+            None => "/_data_stream".to_string(),
+        };
+        let mut result = execute_es_json("GET", &path, None, config, None).await?;
+        if let Some(streams) = result
+            .get("data")
+            .and_then(|d| d.get("data_streams"))
+            .and_then(|v| v.as_array())
+        {
+            let summary: Vec<Value> = streams.iter().map(summarize_data_stream).collect();
+            result["data"] = serde_json::json!({ "data_streams": summary });
+        }
+        Ok(crate::common::format::truncate_tool_output(
+            result.to_string(),
+        ))
+    }
+}
+
+impl_es_handler!(
+    EsCreateDataStream,
+    "PUT",
+    |args: &Value| -> Result<String, String> {
+        Ok(format!(
+            "/_data_stream/{}",
+            crate::common::validation::url_encode_segment(data_stream_name(args, false)?)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsDeleteDataStream,
+    "DELETE",
+    |args: &Value| -> Result<String, String> {
+        Ok(format!(
+            "/_data_stream/{}",
+            crate::common::validation::url_encode_segment(data_stream_name(args, false)?)
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsRolloverDataStream,
+    "POST",
+    |args: &Value| -> Result<String, String> {
+        let mut path = format!(
+            "/{}/_rollover",
+            crate::common::validation::url_encode_segment(data_stream_name(args, false)?)
+        );
+        if args.get("dry_run").and_then(|v| v.as_bool()) == Some(true) {
+            path.push_str("?dry_run=true");
+        }
+        Ok(path)
+    },
+    true
+);
+
+impl_es_handler!(
+    EsDataStreamStats,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        match args.get("name").and_then(|v| v.as_str()) {
+            Some(_) => Ok(format!(
+                "/_data_stream/{}/_stats?human=true",
+                crate::common::validation::url_encode_segment(data_stream_name(args, true)?)
+            )),
+            None => Ok("/_data_stream/_stats?human=true".to_string()),
+        }
+    },
+    false
+);
+
+// ---- Cluster diagnostics ----
+
+impl_es_handler!(
+    EsAllocationExplain,
+    "GET",
+    |_args: &Value| -> Result<String, String> { Ok("/_cluster/allocation/explain".to_string()) },
+    true
+);
+
+/// Optional `node_id` filter (`_local`, a node name/ID, or a comma list) as a
+/// path segment.
+fn node_filter_segment(args: &Value) -> String {
+    args.get("node_id")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|n| format!("/{}", crate::common::validation::url_encode_segment(n)))
+        .unwrap_or_default()
+}
+
+const NODE_STATS_METRICS: &str = "jvm,fs,os,thread_pool,breaker";
+
+fn percent(used: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        (used / total * 1000.0).round() / 10.0
+    } else {
+        0.0
+    }
+}
+
+/// Condense one `_nodes/stats` entry to the numbers on-call looks at first:
+/// heap, old-gen GC, disk, CPU and thread pools with rejections.
+fn summarize_node_stats(node: &Value) -> Value {
+    let u = |pointer: &str| node.pointer(pointer).and_then(|v| v.as_u64()).unwrap_or(0);
+    let old_gc_count = u("/jvm/gc/collectors/old/collection_count");
+    let old_gc_ms = u("/jvm/gc/collectors/old/collection_time_in_millis");
+    let disk_total = u("/fs/total/total_in_bytes");
+    let disk_available = u("/fs/total/available_in_bytes");
+    let rejected: serde_json::Map<String, Value> = node
+        .get("thread_pool")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, pool)| {
+            let rejected = pool.get("rejected").and_then(|v| v.as_u64()).unwrap_or(0);
+            (rejected > 0).then(|| (name.clone(), serde_json::json!(rejected)))
+        })
+        .collect();
+    let tripped: serde_json::Map<String, Value> = node
+        .get("breakers")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, breaker)| {
+            let tripped = breaker.get("tripped").and_then(|v| v.as_u64()).unwrap_or(0);
+            (tripped > 0).then(|| (name.clone(), serde_json::json!(tripped)))
+        })
+        .collect();
+    serde_json::json!({
+        "name": node.get("name"),
+        "heap_used_percent": u("/jvm/mem/heap_used_percent"),
+        "heap_max_in_bytes": u("/jvm/mem/heap_max_in_bytes"),
+        "old_gc_count": old_gc_count,
+        "old_gc_avg_ms": if old_gc_count > 0 { old_gc_ms / old_gc_count } else { 0 },
+        "cpu_percent": u("/os/cpu/percent"),
+        "disk_used_percent": percent(
+            disk_total.saturating_sub(disk_available) as f64,
+            disk_total as f64
+        ),
+        "disk_available_in_bytes": disk_available,
+        "thread_pool_rejections": rejected,
+        "breakers_tripped": tripped,
+    })
+}
+
+fn summarize_nodes_stats(data: &Value) -> Vec<Value> {
+    let mut nodes: Vec<Value> = data
+        .get("nodes")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .map(|(id, node)| {
+            let mut summary = summarize_node_stats(node);
+            summary["id"] = serde_json::json!(id);
+            summary
+        })
+        .collect();
+    nodes.sort_by_key(|n| std::cmp::Reverse(n["heap_used_percent"].as_u64().unwrap_or(0)));
+    nodes
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsNodesStats {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let path = format!(
+            "/_nodes{}/stats/{}",
+            node_filter_segment(args),
+            NODE_STATS_METRICS
+        );
+        let result = execute_es_json("GET", &path, None, config, None).await?;
+        let summary = result
+            .get("data")
+            .filter(|_| result.get("message").is_none())
+            .map(|data| Value::Array(summarize_nodes_stats(data)))
+            .unwrap_or(Value::Null);
+        Ok(summary_then_raw(&result, &summary))
+    }
+}
+
+impl_es_handler!(
+    EsHotThreads,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair(
+            "threads",
+            &args
+                .get("threads")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(3)
+                .to_string(),
+        );
+        if let Some(interval) = args.get("interval").and_then(|v| v.as_str()) {
+            query.append_pair("interval", interval);
+        }
+        if let Some(kind) = args.get("type").and_then(|v| v.as_str()) {
+            if !["cpu", "wait", "block", "mem"].contains(&kind) {
+                return Err(format!(
+                    "Invalid type '{}': expected cpu, wait, block or mem",
+                    kind
+                ));
+            }
+            query.append_pair("type", kind);
+        }
+        Ok(format!(
+            "/_nodes{}/hot_threads?{}",
+            node_filter_segment(args),
+            query.finish()
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsPendingTasks,
+    "GET",
+    |_args: &Value| -> Result<String, String> { Ok("/_cluster/pending_tasks".to_string()) },
+    false
+);
+
+const CAT_THREAD_POOL_COLUMNS: &str = "node_name,name,active,queue,rejected,completed";
+const CAT_SHARDS_COLUMNS: &str = "index,shard,prirep,state,node,unassigned.reason";
+
+impl_es_handler!(
+    EsCatThreadPool,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let pools = match args.get("thread_pool").and_then(|v| v.as_str()) {
+            Some(pools) => {
+                if !pools
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c == '_' || c == ',' || c == '*')
+                {
+                    return Err(format!("Invalid thread_pool filter: {}", pools));
+                }
+                format!("/{}", pools)
+            }
+            None => String::new(),
+        };
+        Ok(format!(
+            "/_cat/thread_pool{}?format=json&h={}",
+            pools, CAT_THREAD_POOL_COLUMNS
+        ))
+    },
+    false
+);
+
+impl_es_handler!(
+    EsCatRecovery,
+    "GET",
+    |args: &Value| -> Result<String, String> {
+        let index = match args.get("index").and_then(|v| v.as_str()) {
+            Some(index) => {
+                crate::common::validation::validate_index_name(index, true)?;
+                format!("/{}", crate::common::validation::url_encode_segment(index))
+            }
+            None => String::new(),
+        };
+        let active_only = args
+            .get("active_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        Ok(format!(
+            "/_cat/recovery{}?format=json&bytes=b&active_only={}",
+            index, active_only
+        ))
+    },
+    false
+);
+
+/// One diagnosis line; `severity` is critical, warning or info.
+fn finding(severity: &str, area: &str, detail: String, next_step: &str) -> Value {
+    serde_json::json!({
+        "severity": severity,
+        "area": area,
+        "finding": detail,
+        "next_step": next_step,
+    })
+}
+
+fn severity_rank(finding: &Value) -> u8 {
+    match finding.get("severity").and_then(|v| v.as_str()) {
+        Some("critical") => 0,
+        Some("warning") => 1,
+        _ => 2,
+    }
+}
+
+/// Turn the raw diagnostics responses into findings ordered critical →
+/// warning → info. Thresholds follow the ES defaults: 85/90/95% disk
+/// watermarks, heap pressure above 75%.
+fn diagnose_findings(
+    health: &Value,
+    shards: &Value,
+    nodes_stats: &Value,
+    pending: &Value,
+    thread_pools: &Value,
+    recoveries: &Value,
+    allocation: Option<&Value>,
+) -> Vec<Value> {
+    let mut findings = Vec::new();
+
+    match health.get("status").and_then(|v| v.as_str()) {
+        Some("red") => findings.push(finding(
+            "critical",
+            "health",
+            format!(
+                "Cluster is RED: {} unassigned shard(s) including at least one primary",
+                health["unassigned_shards"]
+            ),
+            "es__allocation_explain on the unassigned primary",
+        )),
+        Some("yellow") => findings.push(finding(
+            "warning",
+            "health",
+            format!(
+                "Cluster is YELLOW: {} replica shard(s) unassigned",
+                health["unassigned_shards"]
+            ),
+            "Check whether there are enough data nodes for the replica count",
+        )),
+        _ => {}
+    }
+
+    let mut unassigned_by_reason: std::collections::BTreeMap<&str, u64> =
+        std::collections::BTreeMap::new();
+    for shard in shards.as_array().into_iter().flatten() {
+        if shard.get("state").and_then(|v| v.as_str()) == Some("UNASSIGNED") {
+            let reason = shard
+                .get("unassigned.reason")
+                .and_then(|v| v.as_str())
+                .unwrap_or("UNKNOWN");
+            *unassigned_by_reason.entry(reason).or_default() += 1;
+        }
+    }
+    for (reason, count) in &unassigned_by_reason {
+        findings.push(finding(
+            "warning",
+            "shards",
+            format!("{} shard(s) unassigned with reason {}", count, reason),
+            "es__cat_shards to list them, es__allocation_explain for the cause",
+        ));
+    }
+    if let Some(explain) = allocation {
+        let explanation = explain
+            .get("allocate_explanation")
+            .and_then(|v| v.as_str())
+            .unwrap_or("no explanation returned");
+        let deciders: Vec<&str> = explain
+            .get("node_allocation_decisions")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .flat_map(|n| {
+                n.get("deciders")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+            })
+            .filter_map(|d| d.get("explanation").and_then(|v| v.as_str()))
+            .take(3)
+            .collect();
+        findings.push(finding(
+            if health.get("status").and_then(|v| v.as_str()) == Some("red") {
+                "critical"
+            } else {
+                "warning"
+            },
+            "allocation",
+            format!(
+                "{}[{}]: {}{}",
+                explain["index"].as_str().unwrap_or("?"),
+                explain["shard"],
+                explanation,
+                if deciders.is_empty() {
+                    String::new()
+                } else {
+                    format!(" Deciders: {}", deciders.join(" | "))
+                }
+            ),
+            "Fix the blocking decider, then POST _cluster/reroute?retry_failed=true",
+        ));
+    }
+
+    for node in summarize_nodes_stats(nodes_stats) {
+        let name = node["name"].as_str().unwrap_or("?").to_string();
+        let heap = node["heap_used_percent"].as_u64().unwrap_or(0);
+        if heap >= 85 {
+            findings.push(finding(
+                "critical",
+                "jvm",
+                format!("{}: heap at {}%", name, heap),
+                "es__hot_threads type=mem; reduce fielddata, large aggregations or shard count",
+            ));
+        } else if heap >= 75 {
+            findings.push(finding(
+                "warning",
+                "jvm",
+                format!("{}: heap at {}%", name, heap),
+                "Watch old-gen GC frequency",
+            ));
+        }
+        let gc_avg = node["old_gc_avg_ms"].as_u64().unwrap_or(0);
+        if gc_avg >= 1000 {
+            findings.push(finding(
+                "warning",
+                "jvm",
+                format!("{}: old-gen GC averages {} ms", name, gc_avg),
+                "Long GC pauses cause node drops; check heap sizing",
+            ));
+        }
+        let disk = node["disk_used_percent"].as_f64().unwrap_or(0.0);
+        if disk >= 95.0 {
+            findings.push(finding(
+                "critical",
+                "disk",
+                format!(
+                    "{}: disk {}% used, flood stage makes indices read-only",
+                    name, disk
+                ),
+                "Free disk space or add nodes, then clear index.blocks.read_only_allow_delete",
+            ));
+        } else if disk >= 90.0 {
+            findings.push(finding(
+                "critical",
+                "disk",
+                format!("{}: disk {}% used, above the high watermark", name, disk),
+                "Shards are being moved off this node; free disk space",
+            ));
+        } else if disk >= 85.0 {
+            findings.push(finding(
+                "warning",
+                "disk",
+                format!("{}: disk {}% used, above the low watermark", name, disk),
+                "No new shards will be allocated to this node",
+            ));
+        }
+        let cpu = node["cpu_percent"].as_u64().unwrap_or(0);
+        if cpu >= 90 {
+            findings.push(finding(
+                "warning",
+                "cpu",
+                format!("{}: CPU at {}%", name, cpu),
+                "es__hot_threads for this node",
+            ));
+        }
+        if let Some(rejections) = node["thread_pool_rejections"].as_object() {
+            for (pool, count) in rejections {
+                findings.push(finding(
+                    if pool == "write" || pool == "search" {
+                        "warning"
+                    } else {
+                        "info"
+                    },
+                    "thread_pool",
+                    format!(
+                        "{}: {} rejection(s) in the {} pool since start",
+                        name, count, pool
+                    ),
+                    "es__cat_thread_pool to see whether queues are still full",
+                ));
+            }
+        }
+        if let Some(breakers) = node["breakers_tripped"].as_object() {
+            for (breaker, count) in breakers {
+                findings.push(finding(
+                    "warning",
+                    "breakers",
+                    format!("{}: {} breaker tripped {} time(s)", name, breaker, count),
+                    "Requests were rejected to protect the heap",
+                ));
+            }
+        }
+    }
+
+    for pool in thread_pools.as_array().into_iter().flatten() {
+        let queue: u64 = pool
+            .get("queue")
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        if queue > 0 {
+            findings.push(finding(
+                "info",
+                "thread_pool",
+                format!(
+                    "{}: {} task(s) queued in {}",
+                    pool["node_name"].as_str().unwrap_or("?"),
+                    queue,
+                    pool["name"].as_str().unwrap_or("?")
+                ),
+                "Sustained queues precede rejections",
+            ));
+        }
+    }
+
+    let tasks = pending
+        .get("tasks")
+        .and_then(|v| v.as_array())
+        .map(|t| t.as_slice())
+        .unwrap_or(&[]);
+    if !tasks.is_empty() {
+        let oldest_ms = tasks
+            .iter()
+            .filter_map(|t| t.get("time_in_queue_millis").and_then(|v| v.as_u64()))
+            .max()
+            .unwrap_or(0);
+        findings.push(finding(
+            if oldest_ms >= 30_000 {
+                "warning"
+            } else {
+                "info"
+            },
+            "master",
+            format!(
+                "{} pending cluster task(s), oldest queued {} ms",
+                tasks.len(),
+                oldest_ms
+            ),
+            "es__pending_tasks; a backed-up master delays mapping and allocation changes",
+        ));
+    }
+
+    let recovering = recoveries.as_array().map(|r| r.len()).unwrap_or(0);
+    if recovering > 0 {
+        findings.push(finding(
+            "info",
+            "recovery",
+            format!("{} shard recovery(ies) in progress", recovering),
+            "es__cat_recovery to follow progress",
+        ));
+    }
+
+    findings.sort_by_key(severity_rank);
+    findings
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsDiagnoseCluster {
+    async fn handle(
+        &self,
+        _args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let nodes_stats_path = format!("/_nodes/stats/{}", NODE_STATS_METRICS);
+        let thread_pool_path = format!(
+            "/_cat/thread_pool?format=json&h={}",
+            CAT_THREAD_POOL_COLUMNS
+        );
+        let shards_path = cat_shards_path(None, Some(CAT_SHARDS_COLUMNS));
+        let (health, shards, nodes, nodes_stats, pending, thread_pools, recoveries) = tokio::join!(
+            execute_es_json("GET", CLUSTER_HEALTH_PATH, None, config, None),
+            execute_es_json("GET", &shards_path, None, config, None),
+            execute_es_json("GET", CAT_NODES_PATH, None, config, None),
+            execute_es_json("GET", &nodes_stats_path, None, config, None),
+            execute_es_json("GET", "/_cluster/pending_tasks", None, config, None),
+            execute_es_json("GET", &thread_pool_path, None, config, None),
+            execute_es_json(
+                "GET",
+                "/_cat/recovery?format=json&active_only=true",
+                None,
+                config,
+                None
+            ),
+        );
+        // Health is the one call the diagnosis cannot do without.
+        let health = health?;
+        if health["status"].as_u64().unwrap_or(0) >= 400 {
+            return Err(format!(
+                "Cluster health unavailable: HTTP {} {}",
+                health["status"],
+                health["message"].as_str().unwrap_or("")
+            ));
+        }
+        let data = |r: Result<Value, String>| {
+            r.ok()
+                .filter(|v| v["status"].as_u64().unwrap_or(0) < 400)
+                .and_then(|v| v.get("data").cloned())
+                .unwrap_or(Value::Null)
+        };
+        let health = health.get("data").cloned().unwrap_or(Value::Null);
+        let shards = data(shards);
+        let nodes = data(nodes);
+
+        // Allocation explain errors when nothing is unassigned, so only ask
+        // when health says there is something to explain.
+        let allocation = if health["unassigned_shards"].as_u64().unwrap_or(0) > 0 {
+            data(execute_es_json("GET", "/_cluster/allocation/explain", None, config, None).await)
+        } else {
+            Value::Null
+        };
+
+        let findings = diagnose_findings(
+            &health,
+            &shards,
+            &data(nodes_stats),
+            &data(pending),
+            &data(thread_pools),
+            &data(recoveries),
+            Some(&allocation).filter(|a| !a.is_null()),
+        );
+        let result = serde_json::json!({
+            "status": health.get("status"),
+            "cluster": {
+                "name": health.get("cluster_name"),
+                "nodes": nodes.as_array().map(|n| n.len()).or_else(|| health["number_of_nodes"].as_u64().map(|n| n as usize)),
+                "data_nodes": health.get("number_of_data_nodes"),
+                "active_shards_percent": health.get("active_shards_percent_as_number"),
+                "unassigned_shards": health.get("unassigned_shards"),
+            },
+            "findings": findings,
+        });
+        Ok(crate::common::format::truncate_tool_output(
+            result.to_string(),
+        ))
+    }
+}
+
+// ---- Security administration (Elastic _security, OpenSearch security plugin) ----
+
+#[derive(Clone, Copy)]
+enum SecurityObject {
+    User,
+    Role,
+    RoleMapping,
+}
+
+fn security_object_path(flavor: EsFlavor, object: SecurityObject, name: Option<&str>) -> String {
+    let base = match (flavor, object) {
+        (EsFlavor::Elasticsearch, SecurityObject::User) => "/_security/user",
+        (EsFlavor::Elasticsearch, SecurityObject::Role) => "/_security/role",
+        (EsFlavor::Elasticsearch, SecurityObject::RoleMapping) => "/_security/role_mapping",
+        (EsFlavor::OpenSearch, SecurityObject::User) => "/_plugins/_security/api/internalusers",
+        (EsFlavor::OpenSearch, SecurityObject::Role) => "/_plugins/_security/api/roles",
+        (EsFlavor::OpenSearch, SecurityObject::RoleMapping) => {
+            "/_plugins/_security/api/rolesmapping"
+        }
+    };
+    match name {
+        Some(name) => format!(
+            "{}/{}",
+            base,
+            crate::common::validation::url_encode_segment(name)
+        ),
+        None => base.to_string(),
+    }
+}
+
+/// Security object names share their path with API endpoints such as
+/// `/_security/user/_has_privileges`, so reject names that could collide.
+fn security_object_name<'a>(args: &'a Value, required: bool) -> Result<Option<&'a str>, String> {
+    match args.get("name").and_then(|v| v.as_str()) {
+        Some(name) if name.is_empty() || name.starts_with('_') => {
+            Err(format!("Invalid name '{}'", name))
+        }
+        Some(name) => Ok(Some(name)),
+        None if required => Err("Missing name".to_string()),
+        None => Ok(None),
+    }
+}
+
+fn list_security_objects(
+    object: SecurityObject,
+    args: &Value,
+    flavor: EsFlavor,
+) -> Result<EsRequest, String> {
+    let name = security_object_name(args, false)?;
+    Ok(("GET", security_object_path(flavor, object, name), None))
+}
+
+fn put_security_object(
+    object: SecurityObject,
+    args: &Value,
+    flavor: EsFlavor,
+) -> Result<EsRequest, String> {
+    let name = security_object_name(args, true)?;
+    let body = args.get("body").ok_or_else(|| "Missing body".to_string())?;
+    Ok((
+        "PUT",
+        security_object_path(flavor, object, name),
+        Some(body.to_string()),
+    ))
+}
+
+fn delete_security_object(
+    object: SecurityObject,
+    args: &Value,
+    flavor: EsFlavor,
+) -> Result<EsRequest, String> {
+    let name = security_object_name(args, true)?;
+    Ok(("DELETE", security_object_path(flavor, object, name), None))
+}
+
+fn list_users_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    list_security_objects(SecurityObject::User, args, flavor)
+}
+
+fn put_user_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    put_security_object(SecurityObject::User, args, flavor)
+}
+
+fn delete_user_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    delete_security_object(SecurityObject::User, args, flavor)
+}
+
+fn list_roles_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    list_security_objects(SecurityObject::Role, args, flavor)
+}
+
+fn put_role_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    put_security_object(SecurityObject::Role, args, flavor)
+}
+
+fn delete_role_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    delete_security_object(SecurityObject::Role, args, flavor)
+}
+
+fn list_role_mappings_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    list_security_objects(SecurityObject::RoleMapping, args, flavor)
+}
+
+fn put_role_mapping_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    put_security_object(SecurityObject::RoleMapping, args, flavor)
+}
+
+fn delete_role_mapping_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    delete_security_object(SecurityObject::RoleMapping, args, flavor)
+}
+
+impl_es_flavored_handler!(EsListUsers, list_users_request);
+impl_es_flavored_handler!(EsPutUser, put_user_request);
+impl_es_flavored_handler!(EsDeleteUser, delete_user_request);
+impl_es_flavored_handler!(EsListRoles, list_roles_request);
+impl_es_flavored_handler!(EsPutRole, put_role_request);
+impl_es_flavored_handler!(EsDeleteRole, delete_role_request);
+impl_es_flavored_handler!(EsListRoleMappings, list_role_mappings_request);
+impl_es_flavored_handler!(EsPutRoleMapping, put_role_mapping_request);
+impl_es_flavored_handler!(EsDeleteRoleMapping, delete_role_mapping_request);
+
+/// API keys and privilege checks have no counterpart in the OpenSearch
+/// security plugin.
+fn require_elasticsearch(flavor: EsFlavor, api: &str) -> Result<(), String> {
+    match flavor {
+        EsFlavor::Elasticsearch => Ok(()),
+        EsFlavor::OpenSearch => Err(format!(
+            "{} is only available on Elasticsearch; the OpenSearch security plugin has no equivalent API",
+            api
+        )),
+    }
+}
+
+fn create_api_key_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    require_elasticsearch(flavor, "API key management")?;
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing name".to_string())?;
+    let mut body = serde_json::json!({ "name": name });
+    for key in ["role_descriptors", "expiration", "metadata"] {
+        if let Some(value) = args.get(key) {
+            body[key] = value.clone();
+        }
+    }
+    Ok((
+        "POST",
+        "/_security/api_key".to_string(),
+        Some(body.to_string()),
+    ))
+}
+
+fn list_api_keys_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    require_elasticsearch(flavor, "API key management")?;
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for key in ["name", "username", "id"] {
+        if let Some(value) = args.get(key).and_then(|v| v.as_str()) {
+            query.append_pair(key, value);
+        }
+    }
+    if args.get("owner").and_then(|v| v.as_bool()) == Some(true) {
+        query.append_pair("owner", "true");
+    }
+    let query = query.finish();
+    let path = if query.is_empty() {
+        "/_security/api_key".to_string()
+    } else {
+        format!("/_security/api_key?{}", query)
+    };
+    Ok(("GET", path, None))
+}
+
+fn invalidate_api_key_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    require_elasticsearch(flavor, "API key management")?;
+    // Without a selector ES would reject the request; never fall back to
+    // invalidating everything the caller owns.
+    let body = if let Some(ids) = args.get("ids").and_then(|v| v.as_array()) {
+        if ids.is_empty() {
+            return Err("ids must not be empty".to_string());
+        }
+        serde_json::json!({ "ids": ids })
+    } else if let Some(name) = args.get("name").and_then(|v| v.as_str()) {
+        serde_json::json!({ "name": name })
+    } else {
+        return Err("Provide ids or name of the API key(s) to invalidate".to_string());
+    };
+    Ok((
+        "DELETE",
+        "/_security/api_key".to_string(),
+        Some(body.to_string()),
+    ))
+}
+
+fn has_privileges_request(args: &Value, flavor: EsFlavor) -> Result<EsRequest, String> {
+    require_elasticsearch(flavor, "_has_privileges")?;
+    let body = args.get("body").ok_or_else(|| "Missing body".to_string())?;
+    Ok((
+        "POST",
+        "/_security/user/_has_privileges".to_string(),
+        Some(body.to_string()),
+    ))
+}
+
+impl_es_flavored_handler!(EsCreateApiKey, create_api_key_request);
+impl_es_flavored_handler!(EsListApiKeys, list_api_keys_request);
+impl_es_flavored_handler!(EsInvalidateApiKey, invalidate_api_key_request);
+impl_es_flavored_handler!(EsHasPrivileges, has_privileges_request);
+
+// ---- Vector (kNN) search ----
+
+const KNN_MAX_K: u64 = 10_000;
+
+/// Parse a query vector: a non-empty array of numbers.
+fn knn_vector(value: &Value, what: &str) -> Result<Vec<f64>, String> {
+    let items = value
+        .as_array()
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| format!("{} must be a non-empty array of numbers", what))?;
+    items
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .ok_or_else(|| format!("{} must be a non-empty array of numbers", what))
+        })
+        .collect()
+}
+
+/// Value at a dotted field path (`embedding` or `doc.embedding`) in `_source`.
+fn source_field<'a>(source: &'a Value, field: &str) -> Option<&'a Value> {
+    source
+        .get(field)
+        .or_else(|| field.split('.').try_fold(source, |v, key| v.get(key)))
+}
+
+/// Search body for a kNN query in the cluster's dialect. Elasticsearch 8
+/// takes a top-level `knn` section, and a `query` next to it adds the BM25
+/// score (hybrid, weighted by the boosts). OpenSearch takes a `knn` query
+/// clause; hybrid combines it with the text query in a `bool.should`, or in
+/// a `hybrid` query when a normalization `search_pipeline` is named.
+fn knn_search_body(
+    args: &Value,
+    flavor: EsFlavor,
+    vector: &[f64],
+    exclude_id: Option<&str>,
+) -> Result<Value, String> {
+    let field = args
+        .get("field")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "Missing field".to_string())?;
+    let k = args.get("k").and_then(|v| v.as_u64()).unwrap_or(10);
+    if k == 0 || k > KNN_MAX_K {
+        return Err(format!("k must be between 1 and {}", KNN_MAX_K));
+    }
+    let num_candidates = args
+        .get("num_candidates")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(|| (k * 10).clamp(100, KNN_MAX_K));
+    if num_candidates < k || num_candidates > KNN_MAX_K {
+        return Err(format!(
+            "num_candidates must be between k ({}) and {}",
+            k, KNN_MAX_K
+        ));
+    }
+
+    // The document whose neighbors are requested is its own nearest match.
+    let filter = match (args.get("filter"), exclude_id) {
+        (None, None) => None,
+        (Some(filter), None) => Some(filter.clone()),
+        (filter, Some(id)) => {
+            let mut bool_query = serde_json::json!({ "must_not": [{ "ids": { "values": [id] } }] });
+            if let Some(filter) = filter {
+                bool_query["filter"] = serde_json::json!([filter]);
+            }
+            Some(serde_json::json!({ "bool": bool_query }))
+        }
+    };
+    let text_query = args.get("query").filter(|q| !q.is_null());
+    let knn_boost = args.get("knn_boost").and_then(|v| v.as_f64());
+    let query_boost = args.get("query_boost").and_then(|v| v.as_f64());
+    let boosted_query = |query: &Value| match query_boost {
+        Some(boost) => serde_json::json!({ "bool": { "must": [query], "boost": boost } }),
+        None => query.clone(),
+    };
+
+    let mut body = match flavor {
+        EsFlavor::Elasticsearch => {
+            let mut knn = serde_json::json!({
+                "field": field,
+                "query_vector": vector,
+                "k": k,
+                "num_candidates": num_candidates,
+            });
+            if let Some(filter) = filter {
+                knn["filter"] = filter;
+            }
+            if let Some(boost) = knn_boost {
+                knn["boost"] = serde_json::json!(boost);
+            }
+            let mut body = serde_json::json!({ "knn": knn, "size": k });
+            if let Some(query) = text_query {
+                body["query"] = boosted_query(query);
+            }
+            body
+        }
+        EsFlavor::OpenSearch => {
+            let mut params = serde_json::json!({ "vector": vector, "k": k });
+            if let Some(filter) = filter {
+                params["filter"] = filter;
+            }
+            if let Some(boost) = knn_boost {
+                params["boost"] = serde_json::json!(boost);
+            }
+            let knn = serde_json::json!({ "knn": { field: params } });
+            let query = match text_query {
+                None => knn,
+                Some(query) if args.get("search_pipeline").is_some() => {
+                    serde_json::json!({ "hybrid": { "queries": [query, knn] } })
+                }
+                Some(query) => {
+                    serde_json::json!({ "bool": { "should": [knn, boosted_query(query)] } })
+                }
+            };
+            serde_json::json!({ "query": query, "size": k })
+        }
+    };
+    if let Some(size) = args.get("size").and_then(|v| v.as_u64()) {
+        body["size"] = serde_json::json!(size);
+    }
+    if let Some(source) = args.get("_source") {
+        body["_source"] = source.clone();
+    }
+    Ok(body)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsKnnSearch {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let flavor = EsFlavor::from_config(config);
+        let index = args
+            .get("index")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing index".to_string())?;
+        crate::common::validation::validate_search_targets(index)?;
+        let field = args
+            .get("field")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing field".to_string())?;
+
+        let (vector, exclude_id) = match (args.get("vector"), args.get("id")) {
+            (Some(vector), None) => (knn_vector(vector, "vector")?, None),
+            (None, Some(id)) => {
+                let id = id
+                    .as_str()
+                    .ok_or_else(|| "id must be a string".to_string())?;
+                // Look the document up in its own index (or the one given).
+                let doc_index = args
+                    .get("id_index")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(index);
+                crate::common::validation::validate_index_name(doc_index, false)?;
+                let result = execute_es_json(
+                    "GET",
+                    &format!(
+                        "/{}/_doc/{}",
+                        crate::common::validation::url_encode_segment(doc_index),
+                        crate::common::validation::url_encode_segment(id)
+                    ),
+                    None,
+                    config,
+                    None,
+                )
+                .await?;
+                if result.get("status").and_then(|v| v.as_u64()).unwrap_or(0) >= 400 {
+                    return Err(format!(
+                        "Failed to fetch document '{}' from {}: {}",
+                        id,
+                        doc_index,
+                        result
+                            .get("message")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("not found")
+                    ));
+                }
+                let vector = result
+                    .pointer("/data/_source")
+                    .and_then(|source| source_field(source, field))
+                    .ok_or_else(|| {
+                        format!(
+                            "Document '{}' has no '{}' in _source (vectors excluded from _source cannot be reused; pass vector instead)",
+                            id, field
+                        )
+                    })?;
+                (knn_vector(vector, field)?, Some(id))
+            }
+            (Some(_), Some(_)) => return Err("Pass either vector or id, not both".to_string()),
+            (None, None) => return Err("Missing vector or id".to_string()),
+        };
+
+        let body = knn_search_body(args, flavor, &vector, exclude_id)?;
+        let mut path = format!(
+            "/{}/_search",
+            crate::common::validation::url_encode_segment(index)
+        );
+        if let Some(pipeline) = args.get("search_pipeline").and_then(|v| v.as_str()) {
+            if flavor != EsFlavor::OpenSearch {
+                return Err("search_pipeline is only available on OpenSearch".to_string());
+            }
+            path.push_str("?search_pipeline=");
+            path.push_str(&crate::common::validation::url_encode_segment(pipeline));
+        }
+        execute_es_http("POST", &path, Some(&body.to_string()), config, None).await
+    }
+}
+
+// ---- Cross-connection copy ----
+
+/// Default and maximum documents read per search page and written per `_bulk`.
+const COPY_DEFAULT_BATCH: u64 = 500;
+const COPY_MAX_BATCH: u64 = 10_000;
+/// Per-document bulk errors kept in the summary.
+const COPY_MAX_ERROR_SAMPLES: usize = 20;
+
+/// Index settings the cluster assigns itself; copying them fails the create.
+const COPY_SKIPPED_SETTINGS: &[&str] = &[
+    "uuid",
+    "creation_date",
+    "creation_date_string",
+    "provided_name",
+    "version",
+    "routing",
+    "resize",
+    "verified_before_close",
+    "blocks",
+    "history_uuid",
+    "lifecycle",
+    "shrink",
+    "frozen",
+    "replication",
+];
+
+/// Progress of a copy, saved after every batch so an interrupted copy can
+/// pick up from the last written page.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct CopyCheckpoint {
+    source_index: String,
+    target_index: String,
+    pit_id: Option<String>,
+    /// The paging sort `search_after` refers to; a resume must keep it.
+    #[serde(default)]
+    sort: Option<Value>,
+    search_after: Option<Value>,
+    copied: u64,
+    failed: u64,
+    skipped_existing: u64,
+    done: bool,
+}
+
+impl CopyCheckpoint {
+    fn load(path: &std::path::Path) -> Result<Option<Self>, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| format!("Corrupt copy checkpoint {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!(
+                "Failed to read copy checkpoint {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    fn save(&self, path: &std::path::Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text)
+            .map_err(|e| format!("Failed to write copy checkpoint {}: {}", path.display(), e))
+    }
+}
+
+/// Where copy checkpoints live: the app data dir, or the temp dir when the
+/// app handle is unavailable.
+fn copy_checkpoint_dir() -> std::path::PathBuf {
+    use tauri::Manager;
+    crate::APP_HANDLE
+        .get()
+        .and_then(|handle| handle.path().app_data_dir().ok())
+        .unwrap_or_else(std::env::temp_dir)
+        .join("es-copy-checkpoints")
+}
+
+/// Job ids name the checkpoint file, so only a safe character set is kept.
+fn copy_job_id(args: &Value, source_index: &str, target_index: &str) -> String {
+    let raw = match args.get("job_id").and_then(|v| v.as_str()) {
+        Some(id) if !id.is_empty() => id.to_string(),
+        _ => {
+            let target_connection = match args.get("target_connection_id") {
+                Some(Value::String(id)) => id.clone(),
+                Some(Value::Number(id)) => id.to_string(),
+                _ => "same".to_string(),
+            };
+            format!("{}-to-{}-{}", source_index, target_connection, target_index)
+        }
+    };
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The agent and MCP policy checks only see the source `connection_id`, so
+/// the copy target gets the same Elevated check here before it is resolved.
+fn check_copy_target_policy(
+    policy: &data_studio_agent::capabilities::permissions::McpPolicy,
+    target_connection_id: &str,
+) -> Result<(), String> {
+    if policy.allows(RiskLevel::Elevated, Some(target_connection_id)) {
+        return Ok(());
+    }
+    let reason = policy
+        .deny_reason(RiskLevel::Elevated, Some(target_connection_id))
+        .unwrap_or_else(|| "blocked by MCP policy".to_string());
+    Err(format!(
+        "Writing to target connection {} is not allowed: {}",
+        target_connection_id, reason
+    ))
+}
+
+/// Target connection config: the `target_connection_id` connection with its
+/// SSH tunnel started, or the source connection when none is given.
+async fn resolve_copy_target(args: &Value, source_config: &Value) -> Result<Value, String> {
+    use tauri::Manager;
+
+    let connection_id = match args.get("target_connection_id") {
+        None | Some(Value::Null) => return Ok(source_config.clone()),
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        Some(_) => return Err("target_connection_id must be a string or number".to_string()),
+    };
+    let handle = crate::APP_HANDLE
+        .get()
+        .ok_or_else(|| "AppHandle not initialized".to_string())?;
+    let app_data_dir = handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    check_copy_target_policy(
+        &crate::mcp_bridge::McpConfig::load(&app_data_dir).policy,
+        &connection_id,
+    )?;
+    let mut config =
+        crate::common::connection_resolver::ConnectionResolver::resolve(handle, &connection_id)?;
+    match config.get("type").and_then(|v| v.as_str()) {
+        Some("ELASTICSEARCH" | "OPENSEARCH" | "EASYSEARCH") => {}
+        other => {
+            return Err(format!(
+                "Target connection {} is not a search connection (type {})",
+                connection_id,
+                other.unwrap_or("unknown")
+            ))
+        }
+    }
+    crate::common::ssh_bridge::resolve_ssh_in_place(handle, &mut config).await?;
+    Ok(config)
+}
+
+/// Send a request and return the response body, failing on HTTP errors.
+async fn copy_request(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    config: &Value,
+    what: &str,
+) -> Result<Value, String> {
+    let result = execute_es_json(method, path, body, config, None).await?;
+    if result.get("status").and_then(|v| v.as_u64()).unwrap_or(0) >= 400 {
+        return Err(format!(
+            "{} failed: {}",
+            what,
+            result
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Request failed")
+        ));
+    }
+    Ok(result.get("data").cloned().unwrap_or(Value::Null))
+}
+
+/// Create the target index with the source's mapping and/or settings, unless
+/// it already exists. Returns a note for the summary.
+async fn copy_index_definition(
+    source_config: &Value,
+    target_config: &Value,
+    source_index: &str,
+    target_index: &str,
+    copy_mapping: bool,
+    copy_settings: bool,
+) -> Result<String, String> {
+    let target_path = format!(
+        "/{}",
+        crate::common::validation::url_encode_segment(target_index)
+    );
+    let exists = execute_es_json("HEAD", &target_path, None, target_config, None).await?;
+    match exists.get("status").and_then(|v| v.as_u64()).unwrap_or(0) {
+        404 => {}
+        200 => {
+            return Ok(format!(
+                "target index {} already exists; mapping and settings left unchanged",
+                target_index
+            ))
+        }
+        status => {
+            return Err(format!(
+                "Failed to check target index {}: HTTP {}",
+                target_index, status
+            ))
+        }
+    }
+
+    let source_path = crate::common::validation::url_encode_segment(source_index);
+    // The response is keyed by the concrete index name, which differs from
+    // the requested one when the source is an alias.
+    let first_entry = |data: Value, key: &str| {
+        data.as_object()
+            .and_then(|indices| indices.values().next())
+            .and_then(|entry| entry.get(key))
+            .cloned()
+    };
+    let mut body = serde_json::json!({});
+    if copy_mapping {
+        let data = copy_request(
+            "GET",
+            &format!("/{}/_mapping", source_path),
+            None,
+            source_config,
+            "Reading source mapping",
+        )
+        .await?;
+        if let Some(mappings) = first_entry(data, "mappings") {
+            body["mappings"] = mappings;
+        }
+    }
+    if copy_settings {
+        let data = copy_request(
+            "GET",
+            &format!("/{}/_settings", source_path),
+            None,
+            source_config,
+            "Reading source settings",
+        )
+        .await?;
+        if let Some(mut index_settings) =
+            first_entry(data, "settings").and_then(|s| s.get("index").cloned())
+        {
+            if let Some(settings) = index_settings.as_object_mut() {
+                for key in COPY_SKIPPED_SETTINGS {
+                    settings.remove(*key);
+                }
+            }
+            body["settings"] = serde_json::json!({ "index": index_settings });
+        }
+    }
+    copy_request(
+        "PUT",
+        &target_path,
+        Some(&body.to_string()),
+        target_config,
+        "Creating target index",
+    )
+    .await?;
+    let copied: Vec<&str> = [("mapping", copy_mapping), ("settings", copy_settings)]
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    Ok(format!(
+        "created target index {} with the source {}",
+        target_index,
+        copied.join(" and ")
+    ))
+}
+
+/// Remove the value at a dotted path (`a.b.c`, or a literal `a.b.c` key).
+fn take_source_path(source: &mut Value, path: &str) -> Option<Value> {
+    if let Some(value) = source.as_object_mut().and_then(|o| o.remove(path)) {
+        return Some(value);
+    }
+    let (parent, leaf) = path.rsplit_once('.')?;
+    parent
+        .split('.')
+        .try_fold(source, |v, key| v.get_mut(key))?
+        .as_object_mut()?
+        .remove(leaf)
+}
+
+/// Set the value at a dotted path, creating intermediate objects. Falls
+/// back to a literal dotted key when a non-object value is in the way; the
+/// path is checked before anything is created, so a fallback never leaves
+/// half-built objects behind.
+fn put_source_path(source: &mut Value, path: &str, value: Value) {
+    let Some((parent, leaf)) = path.rsplit_once('.') else {
+        if let Some(object) = source.as_object_mut() {
+            object.insert(path.to_string(), value);
+        }
+        return;
+    };
+    let mut probe = &*source;
+    let mut blocked = false;
+    for part in parent.split('.') {
+        match probe.get(part) {
+            Some(next) if next.is_object() => probe = next,
+            Some(_) => {
+                blocked = true;
+                break;
+            }
+            None => break,
+        }
+    }
+    let Some(root) = source.as_object_mut() else {
+        return;
+    };
+    if blocked {
+        root.insert(path.to_string(), value);
+        return;
+    }
+    let mut object = root;
+    for part in parent.split('.') {
+        let Some(next) = object
+            .entry(part.to_string())
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+        else {
+            return;
+        };
+        object = next;
+    }
+    object.insert(leaf.to_string(), value);
+}
+
+/// Apply the `rename` (old path → new path) and `drop` (paths) transforms.
+fn transform_copy_source(source: &mut Value, rename: &[(String, String)], drop: &[String]) {
+    for path in drop {
+        take_source_path(source, path);
+    }
+    for (from, to) in rename {
+        if let Some(value) = take_source_path(source, from) {
+            put_source_path(source, to, value);
+        }
+    }
+}
+
+/// Open a point in time over the source index.
+async fn open_copy_pit(
+    config: &Value,
+    flavor: EsFlavor,
+    index: &str,
+    keep_alive: &str,
+) -> Result<String, String> {
+    let index = crate::common::validation::url_encode_segment(index);
+    let keep_alive = crate::common::validation::url_encode_segment(keep_alive);
+    let (path, id_key) = match flavor {
+        EsFlavor::Elasticsearch => (format!("/{}/_pit?keep_alive={}", index, keep_alive), "id"),
+        EsFlavor::OpenSearch => (
+            format!("/{}/_search/point_in_time?keep_alive={}", index, keep_alive),
+            "pit_id",
+        ),
+    };
+    let data = copy_request("POST", &path, None, config, "Opening point in time").await?;
+    data.get(id_key)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "Point in time response has no id".to_string())
+}
+
+/// Close a point in time. Best effort: it expires on its own anyway.
+async fn close_copy_pit(config: &Value, flavor: EsFlavor, pit_id: &str) {
+    let (path, body) = match flavor {
+        EsFlavor::Elasticsearch => ("/_pit", serde_json::json!({ "id": pit_id })),
+        EsFlavor::OpenSearch => (
+            "/_search/point_in_time",
+            serde_json::json!({ "pit_id": [pit_id] }),
+        ),
+    };
+    let _ = execute_es_json("DELETE", path, Some(&body.to_string()), config, None).await;
+}
+
+/// Append a unique tiebreaker to a paging sort unless it already has one;
+/// without it, documents sharing a sort value can be skipped or repeated
+/// between pages. Elasticsearch gets `_shard_doc`, which is free but tied to
+/// the point in time: its checkpoints resume only while that PIT is still
+/// alive (see `keep_alive`). OpenSearch gets `_id`, which needs id fielddata
+/// (`indices.id_field_data.enabled`, on by default) and survives expiry.
+fn with_copy_tiebreaker(sort: Value, flavor: EsFlavor) -> Result<Value, String> {
+    let Value::Array(mut entries) = sort else {
+        return Err("sort must be an array".to_string());
+    };
+    let is_tiebreaker = |name: &str| name == "_id" || name == "_shard_doc";
+    let has_tiebreaker = entries.iter().any(|entry| match entry {
+        Value::String(name) => is_tiebreaker(name),
+        Value::Object(map) => map.keys().any(|name| is_tiebreaker(name)),
+        _ => false,
+    });
+    if !has_tiebreaker {
+        entries.push(match flavor {
+            EsFlavor::Elasticsearch => serde_json::json!({ "_shard_doc": "asc" }),
+            EsFlavor::OpenSearch => serde_json::json!({ "_id": "asc" }),
+        });
+    }
+    Ok(Value::Array(entries))
+}
+
+/// Whether a failed search means the point in time is gone.
+fn is_missing_pit(result: &Value) -> bool {
+    let status = result.get("status").and_then(|v| v.as_u64()).unwrap_or(0);
+    let message = result
+        .get("message")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_lowercase();
+    status == 404 || message.contains("search context") || message.contains("point in time")
+}
+
+/// `_bulk` NDJSON for one page of hits, after transforms.
+fn copy_bulk_body(
+    hits: &[Value],
+    target_index: &str,
+    op_type: &str,
+    rename: &[(String, String)],
+    drop: &[String],
+) -> String {
+    let mut body = String::new();
+    for hit in hits {
+        let mut action = serde_json::json!({ "_index": target_index });
+        if let Some(id) = hit.get("_id") {
+            action["_id"] = id.clone();
+        }
+        if let Some(routing) = hit.get("_routing") {
+            action["routing"] = routing.clone();
+        }
+        let mut source = hit
+            .get("_source")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+        transform_copy_source(&mut source, rename, drop);
+        body.push_str(&serde_json::json!({ op_type: action }).to_string());
+        body.push('\n');
+        body.push_str(&source.to_string());
+        body.push('\n');
+    }
+    body
+}
+
+/// Copy documents from `source_config` to `target_config`, checkpointing in
+/// `checkpoint_dir`. Returns the summary reported to the caller.
+async fn copy_index(
+    args: &Value,
+    source_config: &Value,
+    target_config: &Value,
+    checkpoint_dir: &std::path::Path,
+) -> Result<Value, String> {
+    let source_index = args
+        .get("index")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing index".to_string())?;
+    crate::common::validation::validate_search_targets(source_index)?;
+    let target_index = match args.get("target_index").and_then(|v| v.as_str()) {
+        Some(index) => index,
+        None if args.get("target_connection_id").is_some()
+            && !source_index.contains(['*', ',']) =>
+        {
+            source_index
+        }
+        None => return Err("Missing target_index".to_string()),
+    };
+    crate::common::validation::validate_index_name(target_index, false)?;
+    if args.get("target_connection_id").is_none() && target_index == source_index {
+        return Err("target_index must differ from index within one connection".to_string());
+    }
+    let batch_size = args
+        .get("batch_size")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(COPY_DEFAULT_BATCH);
+    if batch_size == 0 || batch_size > COPY_MAX_BATCH {
+        return Err(format!(
+            "batch_size must be between 1 and {}",
+            COPY_MAX_BATCH
+        ));
+    }
+    let max_docs = args.get("max_docs").and_then(|v| v.as_u64());
+    let keep_alive = args
+        .get("keep_alive")
+        .and_then(|v| v.as_str())
+        .unwrap_or("10m");
+    let op_type = args
+        .get("op_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("index");
+    if op_type != "index" && op_type != "create" {
+        return Err("op_type must be index or create".to_string());
+    }
+    let rename: Vec<(String, String)> = match args.get("rename") {
+        None => Vec::new(),
+        Some(rename) => rename
+            .as_object()
+            .ok_or_else(|| "rename must be an object of old path to new path".to_string())?
+            .iter()
+            .map(|(from, to)| {
+                to.as_str()
+                    .map(|to| (from.clone(), to.to_string()))
+                    .ok_or_else(|| format!("rename target for '{}' must be a string", from))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let drop: Vec<String> = match args.get("drop") {
+        None => Vec::new(),
+        Some(drop) => drop
+            .as_array()
+            .ok_or_else(|| "drop must be an array of field paths".to_string())?
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| "drop must be an array of field paths".to_string())
+            })
+            .collect::<Result<_, _>>()?,
+    };
+
+    let flavor = EsFlavor::from_config(source_config);
+    let mut sort = with_copy_tiebreaker(
+        args.get("sort")
+            .cloned()
+            .unwrap_or_else(|| Value::Array(Vec::new())),
+        flavor,
+    )?;
+    let job_id = copy_job_id(args, source_index, target_index);
+    let checkpoint_path = checkpoint_dir.join(format!("{}.json", job_id));
+    let resume = args
+        .get("resume")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let (mut checkpoint, resumed) = match CopyCheckpoint::load(&checkpoint_path)? {
+        Some(checkpoint) if resume => {
+            if checkpoint.source_index != source_index || checkpoint.target_index != target_index {
+                return Err(format!(
+                    "Checkpoint {} belongs to a copy of {} into {}",
+                    job_id, checkpoint.source_index, checkpoint.target_index
+                ));
+            }
+            (checkpoint, true)
+        }
+        None if resume => return Err(format!("No checkpoint found for job {}", job_id)),
+        _ => (
+            CopyCheckpoint {
+                source_index: source_index.to_string(),
+                target_index: target_index.to_string(),
+                ..Default::default()
+            },
+            false,
+        ),
+    };
+    if resumed {
+        match &checkpoint.sort {
+            Some(saved) if args.get("sort").is_none() => sort = saved.clone(),
+            Some(saved) if *saved != sort => {
+                return Err(format!(
+                    "Checkpoint {} was taken with sort {}; resume with the same sort",
+                    job_id, saved
+                ));
+            }
+            _ => {}
+        }
+    }
+    checkpoint.sort = Some(sort.clone());
+    // `_shard_doc` values are only meaningful within the PIT that produced
+    // them, so such a checkpoint can only resume while that PIT is alive.
+    let pit_bound_sort = sort.to_string().contains("_shard_doc");
+    if resumed && pit_bound_sort && checkpoint.search_after.is_some() && checkpoint.pit_id.is_none()
+    {
+        return Err(format!(
+            "Checkpoint {} has a _shard_doc position but no point in time to resume it from. Restart without resume",
+            job_id
+        ));
+    }
+    let summary = |checkpoint: &CopyCheckpoint, errors: &[Value], note: Option<&str>| {
+        let mut summary = serde_json::json!({
+            "job_id": job_id,
+            "source_index": checkpoint.source_index,
+            "target_index": checkpoint.target_index,
+            "copied": checkpoint.copied,
+            "failed": checkpoint.failed,
+            "skipped_existing": checkpoint.skipped_existing,
+            "done": checkpoint.done,
+            "resumed": resumed,
+            "checkpoint": checkpoint_path.display().to_string(),
+        });
+        if !errors.is_empty() {
+            summary["errors"] = serde_json::json!(errors);
+        }
+        if let Some(note) = note {
+            summary["target"] = serde_json::json!(note);
+        }
+        if !checkpoint.done {
+            summary["next"] = serde_json::json!(format!(
+                "Call again with resume: true and job_id: {} to continue",
+                job_id
+            ));
+        }
+        summary
+    };
+    if checkpoint.done {
+        return Ok(summary(&checkpoint, &[], None));
+    }
+
+    let copy_mapping = args
+        .get("copy_mapping")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let copy_settings = args
+        .get("copy_settings")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let note = if !resumed && (copy_mapping || copy_settings) {
+        Some(
+            copy_index_definition(
+                source_config,
+                target_config,
+                source_index,
+                target_index,
+                copy_mapping,
+                copy_settings,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    // A resumed copy first tries the checkpoint's PIT, which may have expired.
+    let mut pit_from_checkpoint = resumed && checkpoint.pit_id.is_some();
+    let mut pit_id = match checkpoint.pit_id.clone() {
+        Some(pit_id) => pit_id,
+        None => open_copy_pit(source_config, flavor, source_index, keep_alive).await?,
+    };
+    let mut errors: Vec<Value> = Vec::new();
+    let mut copied_this_run = 0u64;
+    loop {
+        let remaining = max_docs.map(|max| max.saturating_sub(copied_this_run));
+        if remaining == Some(0) {
+            break;
+        }
+        let size = remaining.map_or(batch_size, |r| r.min(batch_size));
+        let mut search = serde_json::json!({
+            "size": size,
+            "pit": { "id": pit_id, "keep_alive": keep_alive },
+            "sort": sort,
+        });
+        if let Some(query) = args.get("query") {
+            search["query"] = query.clone();
+        }
+        if let Some(search_after) = &checkpoint.search_after {
+            search["search_after"] = search_after.clone();
+        }
+        let result = execute_es_json(
+            "POST",
+            "/_search",
+            Some(&search.to_string()),
+            source_config,
+            None,
+        )
+        .await?;
+        if result.get("status").and_then(|v| v.as_u64()).unwrap_or(0) >= 400 {
+            if pit_from_checkpoint && is_missing_pit(&result) {
+                if pit_bound_sort && checkpoint.search_after.is_some() {
+                    return Err(format!(
+                        "The point in time of job {} has expired and its _shard_doc position cannot be reused. Restart without resume, or copy with a sort ending in _id (e.g. [{{\"_id\": \"asc\"}}], which needs indices.id_field_data.enabled) to make the copy resumable across expirations",
+                        job_id
+                    ));
+                }
+                pit_id = open_copy_pit(source_config, flavor, source_index, keep_alive).await?;
+                pit_from_checkpoint = false;
+                continue;
+            }
+            let message = result
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Request failed");
+            let hint = if message.contains("id_field_data") {
+                ". Sorting by _id needs indices.id_field_data.enabled; pass a sort on a unique field instead"
+            } else {
+                ""
+            };
+            return Err(format!(
+                "Reading from {} failed: {}{}",
+                source_index, message, hint
+            ));
+        }
+        pit_from_checkpoint = false;
+        let data = result.get("data").cloned().unwrap_or(Value::Null);
+        // The cluster may hand back a new PIT id on every page.
+        if let Some(id) = data.get("pit_id").and_then(|v| v.as_str()) {
+            pit_id = id.to_string();
+        }
+        checkpoint.pit_id = Some(pit_id.clone());
+        let hits = data
+            .pointer("/hits/hits")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        if hits.is_empty() {
+            checkpoint.done = true;
+            break;
+        }
+
+        let bulk = copy_bulk_body(&hits, target_index, op_type, &rename, &drop);
+        let response = copy_request(
+            "POST",
+            "/_bulk",
+            Some(&bulk),
+            target_config,
+            "Writing to target",
+        )
+        .await
+        .inspect_err(|_| {
+            // Keep the last completed page so a resume retries this one.
+            let _ = checkpoint.save(&checkpoint_path);
+        })?;
+        for item in response
+            .get("items")
+            .and_then(|v| v.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+        {
+            let Some(outcome) = item.get(op_type) else {
+                continue;
+            };
+            match outcome.get("status").and_then(|v| v.as_u64()).unwrap_or(0) {
+                200..=299 => checkpoint.copied += 1,
+                409 if op_type == "create" => checkpoint.skipped_existing += 1,
+                _ => {
+                    checkpoint.failed += 1;
+                    if errors.len() < COPY_MAX_ERROR_SAMPLES {
+                        errors.push(serde_json::json!({
+                            "_id": outcome.get("_id"),
+                            "error": outcome.get("error"),
+                        }));
+                    }
+                }
+            }
+        }
+        copied_this_run += hits.len() as u64;
+        checkpoint.search_after = hits.last().and_then(|hit| hit.get("sort")).cloned();
+        checkpoint.save(&checkpoint_path)?;
+        if (hits.len() as u64) < size {
+            checkpoint.done = true;
+            break;
+        }
+    }
+
+    if checkpoint.done {
+        close_copy_pit(source_config, flavor, &pit_id).await;
+        checkpoint.pit_id = None;
+    }
+    checkpoint.save(&checkpoint_path)?;
+    Ok(summary(&checkpoint, &errors, note.as_deref()))
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for EsCopyIndex {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "ES requires a connection config".to_string())?;
+        let target_config = resolve_copy_target(args, config).await?;
+        let summary = copy_index(args, config, &target_config, &copy_checkpoint_dir()).await?;
+        Ok(summary.to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registration
+// ---------------------------------------------------------------------------
+
+pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
+    // Each entry: (name, description, json_schema_type, is_required).
+    // json_schema_type is the OpenAPI type string ("string", "object", "integer", etc.);
+    // "string|object" declares a field that accepts either type.
+    let es_schema = |props: &[(&str, &str, &str, bool)]| -> Value {
+        let mut properties = serde_json::Map::new();
+        properties.insert(
+            "connection_id".to_string(),
+            serde_json::json!({"type": "string", "description": "ID of the target connection from the session"}),
+        );
+        for (name, desc, type_str, _required) in props {
+            let types: Vec<&str> = type_str.split('|').collect();
+            let type_val = match types.as_slice() {
+                [single] => Value::from(*single),
+                several => Value::from(several.to_vec()),
+            };
+            properties.insert(
+                name.to_string(),
+                serde_json::json!({"type": type_val, "description": desc}),
+            );
+        }
+        let required: Vec<String> = std::iter::once("connection_id".to_string())
+            .chain(
+                props
+                    .iter()
+                    .filter(|(_, _, _, r)| *r)
+                    .map(|(n, _, _, _)| n.to_string()),
+            )
+            .collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    };
+
+    macro_rules! reg {
+        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr, $parallel_ok:expr) => {
+            registry.register(Capability {
+                name: $name,
+                description: $desc,
+                handler: Arc::new($handler),
+                input_schema: $schema,
+                risk_level: $risk,
+                required_permission: $perm,
+                source_kind: SourceKind::Database("ELASTICSEARCH"),
+                tags: $tags,
+                parallel_ok: $parallel_ok,
+            });
+        };
+        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr) => {
+            reg!($name, $desc, $handler, $schema, $risk, $perm, $tags, false)
+        };
+    }
+
+    reg!("es__search", "Execute an Elasticsearch search query using Query DSL and return matching documents with scores.\n\nUse when a task needs data from Elasticsearch/OpenSearch (document counts, content search, aggregations) — instead of shelling out to curl or a local client.\n\nExample: {\"index\": \"orders*\", \"body\": {\"query\": {\"match\": {\"status\": \"shipped\"}}}}.", EsSearch,
+         es_schema(&[("index", "Target index, alias or data stream; comma-separate several and prefix later entries with - to exclude (e.g. logs-*,-logs-debug-*)", "string", true), ("body", "Elasticsearch Query DSL body", "object", true), ("expand_wildcards", "Which targets wildcards match: open (default), closed, hidden, all, none", "string", false)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!(
+        "es__get_document",
+        "Get a single document by its ID from an Elasticsearch index.",
+        EsGetDocument,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            ("id", "Document ID", "string", true)
+        ]),
+        RiskLevel::Safe,
+        "read",
+        &["agent"],
+        true
+    );
+
+    reg!(
+        "es__index_document",
+        "Create or replace a document in an Elasticsearch index. Omit id to auto-generate one.",
+        EsIndexDocument,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            (
+                "id",
+                "Optional document ID; omit to auto-generate",
+                "string",
+                false
+            ),
+            ("body", "Document body to index", "object", true)
+        ]),
+        RiskLevel::Elevated,
+        "create",
+        &["agent"]
+    );
+
+    reg!(
+        "es__update_document",
+        "Partially update an existing document in an Elasticsearch index using the Update API.",
+        EsUpdateDocument,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            ("id", "Document ID to update", "string", true),
+            ("body", "Update body", "object", true)
+        ]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent"]
+    );
+
+    reg!(
+        "es__delete_document",
+        "Delete a single document by ID from an Elasticsearch index.",
+        EsDeleteDocument,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            ("id", "Document ID to delete", "string", true)
+        ]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent"]
+    );
+
+    reg!(
+        "es__delete_by_query",
+        "Delete ALL documents matching a query. WARNING: bulk destructive operation. Pass wait_for_completion=false for large deletes and follow the returned task with es__get_task.",
+        EsDeleteByQuery,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            (
+                "body",
+                "Query DSL to match documents for deletion",
+                "object",
+                true
+            ),
+            (
+                "wait_for_completion",
+                "Set false to run as a background task and return its task ID immediately",
+                "boolean",
+                false
+            )
+        ]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent"]
+    );
+
+    reg!("es__cat_indices", "List user indices with health status, document count, and storage size. Results are sorted alphabetically. System/hidden indices (starting with . or _) are ONLY included when the user explicitly asks for them — pass include_system=true. NEVER include system indices in routine listing. Data streams appear only through their hidden .ds- backing indices; use es__list_data_streams for them. First step for any Elasticsearch task: list indices, then get_mapping to inspect structure, then search. Report results in the user's language (中文/English).", EsCatIndices,
+         es_schema(&[("include_system", "ONLY set to true when the user explicitly asks for system indices or hidden indices. Default false — system indices are excluded.", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"]);
+
+    reg!("es__get_mapping", "Get the field mapping (schema) for an Elasticsearch index, showing field names and data types. Use this to understand an index's structure before querying. Report results in the user's language (中文/English).", EsGetMapping,
+         es_schema(&[("index", "Target index name", "string", true)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!(
+        "es__create_index",
+        "Create a new Elasticsearch index with optional custom mappings and settings.",
+        EsCreateIndex,
+        es_schema(&[
+            ("index", "Name for the new index", "string", true),
+            (
+                "body",
+                "Optional index body with settings and mappings",
+                "object",
+                false
+            )
+        ]),
+        RiskLevel::Elevated,
+        "create",
+        &["agent"]
+    );
+
+    reg!("es__delete_index", "Delete an entire Elasticsearch index and all its data permanently. This action is IRREVERSIBLE.", EsDeleteIndex,
+         es_schema(&[("index", "Name of the index to delete", "string", true)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!(
+        "es__put_mapping",
+        "Add or update field mappings in an existing Elasticsearch index.",
+        EsPutMapping,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            ("body", "Mapping body", "object", true)
+        ]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent"]
+    );
+
+    reg!(
+        "es__cat_aliases",
+        "List all index aliases, their target indices, and routing configuration.",
+        EsCatAliases,
+        es_schema(&[]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "es__get_alias",
+        "Get the aliases defined on a specific index.",
+        EsGetAlias,
+        es_schema(&[("index", "Target index name", "string", true)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent"]
+    );
+
+    reg!(
+        "es__put_alias",
+        "Create or update an alias pointing to a specific index.",
+        EsPutAlias,
         es_schema(&[
             ("index", "Target index name", "string", true),
             ("name", "Alias name", "string", true),
@@ -844,943 +4422,3204 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
                 "object",
                 false
             )
-        ]),
-        RiskLevel::Elevated,
-        "update",
-        &["agent"]
-    );
+        ]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent"]
+    );
+
+    reg!(
+        "es__delete_alias",
+        "Remove an alias from a specific index. Does NOT delete the index or its data.",
+        EsDeleteAlias,
+        es_schema(&[
+            ("index", "Target index name", "string", true),
+            ("name", "Alias name to remove", "string", true)
+        ]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!("es__update_aliases", "Atomically add and/or remove multiple aliases in a single operation using the _aliases endpoint.", EsUpdateAliases,
+         es_schema(&[("body", "Alias actions body", "object", true)]),
+         RiskLevel::Elevated, "update", &["agent"]);
+
+    reg!("es__bulk", "Execute bulk index/create/update/delete operations in a single request using the NDJSON _bulk endpoint.\n\nUse when you need to batch multiple document operations (index, create, update, delete) for performance — instead of calling index_document/update_document/delete_document individually.\n\nExample: {\"index\": \"orders\", \"body\": \"{\\\"index\\\":{\\\"_id\\\":\\\"1\\\"}}\\n{\\\"status\\\":\\\"shipped\\\"}\\n{\\\"delete\\\":{\\\"_id\\\":\\\"2\\\"}}\\n\"}.", EsBulk,
+         es_schema(&[("index", "Target index name", "string", true), ("body", "NDJSON bulk operations body — one action+metadata line per operation, an optional source line for writes, separated by newlines", "string", true)]),
+         RiskLevel::Elevated, "create", &["agent"]);
+
+    reg!("es__count", "Count documents in an Elasticsearch index, optionally matching a query. Supports wildcard patterns and counting across all indices.\n\nUse when you need a quick document count (with optional query filter) — instead of running a full search and checking hits.total.value.\n\nExample: {\"index\": \"orders*\", \"body\": {\"query\": {\"term\": {\"status\": \"shipped\"}}}}.", EsCount,
+         es_schema(&[("index", "Target index name or pattern (supports wildcards, e.g. orders*). Omit to count all indices.", "string", false), ("body", "Optional Elasticsearch query DSL to filter documents before counting", "object", false)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!("es__reindex", "Copy documents from one index to another using the _reindex API, with optional query filtering and script transformations.\n\nUse when migrating data between indices, changing mappings, reindexing a subset of documents, or copying data to a new index.\n\nFor large indices pass wait_for_completion=false: the call returns a task ID at once, which es__get_task, es__rethrottle and es__cancel_task accept.\n\nExample: {\"body\": {\"source\": {\"index\": \"old-orders\"}, \"dest\": {\"index\": \"new-orders\"}}}.", EsReindex,
+         es_schema(&[("body", "Reindex request body with source.index and dest.index", "object", true), ("wait_for_completion", "Set false to run as a background task and return its task ID immediately; poll with es__get_task", "boolean", false)]),
+         RiskLevel::Elevated, "create", &["agent"]);
+
+    reg!("es__cluster_health", "Get the current health status of the Elasticsearch cluster (green/yellow/red) with active shards, pending tasks, and node counts.\n\nUse when checking cluster-wide health or diagnosing why queries/operations are failing.\n\nReport results in the user's language (中文/English).", EsClusterHealth,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__cat_nodes", "List all nodes in the Elasticsearch cluster with their name, role, heap usage, and load metrics (JSON format, sizes in bytes).\n\nUse when inspecting cluster topology, node resource usage, or which nodes participate in the cluster.\n\nReport results in the user's language (中文/English).", EsCatNodes,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__cat_shards", "List all shards across indices with their state (started/unassigned), node assignment, and sizes (JSON format).\n\nUse when inspecting shard distribution, finding unassigned shards, or debugging storage layout.\n\nReport results in the user's language (中文/English).", EsCatShards,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__list_snapshots", "List all snapshots across all registered snapshot repositories.\n\nUse when inspecting available backups before a restore operation.\n\nReport results in the user's language (中文/English).", EsListSnapshots,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__restore_snapshot", "Restore a snapshot into the cluster from a repository. DESTRUCTIVE WARNING: restoring a snapshot can overwrite existing indices and their data — verify the target indices before proceeding.\n\nUse when recovering data from a snapshot backup.\n\nExample: {\"repo\": \"my-backups\", \"snap\": \"snap-2026-01\", \"body\": {\"indices\": \"orders*\", \"rename_pattern\": \"(.+)\", \"rename_replacement\": \"restored_$1\"}}.", EsRestoreSnapshot,
+         es_schema(&[("repo", "Repository containing the snapshot", "string", true), ("snap", "Snapshot name to restore", "string", true), ("body", "Restore request body (indices, rename_pattern, rename_replacement, etc.)", "object", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__list_lifecycle_policies", "List index lifecycle policies. Uses ILM (_ilm/policy) on Elasticsearch and ISM (_plugins/_ism/policies) on OpenSearch, chosen by the connection type.\n\nUse when reviewing how rollover indices age through hot/warm/cold/delete phases.\n\nReport results in the user's language (中文/English).", EsListLifecyclePolicies,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!(
+        "es__get_lifecycle_policy",
+        "Get a single index lifecycle policy (ILM on Elasticsearch, ISM on OpenSearch) by ID.",
+        EsGetLifecyclePolicy,
+        es_schema(&[("policy", "Policy ID", "string", true)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__put_lifecycle_policy", "Create or replace an index lifecycle policy. The body is {\"policy\": {...}} in the ILM (phases) or ISM (states) format of the target cluster.\n\nExample (Elasticsearch): {\"policy\": \"logs\", \"body\": {\"policy\": {\"phases\": {\"hot\": {\"actions\": {\"rollover\": {\"max_age\": \"1d\"}}}, \"delete\": {\"min_age\": \"30d\", \"actions\": {\"delete\": {}}}}}}}.", EsPutLifecyclePolicy,
+         es_schema(&[("policy", "Policy ID", "string", true), ("body", "Policy body: {\"policy\": {...}}", "object", true), ("if_seq_no", "OpenSearch only: sequence number from get_lifecycle_policy, required when updating an existing ISM policy", "integer", false), ("if_primary_term", "OpenSearch only: primary term from get_lifecycle_policy, required when updating an existing ISM policy", "integer", false)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__delete_lifecycle_policy",
+        "Delete an index lifecycle policy. Indices still managed by it stop progressing through their lifecycle.",
+        EsDeleteLifecyclePolicy,
+        es_schema(&[("policy", "Policy ID to delete", "string", true)]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!("es__attach_lifecycle_policy", "Attach a lifecycle policy to existing indices. Sets index.lifecycle.name on Elasticsearch, or calls _plugins/_ism/add on OpenSearch.\n\nExample: {\"index\": \"logs-2026.*\", \"policy\": \"logs\"}.", EsAttachLifecyclePolicy,
+         es_schema(&[("index", "Target index name or pattern (supports wildcards)", "string", true), ("policy", "Policy ID to attach", "string", true)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!("es__explain_lifecycle", "Explain the lifecycle state of indices: managing policy, current phase/state, action, step, and any step error.\n\nUse when an index is stuck in a phase or a rollover did not happen.\n\nReport results in the user's language (中文/English).", EsExplainLifecycle,
+         es_schema(&[("index", "Target index name or pattern (supports wildcards)", "string", true), ("only_errors", "Elasticsearch only: return just the indices whose lifecycle step failed", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__retry_lifecycle", "Retry the failed lifecycle step for indices in an ERROR step. Run explain_lifecycle first and fix the cause.", EsRetryLifecycle,
+         es_schema(&[("index", "Target index name or pattern (supports wildcards)", "string", true), ("state", "OpenSearch only: ISM state to resume from instead of the failed one", "string", false)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__list_pipelines",
+        "List all ingest pipelines with their processors.",
+        EsListPipelines,
+        es_schema(&[]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!(
+        "es__get_pipeline",
+        "Get a single ingest pipeline definition by ID.",
+        EsGetPipeline,
+        es_schema(&[("id", "Pipeline ID", "string", true)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__put_pipeline", "Create or replace an ingest pipeline. Test the definition with simulate_pipeline before saving it.\n\nExample: {\"id\": \"parse-logs\", \"body\": {\"processors\": [{\"grok\": {\"field\": \"message\", \"patterns\": [\"%{IP:client} %{WORD:method}\"]}}]}}.", EsPutPipeline,
+         es_schema(&[("id", "Pipeline ID", "string", true), ("body", "Pipeline definition with description and processors", "object", true)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__delete_pipeline",
+        "Delete an ingest pipeline. Index requests or default_pipeline settings that still reference it will fail.",
+        EsDeletePipeline,
+        es_schema(&[("id", "Pipeline ID to delete", "string", true)]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!("es__simulate_pipeline", "Run sample documents through an ingest pipeline with _ingest/pipeline/_simulate and return the result of every processor (verbose by default). Does not write anything.\n\nUse when debugging grok/dissect/script processors. Test a saved pipeline by id, or an unsaved definition via pipeline. Pass docs inline, or pass index (with optional query/sample_size) to pull real documents via es__search.\n\nExample: {\"pipeline\": {\"processors\": [{\"dissect\": {\"field\": \"message\", \"pattern\": \"%{ts} %{level} %{msg}\"}}]}, \"docs\": [{\"message\": \"2026-01-01 INFO started\"}]}.", EsSimulatePipeline,
+         es_schema(&[("id", "Saved pipeline ID to simulate", "string", false), ("pipeline", "Inline pipeline definition; takes precedence over id", "object", false), ("docs", "Sample documents: plain sources, or objects with _index/_id/_source", "array", false), ("index", "Index to sample documents from when docs is omitted", "string", false), ("query", "Query DSL used when sampling from index (default match_all)", "object", false), ("sample_size", "Number of documents to sample from index (default 5, max 100)", "integer", false), ("verbose", "Return per-processor results (default true)", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!(
+        "es__list_snapshot_repositories",
+        "List registered snapshot repositories with their type and settings.",
+        EsListSnapshotRepositories,
+        es_schema(&[]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!(
+        "es__get_snapshot_repository",
+        "Get the type and settings of a single snapshot repository.",
+        EsGetSnapshotRepository,
+        es_schema(&[("repo", "Repository name", "string", true)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__put_snapshot_repository", "Register or update a snapshot repository. fs repositories require the location to be listed in path.repo on every node.\n\nExample: {\"repo\": \"local-backups\", \"body\": {\"type\": \"fs\", \"settings\": {\"location\": \"/mnt/backups\"}}}.", EsPutSnapshotRepository,
+         es_schema(&[("repo", "Repository name", "string", true), ("body", "Repository definition with type and settings", "object", true)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!(
+        "es__delete_snapshot_repository",
+        "Unregister a snapshot repository. Snapshot files stay in storage, but the cluster can no longer list or restore them until it is registered again.",
+        EsDeleteSnapshotRepository,
+        es_schema(&[("repo", "Repository name to unregister", "string", true)]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "es__verify_snapshot_repository",
+        "Verify that every node can access a snapshot repository. Returns the nodes that passed verification.",
+        EsVerifySnapshotRepository,
+        es_schema(&[("repo", "Repository name", "string", true)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__create_snapshot", "Start a snapshot in a repository. Returns immediately by default (wait_for_completion=false); poll es__snapshot_status for progress.\n\nExample: {\"repo\": \"local-backups\", \"snap\": \"orders-2026-10-19\", \"indices\": \"orders*,-orders-tmp\", \"body\": {\"include_global_state\": false}}.", EsCreateSnapshot,
+         es_schema(&[("repo", "Repository name", "string", true), ("snap", "Snapshot name", "string", true), ("indices", "Comma-separated index names or patterns; prefix with - to exclude. Omit to snapshot all indices", "string", false), ("body", "Additional snapshot options (include_global_state, partial, metadata, ...)", "object", false), ("wait_for_completion", "Block until the snapshot finishes (default false)", "boolean", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__snapshot_status", "Show the progress of snapshots: state, shards done/total and bytes processed/total with a percentage. Omit repo to list the snapshots currently running.\n\nUse to poll a snapshot started with es__create_snapshot.\n\nReport results in the user's language (中文/English).", EsSnapshotStatus,
+         es_schema(&[("repo", "Repository name", "string", false), ("snap", "Snapshot name (requires repo)", "string", false), ("include_details", "Include the per-index and per-shard breakdown (default false)", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__delete_snapshot", "Delete a snapshot from a repository, or abort it if still running. This action is IRREVERSIBLE.", EsDeleteSnapshot,
+         es_schema(&[("repo", "Repository name", "string", true), ("snap", "Snapshot name to delete", "string", true)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("es__list_tasks", "List tasks currently running in the cluster with their description, running time and cancellability. Filter by action (e.g. *reindex, *byquery) or node.\n\nUse when checking on long-running reindex, update/delete-by-query or snapshot operations.\n\nReport results in the user's language (中文/English).", EsListTasks,
+         es_schema(&[("actions", "Comma-separated action filter, wildcards allowed (e.g. *reindex,*byquery)", "string", false), ("nodes", "Comma-separated node IDs or names", "string", false), ("parent_task_id", "Only list children of this task", "string", false), ("group_by", "Group results by nodes (default), parents or none", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__get_task", "Get a single task by ID, including its status counters (total, created, updated, deleted, batches, version_conflicts) and, once finished, its response or error.\n\nUse to poll a task ID returned by es__reindex or es__delete_by_query with wait_for_completion=false.\n\nExample: {\"task_id\": \"oTUltX4IQMOUUVeiohTt8A:12345\"}.", EsGetTask,
+         es_schema(&[("task_id", "Task ID in <node_id>:<task_number> form", "string", true)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__cancel_task", "Cancel a running task. Work already done (e.g. documents already reindexed or deleted) is NOT rolled back.", EsCancelTask,
+         es_schema(&[("task_id", "Task ID in <node_id>:<task_number> form", "string", true)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!("es__rethrottle", "Change the requests_per_second throttle of a running reindex, update-by-query or delete-by-query task. Use -1 to remove throttling.\n\nExample: {\"task_id\": \"oTUltX4IQMOUUVeiohTt8A:12345\", \"operation\": \"reindex\", \"requests_per_second\": 500}.", EsRethrottle,
+         es_schema(&[("task_id", "Task ID in <node_id>:<task_number> form", "string", true), ("operation", "Task kind: reindex (default), update_by_query or delete_by_query", "string", false), ("requests_per_second", "New throttle; -1 for unlimited", "number", true)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!("es__update_by_query", "Update ALL documents matching a query with a painless script using _update_by_query. WARNING: bulk write operation. Run with dry_run=true first to report how many documents match without changing anything. Pass wait_for_completion=false for large updates and follow the returned task with es__get_task.\n\nExample: {\"index\": \"orders\", \"body\": {\"query\": {\"range\": {\"createdAt\": {\"lt\": \"2025-01-01\"}}}, \"script\": {\"source\": \"ctx._source.status = params.status\", \"params\": {\"status\": \"archived\"}}}}.", EsUpdateByQuery,
+         es_schema(&[("index", "Target index name or pattern", "string", true), ("body", "Request body with query and script", "object", true), ("dry_run", "Only count the matching documents; nothing is updated", "boolean", false), ("conflicts", "abort (default) or proceed on version conflicts", "string", false), ("wait_for_completion", "Set false to run as a background task and return its task ID immediately", "boolean", false)]),
+         RiskLevel::Destructive, "update", &["agent"]);
+
+    reg!("es__multi_search", "Run several searches in one round trip with _msearch. Each entry has its own index and Query DSL body; responses come back in the same order.\n\nUse when comparing counts or aggregations across indices — instead of calling es__search repeatedly.\n\nExample: {\"searches\": [{\"index\": \"orders-2025\", \"body\": {\"size\": 0, \"track_total_hits\": true}}, {\"index\": \"orders-2026\", \"body\": {\"size\": 0, \"track_total_hits\": true}}]}.", EsMultiSearch,
+         es_schema(&[("searches", "Array of {index, body} objects; body defaults to match_all", "array", true)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!("es__profile_search", "Run a search with profile=true and return a condensed summary ahead of the raw profile: slowest shards, the most expensive query nodes, collectors and aggregations with their times in ms.\n\nUse when a search is slow and you need to know which clause or aggregation costs the most.\n\nExample: {\"index\": \"orders\", \"body\": {\"query\": {\"wildcard\": {\"sku\": \"*-42\"}}}}.", EsProfileSearch,
+         es_schema(&[("index", "Target index name or pattern", "string", true), ("body", "Query DSL body; profile is enabled automatically", "object", false)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!("es__explain_document", "Explain how a query scores (or fails to match) one document using _explain. Returns matched, the score and a readable, indented score breakdown ahead of the raw explanation.\n\nUse when a document ranks unexpectedly or does not match.\n\nExample: {\"index\": \"products\", \"id\": \"42\", \"body\": {\"query\": {\"match\": {\"title\": \"rust book\"}}}}.", EsExplainDocument,
+         es_schema(&[("index", "Index containing the document", "string", true), ("id", "Document ID", "string", true), ("body", "Body with the query to explain", "object", true)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!("es__analyze", "Run text through an analyzer with the _analyze API and return the token stream with positions and offsets. Analyze with an index field's analyzer (index + field), a named analyzer, or an ad-hoc tokenizer/filter/char_filter chain. Pass compare_with to run a second chain side by side and see which terms differ.\n\nUse with es__get_mapping to debug why a query does not match a document.\n\nExample: {\"index\": \"products\", \"field\": \"title\", \"text\": \"Wi-Fi Routers\", \"compare_with\": {\"tokenizer\": \"whitespace\", \"filter\": [\"lowercase\"]}}.", EsAnalyze,
+         es_schema(&[("text", "Text to analyze, or an array of texts", "string|array", true), ("index", "Index whose analyzers/field mappings to use", "string", false), ("field", "Field whose search/index analyzer to use (requires index)", "string", false), ("analyzer", "Named analyzer, built-in or defined on the index", "string", false), ("tokenizer", "Tokenizer name or inline definition for an ad-hoc chain", "string|object", false), ("filter", "Token filters for an ad-hoc chain, names or inline definitions", "array", false), ("char_filter", "Character filters for an ad-hoc chain", "array", false), ("normalizer", "Normalizer name (keyword fields)", "string", false), ("compare_with", "Second chain to compare against: object with field/analyzer/tokenizer/filter/char_filter/normalizer", "object", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__get_index_settings", "Get the settings of an index (flat keys such as index.number_of_replicas, index.blocks.write). Set include_defaults to also list settings left at their defaults.\n\nReport results in the user's language (中文/English).", EsGetIndexSettings,
+         es_schema(&[("index", "Target index name or pattern", "string", true), ("include_defaults", "Include default values (default false)", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__put_index_settings", "Update dynamic index settings such as replicas, refresh_interval, write blocks or allocation filters. Static settings (e.g. number_of_shards) cannot be changed on an open index.\n\nExample: {\"index\": \"logs-2026.10\", \"body\": {\"index\": {\"number_of_replicas\": 0, \"refresh_interval\": \"30s\"}}}.", EsPutIndexSettings,
+         es_schema(&[("index", "Target index name or pattern", "string", true), ("body", "Settings to update", "object", true)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__open_index",
+        "Open a closed index so it can be searched and written again.",
+        EsOpenIndex,
+        es_schema(&[("index", "Target index name or pattern", "string", true)]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "es__close_index",
+        "Close an index. A closed index keeps its data on disk but rejects all reads and writes until it is opened again.",
+        EsCloseIndex,
+        es_schema(&[(
+            "index",
+            "Exact index name (wildcards and _all are rejected)",
+            "string",
+            true
+        )]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent", "ui"]
+    );
+
+    reg!("es__rollover", "Roll an alias (or data stream) over to a new write index, optionally only when conditions are met. Use dry_run to see which conditions currently match.\n\nExample: {\"alias\": \"logs-write\", \"body\": {\"conditions\": {\"max_age\": \"7d\", \"max_primary_shard_size\": \"50gb\"}}}.", EsRollover,
+         es_schema(&[("alias", "Write alias or data stream to roll over", "string", true), ("new_index", "Explicit name for the new index (default: increments the numeric suffix)", "string", false), ("body", "Optional conditions, settings, mappings and aliases for the new index", "object", false), ("dry_run", "Only evaluate the conditions", "boolean", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__shrink_index", "Shrink an index into a new index with fewer primary shards. Pre-flight checks that the source is write-blocked and that one node holds a copy of every shard, and reports what to fix otherwise.\n\nExample: {\"index\": \"logs-2026.09\", \"target\": \"logs-2026.09-shrunk\", \"body\": {\"settings\": {\"index.number_of_shards\": 1, \"index.blocks.write\": null, \"index.routing.allocation.require._name\": null}}}.", EsShrinkIndex,
+         es_schema(&[("index", "Source index", "string", true), ("target", "New index to create", "string", true), ("body", "Settings and aliases for the target index", "object", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__split_index", "Split an index into a new index with more primary shards (a multiple of the source's). Pre-flight checks that the source is write-blocked.\n\nExample: {\"index\": \"orders\", \"target\": \"orders-split\", \"body\": {\"settings\": {\"index.number_of_shards\": 4}}}.", EsSplitIndex,
+         es_schema(&[("index", "Source index", "string", true), ("target", "New index to create", "string", true), ("body", "Settings (including index.number_of_shards) and aliases for the target index", "object", true)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__clone_index", "Clone an index into a new index with the same shard count. Pre-flight checks that the source is write-blocked.", EsCloneIndex,
+         es_schema(&[("index", "Source index", "string", true), ("target", "New index to create", "string", true), ("body", "Settings and aliases for the target index", "object", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__forcemerge", "Force-merge the segments of an index. I/O heavy: run it only on indices that no longer receive writes.\n\nExample: {\"index\": \"logs-2026.09\", \"max_num_segments\": 1}.", EsForceMerge,
+         es_schema(&[("index", "Target index name or pattern; omit for all indices", "string", false), ("max_num_segments", "Number of segments to merge down to", "integer", false), ("only_expunge_deletes", "Only merge segments containing deleted documents", "boolean", false)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__refresh_index",
+        "Refresh an index so recent writes become visible to search. Omit index to refresh all indices.",
+        EsRefreshIndex,
+        es_schema(&[(
+            "index",
+            "Target index name or pattern; omit for all indices",
+            "string",
+            false
+        )]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "es__flush_index",
+        "Flush an index so operations in the translog are persisted to Lucene segments. Omit index to flush all indices.",
+        EsFlushIndex,
+        es_schema(&[(
+            "index",
+            "Target index name or pattern; omit for all indices",
+            "string",
+            false
+        )]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent", "ui"]
+    );
+
+    reg!("es__clear_cache", "Clear the query, fielddata and/or request caches of an index. With no flags every cache is cleared. Searches are slower until the caches warm up again.", EsClearCache,
+         es_schema(&[("index", "Target index name or pattern; omit for all indices", "string", false), ("query", "Clear the query cache", "boolean", false), ("fielddata", "Clear the fielddata cache", "boolean", false), ("request", "Clear the request cache", "boolean", false)]),
+         RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!("es__list_data_streams", "List data streams with their status, generation, index template, ILM policy, current write index and backing indices. Optionally filter by name or pattern.\n\nReport results in the user's language (中文/English).", EsListDataStreams,
+         es_schema(&[("name", "Data stream name or pattern (default: all)", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__create_data_stream", "Create a data stream. A matching index template with data_stream enabled must already exist; writing to such a name also creates the stream implicitly.", EsCreateDataStream,
+         es_schema(&[("name", "Data stream name", "string", true)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__delete_data_stream", "Delete a data stream together with ALL of its backing indices and documents. Irreversible — confirm with the user first.", EsDeleteDataStream,
+         es_schema(&[("name", "Data stream name", "string", true)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("es__rollover_data_stream", "Roll a data stream over to a new write backing index, optionally only when conditions are met. Use dry_run to evaluate the conditions.\n\nExample: {\"name\": \"logs-nginx-default\", \"body\": {\"conditions\": {\"max_age\": \"1d\"}}}.", EsRolloverDataStream,
+         es_schema(&[("name", "Data stream name", "string", true), ("body", "Optional rollover conditions", "object", false), ("dry_run", "Only evaluate the conditions", "boolean", false)]),
+         RiskLevel::Elevated, "create", &["agent", "ui"]);
+
+    reg!("es__data_stream_stats", "Get data stream statistics: backing index count, store size and maximum @timestamp per stream.", EsDataStreamStats,
+         es_schema(&[("name", "Data stream name or pattern (default: all)", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__allocation_explain", "Explain why a shard is unassigned or why it stays on its current node, including every allocation decider's verdict per node. Without a body, explains the first unassigned shard found.\n\nExample: {\"body\": {\"index\": \"orders\", \"shard\": 0, \"primary\": true}}.", EsAllocationExplain,
+         es_schema(&[("body", "Optional {index, shard, primary} of the shard to explain", "object", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__nodes_stats", "Get node statistics (JVM heap and GC, disk, CPU, thread pools, circuit breakers). Returns a per-node summary sorted by heap usage, followed by the raw stats.\n\nReport results in the user's language (中文/English).", EsNodesStats,
+         es_schema(&[("node_id", "Node name, ID or comma list (default: all nodes)", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__hot_threads", "Sample the busiest threads on each node (plain-text stack traces). Use when a node shows high CPU or slow responses.", EsHotThreads,
+         es_schema(&[("node_id", "Node name, ID or comma list (default: all nodes)", "string", false), ("threads", "Threads per node to report (default 3)", "integer", false), ("interval", "Sampling interval, e.g. 500ms (default 500ms)", "string", false), ("type", "cpu (default), wait, block or mem", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!(
+        "es__pending_tasks",
+        "List cluster-level changes (mappings, allocation, index creation) queued on the master node, with how long each has waited.",
+        EsPendingTasks,
+        es_schema(&[]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__cat_thread_pool", "List thread pools per node with active, queued, rejected and completed counts (JSON format). Rejections in write or search pools mean the node is overloaded.", EsCatThreadPool,
+         es_schema(&[("thread_pool", "Pool name(s), comma-separated, e.g. write,search (default: all)", "string", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__cat_recovery", "List shard recoveries (replica builds, relocations, snapshot restores) with stage and progress (JSON format, sizes in bytes).", EsCatRecovery,
+         es_schema(&[("index", "Target index name or pattern (default: all)", "string", false), ("active_only", "Only ongoing recoveries (default true)", "boolean", false)]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__diagnose_cluster", "Diagnose a red/yellow or struggling cluster in one call: runs cluster health, shards, nodes, node stats, pending tasks, thread pools and recoveries in parallel (plus allocation explain when shards are unassigned) and returns findings ordered critical → warning → info, each with a next step.\n\nUse first when the user reports cluster problems.\n\nReport results in the user's language (中文/English).", EsDiagnoseCluster,
+         es_schema(&[]),
+         RiskLevel::Safe, "read", &["agent", "ui"], true);
+
+    reg!("es__list_users", "List security users, or get one by name. Uses _security on Elasticsearch and the security plugin's internalusers on OpenSearch.", EsListUsers,
+         es_schema(&[("name", "User name (default: all users)", "string", false)]),
+         RiskLevel::Elevated, "read", &["agent", "ui"], true);
+
+    reg!("es__put_user", "Create a security user or replace an existing one. Security change — confirm with the user first.\n\nExample (Elasticsearch): {\"name\": \"reporter\", \"body\": {\"password\": \"...\", \"roles\": [\"viewer\"], \"full_name\": \"Report Bot\"}}. OpenSearch bodies use backend_roles/opendistro_security_roles.", EsPutUser,
+         es_schema(&[("name", "User name", "string", true), ("body", "User definition in the target cluster's format", "object", true)]),
+         RiskLevel::Destructive, "update", &["agent", "ui"]);
+
+    reg!("es__delete_user", "Delete a security user. Anyone authenticating as this user loses access immediately — confirm with the user first.", EsDeleteUser,
+         es_schema(&[("name", "User name", "string", true)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!(
+        "es__list_roles",
+        "List security roles with their cluster and index privileges, or get one by name.",
+        EsListRoles,
+        es_schema(&[("name", "Role name (default: all roles)", "string", false)]),
+        RiskLevel::Elevated,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("es__put_role", "Create a security role or replace an existing one. Security change — confirm with the user first.\n\nExample (Elasticsearch): {\"name\": \"logs_reader\", \"body\": {\"cluster\": [\"monitor\"], \"indices\": [{\"names\": [\"logs-*\"], \"privileges\": [\"read\"]}]}}. OpenSearch uses cluster_permissions/index_permissions.", EsPutRole,
+         es_schema(&[("name", "Role name", "string", true), ("body", "Role definition in the target cluster's format", "object", true)]),
+         RiskLevel::Destructive, "update", &["agent", "ui"]);
+
+    reg!("es__delete_role", "Delete a security role. Users and mappings referencing it lose its privileges — confirm with the user first.", EsDeleteRole,
+         es_schema(&[("name", "Role name", "string", true)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("es__list_role_mappings", "List role mappings (which users, groups or backend roles receive which roles), or get one by name. On OpenSearch the name is the mapped role.", EsListRoleMappings,
+         es_schema(&[("name", "Mapping name (default: all mappings)", "string", false)]),
+         RiskLevel::Elevated, "read", &["agent", "ui"], true);
+
+    reg!("es__put_role_mapping", "Create a role mapping or replace an existing one. Security change — confirm with the user first.\n\nExample (Elasticsearch): {\"name\": \"ldap-admins\", \"body\": {\"roles\": [\"superuser\"], \"enabled\": true, \"rules\": {\"field\": {\"groups\": \"cn=admins,dc=example,dc=com\"}}}}.", EsPutRoleMapping,
+         es_schema(&[("name", "Mapping name (OpenSearch: the role being mapped)", "string", true), ("body", "Mapping definition in the target cluster's format", "object", true)]),
+         RiskLevel::Destructive, "update", &["agent", "ui"]);
+
+    reg!(
+        "es__delete_role_mapping",
+        "Delete a role mapping. Confirm with the user first.",
+        EsDeleteRoleMapping,
+        es_schema(&[("name", "Mapping name", "string", true)]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!("es__create_api_key", "Create an Elasticsearch API key, optionally restricted by role descriptors and with an expiration. The secret is only returned once. Not available on OpenSearch.\n\nExample: {\"name\": \"ingest\", \"expiration\": \"30d\", \"role_descriptors\": {\"writer\": {\"indices\": [{\"names\": [\"logs-*\"], \"privileges\": [\"create_doc\"]}]}}}.", EsCreateApiKey,
+         es_schema(&[("name", "API key name", "string", true), ("role_descriptors", "Privileges the key is limited to (default: the creator's)", "object", false), ("expiration", "Lifetime, e.g. 30d (default: never expires)", "string", false), ("metadata", "Arbitrary metadata", "object", false)]),
+         RiskLevel::Destructive, "create", &["agent", "ui"]);
+
+    reg!("es__list_api_keys", "List Elasticsearch API keys (never their secrets), filtered by name, owner user or ID. Not available on OpenSearch.", EsListApiKeys,
+         es_schema(&[("name", "API key name (wildcards allowed)", "string", false), ("username", "Owner user name", "string", false), ("id", "API key ID", "string", false), ("owner", "Only keys owned by the connection's user", "boolean", false)]),
+         RiskLevel::Elevated, "read", &["agent", "ui"], true);
+
+    reg!("es__invalidate_api_key", "Invalidate Elasticsearch API keys by ID or name. Clients using them are rejected immediately — confirm with the user first.", EsInvalidateApiKey,
+         es_schema(&[("ids", "API key IDs", "array", false), ("name", "API key name", "string", false)]),
+         RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("es__has_privileges", "Check whether the connection's user holds the given cluster and index privileges. Elasticsearch only.\n\nExample: {\"body\": {\"cluster\": [\"monitor\"], \"index\": [{\"names\": [\"logs-*\"], \"privileges\": [\"read\", \"write\"]}]}}.", EsHasPrivileges,
+         es_schema(&[("body", "{cluster: [...], index: [{names, privileges}]}", "object", true)]),
+         RiskLevel::Elevated, "read", &["agent", "ui"], true);
+
+    reg!("es__knn_search", "Vector (kNN) search over a dense_vector (Elasticsearch 8) or knn_vector (OpenSearch) field, in the cluster's own syntax. Find neighbors of a vector or of an existing document's vector; add a Query DSL `query` for hybrid BM25+kNN scoring.\n\nUse instead of es__search for embedding similarity.\n\nExample: {\"index\": \"products\", \"field\": \"embedding\", \"id\": \"42\", \"k\": 5, \"filter\": {\"term\": {\"in_stock\": true}}}.", EsKnnSearch,
+         es_schema(&[("index", "Target index, alias or data stream", "string", true), ("field", "Vector field name", "string", true), ("vector", "Query vector (array of numbers); or pass id", "array", false), ("id", "Find neighbors of this document's vector (excluded from the results)", "string", false), ("id_index", "Index holding the id document, when it differs from index", "string", false), ("k", "Number of neighbors (default 10)", "integer", false), ("num_candidates", "Elasticsearch only: candidates per shard (default max(100, 10*k))", "integer", false), ("filter", "Query DSL filter applied during the kNN search", "object", false), ("query", "Query DSL text query combined with kNN for hybrid scoring", "object", false), ("knn_boost", "Weight of the kNN score in hybrid scoring", "number", false), ("query_boost", "Weight of the text query score in hybrid scoring", "number", false), ("search_pipeline", "OpenSearch only: normalization search pipeline; switches hybrid scoring to the hybrid query", "string", false), ("size", "Hits to return (default k)", "integer", false), ("_source", "Source filtering, e.g. false or [\"title\"]", "object", false)]),
+         RiskLevel::Safe, "read", &["agent"], true);
+
+    reg!("es__copy_index", "Copy an index between two DocKit connections (or within one) client-side: reads the source with a point in time and search_after, writes the target with _bulk. Works across clusters and SSH tunnels where reindex-from-remote is not whitelisted. Optionally creates the target with the source mapping/settings and renames or drops fields on the way. Progress is checkpointed after every batch; an interrupted copy continues with resume: true and the same job_id.\n\nUse es__reindex for copies within one cluster when no transform beyond a query is needed.\n\nExample: {\"index\": \"orders\", \"target_connection_id\": \"7\", \"target_index\": \"orders-v2\", \"copy_mapping\": true, \"rename\": {\"cust.name\": \"customer_name\"}, \"drop\": [\"internal\"]}.", EsCopyIndex,
+         es_schema(&[("index", "Source index, alias or pattern", "string", true), ("target_connection_id", "Target DocKit connection id (default: the source connection)", "string", false), ("target_index", "Target index (default: the source index name when copying to another connection)", "string", false), ("query", "Query DSL selecting the documents to copy", "object", false), ("copy_mapping", "Create the target with the source mapping when it does not exist", "boolean", false), ("copy_settings", "Create the target with the source index settings when it does not exist", "boolean", false), ("rename", "Field renames, old dotted path to new dotted path", "object", false), ("drop", "Dotted field paths removed from each document", "array", false), ("op_type", "index (default, overwrite) or create (skip existing ids)", "string", false), ("batch_size", "Documents per page and bulk request (default 500, max 10000)", "integer", false), ("max_docs", "Stop after this many documents in this call; resume later", "integer", false), ("sort", "Sort for paging (default _shard_doc on Elasticsearch, _id on OpenSearch, which needs indices.id_field_data.enabled). A sort without _id or _shard_doc gets one appended as a tiebreaker. A _shard_doc checkpoint resumes only while its point in time is alive (within keep_alive of the last call); only a sort with _id and no _shard_doc can resume after it expires. A resume reuses the checkpoint's sort", "array", false), ("keep_alive", "Point-in-time keep alive (default 10m); also bounds how long a _shard_doc copy stays resumable", "string", false), ("job_id", "Checkpoint name (default derived from the indices)", "string", false), ("resume", "Continue the job_id checkpoint instead of starting over", "boolean", false)]),
+         RiskLevel::Elevated, "create", &["agent"]);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    /// S1: SSH-tunneled HTTPS request must keep the original hostname for
+    /// SNI / certificate validation while TCP goes through the local tunnel
+    /// endpoint (regression for issue #472).
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_tunnel_preserves_hostname_for_tls() {
+        use crate::common::tls_test_server::{spawn_tls_server, test_root_certificate};
+
+        let addr = spawn_tls_server().await;
+        let config = json!({
+            "host": "127.0.0.1",
+            "port": addr.port(),
+            "tunnelOriginalHost": "es.example.com",
+            "sslCertVerification": true,
+        });
+
+        let result = super::execute_es_http(
+            "GET",
+            "/",
+            None,
+            &config,
+            Some(vec![test_root_certificate()]),
+        )
+        .await;
+
+        assert!(
+            result.is_ok(),
+            "tunneled TLS request must succeed, got: {:?}",
+            result.err()
+        );
+        assert!(
+            result.unwrap().contains("\"status\":200"),
+            "expected 200 from TLS server"
+        );
+    }
+
+    /// A connection's `caBundle` and `tlsServerName` let a direct request to
+    /// the bare IP validate a certificate issued by an internal CA.
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_applies_ca_bundle_and_server_name() {
+        use crate::common::tls_test_server::{spawn_tls_server, test_root_certificate_pem};
+
+        let addr = spawn_tls_server().await;
+        let config = json!({
+            "host": "127.0.0.1",
+            "port": addr.port(),
+            "sslCertVerification": true,
+            "caBundle": test_root_certificate_pem(),
+            "tlsServerName": "es.example.com",
+        });
+
+        let result = super::execute_es_http("GET", "/", None, &config, None).await;
+        assert!(
+            result.is_ok(),
+            "CA bundle request must succeed, got: {:?}",
+            result.err()
+        );
+        assert!(result.unwrap().contains("\"status\":200"));
+
+        // Without the override the certificate does not match the IP.
+        let mut config = config;
+        config.as_object_mut().unwrap().remove("tlsServerName");
+        assert!(super::execute_es_http("GET", "/", None, &config, None)
+            .await
+            .is_err());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn mock_config(server: &wiremock::MockServer) -> serde_json::Value {
+        let addr = server.address();
+        json!({"host": format!("http://{}", addr.ip()), "port": addr.port()})
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_get() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/indices"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"[{"index":"my-index","health":"green"}]"#),
+            )
+            .mount(&server)
+            .await;
+
+        let result =
+            super::execute_es_http("GET", "/_cat/indices", None, &mock_config(&server), None).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("my-index"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_post_with_body() {
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_search"))
+            .and(body_json(json!({"query": {"match_all": {}}})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"hits":{"total":{"value":1},"hits":[]}}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let result = super::execute_es_http(
+            "POST",
+            "/my-index/_search",
+            Some(r#"{"query":{"match_all":{}}}"#),
+            &mock_config(&server),
+            None,
+        )
+        .await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        let body = result.unwrap();
+        assert!(
+            body.contains("hits"),
+            "response should contain query hits, got: {}",
+            body
+        );
+        assert!(
+            body.contains("\"status\":200"),
+            "response should have 200 status, got: {}",
+            body
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_handles_404() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"error":"not_found"}"#))
+            .mount(&server)
+            .await;
+
+        let result =
+            super::execute_es_http("GET", "/missing", None, &mock_config(&server), None).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("404"));
+    }
+
+    // ---- Handler-level tests through CapabilityHandler::handle() ----
+
+    #[tokio::test]
+    async fn test_handler_rejects_missing_config() {
+        use super::CapabilityHandler;
+        use super::EsSearch;
+
+        let handler = EsSearch;
+        let args = json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
+        let result = handler.handle(&args, None).await;
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.contains("connection config"), "got: {}", err);
+    }
+
+    #[tokio::test]
+    async fn test_es_search_missing_index() {
+        use super::CapabilityHandler;
+        use super::EsSearch;
+
+        let handler = EsSearch;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"body": {"query": {"match_all": {}}}});
+        let result = handler.handle(&args, Some(&config)).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing index"));
+    }
+
+    #[tokio::test]
+    async fn test_es_get_document_missing_index() {
+        use super::CapabilityHandler;
+        use super::EsGetDocument;
+
+        let handler = EsGetDocument;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"id": "doc-1"});
+        let result = handler.handle(&args, Some(&config)).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing index"));
+    }
+
+    #[tokio::test]
+    async fn test_es_get_document_missing_id() {
+        use super::CapabilityHandler;
+        use super::EsGetDocument;
+
+        let handler = EsGetDocument;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"index": "my-index"});
+        let result = handler.handle(&args, Some(&config)).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing id"));
+    }
+
+    #[tokio::test]
+    async fn test_es_search_invalid_index_name() {
+        use super::CapabilityHandler;
+        use super::EsSearch;
+
+        let handler = EsSearch;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"index": "INVALID/INDEX", "body": {"query": {"match_all": {}}}});
+        let result = handler.handle(&args, Some(&config)).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("invalid characters"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_create_index_via_wiremock() {
+        use super::CapabilityHandler;
+        use super::EsCreateIndex;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test-index"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsCreateIndex;
+        let args = json!({"index": "test-index", "body": {"settings": {"number_of_shards": 1}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_delete_index_via_wiremock() {
+        use super::CapabilityHandler;
+        use super::EsDeleteIndex;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/old-index"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsDeleteIndex;
+        let args = json!({"index": "old-index"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_search_happy_path_via_wiremock() {
+        use super::CapabilityHandler;
+        use super::EsSearch;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_search"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"hits":{"total":{"value":2},"hits":[{"_index":"my-index","_id":"1","_source":{"name":"hello"}}]}}"#,
+                ),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsSearch;
+        let args = json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        let body = result.unwrap();
+        assert!(
+            body.contains("hits"),
+            "expected hits in response, got: {}",
+            body
+        );
+        assert!(
+            body.contains("my-index"),
+            "expected index name in response, got: {}",
+            body
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_handles_non_json_response() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/indices"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("plain text response"))
+            .mount(&server)
+            .await;
+
+        let result = super::execute_es_http(
+            "GET",
+            "/_cat/indices?format=json&expand_wildcards=all",
+            None,
+            &mock_config(&server),
+            None,
+        )
+        .await;
+
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("plain text response"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_es_http_bad_method() {
+        let server = wiremock::MockServer::start().await;
+        // reqwest rejects methods with spaces as invalid tokens
+        let result =
+            super::execute_es_http("BAD METHOD", "/test", None, &mock_config(&server), None).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Bad method"));
+    }
+
+    // ---- Remaining handler tests ----
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_index_document_with_id() {
+        use super::{CapabilityHandler, EsIndexDocument};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_doc/doc-1"))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_string(r#"{"_id":"doc-1","result":"created"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsIndexDocument;
+        let args =
+            serde_json::json!({"index": "my-index", "id": "doc-1", "body": {"title": "hello"}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("created"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_index_document_without_id() {
+        use super::{CapabilityHandler, EsIndexDocument};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_doc"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_string(r#"{"_id":"auto-id","result":"created"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsIndexDocument;
+        let args = serde_json::json!({"index": "my-index", "body": {"title": "hello"}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("auto-id"));
+    }
+
+    #[tokio::test]
+    async fn test_es_index_document_missing_index() {
+        use super::{CapabilityHandler, EsIndexDocument};
+        let handler = EsIndexDocument;
+        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
+        let args = serde_json::json!({"body": {"title": "hello"}});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing index"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_update_document_via_wiremock() {
+        use super::{CapabilityHandler, EsUpdateDocument};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_update/doc-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"_id":"doc-1","result":"updated"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsUpdateDocument;
+        let args = serde_json::json!({"index": "my-index", "id": "doc-1", "body": {"doc": {"title": "updated"}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("updated"));
+    }
+
+    #[tokio::test]
+    async fn test_es_update_document_missing_id() {
+        use super::{CapabilityHandler, EsUpdateDocument};
+        let handler = EsUpdateDocument;
+        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
+        let args = serde_json::json!({"index": "my-index", "body": {"doc": {"title": "updated"}}});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing id"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_delete_document_via_wiremock() {
+        use super::{CapabilityHandler, EsDeleteDocument};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/my-index/_doc/doc-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"_id":"doc-1","result":"deleted"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsDeleteDocument;
+        let args = serde_json::json!({"index": "my-index", "id": "doc-1"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("deleted"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_delete_by_query_via_wiremock() {
+        use super::{CapabilityHandler, EsDeleteByQuery};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_delete_by_query"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"deleted":5}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsDeleteByQuery;
+        let args = serde_json::json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("deleted"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_get_mapping_via_wiremock() {
+        use super::{CapabilityHandler, EsGetMapping};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/my-index/_mapping"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"my-index":{"mappings":{"properties":{"name":{"type":"text"}}}}}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let handler = EsGetMapping;
+        let args = serde_json::json!({"index": "my-index"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("text"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_put_mapping_via_wiremock() {
+        use super::{CapabilityHandler, EsPutMapping};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/my-index/_mapping"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsPutMapping;
+        let args = serde_json::json!({"index": "my-index", "body": {"properties": {"new_field": {"type": "keyword"}}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_cat_aliases_via_wiremock() {
+        use super::{CapabilityHandler, EsCatAliases};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/aliases"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"[{"alias":"my-alias","index":"my-index"}]"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsCatAliases;
+        let args = serde_json::json!({});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("my-alias"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_get_alias_via_wiremock() {
+        use super::{CapabilityHandler, EsGetAlias};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/my-index/_alias"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"my-index":{"aliases":{"my-alias":{}}}}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsGetAlias;
+        let args = serde_json::json!({"index": "my-index"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("my-alias"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_put_alias_via_wiremock() {
+        use super::{CapabilityHandler, EsPutAlias};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/my-index/_alias/my-alias"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsPutAlias;
+        let args = serde_json::json!({"index": "my-index", "name": "my-alias"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_delete_alias_via_wiremock() {
+        use super::{CapabilityHandler, EsDeleteAlias};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/my-index/_alias/my-alias"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsDeleteAlias;
+        let args = serde_json::json!({"index": "my-index", "name": "my-alias"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_update_aliases_via_wiremock() {
+        use super::{CapabilityHandler, EsUpdateAliases};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/_aliases"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsUpdateAliases;
+        let args = serde_json::json!({"body": {"actions": [{"add": {"index": "my-index", "alias": "my-alias"}}]}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    // ---- EsCatIndices specific tests ----
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_cat_indices_include_system() {
+        use super::CapabilityHandler;
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/indices"))
+            .and(query_param("expand_wildcards", "all"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"[
+                    {"index":".system-index","health":"green"},
+                    {"index":"user-index","health":"yellow"},
+                    {"index":"_internal","health":"green"}
+                ]"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let handler = super::EsCatIndices;
+        let args = serde_json::json!({"include_system": true});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        let body = result.unwrap();
+        assert!(body.contains("user-index"), "should include user indices");
+        assert!(
+            body.contains(".system-index"),
+            "should include system indices when requested"
+        );
+        assert!(
+            body.contains("_internal"),
+            "should include internal indices"
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_cat_indices_sorts_results() {
+        use super::CapabilityHandler;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/indices"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"[
+                    {"index":"z-last","health":"green"},
+                    {"index":"a-first","health":"green"},
+                    {"index":"m-middle","health":"yellow"}
+                ]"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let handler = super::EsCatIndices;
+        let args = serde_json::json!({});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        let body = result.unwrap();
+        // All three index names should appear in the response
+        assert!(body.contains("a-first"));
+        assert!(body.contains("m-middle"));
+        assert!(body.contains("z-last"));
+    }
+
+    // ---- register_all ----
+
+    #[tokio::test]
+    async fn test_es_register_all_registers_capabilities() {
+        use data_studio_agent::capabilities::registry::CapabilityRegistry;
+
+        let mut reg = CapabilityRegistry::new();
+        super::register_all(&mut reg);
+
+        assert!(reg.get("es__search").is_some());
+        assert!(reg.get("es__get_document").is_some());
+        assert!(reg.get("es__index_document").is_some());
+        assert!(reg.get("es__update_document").is_some());
+        assert!(reg.get("es__delete_document").is_some());
+        assert!(reg.get("es__delete_by_query").is_some());
+        assert!(reg.get("es__cat_indices").is_some());
+        assert!(reg.get("es__get_mapping").is_some());
+        assert!(reg.get("es__create_index").is_some());
+        assert!(reg.get("es__delete_index").is_some());
+        assert!(reg.get("es__put_mapping").is_some());
+        assert!(reg.get("es__cat_aliases").is_some());
+        assert!(reg.get("es__get_alias").is_some());
+        assert!(reg.get("es__put_alias").is_some());
+        assert!(reg.get("es__delete_alias").is_some());
+        assert!(reg.get("es__update_aliases").is_some());
+        assert!(reg.get("es__bulk").is_some());
+        assert!(reg.get("es__count").is_some());
+        assert!(reg.get("es__reindex").is_some());
+        assert!(reg.get("es__cluster_health").is_some());
+        assert!(reg.get("es__cat_nodes").is_some());
+        assert!(reg.get("es__cat_shards").is_some());
+        assert!(reg.get("es__list_snapshots").is_some());
+        assert!(reg.get("es__restore_snapshot").is_some());
+        assert!(reg.get("es__list_lifecycle_policies").is_some());
+        assert!(reg.get("es__get_lifecycle_policy").is_some());
+        assert!(reg.get("es__put_lifecycle_policy").is_some());
+        assert!(reg.get("es__delete_lifecycle_policy").is_some());
+        assert!(reg.get("es__attach_lifecycle_policy").is_some());
+        assert!(reg.get("es__explain_lifecycle").is_some());
+        assert!(reg.get("es__retry_lifecycle").is_some());
+        assert!(reg.get("es__list_pipelines").is_some());
+        assert!(reg.get("es__get_pipeline").is_some());
+        assert!(reg.get("es__put_pipeline").is_some());
+        assert!(reg.get("es__delete_pipeline").is_some());
+        assert!(reg.get("es__simulate_pipeline").is_some());
+        assert!(reg.get("es__list_snapshot_repositories").is_some());
+        assert!(reg.get("es__get_snapshot_repository").is_some());
+        assert!(reg.get("es__put_snapshot_repository").is_some());
+        assert!(reg.get("es__delete_snapshot_repository").is_some());
+        assert!(reg.get("es__verify_snapshot_repository").is_some());
+        assert!(reg.get("es__create_snapshot").is_some());
+        assert!(reg.get("es__snapshot_status").is_some());
+        assert!(reg.get("es__delete_snapshot").is_some());
+        assert!(reg.get("es__list_tasks").is_some());
+        assert!(reg.get("es__get_task").is_some());
+        assert!(reg.get("es__cancel_task").is_some());
+        assert!(reg.get("es__rethrottle").is_some());
+        assert!(reg.get("es__update_by_query").is_some());
+        assert!(reg.get("es__multi_search").is_some());
+        assert!(reg.get("es__profile_search").is_some());
+        assert!(reg.get("es__explain_document").is_some());
+        assert!(reg.get("es__analyze").is_some());
+        assert!(reg.get("es__get_index_settings").is_some());
+        assert!(reg.get("es__put_index_settings").is_some());
+        assert!(reg.get("es__open_index").is_some());
+        assert!(reg.get("es__close_index").is_some());
+        assert!(reg.get("es__rollover").is_some());
+        assert!(reg.get("es__shrink_index").is_some());
+        assert!(reg.get("es__split_index").is_some());
+        assert!(reg.get("es__clone_index").is_some());
+        assert!(reg.get("es__forcemerge").is_some());
+        assert!(reg.get("es__refresh_index").is_some());
+        assert!(reg.get("es__flush_index").is_some());
+        assert!(reg.get("es__clear_cache").is_some());
+        assert!(reg.get("es__list_data_streams").is_some());
+        assert!(reg.get("es__create_data_stream").is_some());
+        assert!(reg.get("es__delete_data_stream").is_some());
+        assert!(reg.get("es__rollover_data_stream").is_some());
+        assert!(reg.get("es__data_stream_stats").is_some());
+        assert!(reg.get("es__allocation_explain").is_some());
+        assert!(reg.get("es__nodes_stats").is_some());
+        assert!(reg.get("es__hot_threads").is_some());
+        assert!(reg.get("es__pending_tasks").is_some());
+        assert!(reg.get("es__cat_thread_pool").is_some());
+        assert!(reg.get("es__cat_recovery").is_some());
+        assert!(reg.get("es__diagnose_cluster").is_some());
+        assert!(reg.get("es__list_users").is_some());
+        assert!(reg.get("es__put_user").is_some());
+        assert!(reg.get("es__delete_user").is_some());
+        assert!(reg.get("es__list_roles").is_some());
+        assert!(reg.get("es__put_role").is_some());
+        assert!(reg.get("es__delete_role").is_some());
+        assert!(reg.get("es__list_role_mappings").is_some());
+        assert!(reg.get("es__put_role_mapping").is_some());
+        assert!(reg.get("es__delete_role_mapping").is_some());
+        assert!(reg.get("es__create_api_key").is_some());
+        assert!(reg.get("es__list_api_keys").is_some());
+        assert!(reg.get("es__invalidate_api_key").is_some());
+        assert!(reg.get("es__has_privileges").is_some());
+        assert!(reg.get("es__knn_search").is_some());
+        assert!(reg.get("es__copy_index").is_some());
+
+        let all_agent = reg.agent_tools();
+        let es_agent: Vec<_> = all_agent
+            .iter()
+            .filter(|c| c.name.starts_with("es__"))
+            .collect();
+        assert_eq!(
+            es_agent.len(),
+            92,
+            "expected 92 ES capabilities tagged for agent"
+        );
+    }
+
+    // ---- EsBulk ----
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_bulk_via_wiremock() {
+        use super::{CapabilityHandler, EsBulk};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_bulk"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"errors":false,"items":[{"index":{"_id":"1","result":"created"}},{"delete":{"_id":"2","result":"deleted"}}]}"#),
+            )
+            .mount(&server)
+            .await;
 
-    reg!(
-        "es__delete_alias",
-        "Remove an alias from a specific index. Does NOT delete the index or its data.",
-        EsDeleteAlias,
-        es_schema(&[
-            ("index", "Target index name", "string", true),
-            ("name", "Alias name to remove", "string", true)
-        ]),
-        RiskLevel::Destructive,
-        "delete",
-        &["agent", "ui"]
-    );
+        let handler = EsBulk;
+        let body =
+            "{\"index\":{\"_id\":\"1\"}}\n{\"title\":\"hello\"}\n{\"delete\":{\"_id\":\"2\"}}\n";
+        let args = serde_json::json!({"index": "my-index", "body": body});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("items"));
+    }
 
-    reg!("es__update_aliases", "Atomically add and/or remove multiple aliases in a single operation using the _aliases endpoint.", EsUpdateAliases,
-         es_schema(&[("body", "Alias actions body", "object", true)]),
-         RiskLevel::Elevated, "update", &["agent"]);
+    #[tokio::test]
+    async fn test_es_bulk_missing_index() {
+        use super::{CapabilityHandler, EsBulk};
+        let handler = EsBulk;
+        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
+        let args = serde_json::json!({"body": "{}"});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing index"));
+    }
 
-    reg!("es__bulk", "Execute bulk index/create/update/delete operations in a single request using the NDJSON _bulk endpoint.\n\nUse when you need to batch multiple document operations (index, create, update, delete) for performance — instead of calling index_document/update_document/delete_document individually.\n\nExample: {\"index\": \"orders\", \"body\": \"{\\\"index\\\":{\\\"_id\\\":\\\"1\\\"}}\\n{\\\"status\\\":\\\"shipped\\\"}\\n{\\\"delete\\\":{\\\"_id\\\":\\\"2\\\"}}\\n\"}.", EsBulk,
-         es_schema(&[("index", "Target index name", "string", true), ("body", "NDJSON bulk operations body — one action+metadata line per operation, an optional source line for writes, separated by newlines", "string", true)]),
-         RiskLevel::Elevated, "create", &["agent"]);
+    #[tokio::test]
+    async fn test_es_bulk_missing_body() {
+        use super::{CapabilityHandler, EsBulk};
+        let handler = EsBulk;
+        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
+        let args = serde_json::json!({"index": "my-index"});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing body"));
+    }
 
-    reg!("es__count", "Count documents in an Elasticsearch index, optionally matching a query. Supports wildcard patterns and counting across all indices.\n\nUse when you need a quick document count (with optional query filter) — instead of running a full search and checking hits.total.value.\n\nExample: {\"index\": \"orders*\", \"body\": {\"query\": {\"term\": {\"status\": \"shipped\"}}}}.", EsCount,
-         es_schema(&[("index", "Target index name or pattern (supports wildcards, e.g. orders*). Omit to count all indices.", "string", false), ("body", "Optional Elasticsearch query DSL to filter documents before counting", "object", false)]),
-         RiskLevel::Safe, "read", &["agent"], true);
+    // ---- EsCount ----
 
-    reg!("es__reindex", "Copy documents from one index to another using the _reindex API, with optional query filtering and script transformations.\n\nUse when migrating data between indices, changing mappings, reindexing a subset of documents, or copying data to a new index.\n\nExample: {\"body\": {\"source\": {\"index\": \"old-orders\"}, \"dest\": {\"index\": \"new-orders\"}}}.", EsReindex,
-         es_schema(&[("body", "Reindex request body with source.index and dest.index", "object", true)]),
-         RiskLevel::Elevated, "create", &["agent"]);
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_count_with_index_via_wiremock() {
+        use super::{CapabilityHandler, EsCount};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    reg!("es__cluster_health", "Get the current health status of the Elasticsearch cluster (green/yellow/red) with active shards, pending tasks, and node counts.\n\nUse when checking cluster-wide health or diagnosing why queries/operations are failing.\n\nReport results in the user's language (中文/English).", EsClusterHealth,
-         es_schema(&[]),
-         RiskLevel::Safe, "read", &["agent", "ui"], true);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_count"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"count":42,"_shards":{"total":1,"successful":1}}"#),
+            )
+            .mount(&server)
+            .await;
 
-    reg!("es__cat_nodes", "List all nodes in the Elasticsearch cluster with their name, role, heap usage, and load metrics (JSON format, sizes in bytes).\n\nUse when inspecting cluster topology, node resource usage, or which nodes participate in the cluster.\n\nReport results in the user's language (中文/English).", EsCatNodes,
-         es_schema(&[]),
-         RiskLevel::Safe, "read", &["agent", "ui"], true);
+        let handler = EsCount;
+        let args = serde_json::json!({"index": "my-index"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("count"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_count_all_indices_via_wiremock() {
+        use super::{CapabilityHandler, EsCount};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/_count"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"count":100,"_shards":{"total":5,"successful":5}}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsCount;
+        let args = serde_json::json!({});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("count"));
+    }
 
-    reg!("es__cat_shards", "List all shards across indices with their state (started/unassigned), node assignment, and sizes (JSON format).\n\nUse when inspecting shard distribution, finding unassigned shards, or debugging storage layout.\n\nReport results in the user's language (中文/English).", EsCatShards,
-         es_schema(&[]),
-         RiskLevel::Safe, "read", &["agent", "ui"], true);
+    // ---- EsReindex ----
 
-    reg!("es__list_snapshots", "List all snapshots across all registered snapshot repositories.\n\nUse when inspecting available backups before a restore operation.\n\nReport results in the user's language (中文/English).", EsListSnapshots,
-         es_schema(&[]),
-         RiskLevel::Safe, "read", &["agent", "ui"], true);
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_reindex_via_wiremock() {
+        use super::{CapabilityHandler, EsReindex};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    reg!("es__restore_snapshot", "Restore a snapshot into the cluster from a repository. DESTRUCTIVE WARNING: restoring a snapshot can overwrite existing indices and their data — verify the target indices before proceeding.\n\nUse when recovering data from a snapshot backup.\n\nExample: {\"repo\": \"my-backups\", \"snap\": \"snap-2026-01\", \"body\": {\"indices\": \"orders*\", \"rename_pattern\": \"(.+)\", \"rename_replacement\": \"restored_$1\"}}.", EsRestoreSnapshot,
-         es_schema(&[("repo", "Repository containing the snapshot", "string", true), ("snap", "Snapshot name to restore", "string", true), ("body", "Restore request body (indices, rename_pattern, rename_replacement, etc.)", "object", false)]),
-         RiskLevel::Elevated, "create", &["agent", "ui"]);
-}
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/_reindex"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"took":123,"total":10,"created":10,"batches":1}"#),
+            )
+            .mount(&server)
+            .await;
 
-#[cfg(test)]
-mod tests {
-    use serde_json::json;
+        let handler = EsReindex;
+        let args = serde_json::json!({"body": {"source": {"index": "old-index"}, "dest": {"index": "new-index"}}});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("created"));
+    }
 
-    /// S1: SSH-tunneled HTTPS request must keep the original hostname for
-    /// SNI / certificate validation while TCP goes through the local tunnel
-    /// endpoint (regression for issue #472).
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_execute_es_http_tunnel_preserves_hostname_for_tls() {
-        use crate::common::tls_test_server::{spawn_tls_server, test_root_certificate};
-
-        let addr = spawn_tls_server().await;
-        let config = json!({
-            "host": "127.0.0.1",
-            "port": addr.port(),
-            "tunnelOriginalHost": "es.example.com",
-            "sslCertVerification": true,
-        });
+    async fn test_es_reindex_missing_body() {
+        use super::{CapabilityHandler, EsReindex};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let result = super::execute_es_http(
-            "GET",
-            "/",
-            None,
-            &config,
-            Some(vec![test_root_certificate()]),
-        )
-        .await;
+        let server = MockServer::start().await;
+        // The reindex handler does not validate `body` itself — a missing body is
+        // forwarded as-is and rejected by ES with a 400. Verify the handler
+        // surfaces ES's rejection instead of failing on the missing argument.
+        Mock::given(method("POST"))
+            .and(path("/_reindex"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_string(r#"{"error":{"reason":"request body is required"}}"#),
+            )
+            .mount(&server)
+            .await;
 
+        let handler = EsReindex;
+        let args = serde_json::json!({});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(
             result.is_ok(),
-            "tunneled TLS request must succeed, got: {:?}",
+            "missing body must not error locally, got: {:?}",
             result.err()
         );
-        assert!(
-            result.unwrap().contains("\"status\":200"),
-            "expected 200 from TLS server"
-        );
+        assert!(result.unwrap().contains("400"));
     }
 
+    // ---- Lifecycle policies ----
+
     #[cfg(not(target_os = "windows"))]
-    fn mock_config(server: &wiremock::MockServer) -> serde_json::Value {
-        let addr = server.address();
-        json!({"host": format!("http://{}", addr.ip()), "port": addr.port()})
+    fn mock_config_of_type(server: &wiremock::MockServer, db_type: &str) -> serde_json::Value {
+        let mut config = mock_config(server);
+        config["type"] = json!(db_type);
+        config
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_execute_es_http_get() {
+    async fn test_es_list_lifecycle_policies_uses_ilm_on_elasticsearch() {
+        use super::{CapabilityHandler, EsListLifecyclePolicies};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("GET"))
-            .and(path("/_cat/indices"))
+            .and(path("/_ilm/policy"))
             .respond_with(
-                ResponseTemplate::new(200)
-                    .set_body_string(r#"[{"index":"my-index","health":"green"}]"#),
+                ResponseTemplate::new(200).set_body_string(r#"{"logs":{"version":1,"policy":{}}}"#),
             )
             .mount(&server)
             .await;
 
-        let result =
-            super::execute_es_http("GET", "/_cat/indices", None, &mock_config(&server), None).await;
+        let handler = EsListLifecyclePolicies;
+        let config = mock_config_of_type(&server, "ELASTICSEARCH");
+        let result = handler.handle(&json!({}), Some(&config)).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("my-index"));
+        assert!(result.unwrap().contains("logs"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_execute_es_http_post_with_body() {
+    async fn test_es_list_lifecycle_policies_uses_ism_on_opensearch() {
+        use super::{CapabilityHandler, EsListLifecyclePolicies};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_plugins/_ism/policies"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"policies":[{"_id":"logs","policy":{}}],"total_policies":1}"#,
+                ),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsListLifecyclePolicies;
+        let config = mock_config_of_type(&server, "OPENSEARCH");
+        let result = handler.handle(&json!({}), Some(&config)).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("total_policies"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_attach_lifecycle_policy_sets_ilm_setting() {
+        use super::{CapabilityHandler, EsAttachLifecyclePolicy};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/logs-000001/_settings"))
+            .and(body_json(json!({"index": {"lifecycle": {"name": "logs"}}})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsAttachLifecyclePolicy;
+        let args = json!({"index": "logs-000001", "policy": "logs"});
+        let config = mock_config_of_type(&server, "ELASTICSEARCH");
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_attach_lifecycle_policy_uses_ism_add() {
+        use super::{CapabilityHandler, EsAttachLifecyclePolicy};
         use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_search"))
-            .and(body_json(json!({"query": {"match_all": {}}})))
+            .and(path("/_plugins/_ism/add/logs-000001"))
+            .and(body_json(json!({"policy_id": "logs"})))
             .respond_with(
                 ResponseTemplate::new(200)
-                    .set_body_string(r#"{"hits":{"total":{"value":1},"hits":[]}}"#),
+                    .set_body_string(r#"{"updated_indices":1,"failures":false}"#),
             )
             .mount(&server)
             .await;
 
-        let result = super::execute_es_http(
-            "POST",
-            "/my-index/_search",
-            Some(r#"{"query":{"match_all":{}}}"#),
-            &mock_config(&server),
-            None,
-        )
-        .await;
+        let handler = EsAttachLifecyclePolicy;
+        let args = json!({"index": "logs-000001", "policy": "logs"});
+        let config = mock_config_of_type(&server, "OPENSEARCH");
+        let result = handler.handle(&args, Some(&config)).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        let body = result.unwrap();
-        assert!(
-            body.contains("hits"),
-            "response should contain query hits, got: {}",
-            body
-        );
-        assert!(
-            body.contains("\"status\":200"),
-            "response should have 200 status, got: {}",
-            body
-        );
+        assert!(result.unwrap().contains("updated_indices"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_explain_lifecycle_per_flavor() {
+        use super::{CapabilityHandler, EsExplainLifecycle};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/logs-000001/_ilm/explain"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"indices":{"logs-000001":{"phase":"hot","step":"ERROR"}}}"#,
+                ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_plugins/_ism/explain/logs-000001"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"logs-000001":{"policy_id":"logs","state":{"name":"hot"}}}"#,
+                ),
+            )
+            .mount(&server)
+            .await;
+
+        let handler = EsExplainLifecycle;
+        let args = json!({"index": "logs-000001"});
+        let es = handler
+            .handle(&args, Some(&mock_config_of_type(&server, "ELASTICSEARCH")))
+            .await
+            .unwrap();
+        assert!(es.contains("\"step\":\"ERROR\""), "got: {}", es);
+        let os = handler
+            .handle(&args, Some(&mock_config_of_type(&server, "OPENSEARCH")))
+            .await
+            .unwrap();
+        assert!(os.contains("policy_id"), "got: {}", os);
+    }
+
+    #[tokio::test]
+    async fn test_es_put_lifecycle_policy_missing_body() {
+        use super::{CapabilityHandler, EsPutLifecyclePolicy};
+        let handler = EsPutLifecyclePolicy;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"policy": "logs"});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing body"));
+    }
+
+    // ---- Ingest pipelines ----
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_simulate_pipeline_inline_docs_and_pipeline() {
+        use super::{CapabilityHandler, EsSimulatePipeline};
+        use wiremock::matchers::{body_json, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let pipeline = json!({"processors": [{"dissect": {"field": "message", "pattern": "%{level} %{msg}"}}]});
+        Mock::given(method("POST"))
+            .and(path("/_ingest/pipeline/_simulate"))
+            .and(query_param("verbose", "true"))
+            .and(body_json(json!({
+                "docs": [{"_source": {"message": "INFO started"}}],
+                "pipeline": pipeline,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"docs":[{"processor_results":[{"processor_type":"dissect","status":"success"}]}]}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let handler = EsSimulatePipeline;
+        let args = json!({"pipeline": pipeline, "docs": [{"message": "INFO started"}]});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("processor_results"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_simulate_pipeline_samples_docs_from_index() {
+        use super::{CapabilityHandler, EsSimulatePipeline};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/logs/_search"))
+            .and(body_json(json!({"size": 2, "query": {"match_all": {}}})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"hits":{"hits":[{"_index":"logs","_id":"1","_score":1.0,"_source":{"message":"a"}}]}}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_ingest/pipeline/parse-logs/_simulate"))
+            .and(body_json(json!({
+                "docs": [{"_index": "logs", "_id": "1", "_source": {"message": "a"}}],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"docs":[]}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsSimulatePipeline;
+        let args = json!({"id": "parse-logs", "index": "logs", "sample_size": 2});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("\"status\":200"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_simulate_pipeline_samples_large_docs_whole() {
+        use super::{CapabilityHandler, EsSimulatePipeline};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let message = "x".repeat(40_000);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/logs/_search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "hits": {"hits": [{"_index": "logs", "_id": "1", "_source": {"message": message}}]},
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_ingest/pipeline/parse-logs/_simulate"))
+            .and(body_json(json!({
+                "docs": [{"_index": "logs", "_id": "1", "_source": {"message": message}}],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"docs":[]}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsSimulatePipeline;
+        let args = json!({"id": "parse-logs", "index": "logs", "sample_size": 1});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("\"status\":200"));
+    }
+
+    #[tokio::test]
+    async fn test_es_simulate_pipeline_requires_pipeline() {
+        use super::{CapabilityHandler, EsSimulatePipeline};
+        let handler = EsSimulatePipeline;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"docs": [{"message": "a"}]});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("id or an inline pipeline"));
+    }
+
+    // ---- Snapshots ----
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_put_snapshot_repository_fs() {
+        use super::{CapabilityHandler, EsPutSnapshotRepository};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = json!({"type": "fs", "settings": {"location": "/tmp/es-backups"}});
+        Mock::given(method("PUT"))
+            .and(path("/_snapshot/local-backups"))
+            .and(body_json(body.clone()))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
+
+        let handler = EsPutSnapshotRepository;
+        let args = json!({"repo": "local-backups", "body": body});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("acknowledged"));
+    }
+
+    #[tokio::test]
+    async fn test_es_put_snapshot_repository_requires_type() {
+        use super::{CapabilityHandler, EsPutSnapshotRepository};
+        let handler = EsPutSnapshotRepository;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"repo": "local-backups", "body": {"settings": {"location": "/tmp"}}});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("body.type"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_execute_es_http_handles_404() {
-        use wiremock::matchers::method;
+    async fn test_es_create_snapshot_does_not_wait_by_default() {
+        use super::{CapabilityHandler, EsCreateSnapshot};
+        use wiremock::matchers::{body_json, method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"error":"not_found"}"#))
+        Mock::given(method("PUT"))
+            .and(path("/_snapshot/local-backups/snap-1"))
+            .and(query_param("wait_for_completion", "false"))
+            .and(body_json(
+                json!({"include_global_state": false, "indices": "orders*,-orders-tmp"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"accepted":true}"#))
             .mount(&server)
             .await;
 
-        let result =
-            super::execute_es_http("GET", "/missing", None, &mock_config(&server), None).await;
+        let handler = EsCreateSnapshot;
+        let args = json!({
+            "repo": "local-backups",
+            "snap": "snap-1",
+            "indices": "orders*,-orders-tmp",
+            "body": {"include_global_state": false},
+        });
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("404"));
+        assert!(result.unwrap().contains("accepted"));
     }
 
-    // ---- Handler-level tests through CapabilityHandler::handle() ----
-
     #[tokio::test]
-    async fn test_handler_rejects_missing_config() {
-        use super::CapabilityHandler;
-        use super::EsSearch;
+    async fn test_es_create_snapshot_rejects_invalid_pattern() {
+        use super::{CapabilityHandler, EsCreateSnapshot};
+        let handler = EsCreateSnapshot;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"repo": "r", "snap": "s", "indices": "orders,BAD/INDEX"});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("invalid characters"));
+    }
 
-        let handler = EsSearch;
-        let args = json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
-        let result = handler.handle(&args, None).await;
+    #[test]
+    fn test_summarize_snapshot_status() {
+        let data = json!({"snapshots": [{
+            "snapshot": "snap-1",
+            "repository": "local-backups",
+            "state": "STARTED",
+            "shards_stats": {"done": 3, "failed": 0, "total": 4},
+            "stats": {
+                "processed": {"size_in_bytes": 250},
+                "total": {"size_in_bytes": 1000}
+            },
+            "indices": {}
+        }]});
+        let progress = super::summarize_snapshot_status(&data);
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0]["state"], "STARTED");
+        assert_eq!(progress[0]["shards_done"], 3);
+        assert_eq!(progress[0]["shards_total"], 4);
+        assert_eq!(progress[0]["percent_complete"], 25.0);
+    }
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.contains("connection config"), "got: {}", err);
+    #[test]
+    fn test_summarize_snapshot_status_without_total() {
+        let started = json!({"snapshots": [{
+            "snapshot": "snap-1",
+            "state": "STARTED",
+            "shards_stats": {"done": 0, "failed": 0, "total": 4},
+            "stats": {"processed": {"size_in_bytes": 0}, "total": {"size_in_bytes": 0}}
+        }]});
+        let progress = super::summarize_snapshot_status(&started);
+        assert!(progress[0]["percent_complete"].is_null());
+
+        let empty_done = json!({"snapshots": [{
+            "snapshot": "snap-2",
+            "state": "SUCCESS",
+            "stats": {"processed": {"size_in_bytes": 0}, "total": {"size_in_bytes": 0}}
+        }]});
+        let progress = super::summarize_snapshot_status(&empty_done);
+        assert_eq!(progress[0]["percent_complete"], 100.0);
     }
 
+    #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_search_missing_index() {
-        use super::CapabilityHandler;
-        use super::EsSearch;
+    async fn test_es_snapshot_status_strips_index_details() {
+        use super::{CapabilityHandler, EsSnapshotStatus};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let handler = EsSearch;
-        let config = json!({"host": "http://localhost", "port": 9200});
-        let args = json!({"body": {"query": {"match_all": {}}}});
-        let result = handler.handle(&args, Some(&config)).await;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_snapshot/local-backups/snap-1/_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"snapshots":[{"snapshot":"snap-1","state":"SUCCESS","shards_stats":{"done":1,"total":1},"indices":{"orders":{"shards":{}}}}]}"#,
+            ))
+            .mount(&server)
+            .await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing index"));
+        let handler = EsSnapshotStatus;
+        let args = json!({"repo": "local-backups", "snap": "snap-1"});
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        assert!(result.contains("percent_complete"), "got: {}", result);
+        assert!(!result.contains("\"indices\""), "got: {}", result);
     }
 
+    // ---- Tasks ----
+
+    #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_get_document_missing_index() {
-        use super::CapabilityHandler;
-        use super::EsGetDocument;
+    async fn test_es_reindex_async_returns_task() {
+        use super::{CapabilityHandler, EsReindex};
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let handler = EsGetDocument;
-        let config = json!({"host": "http://localhost", "port": 9200});
-        let args = json!({"id": "doc-1"});
-        let result = handler.handle(&args, Some(&config)).await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/_reindex"))
+            .and(query_param("wait_for_completion", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"task":"node-1:4242"}"#))
+            .mount(&server)
+            .await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing index"));
+        let handler = EsReindex;
+        let args = json!({
+            "body": {"source": {"index": "old"}, "dest": {"index": "new"}},
+            "wait_for_completion": false,
+        });
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("node-1:4242"));
     }
 
+    #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_get_document_missing_id() {
-        use super::CapabilityHandler;
-        use super::EsGetDocument;
+    async fn test_es_delete_by_query_async_returns_task() {
+        use super::{CapabilityHandler, EsDeleteByQuery};
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let handler = EsGetDocument;
-        let config = json!({"host": "http://localhost", "port": 9200});
-        let args = json!({"index": "my-index"});
-        let result = handler.handle(&args, Some(&config)).await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/my-index/_delete_by_query"))
+            .and(query_param("wait_for_completion", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"task":"node-1:7"}"#))
+            .mount(&server)
+            .await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing id"));
+        let handler = EsDeleteByQuery;
+        let args = json!({
+            "index": "my-index",
+            "body": {"query": {"match_all": {}}},
+            "wait_for_completion": false,
+        });
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("node-1:7"));
     }
 
+    #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_search_invalid_index_name() {
-        use super::CapabilityHandler;
-        use super::EsSearch;
+    async fn test_es_list_tasks_filters() {
+        use super::{CapabilityHandler, EsListTasks};
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let handler = EsSearch;
-        let config = json!({"host": "http://localhost", "port": 9200});
-        let args = json!({"index": "INVALID/INDEX", "body": {"query": {"match_all": {}}}});
-        let result = handler.handle(&args, Some(&config)).await;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_tasks"))
+            .and(query_param("detailed", "true"))
+            .and(query_param("actions", "*reindex"))
+            .and(query_param("nodes", "node-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"nodes":{"node-1":{"tasks":{}}}}"#),
+            )
+            .mount(&server)
+            .await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("invalid characters"));
+        let handler = EsListTasks;
+        let args = json!({"actions": "*reindex", "nodes": "node-1"});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
+        assert!(result.is_ok(), "got: {:?}", result.err());
+        assert!(result.unwrap().contains("node-1"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_create_index_via_wiremock() {
-        use super::CapabilityHandler;
-        use super::EsCreateIndex;
-        use wiremock::matchers::{method, path};
+    async fn test_es_rethrottle_update_by_query() {
+        use super::{CapabilityHandler, EsRethrottle};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("PUT"))
-            .and(path("/test-index"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+        Mock::given(method("POST"))
+            .and(path("/_update_by_query/node-1:7/_rethrottle"))
+            .and(query_param("requests_per_second", "-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"nodes":{}}"#))
             .mount(&server)
             .await;
 
-        let handler = EsCreateIndex;
-        let args = json!({"index": "test-index", "body": {"settings": {"number_of_shards": 1}}});
+        let handler = EsRethrottle;
+        let args = json!({
+            "task_id": "node-1:7",
+            "operation": "update_by_query",
+            "requests_per_second": -1,
+        });
         let result = handler.handle(&args, Some(&mock_config(&server))).await;
-
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
+        assert!(result.unwrap().contains("\"status\":200"));
+    }
+
+    #[tokio::test]
+    async fn test_es_cancel_task_rejects_invalid_id() {
+        use super::{CapabilityHandler, EsCancelTask};
+        let handler = EsCancelTask;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"task_id": "../_cluster/settings"});
+        let result = handler.handle(&args, Some(&config)).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid task ID"));
     }
 
+    // ---- Update by query / multi-search ----
+
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_delete_index_via_wiremock() {
-        use super::CapabilityHandler;
-        use super::EsDeleteIndex;
-        use wiremock::matchers::{method, path};
+    async fn test_es_update_by_query_dry_run_only_counts() {
+        use super::{CapabilityHandler, EsUpdateByQuery};
+        use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("DELETE"))
-            .and(path("/old-index"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+        let query = json!({"range": {"createdAt": {"lt": "2025-01-01"}}});
+        Mock::given(method("POST"))
+            .and(path("/orders/_count"))
+            .and(body_json(json!({"query": query})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"count":42}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/orders/_update_by_query"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"updated":42}"#))
+            .expect(0)
             .mount(&server)
             .await;
 
-        let handler = EsDeleteIndex;
-        let args = json!({"index": "old-index"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
+        let handler = EsUpdateByQuery;
+        let args = json!({
+            "index": "orders",
+            "dry_run": true,
+            "body": {"query": query, "script": {"source": "ctx._source.status = 'archived'"}},
+        });
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["dry_run"], true);
+        assert_eq!(parsed["matching_documents"], 42);
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_search_happy_path_via_wiremock() {
-        use super::CapabilityHandler;
-        use super::EsSearch;
-        use wiremock::matchers::{method, path};
+    async fn test_es_update_by_query_executes_with_params() {
+        use super::{CapabilityHandler, EsUpdateByQuery};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_search"))
+            .and(path("/orders/_update_by_query"))
+            .and(query_param("conflicts", "proceed"))
             .respond_with(
-                ResponseTemplate::new(200).set_body_string(
-                    r#"{"hits":{"total":{"value":2},"hits":[{"_index":"my-index","_id":"1","_source":{"name":"hello"}}]}}"#,
-                ),
+                ResponseTemplate::new(200).set_body_string(r#"{"updated":42,"failures":[]}"#),
             )
             .mount(&server)
             .await;
 
-        let handler = EsSearch;
-        let args = json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
+        let handler = EsUpdateByQuery;
+        let args = json!({
+            "index": "orders",
+            "conflicts": "proceed",
+            "body": {"query": {"match_all": {}}, "script": {"source": "ctx._source.n = 1"}},
+        });
         let result = handler.handle(&args, Some(&mock_config(&server))).await;
-
         assert!(result.is_ok(), "got: {:?}", result.err());
-        let body = result.unwrap();
-        assert!(
-            body.contains("hits"),
-            "expected hits in response, got: {}",
-            body
-        );
-        assert!(
-            body.contains("my-index"),
-            "expected index name in response, got: {}",
-            body
-        );
+        assert!(result.unwrap().contains("\"updated\":42"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_handles_non_json_response() {
-        use wiremock::matchers::{method, path};
+    async fn test_es_multi_search_builds_ndjson() {
+        use super::{CapabilityHandler, EsMultiSearch};
+        use wiremock::matchers::{body_string, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .and(path("/_cat/indices"))
-            .respond_with(ResponseTemplate::new(200).set_body_string("plain text response"))
+        Mock::given(method("POST"))
+            .and(path("/_msearch"))
+            .and(body_string(
+                "{\"index\":\"a\"}\n{\"size\":0}\n{\"index\":\"b\"}\n{\"query\":{\"match_all\":{}}}\n",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"responses":[{"hits":{"total":{"value":1}}},{"hits":{"total":{"value":2}}}]}"#,
+            ))
             .mount(&server)
             .await;
 
-        let result = super::execute_es_http(
-            "GET",
-            "/_cat/indices?format=json&expand_wildcards=all",
-            None,
-            &mock_config(&server),
-            None,
-        )
-        .await;
-
+        let handler = EsMultiSearch;
+        let args = json!({"searches": [{"index": "a", "body": {"size": 0}}, {"index": "b"}]});
+        let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("plain text response"));
+        assert!(result.unwrap().contains("responses"));
     }
 
-    #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_execute_es_http_bad_method() {
-        let server = wiremock::MockServer::start().await;
-        // reqwest rejects methods with spaces as invalid tokens
-        let result =
-            super::execute_es_http("BAD METHOD", "/test", None, &mock_config(&server), None).await;
+    async fn test_es_multi_search_reports_bad_entry() {
+        use super::{CapabilityHandler, EsMultiSearch};
+        let handler = EsMultiSearch;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"searches": [{"index": "a"}, {"body": {}}]});
+        let result = handler.handle(&args, Some(&config)).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Bad method"));
+        assert!(result.unwrap_err().contains("searches[1]"));
     }
 
-    // ---- Remaining handler tests ----
+    // ---- Profile / explain ----
+
+    #[test]
+    fn test_summarize_profile_ranks_shards_and_nodes() {
+        let data = json!({
+            "took": 12,
+            "hits": {"total": {"value": 3, "relation": "eq"}},
+            "profile": {"shards": [
+                {
+                    "id": "[n1][orders][0]",
+                    "searches": [{
+                        "query": [{
+                            "type": "BooleanQuery",
+                            "description": "+status:shipped +sku:*-42",
+                            "time_in_nanos": 9_000_000,
+                            "children": [
+                                {"type": "TermQuery", "description": "status:shipped", "time_in_nanos": 1_000_000},
+                                {"type": "WildcardQuery", "description": "sku:*-42", "time_in_nanos": 7_500_000}
+                            ]
+                        }],
+                        "rewrite_time": 500_000,
+                        "collector": [{"name": "SimpleTopScoreDocCollector", "reason": "search_top_hits", "time_in_nanos": 2_000_000}]
+                    }],
+                    "aggregations": []
+                },
+                {
+                    "id": "[n1][orders][1]",
+                    "searches": [{
+                        "query": [{"type": "TermQuery", "description": "status:shipped", "time_in_nanos": 100_000}],
+                        "rewrite_time": 0,
+                        "collector": []
+                    }],
+                    "aggregations": [{"type": "TermsAggregator", "description": "by_status", "time_in_nanos": 300_000}]
+                }
+            ]}
+        });
+        let summary = super::summarize_profile(&data);
+        assert_eq!(summary["shards_profiled"], 2);
+        assert_eq!(summary["slowest_shards"][0]["shard"], "[n1][orders][0]");
+        assert_eq!(summary["slowest_shards"][0]["total_ms"], 11.5);
+        assert_eq!(summary["expensive_queries"][0]["type"], "BooleanQuery");
+        assert_eq!(summary["expensive_queries"][1]["type"], "WildcardQuery");
+        assert_eq!(
+            summary["expensive_collectors"][0]["type"],
+            "SimpleTopScoreDocCollector"
+        );
+        assert_eq!(
+            summary["expensive_aggregations"][0]["description"],
+            "by_status"
+        );
+    }
+
+    #[test]
+    fn test_render_explanation_indents_details() {
+        let explanation = json!({
+            "value": 2.5,
+            "description": "sum of:",
+            "details": [
+                {"value": 1.5, "description": "weight(title:rust in 3)", "details": []},
+                {"value": 1.0, "description": "weight(title:book in 3)", "details": []}
+            ]
+        });
+        let mut lines = Vec::new();
+        super::render_explanation(&explanation, 0, &mut lines);
+        assert_eq!(
+            lines,
+            vec![
+                "2.5 = sum of:",
+                "  1.5 = weight(title:rust in 3)",
+                "  1 = weight(title:book in 3)",
+            ]
+        );
+    }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_index_document_with_id() {
-        use super::{CapabilityHandler, EsIndexDocument};
+    async fn test_es_profile_search_puts_summary_first() {
+        use super::{CapabilityHandler, EsProfileSearch};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/orders/_search"))
+            .and(body_json(
+                json!({"query": {"match_all": {}}, "profile": true}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"took":1,"hits":{"total":{"value":0}},"profile":{"shards":[]}}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let handler = EsProfileSearch;
+        let args = json!({"index": "orders"});
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        assert!(
+            result.starts_with("{\"status\":200,\"summary\":{"),
+            "got: {}",
+            result
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["summary"]["shards_profiled"], 0);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_es_explain_document_breakdown() {
+        use super::{CapabilityHandler, EsExplainDocument};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_doc/doc-1"))
-            .respond_with(
-                ResponseTemplate::new(201).set_body_string(r#"{"_id":"doc-1","result":"created"}"#),
-            )
+            .and(path("/products/_explain/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"matched":true,"explanation":{"value":1.25,"description":"weight(title:rust in 0)","details":[]}}"#,
+            ))
             .mount(&server)
             .await;
 
-        let handler = EsIndexDocument;
-        let args =
-            serde_json::json!({"index": "my-index", "id": "doc-1", "body": {"title": "hello"}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("created"));
+        let handler = EsExplainDocument;
+        let args = json!({"index": "products", "id": "42", "body": {"query": {"match": {"title": "rust"}}}});
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["summary"]["matched"], true);
+        assert_eq!(
+            parsed["summary"]["breakdown"][0],
+            "1.25 = weight(title:rust in 0)"
+        );
     }
 
+    // ---- Analyze ----
+
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_index_document_without_id() {
-        use super::{CapabilityHandler, EsIndexDocument};
-        use wiremock::matchers::{method, path};
+    async fn test_es_analyze_field_analyzer() {
+        use super::{CapabilityHandler, EsAnalyze};
+        use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_doc"))
-            .respond_with(
-                ResponseTemplate::new(201)
-                    .set_body_string(r#"{"_id":"auto-id","result":"created"}"#),
-            )
+            .and(path("/products/_analyze"))
+            .and(body_json(json!({"text": "Wi-Fi", "field": "title"})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"tokens":[{"token":"wi","start_offset":0,"end_offset":2,"type":"<ALPHANUM>","position":0},{"token":"fi","start_offset":3,"end_offset":5,"type":"<ALPHANUM>","position":1}]}"#,
+            ))
             .mount(&server)
             .await;
 
-        let handler = EsIndexDocument;
-        let args = serde_json::json!({"index": "my-index", "body": {"title": "hello"}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("auto-id"));
-    }
-
-    #[tokio::test]
-    async fn test_es_index_document_missing_index() {
-        use super::{CapabilityHandler, EsIndexDocument};
-        let handler = EsIndexDocument;
-        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
-        let args = serde_json::json!({"body": {"title": "hello"}});
-        let result = handler.handle(&args, Some(&config)).await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing index"));
+        let handler = EsAnalyze;
+        let args = json!({"index": "products", "field": "title", "text": "Wi-Fi"});
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["analysis"]["field"], "title");
+        assert_eq!(parsed["tokens"][1]["token"], "fi");
+        assert_eq!(parsed["tokens"][1]["start_offset"], 3);
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_update_document_via_wiremock() {
-        use super::{CapabilityHandler, EsUpdateDocument};
-        use wiremock::matchers::{method, path};
+    async fn test_es_analyze_compare_reports_term_differences() {
+        use super::{CapabilityHandler, EsAnalyze};
+        use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_update/doc-1"))
+            .and(path("/_analyze"))
+            .and(body_json(json!({"text": "Wi-Fi", "analyzer": "standard"})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"tokens":[{"token":"wi","position":0},{"token":"fi","position":1}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_analyze"))
+            .and(body_json(
+                json!({"text": "Wi-Fi", "tokenizer": "whitespace", "filter": ["lowercase"]}),
+            ))
             .respond_with(
-                ResponseTemplate::new(200).set_body_string(r#"{"_id":"doc-1","result":"updated"}"#),
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"tokens":[{"token":"wi-fi","position":0}]}"#),
             )
             .mount(&server)
             .await;
 
-        let handler = EsUpdateDocument;
-        let args = serde_json::json!({"index": "my-index", "id": "doc-1", "body": {"doc": {"title": "updated"}}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("updated"));
+        let handler = EsAnalyze;
+        let args = json!({
+            "text": "Wi-Fi",
+            "analyzer": "standard",
+            "compare_with": {"tokenizer": "whitespace", "filter": ["lowercase"]},
+        });
+        let result = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["only_in_left"], json!(["wi", "fi"]));
+        assert_eq!(parsed["only_in_right"], json!(["wi-fi"]));
     }
 
     #[tokio::test]
-    async fn test_es_update_document_missing_id() {
-        use super::{CapabilityHandler, EsUpdateDocument};
-        let handler = EsUpdateDocument;
-        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
-        let args = serde_json::json!({"index": "my-index", "body": {"doc": {"title": "updated"}}});
+    async fn test_es_analyze_field_requires_index() {
+        use super::{CapabilityHandler, EsAnalyze};
+        let handler = EsAnalyze;
+        let config = json!({"host": "http://localhost", "port": 9200});
+        let args = json!({"field": "title", "text": "hello"});
         let result = handler.handle(&args, Some(&config)).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing id"));
+        assert!(result.unwrap_err().contains("requires an index"));
     }
 
-    #[cfg(not(target_os = "windows"))]
-    #[tokio::test]
-    async fn test_es_delete_document_via_wiremock() {
-        use super::{CapabilityHandler, EsDeleteDocument};
-        use wiremock::matchers::{method, path};
-        use wiremock::{Mock, MockServer, ResponseTemplate};
+    #[test]
+    fn test_es_analyze_schema_accepts_inline_tokenizers() {
+        use data_studio_agent::capabilities::registry::CapabilityRegistry;
 
-        let server = MockServer::start().await;
-        Mock::given(method("DELETE"))
-            .and(path("/my-index/_doc/doc-1"))
-            .respond_with(
-                ResponseTemplate::new(200).set_body_string(r#"{"_id":"doc-1","result":"deleted"}"#),
-            )
-            .mount(&server)
-            .await;
+        let mut reg = CapabilityRegistry::new();
+        super::register_all(&mut reg);
+        let schema = &reg.get("es__analyze").unwrap().input_schema;
+        assert_eq!(
+            schema["properties"]["tokenizer"]["type"],
+            json!(["string", "object"])
+        );
+        assert_eq!(
+            schema["properties"]["text"]["type"],
+            json!(["string", "array"])
+        );
+        assert_eq!(schema["properties"]["index"]["type"], "string");
+    }
 
-        let handler = EsDeleteDocument;
-        let args = serde_json::json!({"index": "my-index", "id": "doc-1"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("deleted"));
+    // ---- Index maintenance ----
+
+    #[tokio::test]
+    async fn test_es_close_index_rejects_patterns() {
+        use super::{CapabilityHandler, EsCloseIndex};
+
+        let config = json!({"host": "http://localhost", "port": 9200});
+        for index in ["logs-*", "logs-?", "_all"] {
+            let args = json!({ "index": index });
+            assert!(
+                EsCloseIndex.handle(&args, Some(&config)).await.is_err(),
+                "{} should be rejected",
+                index
+            );
+        }
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_delete_by_query_via_wiremock() {
-        use super::{CapabilityHandler, EsDeleteByQuery};
+    async fn test_es_shrink_preflight_reports_missing_prerequisites() {
+        use super::{CapabilityHandler, EsShrinkIndex};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/logs/_settings"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"logs":{"settings":{"index.number_of_shards":"2"}}}"#),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/shards/logs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"[{"shard":"0","state":"STARTED","node":"n1"},{"shard":"1","state":"STARTED","node":"n2"}]"#,
+            ))
+            .mount(&server)
+            .await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_delete_by_query"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"deleted":5}"#))
+            .and(path("/logs/_shrink/logs-shrunk"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .expect(0)
             .mount(&server)
             .await;
 
-        let handler = EsDeleteByQuery;
-        let args = serde_json::json!({"index": "my-index", "body": {"query": {"match_all": {}}}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("deleted"));
+        let handler = EsShrinkIndex;
+        let args = json!({"index": "logs", "target": "logs-shrunk"});
+        let err = handler
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap_err();
+        assert!(err.contains("index.blocks.write"), "got: {}", err);
+        assert!(err.contains("one node"), "got: {}", err);
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_get_mapping_via_wiremock() {
-        use super::{CapabilityHandler, EsGetMapping};
+    async fn test_es_shrink_runs_when_prerequisites_met() {
+        use super::{CapabilityHandler, EsShrinkIndex};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("GET"))
-            .and(path("/my-index/_mapping"))
+            .and(path("/logs/_settings"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"logs":{"settings":{"index.blocks.write":"true"}}}"#),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/shards/logs"))
             .respond_with(ResponseTemplate::new(200).set_body_string(
-                r#"{"my-index":{"mappings":{"properties":{"name":{"type":"text"}}}}}"#,
+                r#"[{"shard":"0","state":"STARTED","node":"n1"},{"shard":"0","state":"STARTED","node":"n2"},{"shard":"1","state":"STARTED","node":"n2"}]"#,
             ))
             .mount(&server)
             .await;
+        Mock::given(method("POST"))
+            .and(path("/logs/_shrink/logs-shrunk"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .mount(&server)
+            .await;
 
-        let handler = EsGetMapping;
-        let args = serde_json::json!({"index": "my-index"});
+        let handler = EsShrinkIndex;
+        let args = json!({"index": "logs", "target": "logs-shrunk"});
         let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("text"));
+        assert!(result.unwrap().contains("acknowledged"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_put_mapping_via_wiremock() {
-        use super::{CapabilityHandler, EsPutMapping};
+    async fn test_es_clone_requires_write_block_only() {
+        use super::{CapabilityHandler, EsCloneIndex};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("PUT"))
-            .and(path("/my-index/_mapping"))
+        Mock::given(method("GET"))
+            .and(path("/orders/_settings"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"orders":{"settings":{"index.blocks.read_only":"true"}}}"#,
+                ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/orders/_clone/orders-copy"))
             .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
             .mount(&server)
             .await;
 
-        let handler = EsPutMapping;
-        let args = serde_json::json!({"index": "my-index", "body": {"properties": {"new_field": {"type": "keyword"}}}});
+        let handler = EsCloneIndex;
+        let args = json!({"index": "orders", "target": "orders-copy"});
         let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_cat_aliases_via_wiremock() {
-        use super::{CapabilityHandler, EsCatAliases};
-        use wiremock::matchers::{method, path};
+    async fn test_es_rollover_dry_run_with_new_index() {
+        use super::{CapabilityHandler, EsRollover};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .and(path("/_cat/aliases"))
-            .respond_with(
-                ResponseTemplate::new(200)
-                    .set_body_string(r#"[{"alias":"my-alias","index":"my-index"}]"#),
-            )
+        Mock::given(method("POST"))
+            .and(path("/logs-write/_rollover/logs-000002"))
+            .and(query_param("dry_run", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"dry_run":true,"rolled_over":false,"conditions":{"[max_age: 7d]":false}}"#,
+            ))
             .mount(&server)
             .await;
 
-        let handler = EsCatAliases;
-        let args = serde_json::json!({});
+        let handler = EsRollover;
+        let args = json!({
+            "alias": "logs-write",
+            "new_index": "logs-000002",
+            "dry_run": true,
+            "body": {"conditions": {"max_age": "7d"}},
+        });
         let result = handler.handle(&args, Some(&mock_config(&server))).await;
         assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("my-alias"));
+        assert!(result.unwrap().contains("rolled_over"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_get_alias_via_wiremock() {
-        use super::{CapabilityHandler, EsGetAlias};
-        use wiremock::matchers::{method, path};
+    async fn test_es_forcemerge_and_cluster_wide_refresh() {
+        use super::{CapabilityHandler, EsForceMerge, EsRefreshIndex};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .and(path("/my-index/_alias"))
-            .respond_with(
-                ResponseTemplate::new(200)
-                    .set_body_string(r#"{"my-index":{"aliases":{"my-alias":{}}}}"#),
-            )
+        Mock::given(method("POST"))
+            .and(path("/logs/_forcemerge"))
+            .and(query_param("max_num_segments", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"_shards":{}}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_refresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"_shards":{}}"#))
             .mount(&server)
             .await;
 
-        let handler = EsGetAlias;
-        let args = serde_json::json!({"index": "my-index"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("my-alias"));
+        let config = mock_config(&server);
+        let merged = EsForceMerge
+            .handle(
+                &json!({"index": "logs", "max_num_segments": 1}),
+                Some(&config),
+            )
+            .await
+            .unwrap();
+        assert!(merged.contains("\"status\":200"), "got: {}", merged);
+        let refreshed = EsRefreshIndex
+            .handle(&json!({}), Some(&config))
+            .await
+            .unwrap();
+        assert!(refreshed.contains("\"status\":200"), "got: {}", refreshed);
     }
 
+    // ---- Data streams ----
+
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_put_alias_via_wiremock() {
-        use super::{CapabilityHandler, EsPutAlias};
+    async fn test_es_list_data_streams_condenses_backing_indices() {
+        use super::{CapabilityHandler, EsListDataStreams};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("PUT"))
-            .and(path("/my-index/_alias/my-alias"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+        Mock::given(method("GET"))
+            .and(path("/_data_stream/logs-*"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"data_streams":[{"name":"logs-nginx-default","generation":2,"status":"GREEN","template":"logs","ilm_policy":"logs","indices":[{"index_name":".ds-logs-nginx-default-2026.10.01-000001","index_uuid":"u1"},{"index_name":".ds-logs-nginx-default-2026.10.08-000002","index_uuid":"u2"}]}]}"#,
+            ))
             .mount(&server)
             .await;
 
-        let handler = EsPutAlias;
-        let args = serde_json::json!({"index": "my-index", "name": "my-alias"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
+        let handler = EsListDataStreams;
+        let result = handler
+            .handle(&json!({"name": "logs-*"}), Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let stream = &parsed["data"]["data_streams"][0];
+        assert_eq!(stream["generation"], 2);
+        assert_eq!(stream["template"], "logs");
+        assert_eq!(
+            stream["write_index"],
+            ".ds-logs-nginx-default-2026.10.08-000002"
+        );
+        assert_eq!(stream["backing_indices"].as_array().unwrap().len(), 2);
+        assert!(!result.contains("index_uuid"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_delete_alias_via_wiremock() {
-        use super::{CapabilityHandler, EsDeleteAlias};
+    async fn test_es_data_stream_stats_and_rollover() {
+        use super::{CapabilityHandler, EsDataStreamStats, EsRolloverDataStream};
         use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("DELETE"))
-            .and(path("/my-index/_alias/my-alias"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+        Mock::given(method("GET"))
+            .and(path("/_data_stream/_stats"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"data_stream_count":1,"backing_indices":2}"#),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/logs-nginx-default/_rollover"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"rolled_over":true,"new_index":".ds-logs-nginx-default-2026.10.19-000003"}"#,
+            ))
             .mount(&server)
             .await;
 
-        let handler = EsDeleteAlias;
-        let args = serde_json::json!({"index": "my-index", "name": "my-alias"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
+        let config = mock_config(&server);
+        let stats = EsDataStreamStats
+            .handle(&json!({}), Some(&config))
+            .await
+            .unwrap();
+        assert!(stats.contains("data_stream_count"));
+        let rolled = EsRolloverDataStream
+            .handle(&json!({"name": "logs-nginx-default"}), Some(&config))
+            .await
+            .unwrap();
+        assert!(rolled.contains("rolled_over"));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_update_aliases_via_wiremock() {
-        use super::{CapabilityHandler, EsUpdateAliases};
-        use wiremock::matchers::{method, path};
+    async fn test_es_search_targets_data_stream_with_expand_wildcards() {
+        use super::{CapabilityHandler, EsSearch};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/_aliases"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .and(path("/logs-nginx-default/_search"))
+            .and(query_param("expand_wildcards", "all"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"hits":{"hits":[]}}"#))
             .mount(&server)
             .await;
 
-        let handler = EsUpdateAliases;
-        let args = serde_json::json!({"body": {"actions": [{"add": {"index": "my-index", "alias": "my-alias"}}]}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("acknowledged"));
+        let config = mock_config(&server);
+        let ok = EsSearch
+            .handle(
+                &json!({"index": "logs-nginx-default", "expand_wildcards": "all", "body": {"query": {"match_all": {}}}}),
+                Some(&config),
+            )
+            .await;
+        assert!(ok.is_ok(), "got: {:?}", ok.err());
+        let err = EsSearch
+            .handle(
+                &json!({"index": "logs-nginx-default", "expand_wildcards": "everything", "body": {}}),
+                Some(&config),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("expand_wildcards"));
     }
 
-    // ---- EsCatIndices specific tests ----
+    // ---- Cluster diagnostics ----
+
+    #[test]
+    fn test_summarize_node_stats() {
+        let node = json!({
+            "name": "es-1",
+            "jvm": {
+                "mem": {"heap_used_percent": 91, "heap_max_in_bytes": 1024},
+                "gc": {"collectors": {"old": {"collection_count": 4, "collection_time_in_millis": 6000}}}
+            },
+            "fs": {"total": {"total_in_bytes": 1000, "available_in_bytes": 40}},
+            "os": {"cpu": {"percent": 12}},
+            "thread_pool": {"write": {"rejected": 7}, "search": {"rejected": 0}},
+            "breakers": {"parent": {"tripped": 2}}
+        });
+        let summary = super::summarize_node_stats(&node);
+        assert_eq!(summary["heap_used_percent"], 91);
+        assert_eq!(summary["old_gc_avg_ms"], 1500);
+        assert_eq!(summary["disk_used_percent"], 96.0);
+        assert_eq!(summary["thread_pool_rejections"], json!({"write": 7}));
+        assert_eq!(summary["breakers_tripped"], json!({"parent": 2}));
+    }
+
+    #[test]
+    fn test_diagnose_findings_are_prioritized() {
+        let health = json!({"status": "yellow", "unassigned_shards": 1});
+        let shards = json!([
+            {"index": "orders", "shard": "0", "prirep": "r", "state": "UNASSIGNED", "unassigned.reason": "NODE_LEFT"},
+            {"index": "orders", "shard": "0", "prirep": "p", "state": "STARTED", "node": "es-1"}
+        ]);
+        let nodes_stats = json!({"nodes": {"a": {
+            "name": "es-1",
+            "jvm": {"mem": {"heap_used_percent": 50}},
+            "fs": {"total": {"total_in_bytes": 100, "available_in_bytes": 3}}
+        }}});
+        let pending = json!({"tasks": [{"time_in_queue_millis": 10}]});
+        let findings = super::diagnose_findings(
+            &health,
+            &shards,
+            &nodes_stats,
+            &pending,
+            &json!([]),
+            &json!([]),
+            None,
+        );
+        let severities: Vec<&str> = findings
+            .iter()
+            .map(|f| f["severity"].as_str().unwrap())
+            .collect();
+        assert_eq!(severities, vec!["critical", "warning", "warning", "info"]);
+        assert_eq!(findings[0]["area"], "disk");
+        assert!(findings
+            .iter()
+            .any(|f| f["finding"].as_str().unwrap().contains("NODE_LEFT")));
+    }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_cat_indices_include_system() {
-        use super::CapabilityHandler;
-        use wiremock::matchers::{method, path, query_param};
+    async fn test_es_diagnose_cluster_green_cluster_skips_allocation_explain() {
+        use super::{CapabilityHandler, EsDiagnoseCluster};
+        use wiremock::matchers::{method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("GET"))
-            .and(path("/_cat/indices"))
-            .and(query_param("expand_wildcards", "all"))
+            .and(path("/_cluster/health"))
             .respond_with(ResponseTemplate::new(200).set_body_string(
-                r#"[
-                    {"index":".system-index","health":"green"},
-                    {"index":"user-index","health":"yellow"},
-                    {"index":"_internal","health":"green"}
-                ]"#,
+                r#"{"cluster_name":"prod","status":"green","number_of_nodes":1,"unassigned_shards":0}"#,
             ))
             .mount(&server)
             .await;
+        Mock::given(method("GET"))
+            .and(path("/_cat/nodes"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"[{"name":"es-1"}]"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_cluster/pending_tasks"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"tasks":[]}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_cluster/allocation/explain"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(0)
+            .mount(&server)
+            .await;
 
-        let handler = super::EsCatIndices;
-        let args = serde_json::json!({"include_system": true});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        let body = result.unwrap();
-        assert!(body.contains("user-index"), "should include user indices");
-        assert!(
-            body.contains(".system-index"),
-            "should include system indices when requested"
-        );
-        assert!(
-            body.contains("_internal"),
-            "should include internal indices"
-        );
+        let result = EsDiagnoseCluster
+            .handle(&json!({}), Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["status"], "green");
+        assert_eq!(parsed["cluster"]["nodes"], 1);
+        assert_eq!(parsed["findings"], json!([]));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_cat_indices_sorts_results() {
-        use super::CapabilityHandler;
-        use wiremock::matchers::{method, path};
+    async fn test_es_hot_threads_query() {
+        use super::{CapabilityHandler, EsHotThreads};
+        use wiremock::matchers::{method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("GET"))
-            .and(path("/_cat/indices"))
-            .respond_with(ResponseTemplate::new(200).set_body_string(
-                r#"[
-                    {"index":"z-last","health":"green"},
-                    {"index":"a-first","health":"green"},
-                    {"index":"m-middle","health":"yellow"}
-                ]"#,
-            ))
+            .and(path("/_nodes/es-1/hot_threads"))
+            .and(query_param("threads", "5"))
+            .and(query_param("type", "wait"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("::: {es-1}\n   Hot threads at"),
+            )
             .mount(&server)
             .await;
 
-        let handler = super::EsCatIndices;
-        let args = serde_json::json!({});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        let body = result.unwrap();
-        // All three index names should appear in the response
-        assert!(body.contains("a-first"));
-        assert!(body.contains("m-middle"));
-        assert!(body.contains("z-last"));
+        let config = mock_config(&server);
+        let result = EsHotThreads
+            .handle(
+                &json!({"node_id": "es-1", "threads": 5, "type": "wait"}),
+                Some(&config),
+            )
+            .await
+            .unwrap();
+        assert!(result.contains("Hot threads"));
+        assert!(EsHotThreads
+            .handle(&json!({"type": "gpu"}), Some(&config))
+            .await
+            .is_err());
     }
 
-    // ---- register_all ----
+    // ---- Security administration ----
 
-    #[tokio::test]
-    async fn test_es_register_all_registers_capabilities() {
-        use data_studio_agent::capabilities::registry::CapabilityRegistry;
+    #[test]
+    fn test_security_paths_by_flavor() {
+        use super::{delete_role_mapping_request, list_users_request, put_role_request, EsFlavor};
 
-        let mut reg = CapabilityRegistry::new();
-        super::register_all(&mut reg);
+        let (method, path, _) = list_users_request(&json!({}), EsFlavor::Elasticsearch).unwrap();
+        assert_eq!((method, path.as_str()), ("GET", "/_security/user"));
+        let (_, path, _) = list_users_request(&json!({}), EsFlavor::OpenSearch).unwrap();
+        assert_eq!(path, "/_plugins/_security/api/internalusers");
 
-        assert!(reg.get("es__search").is_some());
-        assert!(reg.get("es__get_document").is_some());
-        assert!(reg.get("es__index_document").is_some());
-        assert!(reg.get("es__update_document").is_some());
-        assert!(reg.get("es__delete_document").is_some());
-        assert!(reg.get("es__delete_by_query").is_some());
-        assert!(reg.get("es__cat_indices").is_some());
-        assert!(reg.get("es__get_mapping").is_some());
-        assert!(reg.get("es__create_index").is_some());
-        assert!(reg.get("es__delete_index").is_some());
-        assert!(reg.get("es__put_mapping").is_some());
-        assert!(reg.get("es__cat_aliases").is_some());
-        assert!(reg.get("es__get_alias").is_some());
-        assert!(reg.get("es__put_alias").is_some());
-        assert!(reg.get("es__delete_alias").is_some());
-        assert!(reg.get("es__update_aliases").is_some());
-        assert!(reg.get("es__bulk").is_some());
-        assert!(reg.get("es__count").is_some());
-        assert!(reg.get("es__reindex").is_some());
-        assert!(reg.get("es__cluster_health").is_some());
-        assert!(reg.get("es__cat_nodes").is_some());
-        assert!(reg.get("es__cat_shards").is_some());
-        assert!(reg.get("es__list_snapshots").is_some());
-        assert!(reg.get("es__restore_snapshot").is_some());
+        let args = json!({"name": "logs_reader", "body": {"cluster": ["monitor"]}});
+        let (method, path, body) = put_role_request(&args, EsFlavor::OpenSearch).unwrap();
+        assert_eq!(method, "PUT");
+        assert_eq!(path, "/_plugins/_security/api/roles/logs_reader");
+        assert!(body.unwrap().contains("monitor"));
 
-        let all_agent = reg.agent_tools();
-        let es_agent: Vec<_> = all_agent
-            .iter()
-            .filter(|c| c.name.starts_with("es__"))
-            .collect();
-        assert_eq!(
-            es_agent.len(),
-            24,
-            "expected 24 ES capabilities tagged for agent"
+        let (method, path, _) =
+            delete_role_mapping_request(&json!({"name": "admins"}), EsFlavor::Elasticsearch)
+                .unwrap();
+        assert_eq!(method, "DELETE");
+        assert_eq!(path, "/_security/role_mapping/admins");
+
+        assert!(
+            list_users_request(&json!({"name": "_has_privileges"}), EsFlavor::Elasticsearch)
+                .is_err()
         );
     }
 
-    // ---- EsBulk ----
+    #[test]
+    fn test_api_keys_are_elasticsearch_only() {
+        use super::{create_api_key_request, invalidate_api_key_request, EsFlavor};
+
+        let args = json!({"name": "ingest", "expiration": "30d"});
+        let (method, path, body) = create_api_key_request(&args, EsFlavor::Elasticsearch).unwrap();
+        assert_eq!((method, path.as_str()), ("POST", "/_security/api_key"));
+        let body: serde_json::Value = serde_json::from_str(&body.unwrap()).unwrap();
+        assert_eq!(body, json!({"name": "ingest", "expiration": "30d"}));
+
+        let err = create_api_key_request(&args, EsFlavor::OpenSearch).unwrap_err();
+        assert!(err.contains("only available on Elasticsearch"));
+
+        assert!(invalidate_api_key_request(&json!({}), EsFlavor::Elasticsearch).is_err());
+        assert!(invalidate_api_key_request(&json!({"ids": []}), EsFlavor::Elasticsearch).is_err());
+    }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_bulk_via_wiremock() {
-        use super::{CapabilityHandler, EsBulk};
-        use wiremock::matchers::{method, path};
+    async fn test_es_invalidate_api_key_sends_ids() {
+        use super::{CapabilityHandler, EsInvalidateApiKey};
+        use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        Mock::given(method("POST"))
-            .and(path("/my-index/_bulk"))
+        Mock::given(method("DELETE"))
+            .and(path("/_security/api_key"))
+            .and(body_json(json!({"ids": ["k1", "k2"]})))
             .respond_with(
                 ResponseTemplate::new(200)
-                    .set_body_string(r#"{"errors":false,"items":[{"index":{"_id":"1","result":"created"}},{"delete":{"_id":"2","result":"deleted"}}]}"#),
+                    .set_body_string(r#"{"invalidated_api_keys":["k1","k2"],"error_count":0}"#),
             )
             .mount(&server)
             .await;
 
-        let handler = EsBulk;
-        let body =
-            "{\"index\":{\"_id\":\"1\"}}\n{\"title\":\"hello\"}\n{\"delete\":{\"_id\":\"2\"}}\n";
-        let args = serde_json::json!({"index": "my-index", "body": body});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("items"));
+        let result = EsInvalidateApiKey
+            .handle(&json!({"ids": ["k1", "k2"]}), Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        assert!(result.contains("invalidated_api_keys"));
     }
 
-    #[tokio::test]
-    async fn test_es_bulk_missing_index() {
-        use super::{CapabilityHandler, EsBulk};
-        let handler = EsBulk;
-        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
-        let args = serde_json::json!({"body": "{}"});
-        let result = handler.handle(&args, Some(&config)).await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing index"));
-    }
+    #[test]
+    fn test_knn_search_body_per_flavor() {
+        use super::{knn_search_body, EsFlavor};
 
-    #[tokio::test]
-    async fn test_es_bulk_missing_body() {
-        use super::{CapabilityHandler, EsBulk};
-        let handler = EsBulk;
-        let config = serde_json::json!({"host": "http://localhost", "port": 9200});
-        let args = serde_json::json!({"index": "my-index"});
-        let result = handler.handle(&args, Some(&config)).await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Missing body"));
-    }
+        let args = json!({
+            "field": "embedding", "k": 5, "filter": {"term": {"in_stock": true}},
+            "query": {"match": {"title": "boots"}}, "query_boost": 0.3, "knn_boost": 0.7
+        });
+        let body = knn_search_body(&args, EsFlavor::Elasticsearch, &[0.5, 1.0], None).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "knn": {
+                    "field": "embedding", "query_vector": [0.5, 1.0], "k": 5,
+                    "num_candidates": 100, "filter": {"term": {"in_stock": true}}, "boost": 0.7
+                },
+                "query": {"bool": {"must": [{"match": {"title": "boots"}}], "boost": 0.3}},
+                "size": 5
+            })
+        );
 
-    // ---- EsCount ----
+        let body = knn_search_body(&args, EsFlavor::OpenSearch, &[0.5, 1.0], Some("42")).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "query": {"bool": {"should": [
+                    {"knn": {"embedding": {
+                        "vector": [0.5, 1.0], "k": 5, "boost": 0.7,
+                        "filter": {"bool": {
+                            "must_not": [{"ids": {"values": ["42"]}}],
+                            "filter": [{"term": {"in_stock": true}}]
+                        }}
+                    }}},
+                    {"bool": {"must": [{"match": {"title": "boots"}}], "boost": 0.3}}
+                ]}},
+                "size": 5
+            })
+        );
+
+        let hybrid =
+            json!({"field": "embedding", "query": {"match_all": {}}, "search_pipeline": "norm"});
+        let body = knn_search_body(&hybrid, EsFlavor::OpenSearch, &[1.0], None).unwrap();
+        assert!(body
+            .pointer("/query/hybrid/queries/1/knn/embedding")
+            .is_some());
+
+        assert!(knn_search_body(
+            &json!({"field": "e", "k": 0}),
+            EsFlavor::Elasticsearch,
+            &[1.0],
+            None
+        )
+        .is_err());
+        assert!(knn_search_body(
+            &json!({"field": "e", "k": 50, "num_candidates": 10}),
+            EsFlavor::Elasticsearch,
+            &[1.0],
+            None
+        )
+        .is_err());
+    }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_count_with_index_via_wiremock() {
-        use super::{CapabilityHandler, EsCount};
-        use wiremock::matchers::{method, path};
+    async fn test_es_knn_search_neighbors_of_document() {
+        use super::{CapabilityHandler, EsKnnSearch};
+        use wiremock::matchers::{body_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/products/_doc/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"_id":"42","found":true,"_source":{"doc":{"embedding":[0.25,0.75]}}}"#,
+            ))
+            .mount(&server)
+            .await;
         Mock::given(method("POST"))
-            .and(path("/my-index/_count"))
+            .and(path("/products/_search"))
+            .and(body_json(json!({
+                "knn": {
+                    "field": "doc.embedding", "query_vector": [0.25, 0.75], "k": 3,
+                    "num_candidates": 100,
+                    "filter": {"bool": {"must_not": [{"ids": {"values": ["42"]}}]}}
+                },
+                "size": 3
+            })))
             .respond_with(
-                ResponseTemplate::new(200)
-                    .set_body_string(r#"{"count":42,"_shards":{"total":1,"successful":1}}"#),
+                ResponseTemplate::new(200).set_body_string(r#"{"hits":{"hits":[{"_id":"7"}]}}"#),
             )
             .mount(&server)
             .await;
 
-        let handler = EsCount;
-        let args = serde_json::json!({"index": "my-index"});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("count"));
+        let args = json!({"index": "products", "field": "doc.embedding", "id": "42", "k": 3});
+        let result = EsKnnSearch
+            .handle(&args, Some(&mock_config(&server)))
+            .await
+            .unwrap();
+        assert!(result.contains("\"_id\":\"7\""));
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_count_all_indices_via_wiremock() {
-        use super::{CapabilityHandler, EsCount};
-        use wiremock::matchers::{method, path};
+    async fn test_es_knn_search_opensearch_query_and_validation() {
+        use super::{CapabilityHandler, EsKnnSearch};
+        use wiremock::matchers::{body_json, method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/_count"))
-            .respond_with(
-                ResponseTemplate::new(200)
-                    .set_body_string(r#"{"count":100,"_shards":{"total":5,"successful":5}}"#),
-            )
+            .and(path("/docs/_search"))
+            .and(query_param("search_pipeline", "norm"))
+            .and(body_json(json!({
+                "query": {"hybrid": {"queries": [
+                    {"match": {"text": "vector"}},
+                    {"knn": {"vec": {"vector": [1.0, 0.0], "k": 10}}}
+                ]}},
+                "size": 10
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"hits":{"hits":[]}}"#))
             .mount(&server)
             .await;
 
-        let handler = EsCount;
-        let args = serde_json::json!({});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("count"));
+        let config = mock_config_of_type(&server, "OPENSEARCH");
+        let args = json!({
+            "index": "docs", "field": "vec", "vector": [1, 0],
+            "query": {"match": {"text": "vector"}}, "search_pipeline": "norm"
+        });
+        let result = EsKnnSearch.handle(&args, Some(&config)).await.unwrap();
+        assert!(result.contains("\"status\":200"));
+
+        let bad = json!({"index": "docs", "field": "vec", "vector": ["x"]});
+        assert!(EsKnnSearch.handle(&bad, Some(&config)).await.is_err());
+        let both = json!({"index": "docs", "field": "vec", "vector": [1], "id": "1"});
+        assert!(EsKnnSearch.handle(&both, Some(&config)).await.is_err());
     }
 
-    // ---- EsReindex ----
+    #[test]
+    fn test_with_copy_tiebreaker() {
+        use super::{with_copy_tiebreaker, EsFlavor};
+
+        assert_eq!(
+            with_copy_tiebreaker(json!([]), EsFlavor::Elasticsearch).unwrap(),
+            json!([{"_shard_doc": "asc"}])
+        );
+        assert_eq!(
+            with_copy_tiebreaker(json!([{"ts": "asc"}]), EsFlavor::OpenSearch).unwrap(),
+            json!([{"ts": "asc"}, {"_id": "asc"}])
+        );
+        assert_eq!(
+            with_copy_tiebreaker(json!(["ts", "_id"]), EsFlavor::Elasticsearch).unwrap(),
+            json!(["ts", "_id"])
+        );
+        assert!(with_copy_tiebreaker(json!({"ts": "asc"}), EsFlavor::Elasticsearch).is_err());
+    }
+
+    #[test]
+    fn test_copy_target_policy_refuses_read_only_target() {
+        use data_studio_agent::capabilities::permissions::{
+            ConnectionMcpOverride, McpPermissionMode, McpPolicy,
+        };
+
+        let policy = McpPolicy {
+            mode: McpPermissionMode::FullAccess,
+            connection_overrides: std::collections::HashMap::from([(
+                "prod".into(),
+                ConnectionMcpOverride {
+                    read_only: true,
+                    allowed_actions: None,
+                },
+            )]),
+            ..McpPolicy::default()
+        };
+        assert!(super::check_copy_target_policy(&policy, "staging").is_ok());
+        let err = super::check_copy_target_policy(&policy, "prod").unwrap_err();
+        assert!(err.contains("target connection prod"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_transform_copy_source_renames_and_drops_paths() {
+        let mut source = json!({
+            "cust": {"name": "Ada", "tier": "gold"},
+            "internal": {"score": 3},
+            "a.b": 1,
+            "keep": true
+        });
+        let rename = vec![
+            ("cust.name".to_string(), "customer.name".to_string()),
+            ("a.b".to_string(), "ab".to_string()),
+        ];
+        super::transform_copy_source(&mut source, &rename, &["internal".to_string()]);
+        assert_eq!(
+            source,
+            json!({
+                "cust": {"tier": "gold"},
+                "customer": {"name": "Ada"},
+                "ab": 1,
+                "keep": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_put_source_path_falls_back_without_partial_objects() {
+        let mut source = json!({"a": {"b": 1}});
+        super::put_source_path(&mut source, "a.b.c.d", json!(2));
+        assert_eq!(source, json!({"a": {"b": 1}, "a.b.c.d": 2}));
+
+        super::put_source_path(&mut source, "x.y.z", json!(3));
+        assert_eq!(source["x"], json!({"y": {"z": 3}}));
+    }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_reindex_via_wiremock() {
-        use super::{CapabilityHandler, EsReindex};
-        use wiremock::matchers::{method, path};
+    async fn test_es_copy_index_between_connections() {
+        use wiremock::matchers::{body_json, body_partial_json, method, path, query_param};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        let server = MockServer::start().await;
+        let source = MockServer::start().await;
+        let target = MockServer::start().await;
         Mock::given(method("POST"))
-            .and(path("/_reindex"))
+            .and(path("/orders/_pit"))
+            .and(query_param("keep_alive", "10m"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"pit-1"}"#))
+            .mount(&source)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/orders/_mapping"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"orders-000001":{"mappings":{"properties":{"cust":{"type":"object"}}}}}"#,
+            ))
+            .mount(&source)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_search"))
+            .and(body_partial_json(json!({"search_after": [1]})))
             .respond_with(
                 ResponseTemplate::new(200)
-                    .set_body_string(r#"{"took":123,"total":10,"created":10,"batches":1}"#),
+                    .set_body_string(r#"{"pit_id":"pit-2","hits":{"hits":[]}}"#),
             )
-            .mount(&server)
+            .mount(&source)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_search"))
+            .and(body_json(json!({
+                "size": 2,
+                "pit": {"id": "pit-1", "keep_alive": "10m"},
+                "sort": [{"_shard_doc": "asc"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"pit_id":"pit-1","hits":{"hits":[
+                    {"_id":"1","_routing":"r1","_source":{"cust":{"name":"Ada"},"internal":1},"sort":[0]},
+                    {"_id":"2","_source":{"cust":{"name":"Bob"}},"sort":[1]}
+                ]}}"#,
+            ))
+            .mount(&source)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/_pit"))
+            .and(body_json(json!({"id": "pit-2"})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"succeeded":true}"#))
+            .expect(1)
+            .mount(&source)
             .await;
 
-        let handler = EsReindex;
-        let args = serde_json::json!({"body": {"source": {"index": "old-index"}, "dest": {"index": "new-index"}}});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(result.is_ok(), "got: {:?}", result.err());
-        assert!(result.unwrap().contains("created"));
+        Mock::given(method("HEAD"))
+            .and(path("/orders-v2"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&target)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/orders-v2"))
+            .and(body_json(json!({
+                "mappings": {"properties": {"cust": {"type": "object"}}}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"acknowledged":true}"#))
+            .expect(1)
+            .mount(&target)
+            .await;
+        // Built with json! so the key order matches however serde_json is configured.
+        let bulk = [
+            json!({"index": {"_index": "orders-v2", "_id": "1", "routing": "r1"}}),
+            json!({"cust": {}, "customer_name": "Ada"}),
+            json!({"index": {"_index": "orders-v2", "_id": "2"}}),
+            json!({"cust": {}, "customer_name": "Bob"}),
+        ]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+        Mock::given(method("POST"))
+            .and(path("/_bulk"))
+            .and(wiremock::matchers::body_string(bulk))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"errors":true,"items":[
+                    {"index":{"_id":"1","status":201}},
+                    {"index":{"_id":"2","status":400,"error":{"type":"mapper_parsing_exception"}}}
+                ]}"#,
+            ))
+            .mount(&target)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("dockit-copy-test-{}", std::process::id()));
+        let args = json!({
+            "index": "orders", "target_connection_id": "7", "target_index": "orders-v2",
+            "copy_mapping": true, "batch_size": 2, "job_id": "orders-job",
+            "rename": {"cust.name": "customer_name"}, "drop": ["internal"]
+        });
+        let summary = super::copy_index(&args, &mock_config(&source), &mock_config(&target), &dir)
+            .await
+            .unwrap();
+        assert_eq!(summary["copied"], 1);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["done"], true);
+        assert_eq!(summary["errors"][0]["_id"], "2");
+        assert!(summary["target"]
+            .as_str()
+            .unwrap()
+            .starts_with("created target index"));
+        let checkpoint = std::fs::read_to_string(dir.join("orders-job.json")).unwrap();
+        assert!(checkpoint.contains("\"done\": true"));
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
-    async fn test_es_reindex_missing_body() {
-        use super::{CapabilityHandler, EsReindex};
-        use wiremock::matchers::{method, path};
+    async fn test_es_copy_index_resumes_opensearch_after_max_docs() {
+        use wiremock::matchers::{body_partial_json, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let server = MockServer::start().await;
-        // The reindex handler does not validate `body` itself — a missing body is
-        // forwarded as-is and rejected by ES with a 400. Verify the handler
-        // surfaces ES's rejection instead of failing on the missing argument.
         Mock::given(method("POST"))
-            .and(path("/_reindex"))
-            .respond_with(
-                ResponseTemplate::new(400)
-                    .set_body_string(r#"{"error":{"reason":"request body is required"}}"#),
-            )
+            .and(path("/logs/_search/point_in_time"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"pit_id":"os-pit"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_search"))
+            .and(body_partial_json(json!({"search_after": ["a"]})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"hits":{"hits":[{"_id":"b","_source":{"n":2},"sort":["b"]}]}}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_search"))
+            .and(body_partial_json(
+                json!({"sort": [{"_id": "asc"}], "size": 1}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"hits":{"hits":[{"_id":"a","_source":{"n":1},"sort":["a"]}]}}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/_bulk"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"errors":true,"items":[{"create":{"_id":"x","status":409}}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/_search/point_in_time"))
+            .and(body_partial_json(json!({"pit_id": ["os-pit"]})))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .expect(1)
             .mount(&server)
             .await;
 
-        let handler = EsReindex;
-        let args = serde_json::json!({});
-        let result = handler.handle(&args, Some(&mock_config(&server))).await;
-        assert!(
-            result.is_ok(),
-            "missing body must not error locally, got: {:?}",
-            result.err()
-        );
-        assert!(result.unwrap().contains("400"));
+        let config = mock_config_of_type(&server, "OPENSEARCH");
+        let dir =
+            std::env::temp_dir().join(format!("dockit-copy-resume-test-{}", std::process::id()));
+        let args = json!({
+            "index": "logs", "target_index": "logs-copy", "batch_size": 1,
+            "max_docs": 1, "op_type": "create"
+        });
+        let first = super::copy_index(&args, &config, &config, &dir)
+            .await
+            .unwrap();
+        assert_eq!(first["done"], false);
+        assert_eq!(first["skipped_existing"], 1);
+        assert_eq!(first["job_id"], "logs-to-same-logs-copy");
+
+        let mut resume = args.clone();
+        resume["resume"] = json!(true);
+        resume["max_docs"] = json!(10);
+        resume["batch_size"] = json!(2);
+        let second = super::copy_index(&resume, &config, &config, &dir)
+            .await
+            .unwrap();
+        assert_eq!(second["resumed"], true);
+        assert_eq!(second["skipped_existing"], 2);
+        assert_eq!(second["done"], true);
+
+        resume["sort"] = json!([{"ts": "asc"}]);
+        let err = super::copy_index(&resume, &config, &config, &dir)
+            .await
+            .unwrap_err();
+        assert!(err.contains("same sort"), "got: {}", err);
+
+        let same = json!({"index": "logs", "target_index": "logs"});
+        assert!(super::copy_index(&same, &config, &config, &dir)
+            .await
+            .is_err());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
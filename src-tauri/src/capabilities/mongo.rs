@@ -614,7 +614,7 @@ impl CapabilityHandler for MongoFind {
             .and_then(|v| v.as_str())
             .ok_or("Missing collection")?;
         let filter_val = args.get("filter").cloned().unwrap_or(serde_json::json!({}));
-        let filter = crate::common::bson::json_to_bson_doc_agent(&filter_val)?;
+        let filter = crate::common::bson::json_to_bson_doc(&filter_val)?;
         let limit = args
             .get("limit")
             .and_then(|v| v.as_u64())
@@ -628,10 +628,10 @@ impl CapabilityHandler for MongoFind {
         let mut find_options = mongodb::options::FindOptions::default();
         find_options.limit = Some(limit);
         if let Some(sort_val) = args.get("sort") {
-            find_options.sort = Some(crate::common::bson::json_to_bson_doc_agent(sort_val)?);
+            find_options.sort = Some(crate::common::bson::json_to_bson_doc(sort_val)?);
         }
         if let Some(proj_val) = args.get("projection") {
-            find_options.projection = Some(crate::common::bson::json_to_bson_doc_agent(proj_val)?);
+            find_options.projection = Some(crate::common::bson::json_to_bson_doc(proj_val)?);
         }
 
         let mut cursor = coll
@@ -675,7 +675,7 @@ impl CapabilityHandler for MongoAggregate {
             .ok_or("Missing or invalid pipeline")?;
         let pipeline: Vec<Document> = pipeline_val
             .iter()
-            .map(crate::common::bson::json_to_bson_doc_agent)
+            .map(crate::common::bson::json_to_bson_doc)
             .collect::<Result<Vec<_>, _>>()?;
 
         let db = client.database(&db_name);
@@ -718,7 +718,7 @@ impl CapabilityHandler for MongoInsertOne {
             .and_then(|v| v.as_str())
             .ok_or("Missing collection")?;
         let document_val = args.get("document").ok_or("Missing document")?;
-        let document = crate::common::bson::json_to_bson_doc_agent(document_val)?;
+        let document = crate::common::bson::json_to_bson_doc(document_val)?;
 
         let db = client.database(&db_name);
         let coll = db.collection::<Document>(collection_name);
@@ -749,8 +749,8 @@ impl CapabilityHandler for MongoUpdateMany {
             .ok_or("Missing collection")?;
         let filter_val = args.get("filter").ok_or("Missing filter")?;
         let update_val = args.get("update").ok_or("Missing update")?;
-        let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
-        let update = crate::common::bson::json_to_bson_doc_agent(update_val)?;
+        let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
+        let update = crate::common::bson::json_to_bson_doc(update_val)?;
         let upsert = args
             .get("upsert")
             .and_then(|v| v.as_bool())
@@ -793,7 +793,7 @@ impl CapabilityHandler for MongoDeleteMany {
             .and_then(|v| v.as_str())
             .ok_or("Missing collection")?;
         let filter_val = args.get("filter").ok_or("Missing filter")?;
-        let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
+        let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
 
         let db = client.database(&db_name);
         let coll = db.collection::<Document>(collection_name);
@@ -1043,7 +1043,7 @@ impl CapabilityHandler for MongoCreateCollection {
                 coll_opts.timeseries = Some(ts_opts);
             }
             if let Some(validator_val) = opts_val.get("validator") {
-                let validator_doc = crate::common::bson::json_to_bson_doc_agent(validator_val)?;
+                let validator_doc = crate::common::bson::json_to_bson_doc(validator_val)?;
                 coll_opts.validator = Some(validator_doc);
             }
         }
@@ -1466,7 +1466,7 @@ impl CapabilityHandler for MongoCountDocuments {
             Some(f) if !f.is_empty() => {
                 let filter_val: Value =
                     serde_json::from_str(f).map_err(|e| format!("Invalid filter JSON: {}", e))?;
-                crate::common::bson::json_to_bson_doc_agent(&filter_val)?
+                crate::common::bson::json_to_bson_doc(&filter_val)?
             }
             _ => doc! {},
         };
@@ -1509,7 +1509,7 @@ impl CapabilityHandler for MongoUpdateDocument {
 
         let doc_val: Value = serde_json::from_str(document_str)
             .map_err(|e| format!("Invalid document JSON: {}", e))?;
-        let new_doc = crate::common::bson::json_to_bson_doc_agent(&doc_val)?;
+        let new_doc = crate::common::bson::json_to_bson_doc(&doc_val)?;
         let update = doc! { "$set": new_doc };
 
         let filter = if let Ok(oid) = ObjectId::parse_str(id_str) {
@@ -1863,7 +1863,7 @@ impl CapabilityHandler for MongoCreateIndex {
             .and_then(|v| v.as_str())
             .ok_or("Missing collection")?;
         let keys_val = args.get("keys").ok_or("Missing keys")?;
-        let keys = crate::common::bson::json_to_bson_doc_agent(keys_val)?;
+        let keys = crate::common::bson::json_to_bson_doc(keys_val)?;
 
         let db = client.database(&db_name);
         let coll = db.collection::<Document>(collection_name);
@@ -2024,7 +2024,7 @@ impl CapabilityHandler for MongoInsertMany {
             .ok_or("Missing or invalid documents array")?;
         let docs: Vec<Document> = docs_val
             .iter()
-            .map(crate::common::bson::json_to_bson_doc_agent)
+            .map(crate::common::bson::json_to_bson_doc)
             .collect::<Result<Vec<_>, _>>()?;
 
         let db = client.database(&db_name);
@@ -2068,8 +2068,8 @@ impl CapabilityHandler for MongoFindOneAndUpdate {
             .ok_or("Missing collection")?;
         let filter_val = args.get("filter").ok_or("Missing filter")?;
         let update_val = args.get("update").ok_or("Missing update")?;
-        let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
-        let update = crate::common::bson::json_to_bson_doc_agent(update_val)?;
+        let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
+        let update = crate::common::bson::json_to_bson_doc(update_val)?;
 
         let db = client.database(&db_name);
         let coll = db.collection::<Document>(collection_name);
@@ -2096,7 +2096,7 @@ impl CapabilityHandler for MongoFindOneAndUpdate {
             builder = builder.upsert(true);
         }
         if let Some(sort) = sort_val {
-            let sort_doc = crate::common::bson::json_to_bson_doc_agent(sort)?;
+            let sort_doc = crate::common::bson::json_to_bson_doc(sort)?;
             builder = builder.sort(sort_doc);
         }
 
@@ -2160,7 +2160,7 @@ impl CapabilityHandler for MongoBulkWrite {
                     let doc_val = op
                         .get("document")
                         .ok_or("insert_one operation missing 'document'")?;
-                    let doc = crate::common::bson::json_to_bson_doc_agent(doc_val)?;
+                    let doc = crate::common::bson::json_to_bson_doc(doc_val)?;
                     models.push(mongodb::options::WriteModel::InsertOne(
                         mongodb::options::InsertOneModel::builder()
                             .namespace(ns.clone())
@@ -2171,8 +2171,8 @@ impl CapabilityHandler for MongoBulkWrite {
                 "update_one" => {
                     let filter_val = op.get("filter").ok_or("update_one missing 'filter'")?;
                     let update_val = op.get("update").ok_or("update_one missing 'update'")?;
-                    let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
-                    let update = crate::common::bson::json_to_bson_doc_agent(update_val)?;
+                    let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
+                    let update = crate::common::bson::json_to_bson_doc(update_val)?;
                     let model = if let Some(true) = op.get("upsert").and_then(|v| v.as_bool()) {
                         mongodb::options::UpdateOneModel::builder()
                             .namespace(ns.clone())
@@ -2192,8 +2192,8 @@ impl CapabilityHandler for MongoBulkWrite {
                 "update_many" => {
                     let filter_val = op.get("filter").ok_or("update_many missing 'filter'")?;
                     let update_val = op.get("update").ok_or("update_many missing 'update'")?;
-                    let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
-                    let update = crate::common::bson::json_to_bson_doc_agent(update_val)?;
+                    let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
+                    let update = crate::common::bson::json_to_bson_doc(update_val)?;
                     let model = if let Some(true) = op.get("upsert").and_then(|v| v.as_bool()) {
                         mongodb::options::UpdateManyModel::builder()
                             .namespace(ns.clone())
@@ -2212,7 +2212,7 @@ impl CapabilityHandler for MongoBulkWrite {
                 }
                 "delete_one" => {
                     let filter_val = op.get("filter").ok_or("delete_one missing 'filter'")?;
-                    let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
+                    let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
                     models.push(mongodb::options::WriteModel::DeleteOne(
                         mongodb::options::DeleteOneModel::builder()
                             .namespace(ns.clone())
@@ -2222,7 +2222,7 @@ impl CapabilityHandler for MongoBulkWrite {
                 }
                 "delete_many" => {
                     let filter_val = op.get("filter").ok_or("delete_many missing 'filter'")?;
-                    let filter = crate::common::bson::json_to_bson_doc_agent(filter_val)?;
+                    let filter = crate::common::bson::json_to_bson_doc(filter_val)?;
                     models.push(mongodb::options::WriteModel::DeleteMany(
                         mongodb::options::DeleteManyModel::builder()
                             .namespace(ns.clone())
@@ -2276,7 +2276,7 @@ impl CapabilityHandler for MongoDistinct {
             .ok_or("Missing field")?;
         let filter = args
             .get("filter")
-            .map(crate::common::bson::json_to_bson_doc_agent)
+            .map(crate::common::bson::json_to_bson_doc)
             .transpose()?
             .unwrap_or_else(|| doc! {});
 
@@ -2298,6 +2298,1240 @@ impl CapabilityHandler for MongoDistinct {
     }
 }
 
+// ---------------------------------------------------------------------------
+// change stream handler
+// ---------------------------------------------------------------------------
+
+const WATCH_DEFAULT_EVENTS: u64 = 20;
+const WATCH_MAX_EVENTS: u64 = 500;
+const WATCH_DEFAULT_SECONDS: u64 = 10;
+const WATCH_MAX_SECONDS: u64 = 120;
+
+pub(crate) struct MongoWatch {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoWatch {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+fn watch_target(args: &Value, config: &Value) -> Result<crate::common::mongo::WatchTarget, String> {
+    use crate::common::mongo::WatchTarget;
+
+    let collection = args
+        .get("collection")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty());
+    let scope = args
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .unwrap_or(if collection.is_some() {
+            "collection"
+        } else {
+            "database"
+        });
+    match scope {
+        "collection" => Ok(WatchTarget::Collection {
+            database: get_db_name(args, config)?,
+            collection: collection
+                .ok_or("scope 'collection' requires a collection")?
+                .to_string(),
+        }),
+        "database" => Ok(WatchTarget::Database(get_db_name(args, config)?)),
+        "deployment" => Ok(WatchTarget::Deployment),
+        other => Err(format!(
+            "Unsupported scope '{}': expected collection, database or deployment",
+            other
+        )),
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoWatch {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let target = watch_target(args, config)?;
+        let pipeline: Vec<Document> = match args.get("pipeline") {
+            Some(Value::Array(stages)) => stages
+                .iter()
+                .map(crate::common::bson::json_to_bson_doc)
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(Value::Null) | None => Vec::new(),
+            Some(_) => return Err("pipeline must be an array of stages".to_string()),
+        };
+        let options = crate::common::mongo::change_stream_options(
+            args.get("full_document").and_then(|v| v.as_str()),
+            args.get("resume_after"),
+        )?;
+        let max_events = args
+            .get("max_events")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(WATCH_DEFAULT_EVENTS)
+            .clamp(1, WATCH_MAX_EVENTS) as usize;
+        let max_seconds = args
+            .get("max_seconds")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(WATCH_DEFAULT_SECONDS)
+            .clamp(1, WATCH_MAX_SECONDS);
+
+        let (client, _) = self.factory.create_client(config).await?;
+        let mut stream =
+            crate::common::mongo::open_change_stream(&client, &target, pipeline, options).await?;
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(max_seconds);
+        let mut events: Vec<Value> = Vec::new();
+        let mut timed_out = false;
+        while events.len() < max_events && stream.is_alive() {
+            match tokio::time::timeout_at(deadline, stream.next_if_any()).await {
+                Ok(Ok(Some(event))) => events.push(crate::common::bson::doc_to_value(&event)),
+                Ok(Ok(None)) => continue,
+                Ok(Err(e)) => return Err(format!("change stream error: {}", e)),
+                Err(_) => {
+                    timed_out = true;
+                    break;
+                }
+            }
+        }
+
+        let data = serde_json::json!({
+            "target": target.describe(),
+            "count": events.len(),
+            "events": events,
+            "resume_token": crate::common::mongo::change_stream_resume_token(&stream),
+            "timed_out": timed_out,
+            "invalidated": !stream.is_alive(),
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// explain handler
+// ---------------------------------------------------------------------------
+
+pub(crate) struct MongoExplain {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoExplain {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+/// Builds the find, aggregate or count command that `mongo__explain` wraps.
+fn explain_target_command(args: &Value, collection: &str) -> Result<(String, Document), String> {
+    let optional_doc = |key: &str| -> Result<Option<Document>, String> {
+        args.get(key)
+            .filter(|v| !v.is_null())
+            .map(crate::common::bson::json_to_bson_doc)
+            .transpose()
+    };
+    let operation = args.get("operation").and_then(|v| v.as_str()).unwrap_or(
+        if args.get("pipeline").is_some() {
+            "aggregate"
+        } else {
+            "find"
+        },
+    );
+    let filter = optional_doc("filter")?.unwrap_or_default();
+
+    let mut command = match operation {
+        "find" => {
+            let mut command = doc! { "find": collection, "filter": filter };
+            if let Some(projection) = optional_doc("projection")? {
+                command.insert("projection", projection);
+            }
+            if let Some(sort) = optional_doc("sort")? {
+                command.insert("sort", sort);
+            }
+            if let Some(limit) = args.get("limit").and_then(|v| v.as_i64()) {
+                command.insert("limit", limit);
+            }
+            if let Some(skip) = args.get("skip").and_then(|v| v.as_i64()) {
+                command.insert("skip", skip);
+            }
+            command
+        }
+        "aggregate" => {
+            let pipeline: Vec<Document> = args
+                .get("pipeline")
+                .and_then(|v| v.as_array())
+                .ok_or("aggregate requires a pipeline array")?
+                .iter()
+                .map(crate::common::bson::json_to_bson_doc)
+                .collect::<Result<Vec<_>, _>>()?;
+            doc! { "aggregate": collection, "pipeline": pipeline, "cursor": {} }
+        }
+        "count" => doc! { "count": collection, "query": filter },
+        other => {
+            return Err(format!(
+                "Unsupported operation '{}': expected find, aggregate or count",
+                other
+            ))
+        }
+    };
+    match args.get("hint") {
+        Some(Value::String(name)) if !name.is_empty() => {
+            command.insert("hint", name.as_str());
+        }
+        Some(keys @ Value::Object(_)) => {
+            command.insert("hint", crate::common::bson::json_to_bson_doc(keys)?);
+        }
+        _ => {}
+    }
+    Ok((operation.to_string(), command))
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoExplain {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let collection_name = args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing collection")?;
+        let verbosity = crate::common::mongo_explain::validate_verbosity(
+            args.get("verbosity")
+                .and_then(|v| v.as_str())
+                .unwrap_or("executionStats"),
+        )?;
+        let (operation, command) = explain_target_command(args, collection_name)?;
+        // executionStats runs the pipeline, so a $out/$merge stage would write.
+        if verbosity != "queryPlanner" {
+            let writes = command.get_array("pipeline").is_ok_and(|stages| {
+                stages.iter().any(|stage| {
+                    stage
+                        .as_document()
+                        .is_some_and(|s| s.contains_key("$out") || s.contains_key("$merge"))
+                })
+            });
+            if writes {
+                return Err(
+                    "Pipelines with $out or $merge can only be explained at queryPlanner verbosity"
+                        .to_string(),
+                );
+            }
+        }
+
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let explain = client
+            .database(&db_name)
+            .run_command(crate::common::mongo_explain::explain_command(
+                command, verbosity,
+            ))
+            .await
+            .map_err(|e| format!("explain failed: {}", e))?;
+
+        let mut data = serde_json::json!({
+            "operation": operation,
+            "verbosity": verbosity,
+            "summary": crate::common::mongo_explain::summarize_explain(&explain),
+        });
+        if args.get("include_plan").and_then(|v| v.as_bool()) != Some(false) {
+            data["explain"] = crate::common::bson::doc_to_value(&explain);
+        }
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Profiler and index advisor handlers
+// ---------------------------------------------------------------------------
+
+const ADVISOR_DEFAULT_ENTRIES: i64 = 1000;
+const ADVISOR_MAX_ENTRIES: i64 = 10_000;
+
+pub(crate) struct MongoGetProfiling {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGetProfiling {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+fn profiling_settings(reply: &Document) -> Value {
+    let field = |key: &str| {
+        reply
+            .get(key)
+            .map(crate::common::bson::bson_to_value)
+            .unwrap_or(Value::Null)
+    };
+    serde_json::json!({
+        "level": field("was"),
+        "slowms": field("slowms"),
+        "sample_rate": field("sampleRate"),
+        "filter": field("filter"),
+    })
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGetProfiling {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let reply = client
+            .database(&db_name)
+            .run_command(doc! { "profile": -1 })
+            .await
+            .map_err(|e| format!("profile failed: {}", e))?;
+
+        let mut data = profiling_settings(&reply);
+        data["database"] = Value::from(db_name);
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoSetProfiling {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoSetProfiling {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+/// The `profile` command for the requested level, slowms, sample rate and filter.
+fn set_profiling_command(args: &Value) -> Result<Document, String> {
+    let level = args
+        .get("level")
+        .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+        .ok_or("Missing level (0 = off, 1 = slow operations, 2 = all operations)")?;
+    if !(0..=2).contains(&level) {
+        return Err(format!(
+            "Invalid profiling level {}: expected 0, 1 or 2",
+            level
+        ));
+    }
+    let mut command = doc! { "profile": level as i32 };
+    if let Some(slowms) = args.get("slowms").and_then(|v| v.as_i64()) {
+        command.insert("slowms", slowms);
+    }
+    if let Some(rate) = args
+        .get("sample_rate")
+        .and_then(|v| v.as_f64().or_else(|| v.as_str()?.parse().ok()))
+    {
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(format!("Invalid sample_rate {}: expected 0.0 to 1.0", rate));
+        }
+        command.insert("sampleRate", rate);
+    }
+    if let Some(filter) = args.get("filter").filter(|v| !v.is_null()) {
+        command.insert("filter", crate::common::bson::json_to_bson_doc(filter)?);
+    }
+    Ok(command)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoSetProfiling {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let command = set_profiling_command(args)?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let db = client.database(&db_name);
+        // The reply to a set carries the settings that were in effect before it.
+        let previous = db
+            .run_command(command)
+            .await
+            .map_err(|e| format!("profile failed: {}", e))?;
+        let current = db
+            .run_command(doc! { "profile": -1 })
+            .await
+            .map_err(|e| format!("profile failed: {}", e))?;
+
+        let data = serde_json::json!({
+            "database": db_name,
+            "previous": profiling_settings(&previous),
+            "current": profiling_settings(&current),
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+async fn list_index_documents(
+    db: &mongodb::Database,
+    collection: &str,
+) -> Result<Vec<Document>, String> {
+    let reply = db
+        .run_command(doc! { "listIndexes": collection })
+        .await
+        .map_err(|e| format!("listIndexes failed: {}", e))?;
+    Ok(reply
+        .get_document("cursor")
+        .and_then(|cursor| cursor.get_array("firstBatch"))
+        .map(|batch| {
+            batch
+                .iter()
+                .filter_map(|b| b.as_document().cloned())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+pub(crate) struct MongoSuggestIndexes {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoSuggestIndexes {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoSuggestIndexes {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        use crate::common::mongo_advisor::{group_profiled_shapes, index_keys, propose_indexes};
+
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let db = client.database(&db_name);
+
+        let mut filter =
+            doc! { "op": { "$in": ["query", "command", "update", "remove", "getmore"] } };
+        if let Some(collection) = args.get("collection").and_then(|v| v.as_str()) {
+            filter.insert("ns", format!("{}.{}", db_name, collection));
+        }
+        if let Some(min_millis) = args.get("min_millis").and_then(|v| v.as_i64()) {
+            filter.insert("millis", doc! { "$gte": min_millis });
+        }
+        let mut opts = mongodb::options::FindOptions::default();
+        opts.sort = Some(doc! { "ts": -1 });
+        opts.limit = Some(
+            args.get("limit")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(ADVISOR_DEFAULT_ENTRIES)
+                .clamp(1, ADVISOR_MAX_ENTRIES),
+        );
+        let entries: Vec<Document> = db
+            .collection::<Document>("system.profile")
+            .find(filter)
+            .with_options(opts)
+            .await
+            .map_err(|e| format!("Failed to read system.profile: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to read system.profile: {}", e))?;
+
+        let shapes = group_profiled_shapes(&entries);
+        let mut existing = std::collections::HashMap::new();
+        for stats in &shapes {
+            let namespace = &stats.shape.namespace;
+            if existing.contains_key(namespace) {
+                continue;
+            }
+            // A dropped collection simply has no indexes to match against.
+            let indexes = list_index_documents(&db, stats.shape.collection())
+                .await
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|index| {
+                    let name = index.get_str("name").ok()?.to_string();
+                    Some((name, index_keys(index.get_document("key").ok()?)?))
+                })
+                .collect::<Vec<_>>();
+            existing.insert(namespace.clone(), indexes);
+        }
+        let (proposals, already_indexed) = propose_indexes(&shapes, &existing);
+
+        let mut data = serde_json::json!({
+            "database": db_name,
+            "profiled_operations": entries.len(),
+            "query_shapes": shapes.len(),
+            "proposals": proposals,
+            "already_indexed": already_indexed,
+        });
+        if entries.is_empty() {
+            data["note"] = Value::from(format!(
+                "system.profile has no query entries for {}. Enable the profiler with mongo__set_profiling (for example level 1, slowms 50), let the workload run, then call this again.",
+                db_name
+            ));
+        }
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoIndexUsage {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoIndexUsage {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+/// Folds `$indexStats` rows (one per index per node) into one entry per index.
+fn index_usage(rows: &[Document]) -> Vec<(crate::common::mongo_advisor::IndexInfo, i64, Value)> {
+    use crate::common::mongo_advisor::IndexInfo;
+
+    let mut usage: Vec<(IndexInfo, i64, Value)> = Vec::new();
+    for row in rows {
+        let Ok(name) = row.get_str("name") else {
+            continue;
+        };
+        let accesses = row.get_document("accesses").ok();
+        let ops = match accesses.and_then(|a| a.get("ops")) {
+            Some(Bson::Int64(n)) => *n,
+            Some(Bson::Int32(n)) => i64::from(*n),
+            _ => 0,
+        };
+        let since = accesses
+            .and_then(|a| a.get("since"))
+            .map(crate::common::bson::bson_to_value)
+            .unwrap_or(Value::Null);
+        match usage.iter_mut().find(|(info, _, _)| info.name == name) {
+            Some((_, total, _)) => *total += ops,
+            None => usage.push((
+                IndexInfo {
+                    name: name.to_string(),
+                    key: row.get_document("key").cloned().unwrap_or_default(),
+                    spec: row.get_document("spec").cloned().unwrap_or_default(),
+                },
+                ops,
+                since,
+            )),
+        }
+    }
+    usage
+}
+
+/// What an idle index is kept for even though it serves no queries: unique
+/// indexes enforce a constraint and TTL indexes expire documents.
+fn idle_index_role(spec: &Document) -> Option<&'static str> {
+    if spec.get_bool("unique").unwrap_or(false) {
+        Some("unique")
+    } else if spec.contains_key("expireAfterSeconds") {
+        Some("ttl")
+    } else {
+        None
+    }
+}
+
+/// Indexes with no operations, split into drop candidates and unique/TTL
+/// indexes that do their job without serving queries. `_id_` is never listed.
+fn idle_indexes(
+    usage: &[(crate::common::mongo_advisor::IndexInfo, i64, Value)],
+) -> (Vec<&str>, Vec<Value>) {
+    let mut unused = Vec::new();
+    let mut kept = Vec::new();
+    for (info, ops, _) in usage {
+        if *ops != 0 || info.name == "_id_" {
+            continue;
+        }
+        match idle_index_role(&info.spec) {
+            Some(role) => kept.push(serde_json::json!({ "index": info.name, "role": role })),
+            None => unused.push(info.name.as_str()),
+        }
+    }
+    (unused, kept)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoIndexUsage {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let db = client.database(&db_name);
+
+        let collections: Vec<String> = match args.get("collection").and_then(|v| v.as_str()) {
+            Some(collection) => vec![collection.to_string()],
+            None => db
+                .list_collection_names()
+                .filter(doc! { "type": "collection" })
+                .await
+                .map_err(|e| format!("Failed to list collections: {}", e))?
+                .into_iter()
+                .filter(|name| !name.starts_with("system."))
+                .collect(),
+        };
+
+        let mut results: Vec<Value> = Vec::new();
+        let (mut unused_total, mut redundant_total) = (0usize, 0usize);
+        for collection in collections {
+            let rows: Vec<Document> = db
+                .collection::<Document>(&collection)
+                .aggregate(vec![doc! { "$indexStats": {} }])
+                .await
+                .map_err(|e| format!("$indexStats failed on {}: {}", collection, e))?
+                .try_collect()
+                .await
+                .map_err(|e| format!("$indexStats failed on {}: {}", collection, e))?;
+            let usage = index_usage(&rows);
+            let (unused, idle_constraints) = idle_indexes(&usage);
+            let infos: Vec<crate::common::mongo_advisor::IndexInfo> =
+                usage.iter().map(|(info, _, _)| info.clone()).collect();
+            let redundant: Vec<Value> = crate::common::mongo_advisor::redundant_indexes(&infos)
+                .into_iter()
+                .map(|(index, covered_by)| {
+                    serde_json::json!({ "index": index, "covered_by": covered_by })
+                })
+                .collect();
+            unused_total += unused.len();
+            redundant_total += redundant.len();
+            results.push(serde_json::json!({
+                "collection": collection,
+                "indexes": usage.iter().map(|(info, ops, since)| serde_json::json!({
+                    "name": info.name,
+                    "key": info.key.to_string(),
+                    "ops": ops,
+                    "since": since,
+                })).collect::<Vec<_>>(),
+                "unused": unused,
+                "idle_constraints": idle_constraints,
+                "redundant": redundant,
+            }));
+        }
+
+        let data = serde_json::json!({
+            "database": db_name,
+            "collections": results,
+            "unused_count": unused_total,
+            "redundant_count": redundant_total,
+            "note": "$indexStats counters reset when a server restarts and only cover the node that answered; check every replica set member before dropping an index.",
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Schema analysis handler
+// ---------------------------------------------------------------------------
+
+const SCHEMA_DEFAULT_SAMPLE: i64 = 100;
+const SCHEMA_MAX_SAMPLE: i64 = 1000;
+const SCHEMA_DEFAULT_DEPTH: u64 = 8;
+
+pub(crate) struct MongoAnalyzeSchema {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoAnalyzeSchema {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoAnalyzeSchema {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let collection_name = args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing collection")?;
+        let sample_size = args
+            .get("sample_size")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(SCHEMA_DEFAULT_SAMPLE)
+            .clamp(1, SCHEMA_MAX_SAMPLE);
+        let max_depth = args
+            .get("max_depth")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(SCHEMA_DEFAULT_DEPTH) as usize;
+        let mut pipeline = Vec::new();
+        if let Some(filter) = args.get("filter").filter(|v| !v.is_null()) {
+            pipeline.push(doc! { "$match": crate::common::bson::json_to_bson_doc(filter)? });
+        }
+        pipeline.push(doc! { "$sample": { "size": sample_size } });
+
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let mut opts = mongodb::options::AggregateOptions::default();
+        opts.max_time = Some(std::time::Duration::from_secs(30));
+        let mut cursor = client
+            .database(&db_name)
+            .collection::<Document>(collection_name)
+            .aggregate(pipeline)
+            .with_options(opts)
+            .await
+            .map_err(|e| format!("$sample failed: {}", e))?;
+
+        let mut analyzer = crate::common::mongo_schema::SchemaAnalyzer::new(max_depth);
+        while let Some(doc) = cursor
+            .try_next()
+            .await
+            .map_err(|e| format!("cursor error: {}", e))?
+        {
+            analyzer.add_document(&doc);
+        }
+
+        let mut data = serde_json::json!({
+            "database": db_name,
+            "collection": collection_name,
+            "sampled": analyzer.documents(),
+            "field_count": analyzer.field_count(),
+            "fields": analyzer.field_tree(),
+            "mixed_type_fields": analyzer.mixed_type_fields(),
+        });
+        if analyzer.truncated() {
+            data["truncated"] = Value::from(true);
+        }
+        if args.get("emit_validator").and_then(|v| v.as_bool()) == Some(true) {
+            data["validator"] = analyzer.json_schema();
+        }
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validator handlers
+// ---------------------------------------------------------------------------
+
+const VALIDATION_LEVELS: [&str; 3] = ["off", "strict", "moderate"];
+const VALIDATION_ACTIONS: [&str; 3] = ["error", "warn", "errorAndLog"];
+const DRY_RUN_DEFAULT_SAMPLES: i64 = 5;
+const DRY_RUN_MAX_SAMPLES: i64 = 50;
+
+/// The `listCollections` entry of one collection.
+async fn collection_info(db: &mongodb::Database, collection: &str) -> Result<Document, String> {
+    let reply = db
+        .run_command(doc! { "listCollections": 1, "filter": { "name": collection } })
+        .await
+        .map_err(|e| format!("listCollections failed: {}", e))?;
+    reply
+        .get_document("cursor")
+        .and_then(|cursor| cursor.get_array("firstBatch"))
+        .ok()
+        .and_then(|batch| batch.first())
+        .and_then(Bson::as_document)
+        .cloned()
+        .ok_or_else(|| format!("Collection '{}' not found", collection))
+}
+
+fn validation_settings(info: &Document) -> Value {
+    let options = info.get_document("options").cloned().unwrap_or_default();
+    serde_json::json!({
+        "validator": options
+            .get_document("validator")
+            .map(crate::common::bson::doc_to_value)
+            .unwrap_or(Value::Null),
+        "validation_level": options.get_str("validationLevel").unwrap_or("strict"),
+        "validation_action": options.get_str("validationAction").unwrap_or("error"),
+    })
+}
+
+pub(crate) struct MongoGetValidator {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGetValidator {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGetValidator {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let collection_name = args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing collection")?;
+
+        let info = collection_info(&client.database(&db_name), collection_name).await?;
+        let mut data = validation_settings(&info);
+        data["database"] = Value::from(db_name);
+        data["collection"] = Value::from(collection_name);
+        data["type"] = Value::from(info.get_str("type").unwrap_or("collection"));
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoSetValidator {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoSetValidator {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+/// The `collMod` command that sets the validator, level and action given in `args`.
+fn set_validator_command(args: &Value, collection: &str) -> Result<Document, String> {
+    let mut command = doc! { "collMod": collection };
+    if let Some(validator) = args.get("validator").filter(|v| !v.is_null()) {
+        command.insert(
+            "validator",
+            crate::common::bson::json_to_bson_doc(validator)?,
+        );
+    }
+    if let Some(level) = args.get("validation_level").and_then(|v| v.as_str()) {
+        if !VALIDATION_LEVELS.contains(&level) {
+            return Err(format!(
+                "Invalid validation_level '{}': expected {}",
+                level,
+                VALIDATION_LEVELS.join(", ")
+            ));
+        }
+        command.insert("validationLevel", level);
+    }
+    if let Some(action) = args.get("validation_action").and_then(|v| v.as_str()) {
+        if !VALIDATION_ACTIONS.contains(&action) {
+            return Err(format!(
+                "Invalid validation_action '{}': expected {}",
+                action,
+                VALIDATION_ACTIONS.join(", ")
+            ));
+        }
+        command.insert("validationAction", action);
+    }
+    if command.len() == 1 {
+        return Err(
+            "Nothing to change: pass validator, validation_level or validation_action".to_string(),
+        );
+    }
+    Ok(command)
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoSetValidator {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let collection_name = args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing collection")?;
+        let command = set_validator_command(args, collection_name)?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let db = client.database(&db_name);
+
+        let previous = collection_info(&db, collection_name).await?;
+        db.run_command(command)
+            .await
+            .map_err(|e| format!("collMod failed: {}", e))?;
+        let current = collection_info(&db, collection_name).await?;
+
+        let data = serde_json::json!({
+            "database": db_name,
+            "collection": collection_name,
+            "previous": validation_settings(&previous),
+            "current": validation_settings(&current),
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoDryRunValidator {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoDryRunValidator {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoDryRunValidator {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let collection_name = args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing collection")?;
+        let validator = args
+            .get("validator")
+            .filter(|v| v.is_object())
+            .ok_or("Missing validator, e.g. {\"$jsonSchema\": {...}}")?;
+        let validator = crate::common::bson::json_to_bson_doc(validator)?;
+        let sample_limit = args
+            .get("sample_limit")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(DRY_RUN_DEFAULT_SAMPLES)
+            .clamp(0, DRY_RUN_MAX_SAMPLES);
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let coll = client
+            .database(&db_name)
+            .collection::<Document>(collection_name);
+
+        // Documents the validator rejects are exactly those matching $nor of it.
+        let failing_filter = doc! { "$nor": [validator] };
+        let mut count_opts = mongodb::options::CountOptions::default();
+        count_opts.max_time = Some(std::time::Duration::from_secs(60));
+        let failing = coll
+            .count_documents(failing_filter.clone())
+            .with_options(count_opts)
+            .await
+            .map_err(|e| format!("Dry run failed: {}", e))?;
+        let total = coll
+            .estimated_document_count()
+            .await
+            .map_err(|e| format!("Dry run failed: {}", e))?;
+        let samples: Vec<Value> = if sample_limit > 0 && failing > 0 {
+            let mut opts = mongodb::options::FindOptions::default();
+            opts.limit = Some(sample_limit);
+            coll.find(failing_filter)
+                .with_options(opts)
+                .await
+                .map_err(|e| format!("Dry run failed: {}", e))?
+                .try_collect::<Vec<Document>>()
+                .await
+                .map_err(|e| format!("Dry run failed: {}", e))?
+                .iter()
+                .map(crate::common::bson::doc_to_value)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let data = serde_json::json!({
+            "database": db_name,
+            "collection": collection_name,
+            "failing_count": failing,
+            "total_count": total,
+            "passes": failing == 0,
+            "failing_samples": samples,
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GridFS handlers
+// ---------------------------------------------------------------------------
+
+const GRIDFS_DEFAULT_PAGE: i64 = 50;
+const GRIDFS_MAX_PAGE: i64 = 500;
+
+fn gridfs_bucket_name(args: &Value) -> &str {
+    args.get("bucket")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or(crate::common::mongo_gridfs::DEFAULT_BUCKET)
+}
+
+fn gridfs_file_id(args: &Value) -> Result<Bson, String> {
+    crate::common::mongo_gridfs::file_id_from_json(args.get("file_id").unwrap_or(&Value::Null))
+}
+
+pub(crate) struct MongoGridfsListBuckets {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGridfsListBuckets {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGridfsListBuckets {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+
+        let buckets = crate::common::mongo_gridfs::list_buckets(&client.database(&db_name)).await?;
+        let data = serde_json::json!({
+            "database": db_name,
+            "buckets": buckets,
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoGridfsListFiles {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGridfsListFiles {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGridfsListFiles {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let filter = args
+            .get("filter")
+            .map(crate::common::bson::json_to_bson_doc)
+            .transpose()?
+            .unwrap_or_else(|| doc! {});
+        let sort = args
+            .get("sort")
+            .map(crate::common::bson::json_to_bson_doc)
+            .transpose()?;
+        let skip = args.get("skip").and_then(|v| v.as_u64()).unwrap_or(0);
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(GRIDFS_DEFAULT_PAGE)
+            .clamp(1, GRIDFS_MAX_PAGE);
+
+        let mut data = crate::common::mongo_gridfs::list_files(
+            &client.database(&db_name),
+            gridfs_bucket_name(args),
+            filter,
+            sort,
+            skip,
+            limit,
+        )
+        .await?;
+        data["database"] = Value::from(db_name);
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoGridfsDeleteFile {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGridfsDeleteFile {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGridfsDeleteFile {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let id = gridfs_file_id(args)?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let bucket_name = gridfs_bucket_name(args);
+
+        let bucket = crate::common::mongo_gridfs::bucket(&client.database(&db_name), bucket_name);
+        bucket
+            .delete(id.clone())
+            .await
+            .map_err(|e| format!("GridFS delete failed: {}", e))?;
+        let data = serde_json::json!({
+            "bucket": bucket_name,
+            "file_id": crate::common::bson::bson_to_value(&id),
+            "deleted": true,
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
+pub(crate) struct MongoGridfsRenameFile {
+    factory: Box<dyn MongoClientFactory>,
+}
+
+impl MongoGridfsRenameFile {
+    pub(crate) fn new() -> Self {
+        Self {
+            factory: Box::new(RealMongoClientFactory),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_factory(factory: Box<dyn MongoClientFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[async_trait::async_trait]
+impl CapabilityHandler for MongoGridfsRenameFile {
+    async fn handle(
+        &self,
+        args: &Value,
+        connection_config: Option<&Value>,
+    ) -> Result<String, String> {
+        let config =
+            connection_config.ok_or_else(|| "MongoDB requires a connection config".to_string())?;
+        let id = gridfs_file_id(args)?;
+        let new_filename = args
+            .get("new_filename")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or("Missing new_filename")?;
+        let (client, _) = self.factory.create_client(config).await?;
+        let db_name = get_db_name(args, config)?;
+        let bucket_name = gridfs_bucket_name(args);
+
+        let bucket = crate::common::mongo_gridfs::bucket(&client.database(&db_name), bucket_name);
+        bucket
+            .rename(id.clone(), new_filename)
+            .await
+            .map_err(|e| format!("GridFS rename failed: {}", e))?;
+        let data = serde_json::json!({
+            "bucket": bucket_name,
+            "file_id": crate::common::bson::bson_to_value(&id),
+            "filename": new_filename,
+        });
+        Ok(ApiResponse::json(data).into_string())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Registration
 // ---------------------------------------------------------------------------
@@ -2310,12 +3544,21 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
             serde_json::json!({"type": "string", "description": "ID of the target connection from the session"}),
         );
         for (name, desc, type_str, _required) in props {
-            let val = if *type_str == "object" || *type_str == "array" || *type_str == "integer" {
-                serde_json::json!({"type": type_str, "description": desc})
-            } else {
-                serde_json::json!({"type": "string", "description": desc})
+            let types: Vec<&str> = type_str
+                .split('|')
+                .map(|t| match t {
+                    "object" | "array" | "integer" | "number" | "boolean" => t,
+                    _ => "string",
+                })
+                .collect();
+            let type_val = match types.as_slice() {
+                [single] => Value::from(*single),
+                several => Value::from(several.to_vec()),
             };
-            properties.insert(name.to_string(), val);
+            properties.insert(
+                name.to_string(),
+                serde_json::json!({"type": type_val, "description": desc}),
+            );
         }
         let required: Vec<String> = std::iter::once("connection_id".to_string())
             .chain(
@@ -2332,101 +3575,412 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
         })
     };
 
-    macro_rules! reg {
-        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr, $parallel_ok:expr) => {
-            registry.register(Capability {
-                name: $name,
-                description: $desc,
-                handler: Arc::new($handler),
-                input_schema: $schema,
-                risk_level: $risk,
-                required_permission: $perm,
-                source_kind: SourceKind::Database("MONGODB"),
-                tags: $tags,
-                parallel_ok: $parallel_ok,
-            });
-        };
-        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr) => {
-            reg!($name, $desc, $handler, $schema, $risk, $perm, $tags, false)
-        };
-    }
+    macro_rules! reg {
+        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr, $parallel_ok:expr) => {
+            registry.register(Capability {
+                name: $name,
+                description: $desc,
+                handler: Arc::new($handler),
+                input_schema: $schema,
+                risk_level: $risk,
+                required_permission: $perm,
+                source_kind: SourceKind::Database("MONGODB"),
+                tags: $tags,
+                parallel_ok: $parallel_ok,
+            });
+        };
+        ($name:expr, $desc:expr, $handler:expr, $schema:expr, $risk:expr, $perm:expr, $tags:expr) => {
+            reg!($name, $desc, $handler, $schema, $risk, $perm, $tags, false)
+        };
+    }
+
+    reg!("mongo__list_databases", "List all database names on a MongoDB server. Use this first when no database is known so you can pick one for subsequent calls. First step for any MongoDB task: list databases, then list_collections, then analyze_schema to learn a collection's fields, then find. Use data_studio/dockit tools for ALL MongoDB access instead of local mongosh CLI. Report results in the user's language (中文/English).",
+          MongoListDatabases::new(),
+          mongo_schema(&[]),
+          RiskLevel::Safe, "read", &["agent", "ui"]);
+
+    reg!(
+        "mongo__list_collections",
+        "List all collection names in a MongoDB database. Use this to discover which collections exist before querying. Report results in the user's language (中文/English).",
+        MongoListCollections::new(),
+        mongo_schema(&[("database", "MongoDB database name", "string", false)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__find",
+        "Query documents from a MongoDB collection using a filter and return the matching documents. Documents come back as Extended JSON ({\"$oid\": ...}, {\"$date\": ...}, {\"$numberLong\": ...}), and filters and documents passed to any mongo__ tool accept the same forms.\n\nUse when a task needs MongoDB data (document counts, content lookup, aggregation of records) — instead of shelling out to mongosh.\n\nExample: {\"database\": \"app\", \"collection\": \"users\", \"filter\": {\"age\": {\"$gt\": 30}}}.",
+        MongoFind::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name to query", "string", true),
+            (
+                "filter",
+                "MongoDB query filter, e.g. {\"status\": \"active\"}",
+                "object",
+                true
+            ),
+            (
+                "projection",
+                "Optional fields to include/exclude",
+                "object",
+                false
+            ),
+            (
+                "limit",
+                "Maximum documents to return (default 20, max 100)",
+                "integer",
+                false
+            ),
+            ("sort", "Optional sort specification", "object", false),
+        ]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!(
+        "mongo__aggregate",
+        "Execute a MongoDB aggregation pipeline on a collection.",
+        MongoAggregate::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            (
+                "pipeline",
+                "Aggregation pipeline stages as array",
+                "array",
+                true
+            ),
+        ]),
+        RiskLevel::Elevated,
+        "read",
+        &["agent"],
+        false
+    );
+
+    reg!(
+        "mongo__insert_one",
+        "Insert a single document into a MongoDB collection.",
+        MongoInsertOne::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            ("document", "Document to insert", "object", true),
+        ]),
+        RiskLevel::Elevated,
+        "create",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__update_many",
+        "Update documents in a MongoDB collection matching a filter.",
+        MongoUpdateMany::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            (
+                "filter",
+                "Filter to match documents to update",
+                "object",
+                true
+            ),
+            (
+                "update",
+                "Update operations, e.g. {\"$set\": {\"status\": \"inactive\"}}",
+                "object",
+                true
+            ),
+            (
+                "upsert",
+                "If true, insert if none matches (default false)",
+                "boolean",
+                false
+            ),
+        ]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent"]
+    );
+
+    reg!("mongo__delete_many", "Delete documents from a MongoDB collection matching a filter. DESTRUCTIVE: permanently removes data.",
+          MongoDeleteMany::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name", "string", true),
+              ("filter", "Filter to match documents to delete", "object", true),
+          ]),
+          RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("mongo__collection_stats", "Get detailed statistics for a MongoDB collection including document count, storage size, and index sizes.",
+          MongoCollectionStats::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name", "string", true),
+          ]),
+          RiskLevel::Safe, "read", &["agent", "ui"]);
+
+    reg!("mongo__database_stats", "Get statistics for a MongoDB database including collection count, object count, and storage metrics.",
+          MongoDatabaseStats::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+          ]),
+          RiskLevel::Safe, "read", &["agent", "ui"]);
+
+    reg!(
+        "mongo__create_database",
+        "Create a new MongoDB database by inserting a temporary document into a collection.",
+        MongoCreateDatabase::new(),
+        mongo_schema(&[
+            ("database", "Database name to create", "string", true),
+            ("collection", "Initial collection name", "string", true),
+        ]),
+        RiskLevel::Elevated,
+        "create",
+        &["agent", "ui"]
+    );
+
+    reg!("mongo__drop_database", "Drop a MongoDB database and all its collections. DESTRUCTIVE: permanently removes all data.",
+          MongoDropDatabase::new(),
+          mongo_schema(&[
+              ("database", "Database name to drop", "string", true),
+          ]),
+          RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!("mongo__create_collection", "Create a new collection in a MongoDB database with optional settings (capped, size, max, timeseries, validator).",
+          MongoCreateCollection::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name to create", "string", true),
+              ("options", "Optional collection settings {capped, size, max, timeseries, validator}", "object", false),
+          ]),
+          RiskLevel::Elevated, "create", &["agent", "ui"]);
 
-    reg!("mongo__list_databases", "List all database names on a MongoDB server. Use this first when no database is known so you can pick one for subsequent calls. First step for any MongoDB task: list databases, then list_collections, then find. Use data_studio/dockit tools for ALL MongoDB access instead of local mongosh CLI. Report results in the user's language (中文/English).",
-          MongoListDatabases::new(),
+    reg!(
+        "mongo__drop_collection",
+        "Drop a MongoDB collection and all its documents. DESTRUCTIVE: permanently removes data.",
+        MongoDropCollection::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name to drop", "string", true),
+        ]),
+        RiskLevel::Destructive,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!("mongo__server_status", "Get MongoDB server status including host, version, uptime, connections, network, and memory usage.",
+          MongoServerStatus::new(),
           mongo_schema(&[]),
           RiskLevel::Safe, "read", &["agent", "ui"]);
 
     reg!(
-        "mongo__list_collections",
-        "List all collection names in a MongoDB database. Use this to discover which collections exist before querying. Report results in the user's language (中文/English).",
-        MongoListCollections::new(),
-        mongo_schema(&[("database", "MongoDB database name", "string", false)]),
+        "mongo__repl_set_status",
+        "Get MongoDB replica set status including set name, members, states, and health.",
+        MongoReplSetStatus::new(),
+        mongo_schema(&[]),
         RiskLevel::Safe,
         "read",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__find",
-        "Query documents from a MongoDB collection using a filter and return the matching documents.\n\nUse when a task needs MongoDB data (document counts, content lookup, aggregation of records) — instead of shelling out to mongosh.\n\nExample: {\"database\": \"app\", \"collection\": \"users\", \"filter\": {\"age\": {\"$gt\": 30}}}.",
-        MongoFind::new(),
+        "mongo__shard_status",
+        "Get MongoDB sharding status including shards and mongos instances.",
+        MongoShardStatus::new(),
+        mongo_schema(&[]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__count_documents",
+        "Count documents in a MongoDB collection matching an optional filter. Use this for row-count-style questions instead of fetching all documents. Report results in the user's language (中文/English).",
+        MongoCountDocuments::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name to query", "string", true),
+            ("collection", "Collection name", "string", true),
             (
                 "filter",
-                "MongoDB query filter, e.g. {\"status\": \"active\"}",
-                "object",
+                "Optional JSON filter string, e.g. {\"status\": \"active\"}",
+                "string",
+                false
+            ),
+        ]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
+    );
+
+    reg!("mongo__update_document", "Update a single document in a MongoDB collection by its _id using $set with the provided fields.",
+          MongoUpdateDocument::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name", "string", true),
+              ("id", "Document _id value (ObjectId string or regular string)", "string", true),
+              ("document", "JSON document with fields to update (will be applied as $set)", "string", true),
+          ]),
+          RiskLevel::Elevated, "update", &["agent", "ui"]);
+
+    reg!("mongo__delete_document", "Delete a single document from a MongoDB collection by its _id. DESTRUCTIVE: permanently removes the document.",
+          MongoDeleteDocument::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name", "string", true),
+              ("id", "Document _id value (ObjectId string or regular string)", "string", true),
+          ]),
+          RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!(
+        "mongo__rename_collection",
+        "Rename a MongoDB collection within the same database.",
+        MongoRenameCollection::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Current collection name", "string", true),
+            ("to_collection", "New collection name", "string", true),
+        ]),
+        RiskLevel::Elevated,
+        "update",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__clone_collection",
+        "Clone a MongoDB collection including all documents and indexes to a new collection.",
+        MongoCloneCollection::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            (
+                "source_collection",
+                "Source collection to clone from",
+                "string",
                 true
             ),
             (
-                "projection",
-                "Optional fields to include/exclude",
+                "target_collection",
+                "Target collection to clone into",
+                "string",
+                true
+            ),
+        ]),
+        RiskLevel::Elevated,
+        "create",
+        &["agent", "ui"]
+    );
+
+    reg!("mongo__truncate_collection", "Remove all documents from a MongoDB collection while preserving the collection and indexes. DESTRUCTIVE: permanently removes all documents.",
+          MongoTruncateCollection::new(),
+          mongo_schema(&[
+              ("database", "MongoDB database name", "string", false),
+              ("collection", "Collection name to truncate", "string", true),
+          ]),
+          RiskLevel::Destructive, "delete", &["agent", "ui"]);
+
+    reg!(
+        "mongo__list_indexes",
+        "List all indexes on a MongoDB collection with their key fields and options.",
+        MongoListIndexes::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+        ]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__create_index",
+        "Create an index on a MongoDB collection with optional settings (unique, sparse, TTL).",
+        MongoCreateIndex::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            (
+                "keys",
+                "Index key specification, e.g. {\"field\": 1}",
                 "object",
+                true
+            ),
+            ("name", "Optional index name", "string", false),
+            (
+                "unique",
+                "Whether the index should enforce uniqueness",
+                "boolean",
                 false
             ),
             (
-                "limit",
-                "Maximum documents to return (default 20, max 100)",
+                "sparse",
+                "Whether the index should only reference documents with the indexed fields",
+                "boolean",
+                false
+            ),
+            (
+                "expire_after_seconds",
+                "TTL index expiration in seconds",
                 "integer",
                 false
             ),
-            ("sort", "Optional sort specification", "object", false),
         ]),
-        RiskLevel::Safe,
-        "read",
-        &["agent", "ui"],
-        true
+        RiskLevel::Elevated,
+        "create",
+        &["agent", "ui"]
     );
 
     reg!(
-        "mongo__aggregate",
-        "Execute a MongoDB aggregation pipeline on a collection.",
-        MongoAggregate::new(),
+        "mongo__drop_index",
+        "Drop an index from a MongoDB collection. Cannot drop the _id_ index.",
+        MongoDropIndex::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            ("index_name", "Name of the index to drop", "string", true),
+        ]),
+        RiskLevel::Elevated,
+        "delete",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__sample_documents",
+        "Sample documents from a MongoDB collection with a configurable limit (default 10).",
+        MongoSampleDocuments::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
             (
-                "pipeline",
-                "Aggregation pipeline stages as array",
-                "array",
-                true
+                "limit",
+                "Maximum number of documents to return (default 10, max 1000)",
+                "integer",
+                false
             ),
         ]),
-        RiskLevel::Elevated,
+        RiskLevel::Safe,
         "read",
-        &["agent"],
-        false
+        &["agent", "ui"],
+        true
     );
 
     reg!(
-        "mongo__insert_one",
-        "Insert a single document into a MongoDB collection.",
-        MongoInsertOne::new(),
+        "mongo__insert_many",
+        "Insert multiple documents into a MongoDB collection in a single round trip.\n\nUse when you need to insert several documents at once — more efficient than multiple insert_one calls for bulk inserts.\n\nExample: {\"database\": \"app\", \"collection\": \"users\", \"documents\": [{\"name\": \"Alice\"}, {\"name\": \"Bob\"}]}.",
+        MongoInsertMany::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
-            ("document", "Document to insert", "object", true),
+            (
+                "documents",
+                "Array of documents to insert",
+                "array",
+                true
+            ),
         ]),
         RiskLevel::Elevated,
         "create",
@@ -2434,28 +3988,28 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__update_many",
-        "Update documents in a MongoDB collection matching a filter.",
-        MongoUpdateMany::new(),
+        "mongo__find_one_and_update",
+        "Find a single document in a MongoDB collection, update it atomically, and return either the original or updated version.\n\nUse when you need to atomically read and modify a document — e.g., increment a counter, claim a task, or reserve a resource.\n\nExample: {\"database\": \"app\", \"collection\": \"tasks\", \"filter\": {\"status\": \"pending\"}, \"update\": {\"$set\": {\"status\": \"processing\"}}, \"options\": {\"return_document\": \"after\", \"sort\": {\"priority\": 1}}}.",
+        MongoFindOneAndUpdate::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
             (
                 "filter",
-                "Filter to match documents to update",
+                "Query filter to match the document to update",
                 "object",
                 true
             ),
             (
                 "update",
-                "Update operations, e.g. {\"$set\": {\"status\": \"inactive\"}}",
+                "Update operations, e.g. {\"$set\": {\"status\": \"done\"}}",
                 "object",
                 true
-            ),
-            (
-                "upsert",
-                "If true, insert if none matches (default false)",
-                "boolean",
+            ),
+            (
+                "options",
+                "Optional settings: {return_document: \"before\"|\"after\", upsert: bool, sort: object}",
+                "object",
                 false
             ),
         ]),
@@ -2464,81 +4018,55 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
         &["agent"]
     );
 
-    reg!("mongo__delete_many", "Delete documents from a MongoDB collection matching a filter. DESTRUCTIVE: permanently removes data.",
-          MongoDeleteMany::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name", "string", true),
-              ("filter", "Filter to match documents to delete", "object", true),
-          ]),
-          RiskLevel::Destructive, "delete", &["agent", "ui"]);
-
-    reg!("mongo__collection_stats", "Get detailed statistics for a MongoDB collection including document count, storage size, and index sizes.",
-          MongoCollectionStats::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name", "string", true),
-          ]),
-          RiskLevel::Safe, "read", &["agent", "ui"]);
-
-    reg!("mongo__database_stats", "Get statistics for a MongoDB database including collection count, object count, and storage metrics.",
-          MongoDatabaseStats::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-          ]),
-          RiskLevel::Safe, "read", &["agent", "ui"]);
-
     reg!(
-        "mongo__create_database",
-        "Create a new MongoDB database by inserting a temporary document into a collection.",
-        MongoCreateDatabase::new(),
+        "mongo__bulk_write",
+        "Execute multiple write operations (insert, update, delete) in a single round trip to a MongoDB collection.\n\nUse when you need to batch mixed write operations — insert some docs, update others, delete some — all in one efficient call. Supports insert_one, update_one, update_many, delete_one, delete_many.\n\nExample: {\"database\": \"app\", \"collection\": \"logs\", \"operations\": [{\"op\": \"insert_one\", \"document\": {\"msg\": \"start\"}}, {\"op\": \"delete_many\", \"filter\": {\"level\": \"debug\"}}]}.",
+        MongoBulkWrite::new(),
         mongo_schema(&[
-            ("database", "Database name to create", "string", true),
-            ("collection", "Initial collection name", "string", true),
+            ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
+            (
+                "operations",
+                "Array of operations. Each has 'op' field: insert_one (needs 'document'), update_one/update_many (needs 'filter','update', optional 'upsert'), delete_one/delete_many (needs 'filter')",
+                "array",
+                true
+            ),
         ]),
         RiskLevel::Elevated,
-        "create",
-        &["agent", "ui"]
+        "write",
+        &["agent"]
     );
 
-    reg!("mongo__drop_database", "Drop a MongoDB database and all its collections. DESTRUCTIVE: permanently removes all data.",
-          MongoDropDatabase::new(),
-          mongo_schema(&[
-              ("database", "Database name to drop", "string", true),
-          ]),
-          RiskLevel::Destructive, "delete", &["agent", "ui"]);
-
-    reg!("mongo__create_collection", "Create a new collection in a MongoDB database with optional settings (capped, size, max, timeseries, validator).",
-          MongoCreateCollection::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name to create", "string", true),
-              ("options", "Optional collection settings {capped, size, max, timeseries, validator}", "object", false),
-          ]),
-          RiskLevel::Elevated, "create", &["agent", "ui"]);
-
     reg!(
-        "mongo__drop_collection",
-        "Drop a MongoDB collection and all its documents. DESTRUCTIVE: permanently removes data.",
-        MongoDropCollection::new(),
+        "mongo__distinct",
+        "Get the distinct values for a specific field across a MongoDB collection, optionally filtered.\n\nUse when you need to enumerate all unique values of a field — e.g., list all categories, tags, or status codes used in a collection.\n\nExample: {\"database\": \"app\", \"collection\": \"products\", \"field\": \"category\", \"filter\": {\"active\": true}}.",
+        MongoDistinct::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name to drop", "string", true),
+            ("collection", "Collection name", "string", true),
+            (
+                "field",
+                "Field name to find distinct values for",
+                "string",
+                true
+            ),
+            (
+                "filter",
+                "Optional query filter to narrow results",
+                "object",
+                false
+            ),
         ]),
-        RiskLevel::Destructive,
-        "delete",
-        &["agent", "ui"]
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"],
+        true
     );
 
-    reg!("mongo__server_status", "Get MongoDB server status including host, version, uptime, connections, network, and memory usage.",
-          MongoServerStatus::new(),
-          mongo_schema(&[]),
-          RiskLevel::Safe, "read", &["agent", "ui"]);
-
     reg!(
-        "mongo__repl_set_status",
-        "Get MongoDB replica set status including set name, members, states, and health.",
-        MongoReplSetStatus::new(),
+        "mongo__get_slow_queries",
+        "Get MongoDB slow query data. Lists currently running operations (currentOp) with namespace, operation, seconds running, and message; if the server denies currentOp, falls back to the 20 most recent system.profile entries. For index recommendations from the profiler use mongo__suggest_indexes. Report results in the user's language (中文/English).",
+        MongoGetSlowQueries::new(),
         mongo_schema(&[]),
         RiskLevel::Safe,
         "read",
@@ -2546,9 +4074,9 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__shard_status",
-        "Get MongoDB sharding status including shards and mongos instances.",
-        MongoShardStatus::new(),
+        "mongo__list_users",
+        "List users defined on a MongoDB server across all databases, including user id, database, roles, and customData. Report results in the user's language (中文/English).",
+        MongoListUsers::new(),
         mongo_schema(&[]),
         RiskLevel::Safe,
         "read",
@@ -2556,168 +4084,227 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__count_documents",
-        "Count documents in a MongoDB collection matching an optional filter. Use this for row-count-style questions instead of fetching all documents. Report results in the user's language (中文/English).",
-        MongoCountDocuments::new(),
+        "mongo__watch",
+        "Open a MongoDB change stream and return the events that arrive within a bounded time window or event count. Watches a collection, a whole database, or the entire deployment (scope). Requires a replica set or sharded cluster; a single-node replica set works.\n\nPass the returned resume_token as resume_after on the next call to continue exactly where the previous window stopped. Events are Extended JSON.\n\nExample: {\"database\": \"app\", \"collection\": \"orders\", \"pipeline\": [{\"$match\": {\"operationType\": {\"$in\": [\"insert\", \"update\"]}}}], \"full_document\": \"updateLookup\", \"max_events\": 10, \"max_seconds\": 30}.",
+        MongoWatch::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name", "string", true),
             (
-                "filter",
-                "Optional JSON filter string, e.g. {\"status\": \"active\"}",
+                "collection",
+                "Collection to watch (omit to watch the database)",
+                "string",
+                false
+            ),
+            (
+                "scope",
+                "collection, database or deployment (default: collection when given, else database)",
+                "string",
+                false
+            ),
+            (
+                "pipeline",
+                "Optional change stream pipeline, e.g. [{\"$match\": {...}}]",
+                "array",
+                false
+            ),
+            (
+                "full_document",
+                "default, updateLookup, whenAvailable or required",
                 "string",
                 false
             ),
+            (
+                "resume_after",
+                "Resume token from a previous mongo__watch call",
+                "object",
+                false
+            ),
+            (
+                "max_events",
+                "Stop after this many events (default 20, max 500)",
+                "integer",
+                false
+            ),
+            (
+                "max_seconds",
+                "Stop after this many seconds (default 10, max 120)",
+                "integer",
+                false
+            ),
         ]),
         RiskLevel::Safe,
         "read",
-        &["agent", "ui"],
-        true
-    );
-
-    reg!("mongo__update_document", "Update a single document in a MongoDB collection by its _id using $set with the provided fields.",
-          MongoUpdateDocument::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name", "string", true),
-              ("id", "Document _id value (ObjectId string or regular string)", "string", true),
-              ("document", "JSON document with fields to update (will be applied as $set)", "string", true),
-          ]),
-          RiskLevel::Elevated, "update", &["agent", "ui"]);
-
-    reg!("mongo__delete_document", "Delete a single document from a MongoDB collection by its _id. DESTRUCTIVE: permanently removes the document.",
-          MongoDeleteDocument::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name", "string", true),
-              ("id", "Document _id value (ObjectId string or regular string)", "string", true),
-          ]),
-          RiskLevel::Destructive, "delete", &["agent", "ui"]);
-
-    reg!(
-        "mongo__rename_collection",
-        "Rename a MongoDB collection within the same database.",
-        MongoRenameCollection::new(),
-        mongo_schema(&[
-            ("database", "MongoDB database name", "string", false),
-            ("collection", "Current collection name", "string", true),
-            ("to_collection", "New collection name", "string", true),
-        ]),
-        RiskLevel::Elevated,
-        "update",
-        &["agent", "ui"]
+        &["agent"]
     );
 
     reg!(
-        "mongo__clone_collection",
-        "Clone a MongoDB collection including all documents and indexes to a new collection.",
-        MongoCloneCollection::new(),
+        "mongo__explain",
+        "Explain how MongoDB executes a find, aggregate or count and whether it uses an index. Returns a summary (winning stage tree, COLLSCAN detection, indexes used, covered query, in-memory sort, documents and keys examined vs returned, warnings) plus the raw plan.\n\nUse this first when a query is slow or before recommending an index. Verbosity: queryPlanner (plan only, does not run the query), executionStats (default, runs the query), allPlansExecution.\n\nExample: {\"collection\": \"orders\", \"filter\": {\"status\": \"A\"}, \"sort\": {\"created\": -1}, \"limit\": 20}. Report results in the user's language (中文/English).",
+        MongoExplain::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
+            ("collection", "Collection name", "string", true),
             (
-                "source_collection",
-                "Source collection to clone from",
+                "operation",
+                "find, aggregate or count (default: aggregate when pipeline is given, else find)",
                 "string",
-                true
+                false
             ),
+            ("filter", "Query filter for find or count", "object", false),
+            ("projection", "Projection for find", "object", false),
+            ("sort", "Sort specification for find", "object", false),
+            ("limit", "Limit for find", "integer", false),
+            ("skip", "Skip for find", "integer", false),
+            ("pipeline", "Aggregation pipeline stages", "array", false),
             (
-                "target_collection",
-                "Target collection to clone into",
+                "hint",
+                "Index name, or key pattern object, to force",
+                "string|object",
+                false
+            ),
+            (
+                "verbosity",
+                "queryPlanner, executionStats (default) or allPlansExecution",
                 "string",
-                true
+                false
+            ),
+            (
+                "include_plan",
+                "Set false to return only the summary without the raw plan",
+                "boolean",
+                false
             ),
         ]),
-        RiskLevel::Elevated,
-        "create",
+        RiskLevel::Safe,
+        "read",
         &["agent", "ui"]
     );
 
-    reg!("mongo__truncate_collection", "Remove all documents from a MongoDB collection while preserving the collection and indexes. DESTRUCTIVE: permanently removes all documents.",
-          MongoTruncateCollection::new(),
-          mongo_schema(&[
-              ("database", "MongoDB database name", "string", false),
-              ("collection", "Collection name to truncate", "string", true),
-          ]),
-          RiskLevel::Destructive, "delete", &["agent", "ui"]);
-
     reg!(
-        "mongo__list_indexes",
-        "List all indexes on a MongoDB collection with their key fields and options.",
-        MongoListIndexes::new(),
-        mongo_schema(&[
-            ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name", "string", true),
-        ]),
+        "mongo__get_profiling",
+        "Get the database profiler settings of a MongoDB database: level (0 off, 1 slow operations, 2 all), slowms threshold, sampleRate and filter.",
+        MongoGetProfiling::new(),
+        mongo_schema(&[("database", "MongoDB database name", "string", false)]),
         RiskLevel::Safe,
         "read",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__create_index",
-        "Create an index on a MongoDB collection with optional settings (unique, sparse, TTL).",
-        MongoCreateIndex::new(),
+        "mongo__set_profiling",
+        "Set the database profiler of a MongoDB database. Level 1 records operations slower than slowms into system.profile, which mongo__suggest_indexes analyzes; level 2 records every operation and adds overhead. Returns the previous and current settings.\n\nExample: {\"database\": \"app\", \"level\": 1, \"slowms\": 50}.",
+        MongoSetProfiling::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name", "string", true),
             (
-                "keys",
-                "Index key specification, e.g. {\"field\": 1}",
-                "object",
+                "level",
+                "0 = off, 1 = slow operations, 2 = all operations",
+                "integer",
                 true
             ),
-            ("name", "Optional index name", "string", false),
             (
-                "unique",
-                "Whether the index should enforce uniqueness",
-                "boolean",
+                "slowms",
+                "Slow operation threshold in milliseconds",
+                "integer",
                 false
             ),
             (
-                "sparse",
-                "Whether the index should only reference documents with the indexed fields",
-                "boolean",
+                "sample_rate",
+                "Fraction of slow operations to record, 0.0 to 1.0",
+                "number",
                 false
             ),
             (
-                "expire_after_seconds",
-                "TTL index expiration in seconds",
-                "integer",
+                "filter",
+                "Optional profiler filter, e.g. {\"op\": \"query\"}",
+                "object",
                 false
             ),
         ]),
         RiskLevel::Elevated,
-        "create",
+        "update",
+        &["agent"]
+    );
+
+    reg!(
+        "mongo__suggest_indexes",
+        "Suggest MongoDB indexes from the profiler. Groups recent system.profile operations by namespace and query shape (equality, sort and range fields), checks each shape against the existing indexes, and proposes compound indexes in ESR order (equality, sort, range) with the number of profiled queries each would serve.\n\nRequires the profiler to be on (see mongo__set_profiling). Verify a proposal with mongo__explain before creating it. Report results in the user's language (中文/English).",
+        MongoSuggestIndexes::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            (
+                "collection",
+                "Only analyze this collection",
+                "string",
+                false
+            ),
+            (
+                "limit",
+                "Most recent profile entries to analyze (default 1000, max 10000)",
+                "integer",
+                false
+            ),
+            (
+                "min_millis",
+                "Only analyze operations at least this slow",
+                "integer",
+                false
+            ),
+        ]),
+        RiskLevel::Safe,
+        "read",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__drop_index",
-        "Drop an index from a MongoDB collection. Cannot drop the _id_ index.",
-        MongoDropIndex::new(),
+        "mongo__index_usage",
+        "Report MongoDB index usage from $indexStats: operations served per index since the server started, unused indexes (no operations; _id_ is skipped, and idle unique and TTL indexes are listed as idle_constraints instead, since they enforce uniqueness or expire documents without serving queries), and redundant indexes whose keys are a prefix of another index. Unique, partial, sparse and TTL indexes are never reported as redundant. Report results in the user's language (中文/English).",
+        MongoIndexUsage::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name", "string", true),
-            ("index_name", "Name of the index to drop", "string", true),
+            (
+                "collection",
+                "Only check this collection (default: every collection)",
+                "string",
+                false
+            ),
         ]),
-        RiskLevel::Elevated,
-        "delete",
+        RiskLevel::Safe,
+        "read",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__sample_documents",
-        "Sample documents from a MongoDB collection with a configurable limit (default 10).",
-        MongoSampleDocuments::new(),
+        "mongo__analyze_schema",
+        "Infer the schema of a MongoDB collection from a random $sample of documents (default 100). Returns a field tree with the BSON type distribution and presence percentage per path, array length stats, value counts for low-cardinality fields and examples for the rest, flags mixed-type fields, and can emit a draft $jsonSchema validator.\n\nCheap: prefer this over mongo__sample_documents to learn a collection's structure before writing queries. Example: {\"collection\": \"orders\", \"sample_size\": 200, \"emit_validator\": true}.",
+        MongoAnalyzeSchema::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
             (
-                "limit",
-                "Maximum number of documents to return (default 10, max 1000)",
+                "sample_size",
+                "Documents to sample (default 100, max 1000)",
+                "integer",
+                false
+            ),
+            (
+                "filter",
+                "Optional filter applied before sampling",
+                "object",
+                false
+            ),
+            (
+                "max_depth",
+                "Maximum nesting depth to analyze (default 8)",
                 "integer",
                 false
             ),
+            (
+                "emit_validator",
+                "Also return a draft $jsonSchema validator",
+                "boolean",
+                false
+            ),
         ]),
         RiskLevel::Safe,
         "read",
@@ -2726,49 +4313,33 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__insert_many",
-        "Insert multiple documents into a MongoDB collection in a single round trip.\n\nUse when you need to insert several documents at once — more efficient than multiple insert_one calls for bulk inserts.\n\nExample: {\"database\": \"app\", \"collection\": \"users\", \"documents\": [{\"name\": \"Alice\"}, {\"name\": \"Bob\"}]}.",
-        MongoInsertMany::new(),
+        "mongo__get_validator",
+        "Get the schema validation settings of a MongoDB collection: the validator (usually {$jsonSchema: ...}), validationLevel (off, strict, moderate) and validationAction (error, warn).",
+        MongoGetValidator::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
-            (
-                "documents",
-                "Array of documents to insert",
-                "array",
-                true
-            ),
         ]),
-        RiskLevel::Elevated,
-        "create",
+        RiskLevel::Safe,
+        "read",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__find_one_and_update",
-        "Find a single document in a MongoDB collection, update it atomically, and return either the original or updated version.\n\nUse when you need to atomically read and modify a document — e.g., increment a counter, claim a task, or reserve a resource.\n\nExample: {\"database\": \"app\", \"collection\": \"tasks\", \"filter\": {\"status\": \"pending\"}, \"update\": {\"$set\": {\"status\": \"processing\"}}, \"options\": {\"return_document\": \"after\", \"sort\": {\"priority\": 1}}}.",
-        MongoFindOneAndUpdate::new(),
+        "mongo__set_validator",
+        "Set or replace the schema validation of a MongoDB collection via collMod. Pass any of validator, validation_level and validation_action; omitted settings are left unchanged, and an empty validator {} removes validation. Run mongo__dry_run_validator first to see which existing documents would fail. Returns the previous and current settings.\n\nExample: {\"collection\": \"orders\", \"validator\": {\"$jsonSchema\": {\"bsonType\": \"object\", \"required\": [\"status\"]}}, \"validation_level\": \"moderate\"}.",
+        MongoSetValidator::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
             (
-                "filter",
-                "Query filter to match the document to update",
-                "object",
-                true
-            ),
-            (
-                "update",
-                "Update operations, e.g. {\"$set\": {\"status\": \"done\"}}",
-                "object",
-                true
-            ),
-            (
-                "options",
-                "Optional settings: {return_document: \"before\"|\"after\", upsert: bool, sort: object}",
+                "validator",
+                "Validator document, e.g. {\"$jsonSchema\": {...}}",
                 "object",
                 false
             ),
+            ("validation_level", "off, strict or moderate", "string", false),
+            ("validation_action", "error, warn or errorAndLog", "string", false),
         ]),
         RiskLevel::Elevated,
         "update",
@@ -2776,43 +4347,46 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__bulk_write",
-        "Execute multiple write operations (insert, update, delete) in a single round trip to a MongoDB collection.\n\nUse when you need to batch mixed write operations — insert some docs, update others, delete some — all in one efficient call. Supports insert_one, update_one, update_many, delete_one, delete_many.\n\nExample: {\"database\": \"app\", \"collection\": \"logs\", \"operations\": [{\"op\": \"insert_one\", \"document\": {\"msg\": \"start\"}}, {\"op\": \"delete_many\", \"filter\": {\"level\": \"debug\"}}]}.",
-        MongoBulkWrite::new(),
+        "mongo__dry_run_validator",
+        "Test a candidate validator against the existing documents of a MongoDB collection without changing anything. Counts the documents that would fail (matching {$nor: [validator]}) and returns samples of them.\n\nExample: {\"collection\": \"orders\", \"validator\": {\"$jsonSchema\": {\"required\": [\"status\"]}}, \"sample_limit\": 5}.",
+        MongoDryRunValidator::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
             ("collection", "Collection name", "string", true),
+            ("validator", "Candidate validator document", "object", true),
             (
-                "operations",
-                "Array of operations. Each has 'op' field: insert_one (needs 'document'), update_one/update_many (needs 'filter','update', optional 'upsert'), delete_one/delete_many (needs 'filter')",
-                "array",
-                true
+                "sample_limit",
+                "Failing documents to return (default 5, max 50)",
+                "integer",
+                false
             ),
         ]),
-        RiskLevel::Elevated,
-        "write",
-        &["agent"]
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
     );
 
     reg!(
-        "mongo__distinct",
-        "Get the distinct values for a specific field across a MongoDB collection, optionally filtered.\n\nUse when you need to enumerate all unique values of a field — e.g., list all categories, tags, or status codes used in a collection.\n\nExample: {\"database\": \"app\", \"collection\": \"products\", \"field\": \"category\", \"filter\": {\"active\": true}}.",
-        MongoDistinct::new(),
+        "mongo__gridfs_list_buckets",
+        "List the GridFS buckets in a MongoDB database (each a <name>.files / <name>.chunks collection pair) with their file counts and total bytes.",
+        MongoGridfsListBuckets::new(),
+        mongo_schema(&[("database", "MongoDB database name", "string", false)]),
+        RiskLevel::Safe,
+        "read",
+        &["agent", "ui"]
+    );
+
+    reg!(
+        "mongo__gridfs_list_files",
+        "List files in a GridFS bucket with their id, filename, length, upload date and metadata, newest first. Use filter to match on files collection fields.\n\nExample: {\"bucket\": \"attachments\", \"filter\": {\"metadata.owner\": \"billing\"}, \"limit\": 20}.",
+        MongoGridfsListFiles::new(),
         mongo_schema(&[
             ("database", "MongoDB database name", "string", false),
-            ("collection", "Collection name", "string", true),
-            (
-                "field",
-                "Field name to find distinct values for",
-                "string",
-                true
-            ),
-            (
-                "filter",
-                "Optional query filter to narrow results",
-                "object",
-                false
-            ),
+            ("bucket", "Bucket name (default fs)", "string", false),
+            ("filter", "Filter on the files collection", "object", false),
+            ("sort", "Sort document (default {uploadDate: -1})", "object", false),
+            ("skip", "Files to skip", "integer", false),
+            ("limit", "Files to return (default 50, max 500)", "integer", false),
         ]),
         RiskLevel::Safe,
         "read",
@@ -2821,22 +4395,31 @@ pub(crate) fn register_all(registry: &mut CapabilityRegistry) {
     );
 
     reg!(
-        "mongo__get_slow_queries",
-        "Get MongoDB slow query data. Lists currently running operations (currentOp) with namespace, operation, seconds running, and message; if the server denies currentOp, falls back to the 20 most recent system.profile entries. Report results in the user's language (中文/English).",
-        MongoGetSlowQueries::new(),
-        mongo_schema(&[]),
-        RiskLevel::Safe,
-        "read",
+        "mongo__gridfs_delete_file",
+        "Delete a file and all of its chunks from a GridFS bucket. DESTRUCTIVE: permanently removes the file.",
+        MongoGridfsDeleteFile::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("bucket", "Bucket name (default fs)", "string", false),
+            ("file_id", "File id (ObjectId string or Extended JSON)", "string", true),
+        ]),
+        RiskLevel::Destructive,
+        "delete",
         &["agent", "ui"]
     );
 
     reg!(
-        "mongo__list_users",
-        "List users defined on a MongoDB server across all databases, including user id, database, roles, and customData. Report results in the user's language (中文/English).",
-        MongoListUsers::new(),
-        mongo_schema(&[]),
-        RiskLevel::Safe,
-        "read",
+        "mongo__gridfs_rename_file",
+        "Rename a file in a GridFS bucket. Only this file id is renamed, not other revisions with the same filename.",
+        MongoGridfsRenameFile::new(),
+        mongo_schema(&[
+            ("database", "MongoDB database name", "string", false),
+            ("bucket", "Bucket name (default fs)", "string", false),
+            ("file_id", "File id (ObjectId string or Extended JSON)", "string", true),
+            ("new_filename", "New filename", "string", true),
+        ]),
+        RiskLevel::Elevated,
+        "update",
         &["agent", "ui"]
     );
 }
@@ -3698,6 +5281,366 @@ mod tests {
         assert!(result.unwrap_err().contains("factory error"));
     }
 
+    // ---- watch ----
+
+    #[tokio::test]
+    async fn test_mongo_watch_missing_config() {
+        let handler = MongoWatch::new();
+        let result = handler.handle(&json!({"collection": "orders"}), None).await;
+        assert!(result.unwrap_err().contains("connection config"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_watch_factory_error() {
+        let handler = MongoWatch::with_factory(Box::new(err_factory()));
+        let result = handler
+            .handle(&json!({"collection": "orders"}), Some(&mock_config()))
+            .await;
+        assert!(result.unwrap_err().contains("factory error"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_watch_rejects_bad_arguments_before_connecting() {
+        let handler = MongoWatch::with_factory(Box::new(MockMongoClientFactory::new()));
+        let err = handler
+            .handle(
+                &json!({"collection": "orders", "full_document": "everything"}),
+                Some(&mock_config()),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("Unsupported fullDocument"), "got: {}", err);
+
+        let err = handler
+            .handle(&json!({"scope": "collection"}), Some(&mock_config()))
+            .await
+            .unwrap_err();
+        assert!(err.contains("requires a collection"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_mongo_watch_target_scopes() {
+        use crate::common::mongo::WatchTarget;
+
+        let config = mock_config();
+        assert_eq!(
+            watch_target(&json!({"collection": "orders"}), &config).unwrap(),
+            WatchTarget::Collection {
+                database: "testdb".into(),
+                collection: "orders".into()
+            }
+        );
+        assert_eq!(
+            watch_target(&json!({"database": "app"}), &config).unwrap(),
+            WatchTarget::Database("app".into())
+        );
+        assert_eq!(
+            watch_target(&json!({"scope": "deployment", "collection": "x"}), &config).unwrap(),
+            WatchTarget::Deployment
+        );
+    }
+
+    // ---- explain ----
+
+    #[tokio::test]
+    async fn test_mongo_explain_missing_config() {
+        let handler = MongoExplain::new();
+        let result = handler.handle(&json!({"collection": "orders"}), None).await;
+        assert!(result.unwrap_err().contains("connection config"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_explain_factory_error() {
+        let handler = MongoExplain::with_factory(Box::new(err_factory()));
+        let result = handler
+            .handle(&json!({"collection": "orders"}), Some(&mock_config()))
+            .await;
+        assert!(result.unwrap_err().contains("factory error"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_explain_rejects_writing_pipeline_at_execution_stats() {
+        let handler = MongoExplain::with_factory(Box::new(MockMongoClientFactory::new()));
+        let err = handler
+            .handle(
+                &json!({"collection": "orders", "pipeline": [{"$match": {}}, {"$out": "copy"}]}),
+                Some(&mock_config()),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("queryPlanner"), "got: {}", err);
+
+        let err = handler
+            .handle(
+                &json!({"collection": "orders", "verbosity": "full"}),
+                Some(&mock_config()),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("Unknown explain verbosity"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_mongo_explain_target_commands() {
+        let (operation, command) = explain_target_command(
+            &json!({"filter": {"status": "A"}, "sort": {"qty": -1}, "limit": 5, "hint": "status_1"}),
+            "orders",
+        )
+        .unwrap();
+        assert_eq!(operation, "find");
+        assert_eq!(command.get_str("find").unwrap(), "orders");
+        assert_eq!(command.get_i64("limit").unwrap(), 5);
+        assert_eq!(command.get_str("hint").unwrap(), "status_1");
+
+        let (operation, command) =
+            explain_target_command(&json!({"pipeline": [{"$match": {"a": 1}}]}), "orders").unwrap();
+        assert_eq!(operation, "aggregate");
+        assert_eq!(command.get_array("pipeline").unwrap().len(), 1);
+
+        let (operation, command) = explain_target_command(
+            &json!({"operation": "count", "filter": {"a": 1}, "hint": {"a": 1}}),
+            "orders",
+        )
+        .unwrap();
+        assert_eq!(operation, "count");
+        assert!(command.get_document("query").is_ok());
+        assert!(command.get_document("hint").is_ok());
+
+        assert!(explain_target_command(&json!({"operation": "update"}), "orders").is_err());
+    }
+
+    // ---- profiler and index advisor ----
+
+    #[tokio::test]
+    async fn test_mongo_profiler_and_advisor_missing_config() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGetProfiling::new()),
+            Box::new(MongoSetProfiling::new()),
+            Box::new(MongoSuggestIndexes::new()),
+            Box::new(MongoIndexUsage::new()),
+        ];
+        for handler in handlers {
+            let result = handler.handle(&json!({"level": 1}), None).await;
+            assert!(result.unwrap_err().contains("connection config"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_profiler_and_advisor_factory_error() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGetProfiling::with_factory(Box::new(err_factory()))),
+            Box::new(MongoSetProfiling::with_factory(Box::new(err_factory()))),
+            Box::new(MongoSuggestIndexes::with_factory(Box::new(err_factory()))),
+            Box::new(MongoIndexUsage::with_factory(Box::new(err_factory()))),
+        ];
+        for handler in handlers {
+            let result = handler
+                .handle(&json!({"level": 1}), Some(&mock_config()))
+                .await;
+            assert!(result.unwrap_err().contains("factory error"));
+        }
+    }
+
+    #[test]
+    fn test_mongo_set_profiling_command() {
+        let command =
+            set_profiling_command(&json!({"level": 1, "slowms": 50, "sample_rate": "0.5"}))
+                .unwrap();
+        assert_eq!(command.get_i32("profile").unwrap(), 1);
+        assert_eq!(command.get_i64("slowms").unwrap(), 50);
+        assert_eq!(command.get_f64("sampleRate").unwrap(), 0.5);
+
+        assert!(set_profiling_command(&json!({})).is_err());
+        assert!(set_profiling_command(&json!({"level": 3})).is_err());
+        assert!(set_profiling_command(&json!({"level": 1, "sample_rate": 2.0})).is_err());
+    }
+
+    #[test]
+    fn test_mongo_index_usage_sums_nodes_and_flags_redundant() {
+        let rows = vec![
+            doc! { "name": "_id_", "key": { "_id": 1 }, "accesses": { "ops": 0_i64 } },
+            doc! { "name": "a_1", "key": { "a": 1 }, "accesses": { "ops": 0_i64 } },
+            // The same index reported by a second node.
+            doc! { "name": "a_1", "key": { "a": 1 }, "accesses": { "ops": 3_i64 } },
+            doc! { "name": "a_1_b_1", "key": { "a": 1, "b": 1 }, "accesses": { "ops": 0_i64 } },
+        ];
+        let usage = index_usage(&rows);
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[1].1, 3, "ops from both nodes are summed");
+        let infos: Vec<_> = usage.iter().map(|(info, _, _)| info.clone()).collect();
+        assert_eq!(
+            crate::common::mongo_advisor::redundant_indexes(&infos),
+            vec![("a_1".to_string(), "a_1_b_1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_mongo_index_usage_keeps_idle_unique_and_ttl_indexes() {
+        let rows = vec![
+            doc! { "name": "_id_", "key": { "_id": 1 }, "accesses": { "ops": 0_i64 } },
+            doc! { "name": "a_1", "key": { "a": 1 }, "accesses": { "ops": 0_i64 } },
+            doc! {
+                "name": "email_1", "key": { "email": 1 },
+                "spec": { "unique": true }, "accesses": { "ops": 0_i64 },
+            },
+            doc! {
+                "name": "created_1", "key": { "created": 1 },
+                "spec": { "expireAfterSeconds": 3600 }, "accesses": { "ops": 0_i64 },
+            },
+        ];
+        let usage = index_usage(&rows);
+        let (unused, kept) = idle_indexes(&usage);
+        assert_eq!(unused, vec!["a_1"]);
+        assert_eq!(
+            kept,
+            vec![
+                json!({"index": "email_1", "role": "unique"}),
+                json!({"index": "created_1", "role": "ttl"}),
+            ]
+        );
+    }
+
+    // ---- analyze_schema ----
+
+    #[tokio::test]
+    async fn test_mongo_analyze_schema_missing_config() {
+        let handler = MongoAnalyzeSchema::new();
+        let result = handler.handle(&json!({"collection": "orders"}), None).await;
+        assert!(result.unwrap_err().contains("connection config"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_analyze_schema_missing_collection() {
+        let handler = MongoAnalyzeSchema::with_factory(Box::new(MockMongoClientFactory::new()));
+        let result = handler.handle(&json!({}), Some(&mock_config())).await;
+        assert!(result.unwrap_err().contains("Missing collection"));
+    }
+
+    #[tokio::test]
+    async fn test_mongo_analyze_schema_factory_error() {
+        let handler = MongoAnalyzeSchema::with_factory(Box::new(err_factory()));
+        let result = handler
+            .handle(&json!({"collection": "orders"}), Some(&mock_config()))
+            .await;
+        assert!(result.unwrap_err().contains("factory error"));
+    }
+
+    // ---- validators ----
+
+    #[tokio::test]
+    async fn test_mongo_validator_handlers_missing_config() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGetValidator::new()),
+            Box::new(MongoSetValidator::new()),
+            Box::new(MongoDryRunValidator::new()),
+        ];
+        let args = json!({"collection": "orders", "validator": {"$jsonSchema": {}}});
+        for handler in handlers {
+            let result = handler.handle(&args, None).await;
+            assert!(result.unwrap_err().contains("connection config"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_validator_handlers_factory_error() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGetValidator::with_factory(Box::new(err_factory()))),
+            Box::new(MongoSetValidator::with_factory(Box::new(err_factory()))),
+            Box::new(MongoDryRunValidator::with_factory(Box::new(err_factory()))),
+        ];
+        let args = json!({"collection": "orders", "validator": {"$jsonSchema": {}}});
+        for handler in handlers {
+            let result = handler.handle(&args, Some(&mock_config())).await;
+            assert!(result.unwrap_err().contains("factory error"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_dry_run_validator_requires_validator() {
+        let handler = MongoDryRunValidator::with_factory(Box::new(MockMongoClientFactory::new()));
+        let result = handler
+            .handle(&json!({"collection": "orders"}), Some(&mock_config()))
+            .await;
+        assert!(result.unwrap_err().contains("Missing validator"));
+    }
+
+    #[test]
+    fn test_mongo_set_validator_command() {
+        let command = set_validator_command(
+            &json!({
+                "validator": {"$jsonSchema": {"required": ["status"]}},
+                "validation_level": "moderate",
+                "validation_action": "warn",
+            }),
+            "orders",
+        )
+        .unwrap();
+        assert_eq!(command.get_str("collMod").unwrap(), "orders");
+        assert!(command.get_document("validator").is_ok());
+        assert_eq!(command.get_str("validationLevel").unwrap(), "moderate");
+        assert_eq!(command.get_str("validationAction").unwrap(), "warn");
+
+        assert!(set_validator_command(&json!({}), "orders")
+            .unwrap_err()
+            .contains("Nothing to change"));
+        assert!(set_validator_command(&json!({"validation_level": "lax"}), "orders").is_err());
+        assert!(set_validator_command(&json!({"validation_action": "log"}), "orders").is_err());
+    }
+
+    // ---- gridfs ----
+
+    #[tokio::test]
+    async fn test_mongo_gridfs_handlers_missing_config() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGridfsListBuckets::new()),
+            Box::new(MongoGridfsListFiles::new()),
+            Box::new(MongoGridfsDeleteFile::new()),
+            Box::new(MongoGridfsRenameFile::new()),
+        ];
+        let args = json!({"file_id": "65a1b2c3d4e5f60718293a4b", "new_filename": "a.pdf"});
+        for handler in handlers {
+            let result = handler.handle(&args, None).await;
+            assert!(result.unwrap_err().contains("connection config"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_gridfs_handlers_factory_error() {
+        let handlers: Vec<Box<dyn CapabilityHandler>> = vec![
+            Box::new(MongoGridfsListBuckets::with_factory(
+                Box::new(err_factory()),
+            )),
+            Box::new(MongoGridfsListFiles::with_factory(Box::new(err_factory()))),
+            Box::new(MongoGridfsDeleteFile::with_factory(Box::new(err_factory()))),
+            Box::new(MongoGridfsRenameFile::with_factory(Box::new(err_factory()))),
+        ];
+        let args = json!({"file_id": "65a1b2c3d4e5f60718293a4b", "new_filename": "a.pdf"});
+        for handler in handlers {
+            let result = handler.handle(&args, Some(&mock_config())).await;
+            assert!(result.unwrap_err().contains("factory error"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_gridfs_file_handlers_validate_args() {
+        let delete = MongoGridfsDeleteFile::with_factory(Box::new(MockMongoClientFactory::new()));
+        let result = delete.handle(&json!({}), Some(&mock_config())).await;
+        assert!(result.unwrap_err().contains("Missing file_id"));
+
+        let rename = MongoGridfsRenameFile::with_factory(Box::new(MockMongoClientFactory::new()));
+        let result = rename
+            .handle(&json!({"file_id": "report"}), Some(&mock_config()))
+            .await;
+        assert!(result.unwrap_err().contains("Missing new_filename"));
+    }
+
+    #[test]
+    fn test_mongo_gridfs_bucket_name_defaults_to_fs() {
+        assert_eq!(gridfs_bucket_name(&json!({})), "fs");
+        assert_eq!(gridfs_bucket_name(&json!({"bucket": ""})), "fs");
+        assert_eq!(gridfs_bucket_name(&json!({"bucket": "avatars"})), "avatars");
+    }
+
     // ---- register_all ----
 
     #[test]
@@ -3707,6 +5650,13 @@ mod tests {
 
         assert!(reg.get("mongo__get_slow_queries").is_some());
         assert!(reg.get("mongo__list_users").is_some());
+        assert!(reg.get("mongo__watch").is_some());
+        assert!(reg.get("mongo__explain").is_some());
+        assert!(reg.get("mongo__suggest_indexes").is_some());
+        assert!(reg.get("mongo__index_usage").is_some());
+        assert!(reg.get("mongo__analyze_schema").is_some());
+        assert!(reg.get("mongo__set_validator").is_some());
+        assert!(reg.get("mongo__gridfs_list_files").is_some());
 
         let all_agent = reg.agent_tools();
         let mongo_agent: Vec<_> = all_agent
@@ -3715,8 +5665,18 @@ mod tests {
             .collect();
         assert_eq!(
             mongo_agent.len(),
-            32,
-            "expected 32 MongoDB capabilities tagged for agent"
+            46,
+            "expected 46 MongoDB capabilities tagged for agent"
+        );
+
+        let profiling = &reg.get("mongo__set_profiling").unwrap().input_schema;
+        assert_eq!(profiling["properties"]["sample_rate"]["type"], "number");
+        let analyze = &reg.get("mongo__analyze_schema").unwrap().input_schema;
+        assert_eq!(analyze["properties"]["emit_validator"]["type"], "boolean");
+        let explain = &reg.get("mongo__explain").unwrap().input_schema;
+        assert_eq!(
+            explain["properties"]["hint"]["type"],
+            json!(["string", "object"])
         );
     }
 }
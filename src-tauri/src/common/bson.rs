@@ -1,42 +1,71 @@
+//! Extended JSON codec shared by every Mongo code path (agent capabilities,
+//! the query editor, export and import), so a document read, edited and
+//! written back keeps its BSON types.
+//!
+//! Output is relaxed Extended JSON v2, except where relaxed JSON would be
+//! read back as a different type: Int64 stays `{"$numberLong": ...}` and
+//! integral doubles stay `{"$numberDouble": ...}` (a JavaScript client would
+//! otherwise turn `1.0` into `1`, an Int32). Input accepts both relaxed and
+//! canonical Extended JSON.
+
 use mongodb::bson::{Bson, Document};
 use serde_json::{json, Value};
 
 pub(crate) fn bson_to_value(bson: &Bson) -> Value {
     match bson {
-        Bson::Double(v) => json!(*v),
-        Bson::String(v) => json!(v),
         Bson::Array(arr) => Value::Array(arr.iter().map(bson_to_value).collect()),
-        Bson::Document(d) => {
-            let map: serde_json::Map<String, Value> = d
-                .iter()
-                .map(|(k, v)| (k.clone(), bson_to_value(v)))
-                .collect();
-            Value::Object(map)
+        Bson::Document(d) => doc_to_value(d),
+        Bson::Int64(v) => json!({ "$numberLong": v.to_string() }),
+        Bson::Double(v) if v.is_finite() && v.fract() == 0.0 => {
+            Bson::Double(*v).into_canonical_extjson()
+        }
+        Bson::JavaScriptCodeWithScope(code) => {
+            json!({ "$code": code.code, "$scope": doc_to_value(&code.scope) })
         }
-        Bson::Boolean(v) => json!(*v),
-        Bson::Null => Value::Null,
-        Bson::Int32(v) => json!(*v),
-        Bson::Int64(v) => json!(*v),
-        Bson::ObjectId(oid) => json!(oid.to_string()),
-        Bson::DateTime(dt) => json!(dt.timestamp_millis()),
-        other => json!(other.to_string()),
+        other => other.clone().into_relaxed_extjson(),
     }
 }
 
-pub(crate) fn json_to_bson_doc_agent(val: &Value) -> Result<Document, String> {
-    mongodb::bson::to_document(val)
-        .map_err(|e| format!("Failed to convert to BSON document: {}", e))
+pub(crate) fn doc_to_value(doc: &Document) -> Value {
+    Value::Object(
+        doc.iter()
+            .map(|(k, v)| (k.clone(), bson_to_value(v)))
+            .collect(),
+    )
+}
+
+/// Parse relaxed or canonical Extended JSON. Plain integers become Int32
+/// when they fit and Int64 otherwise; other plain numbers become doubles.
+pub(crate) fn value_to_bson(val: &Value) -> Result<Bson, String> {
+    Bson::try_from(val.clone()).map_err(|e| format!("Invalid Extended JSON: {}", e))
+}
+
+pub(crate) fn json_to_bson_doc(val: &Value) -> Result<Document, String> {
+    match value_to_bson(val) {
+        Ok(Bson::Document(doc)) => Ok(doc),
+        Ok(other) => Err(format!(
+            "Failed to convert to BSON document: expected an object, got {:?}",
+            other.element_type()
+        )),
+        Err(e) => Err(format!("Failed to convert to BSON document: {}", e)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use mongodb::bson::oid::ObjectId;
-    use mongodb::bson::DateTime;
+    use mongodb::bson::spec::{BinarySubtype, ElementType};
+    use mongodb::bson::{doc, Binary, DateTime, Decimal128, JavaScriptCodeWithScope, Regex};
+    use std::collections::HashSet;
 
     #[test]
     fn test_bson_to_value_double() {
         assert_eq!(bson_to_value(&Bson::Double(3.14)), json!(3.14));
+        assert_eq!(
+            bson_to_value(&Bson::Double(2.0)),
+            json!({"$numberDouble": "2.0"})
+        );
     }
 
     #[test]
@@ -61,21 +90,24 @@ mod tests {
 
     #[test]
     fn test_bson_to_value_int64() {
-        assert_eq!(bson_to_value(&Bson::Int64(999_999_999)), json!(999_999_999));
+        assert_eq!(
+            bson_to_value(&Bson::Int64(999_999_999)),
+            json!({"$numberLong": "999999999"})
+        );
     }
 
     #[test]
     fn test_bson_to_value_object_id() {
         let oid = ObjectId::new();
         let result = bson_to_value(&Bson::ObjectId(oid));
-        assert_eq!(result, json!(oid.to_string()));
+        assert_eq!(result, json!({"$oid": oid.to_hex()}));
     }
 
     #[test]
     fn test_bson_to_value_datetime() {
-        let dt = DateTime::now();
+        let dt = DateTime::from_millis(1_700_000_000_123);
         let result = bson_to_value(&Bson::DateTime(dt));
-        assert_eq!(result, json!(dt.timestamp_millis()));
+        assert_eq!(result, json!({"$date": "2023-11-14T22:13:20.123Z"}));
     }
 
     #[test]
@@ -86,27 +118,27 @@ mod tests {
 
     #[test]
     fn test_bson_to_value_document() {
-        use mongodb::bson::doc;
         let doc = Bson::Document(doc! { "name": "test", "count": 5 });
         let result = bson_to_value(&doc);
         assert_eq!(result, json!({"name": "test", "count": 5}));
     }
 
     #[test]
-    fn test_bson_to_value_other_arm() {
-        // Timestamp has no special case → hits the `other` arm → stringified
+    fn test_bson_to_value_timestamp() {
         let ts = Bson::Timestamp(mongodb::bson::Timestamp {
             time: 100,
             increment: 1,
         });
-        let result = bson_to_value(&ts);
-        assert!(result.is_string());
+        assert_eq!(
+            bson_to_value(&ts),
+            json!({"$timestamp": {"t": 100, "i": 1}})
+        );
     }
 
     #[test]
     fn test_json_to_bson_doc_roundtrip() {
         let val = json!({"name": "test", "count": 5, "active": true});
-        let doc = json_to_bson_doc_agent(&val).unwrap();
+        let doc = json_to_bson_doc(&val).unwrap();
         // Convert back to verify round-trip
         let back = bson_to_value(&Bson::Document(doc));
         assert_eq!(back, val);
@@ -115,7 +147,7 @@ mod tests {
     #[test]
     fn test_json_to_bson_doc_nested() {
         let val = json!({"nested": {"a": 1, "b": "two"}});
-        let doc = json_to_bson_doc_agent(&val).unwrap();
+        let doc = json_to_bson_doc(&val).unwrap();
         let back = bson_to_value(&Bson::Document(doc));
         assert_eq!(back, val);
     }
@@ -123,7 +155,159 @@ mod tests {
     #[test]
     fn test_json_to_bson_doc_invalid_type() {
         let val = json!([1, 2, 3]); // array, not object
-        let result = json_to_bson_doc_agent(&val);
+        let result = json_to_bson_doc(&val);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_json_to_bson_doc_parses_extended_json() {
+        let oid = ObjectId::new();
+        let val = json!({
+            "_id": {"$oid": oid.to_hex()},
+            "at": {"$date": {"$numberLong": "86400000"}},
+            "n": {"$numberLong": "7"},
+            "big": 9_000_000_000i64,
+            "price": {"$numberDecimal": "9.99"}
+        });
+        let doc = json_to_bson_doc(&val).unwrap();
+        assert_eq!(doc.get("_id"), Some(&Bson::ObjectId(oid)));
+        assert_eq!(
+            doc.get("at"),
+            Some(&Bson::DateTime(DateTime::from_millis(86_400_000)))
+        );
+        assert_eq!(doc.get("n"), Some(&Bson::Int64(7)));
+        assert_eq!(doc.get("big"), Some(&Bson::Int64(9_000_000_000)));
+        assert_eq!(
+            doc.get("price").map(|v| v.element_type()),
+            Some(ElementType::Decimal128)
+        );
+    }
+
+    #[test]
+    fn test_json_to_bson_doc_rejects_malformed_extended_json() {
+        assert!(json_to_bson_doc(&json!({"_id": {"$oid": "not-hex"}})).is_err());
+        assert!(json_to_bson_doc(&json!({"n": {"$numberLong": "x"}})).is_err());
+    }
+
+    /// Samples of every BSON type, with the edge values of each.
+    fn samples() -> Vec<Bson> {
+        let oid = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        let db_pointer = Bson::try_from(json!({
+            "$dbPointer": {"$ref": "things", "$id": {"$oid": oid.to_hex()}}
+        }))
+        .unwrap();
+        let mut samples = vec![
+            Bson::String(String::new()),
+            Bson::String("ünïcode \"quoted\" $field".into()),
+            Bson::Array(vec![]),
+            Bson::Document(doc! {}),
+            Bson::Boolean(false),
+            Bson::Null,
+            Bson::Undefined,
+            Bson::MaxKey,
+            Bson::MinKey,
+            Bson::ObjectId(oid),
+            Bson::RegularExpression(Regex::new("^a.*b$", "im")),
+            Bson::JavaScriptCode("function () { return 1; }".into()),
+            Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+                code: "x + y".into(),
+                scope: doc! { "x": 1, "y": 2i64 },
+            }),
+            Bson::Symbol("sym".into()),
+            Bson::Timestamp(mongodb::bson::Timestamp {
+                time: u32::MAX,
+                increment: 7,
+            }),
+            Bson::Decimal128("1234.5678".parse::<Decimal128>().unwrap()),
+            Bson::Decimal128("-0E-10".parse::<Decimal128>().unwrap()),
+            db_pointer,
+        ];
+        samples.extend([i32::MIN, -1, 0, 1, i32::MAX].map(Bson::Int32));
+        samples.extend([i64::MIN, -1, 0, 1, i32::MAX as i64 + 1, i64::MAX].map(Bson::Int64));
+        samples.extend(
+            [
+                0.0,
+                -0.0,
+                1.0,
+                -2.5,
+                0.1,
+                1e300,
+                f64::MIN_POSITIVE,
+                f64::MAX,
+                f64::NAN,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ]
+            .map(Bson::Double),
+        );
+        // Relaxed JSON writes dates in 1970..=9999 as ISO strings, others as millis.
+        samples.extend(
+            [
+                i64::MIN,
+                -1,
+                0,
+                1_700_000_000_123,
+                253_402_300_799_999,
+                253_402_300_800_000,
+            ]
+            .map(|ms| Bson::DateTime(DateTime::from_millis(ms))),
+        );
+        for subtype in [
+            BinarySubtype::Generic,
+            BinarySubtype::Uuid,
+            BinarySubtype::Md5,
+            BinarySubtype::UserDefined(0x80),
+        ] {
+            samples.push(Bson::Binary(Binary {
+                subtype,
+                bytes: (0..=255u8).take(16).collect(),
+            }));
+        }
+        samples
+    }
+
+    /// Canonical Extended JSON is type-exact (and compares NaN by text).
+    fn assert_round_trips(bson: &Bson) {
+        let expected = bson.clone().into_canonical_extjson();
+        let value = bson_to_value(bson);
+        let back = value_to_bson(&value).unwrap_or_else(|e| panic!("{}: {}", value, e));
+        assert_eq!(back.into_canonical_extjson(), expected, "via {}", value);
+        // And through text, as the frontend and export files see it.
+        let text = serde_json::to_string(&value).unwrap();
+        let reparsed: Value = serde_json::from_str(&text).unwrap();
+        let back = value_to_bson(&reparsed).unwrap();
+        assert_eq!(back.into_canonical_extjson(), expected, "via {}", text);
+    }
+
+    #[test]
+    fn test_round_trip_every_bson_type() {
+        let samples = samples();
+        let covered: HashSet<u8> = samples.iter().map(|b| b.element_type() as u8).collect();
+        // Every BSON element type, 0x01 (double) to 0x13 (decimal128) plus min/max key.
+        assert_eq!(covered.len(), 21, "a Bson variant has no sample");
+        for bson in &samples {
+            assert_round_trips(bson);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_nested_documents() {
+        let samples = samples();
+        let mut nested = Document::new();
+        for (i, bson) in samples.iter().enumerate() {
+            nested.insert(format!("f{}", i), bson.clone());
+        }
+        let doc = doc! {
+            "_id": ObjectId::new(),
+            "all": nested.clone(),
+            "list": samples.clone(),
+            "deep": { "a": [{ "b": nested }] },
+        };
+        assert_round_trips(&Bson::Document(doc.clone()));
+        let back = json_to_bson_doc(&doc_to_value(&doc)).unwrap();
+        assert_eq!(
+            Bson::Document(back).into_canonical_extjson(),
+            Bson::Document(doc).into_canonical_extjson()
+        );
+    }
 }
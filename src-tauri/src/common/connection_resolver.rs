@@ -80,6 +80,16 @@ fn normalize_config(connection: Value) -> Result<Value, String> {
     Ok(config)
 }
 
+/// Copy the per-connection TLS settings (see `common::tls`) shared by every
+/// source.
+pub(crate) fn copy_tls_settings(conn: &Value, config: &mut serde_json::Map<String, Value>) {
+    for key in crate::common::tls::TLS_CONFIG_KEYS {
+        if let Some(v) = conn.get(key).and_then(|v| v.as_str()) {
+            config.insert(key.to_string(), Value::String(v.to_string()));
+        }
+    }
+}
+
 fn normalize_es(conn: Value) -> Value {
     let mut config = serde_json::Map::new();
     if let Some(v) = conn.get("host").and_then(|v| v.as_str()) {
@@ -111,6 +121,15 @@ fn normalize_es(conn: Value) -> Value {
     if let Some(v) = conn.get("apiKey").and_then(|v| v.as_str()) {
         config.insert("apiKey".to_string(), Value::String(v.to_string()));
     }
+    for key in ["token", "awsRegion", "awsService"] {
+        if let Some(v) = conn.get(key).and_then(|v| v.as_str()) {
+            config.insert(key.to_string(), Value::String(v.to_string()));
+        }
+    }
+    if let Some(v) = conn.get("awsAuth") {
+        config.insert("awsAuth".to_string(), v.clone());
+    }
+    copy_tls_settings(&conn, &mut config);
     if let Some(v) = conn.get("sslCertVerification") {
         config.insert("sslCertVerification".to_string(), v.clone());
     }
@@ -178,6 +197,7 @@ fn normalize_dynamo(conn: Value) -> Result<Value, String> {
         }
     }
 
+    copy_tls_settings(&conn, &mut config);
     if let Some(ssh) = conn.get("sshTunnel") {
         config.insert("sshTunnel".to_string(), ssh.clone());
     }
@@ -200,6 +220,7 @@ fn normalize_mongo(conn: Value) -> Value {
     if let Some(v) = conn.get("tls") {
         config.insert("tls".to_string(), v.clone());
     }
+    copy_tls_settings(&conn, &mut config);
     if let Some(v) = conn.get("database").and_then(|v| v.as_str()) {
         config.insert("database".to_string(), Value::String(v.to_string()));
     }
@@ -269,6 +290,23 @@ mod tests {
         assert!(cfg.get("username").is_none());
     }
 
+    #[test]
+    fn test_normalize_es_keeps_extended_auth_fields() {
+        let conn = json!({
+            "host": "search-logs.us-east-1.es.amazonaws.com", "port": 443,
+            "authType": "awsSigV4", "awsRegion": "us-east-1", "awsService": "es",
+            "awsAuth": {"kind": "profile", "profile_name": "ops"},
+            "token": "t", "clientCert": "/certs/client.pem", "clientKey": "/certs/client.key"
+        });
+        let cfg = normalize_es(conn);
+        assert_eq!(cfg.get("authType").unwrap(), "awsSigV4");
+        assert_eq!(cfg.get("awsRegion").unwrap(), "us-east-1");
+        assert_eq!(cfg["awsAuth"]["profile_name"], "ops");
+        assert_eq!(cfg.get("token").unwrap(), "t");
+        assert_eq!(cfg.get("clientCert").unwrap(), "/certs/client.pem");
+        assert_eq!(cfg.get("clientKey").unwrap(), "/certs/client.key");
+    }
+
     #[test]
     fn test_normalize_es_strips_scheme_prefix() {
         let conn =
@@ -414,6 +452,29 @@ mod tests {
         assert_eq!(cfg.get("authKind").unwrap(), "uri");
     }
 
+    #[test]
+    fn test_normalize_keeps_tls_settings() {
+        let tls = json!({
+            "caBundle": "/certs/ca.pem", "clientCert": "/certs/client.pem",
+            "clientKey": "/certs/client.key", "tlsServerName": "db.internal", "tlsMinVersion": "1.3"
+        });
+        let with_tls = |base: Value| {
+            let mut conn = base;
+            conn.as_object_mut()
+                .unwrap()
+                .extend(tls.as_object().unwrap().clone());
+            conn
+        };
+        let mongo = normalize_mongo(with_tls(json!({"host": "mongo.host", "port": 27017})));
+        let es = normalize_es(with_tls(json!({"host": "es.host", "port": 9200})));
+        let dynamo = normalize_dynamo(with_tls(json!({"region": "us-east-1"}))).unwrap();
+        for cfg in [mongo, es, dynamo] {
+            for key in crate::common::tls::TLS_CONFIG_KEYS {
+                assert_eq!(cfg[key], tls[key], "{} must be kept", key);
+            }
+        }
+    }
+
     #[test]
     fn test_normalize_es_passes_ssh_tunnel() {
         let conn = json!({
@@ -2,9 +2,37 @@ use aws_config::meta::region::RegionProviderChain;
 use aws_config::profile::ProfileFileCredentialsProvider;
 use aws_config::Region;
 use aws_sdk_cloudwatch::Client as CloudWatchClient;
-use aws_sdk_dynamodb::{config::Credentials, Client as DynamoClient};
+use aws_sdk_dynamodb::config::{Credentials, SharedHttpClient};
+use aws_sdk_dynamodb::Client as DynamoClient;
+use aws_smithy_http_client::tls::TlsContext;
 use serde_json::Value;
 
+/// HTTP client for the AWS SDK when its default will not do: routing through
+/// an SSH tunnel's CONNECT proxy and/or trusting a custom endpoint's CA
+/// bundle on top of the platform roots.
+pub(crate) fn aws_http_client(
+    proxy_url: Option<String>,
+    tls_context: Option<TlsContext>,
+) -> SharedHttpClient {
+    aws_smithy_http_client::Builder::new().build_with_connector_fn(move |_, _| {
+        let mut connector = aws_smithy_http_client::Connector::builder().tls_provider(
+            aws_smithy_http_client::tls::Provider::Rustls(
+                aws_smithy_http_client::tls::rustls_provider::CryptoMode::AwsLc,
+            ),
+        );
+        if let Some(ctx) = &tls_context {
+            connector = connector.tls_context(ctx.clone());
+        }
+        if let Some(proxy_url) = &proxy_url {
+            connector = connector.proxy_config(
+                aws_smithy_http_client::proxy::ProxyConfig::https(proxy_url)
+                    .expect("valid DynamoDB tunnel proxy URL"),
+            );
+        }
+        connector.build()
+    })
+}
+
 pub(crate) async fn create_dynamo_client(
     config: &Value,
     tunnel_port: Option<u16>,
@@ -13,6 +41,8 @@ pub(crate) async fn create_dynamo_client(
         .get("region")
         .and_then(|v| v.as_str())
         .ok_or("Missing region")?;
+    // Custom endpoints behind an internal CA carry a `caBundle`.
+    let tls_context = crate::common::tls::ConnectionTls::from_config(config)?.aws_tls_context()?;
 
     let region_provider = RegionProviderChain::first_try(Region::new(region.to_string()))
         .or_default_provider()
@@ -94,18 +124,7 @@ pub(crate) async fn create_dynamo_client(
         .map(|s| s.to_string());
     if let Some(proxy) = http_proxy {
         let proxy_url = format!("http://{}", proxy);
-        let client = aws_smithy_http_client::Builder::new().build_with_connector_fn(move |_, _| {
-            aws_smithy_http_client::Connector::builder()
-                .tls_provider(aws_smithy_http_client::tls::Provider::Rustls(
-                    aws_smithy_http_client::tls::rustls_provider::CryptoMode::AwsLc,
-                ))
-                .proxy_config(
-                    aws_smithy_http_client::proxy::ProxyConfig::https(&proxy_url)
-                        .expect("valid DynamoDB tunnel proxy URL"),
-                )
-                .build()
-        });
-        config_builder = config_builder.http_client(client);
+        config_builder = config_builder.http_client(aws_http_client(Some(proxy_url), tls_context));
         if let Some(endpoint) = config.get("endpointUrl").and_then(|v| v.as_str()) {
             if !endpoint.is_empty() {
                 config_builder = config_builder.endpoint_url(endpoint);
@@ -117,6 +136,9 @@ pub(crate) async fn create_dynamo_client(
     } else if let Some(endpoint) = config.get("endpointUrl").and_then(|v| v.as_str()) {
         if !endpoint.is_empty() {
             config_builder = config_builder.endpoint_url(endpoint);
+            if tls_context.is_some() {
+                config_builder = config_builder.http_client(aws_http_client(None, tls_context));
+            }
         }
     }
 
@@ -132,6 +154,8 @@ pub(crate) async fn create_cloudwatch_client(
         .get("region")
         .and_then(|v| v.as_str())
         .ok_or("Missing region")?;
+    // Custom endpoints behind an internal CA carry a `caBundle`.
+    let tls_context = crate::common::tls::ConnectionTls::from_config(config)?.aws_tls_context()?;
 
     let region_provider = RegionProviderChain::first_try(Region::new(region.to_string()))
         .or_default_provider()
@@ -200,6 +224,9 @@ pub(crate) async fn create_cloudwatch_client(
     } else if let Some(endpoint) = config.get("endpointUrl").and_then(|v| v.as_str()) {
         if !endpoint.is_empty() {
             config_builder = config_builder.endpoint_url(endpoint);
+            if tls_context.is_some() {
+                config_builder = config_builder.http_client(aws_http_client(None, tls_context));
+            }
         }
     }
 
@@ -269,6 +296,25 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_create_dynamo_client_endpoint_ca_bundle() {
+        let mut config = json!({
+            "region": "us-east-1",
+            "authKind": "accessKey",
+            "accessKeyId": "AKID",
+            "secretAccessKey": "SAK",
+            "endpointUrl": "https://ddb.corp:8000",
+            "caBundle": crate::common::tls_test_server::test_root_certificate_pem(),
+        });
+        let result = create_dynamo_client(&config, None).await;
+        assert!(result.is_ok(), "CA bundle should build: {:?}", result.err());
+
+        // The SDK connector only takes a trust store.
+        config["tlsServerName"] = json!("ddb.internal");
+        let err = create_dynamo_client(&config, None).await.unwrap_err();
+        assert!(err.contains("caBundle"));
+    }
+
     #[tokio::test]
     async fn test_create_dynamo_client_http_connect_proxy() {
         // Socks5/CONNECT mode: real endpoint URL preserved + proxy connector.
@@ -76,12 +76,159 @@ pub(crate) fn build_es_headers(config: &Value) -> reqwest::header::HeaderMap {
                 headers.insert("Authorization", val);
             }
         }
+        // Elastic service account tokens and OAuth/OIDC access tokens.
+        "bearer" => {
+            let token = config.get("token").and_then(|v| v.as_str()).unwrap_or("");
+            if let Ok(val) = format!("Bearer {}", token).parse() {
+                headers.insert("Authorization", val);
+            }
+        }
+        // "pki" authenticates with the TLS client certificate and "awsSigV4"
+        // signs each request, so neither adds a static header here.
         _ => {}
     }
 
     headers
 }
 
+/// TLS settings for an ES/OpenSearch connection (see `common::tls`).
+/// `authType: "pki"` authenticates with the client certificate, so it must
+/// be configured.
+pub(crate) fn es_connection_tls(
+    config: &Value,
+) -> Result<crate::common::tls::ConnectionTls, String> {
+    let tls = crate::common::tls::ConnectionTls::from_config(config)?;
+    if config.get("authType").and_then(|v| v.as_str()) == Some("pki") && !tls.has_client_identity()
+    {
+        return Err("PKI authentication requires clientCert and clientKey".to_string());
+    }
+    Ok(tls)
+}
+
+/// Parse `awsAuth` into the DynamoDB auth selection. Stored connections use
+/// the frontend's camelCase keys (`accessKeyId`, `profileName`), the
+/// DynamoDB command path uses snake_case; accept both.
+fn aws_auth_from_config(config: &Value) -> Result<crate::dynamo_client::DynamoAuth, String> {
+    let mut auth = config
+        .get("awsAuth")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .ok_or("SigV4 authentication requires awsAuth")?;
+    for (camel, snake) in [
+        ("accessKeyId", "access_key_id"),
+        ("secretAccessKey", "secret_access_key"),
+        ("sessionToken", "session_token"),
+        ("profileName", "profile_name"),
+    ] {
+        if let Some(v) = auth.remove(camel) {
+            auth.entry(snake).or_insert(v);
+        }
+    }
+    serde_json::from_value(Value::Object(auth)).map_err(|e| format!("Invalid awsAuth: {}", e))
+}
+
+pub(crate) fn es_uses_sigv4(config: &Value) -> bool {
+    config.get("authType").and_then(|v| v.as_str()) == Some("awsSigV4")
+}
+
+/// Sign a request for Amazon OpenSearch Service (`awsService: "es"`, the
+/// default) or OpenSearch Serverless (`"aoss"`). Credentials come from
+/// `awsAuth`, which uses the same shape and resolution (access key, profile,
+/// SSO, assumed role) as DynamoDB connections.
+pub(crate) async fn sign_es_request(
+    config: &Value,
+    method: &str,
+    url: &str,
+    headers: &mut reqwest::header::HeaderMap,
+    body: &[u8],
+) -> Result<(), String> {
+    use aws_credential_types::provider::ProvideCredentials;
+    use aws_sigv4::http_request::{
+        sign, PayloadChecksumKind, SignableBody, SignableRequest, SigningSettings,
+    };
+    use aws_sigv4::sign::v4;
+
+    let auth = aws_auth_from_config(config)?;
+    let region = config
+        .get("awsRegion")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let service = config
+        .get("awsService")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("es");
+
+    let sdk_config = crate::dynamo_client::aws_config_loader(region, &auth)
+        .load()
+        .await;
+    let signing_region = sdk_config
+        .region()
+        .map(|r| r.to_string())
+        .ok_or("Unable to determine the AWS region for SigV4 signing")?;
+    let credentials = sdk_config
+        .credentials_provider()
+        .ok_or("No AWS credentials provider configured")?
+        .provide_credentials()
+        .await
+        .map_err(|e| format!("Failed to resolve AWS credentials: {}", e))?;
+    let identity = credentials.into();
+
+    // OpenSearch Serverless requires the payload hash header; the managed
+    // service accepts it too.
+    let mut settings = SigningSettings::default();
+    settings.payload_checksum_kind = PayloadChecksumKind::XAmzSha256;
+    let params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(&signing_region)
+        .name(service)
+        .time(std::time::SystemTime::now())
+        .settings(settings)
+        .build()
+        .map_err(|e| format!("Invalid SigV4 parameters: {}", e))?
+        .into();
+
+    let signable = SignableRequest::new(
+        method,
+        url,
+        headers
+            .iter()
+            .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.as_str(), v))),
+        SignableBody::Bytes(body),
+    )
+    .map_err(|e| format!("Failed to prepare request for signing: {}", e))?;
+    let (instructions, _signature) = sign(signable, &params)
+        .map_err(|e| format!("SigV4 signing failed: {}", e))?
+        .into_parts();
+    for (name, value) in instructions.headers() {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid signed header name: {}", e))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid signed header value: {}", e))?;
+        headers.insert(name, value);
+    }
+    Ok(())
+}
+
+/// Cluster distribution, used to pick between Elastic and OpenSearch plugin
+/// endpoints for APIs whose paths diverged (e.g. `_ilm` vs `_plugins/_ism`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EsFlavor {
+    Elasticsearch,
+    OpenSearch,
+}
+
+impl EsFlavor {
+    /// Read the flavor from the connection `type` carried by the normalized
+    /// config. EasySearch and unknown types follow the Elastic API surface.
+    pub(crate) fn from_config(config: &Value) -> Self {
+        match config.get("type").and_then(|v| v.as_str()) {
+            Some("OPENSEARCH") => EsFlavor::OpenSearch,
+            _ => EsFlavor::Elasticsearch,
+        }
+    }
+}
+
 pub(crate) fn get_es_ssl_flag(config: &Value) -> bool {
     config
         .get("sslCertVerification")
@@ -209,6 +356,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_es_headers_bearer() {
+        let h = build_es_headers(&json!({"authType": "bearer", "token": "AAEAAWVsYXN0aWM"}));
+        assert_eq!(
+            h.get("Authorization").unwrap().to_str().unwrap(),
+            "Bearer AAEAAWVsYXN0aWM"
+        );
+    }
+
+    #[test]
+    fn test_build_es_headers_pki_and_sigv4_add_no_header() {
+        assert!(build_es_headers(&json!({"authType": "pki"}))
+            .get("Authorization")
+            .is_none());
+        assert!(build_es_headers(&json!({"authType": "awsSigV4"}))
+            .get("Authorization")
+            .is_none());
+    }
+
+    #[test]
+    fn test_es_connection_tls_pki_requires_cert_and_key() {
+        assert!(es_connection_tls(&json!({"authType": "basic"}))
+            .unwrap()
+            .is_empty());
+        let err = es_connection_tls(&json!({"authType": "pki"})).unwrap_err();
+        assert!(err.contains("clientCert"));
+        assert!(es_connection_tls(&json!({"authType": "pki", "clientKey": "x"})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_es_request_adds_sigv4_headers() {
+        let config = json!({
+            "authType": "awsSigV4",
+            "awsRegion": "us-east-1",
+            "awsAuth": {
+                "kind": "accessKey",
+                "accessKeyId": "AKIDEXAMPLE",
+                "secretAccessKey": "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"
+            }
+        });
+        let mut headers = build_es_headers(&config);
+        sign_es_request(
+            &config,
+            "GET",
+            "https://search-domain.us-east-1.es.amazonaws.com/_cluster/health",
+            &mut headers,
+            b"",
+        )
+        .await
+        .unwrap();
+        let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(auth.contains("/us-east-1/es/aws4_request"));
+        assert!(headers.get("x-amz-date").is_some());
+        assert!(headers.get("x-amz-content-sha256").is_some());
+    }
+
     #[test]
     fn test_get_es_ssl_flag_true() {
         assert!(get_es_ssl_flag(&json!({"sslCertVerification": true})));
@@ -223,4 +427,21 @@ mod tests {
     fn test_get_es_ssl_flag_default() {
         assert!(!get_es_ssl_flag(&json!({})));
     }
+
+    #[test]
+    fn test_es_flavor_from_config() {
+        assert_eq!(
+            EsFlavor::from_config(&json!({"type": "OPENSEARCH"})),
+            EsFlavor::OpenSearch
+        );
+        assert_eq!(
+            EsFlavor::from_config(&json!({"type": "ELASTICSEARCH"})),
+            EsFlavor::Elasticsearch
+        );
+        assert_eq!(
+            EsFlavor::from_config(&json!({"type": "EASYSEARCH"})),
+            EsFlavor::Elasticsearch
+        );
+        assert_eq!(EsFlavor::from_config(&json!({})), EsFlavor::Elasticsearch);
+    }
 }
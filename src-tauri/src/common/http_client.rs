@@ -120,13 +120,24 @@ pub async fn detect_system_proxy(
     }
 }
 
+/// Per-connection TLS material for `create_http_client`, built from the
+/// connection config by `common::tls::ConnectionTls::http_options`.
+#[derive(Default)]
+pub struct HttpTlsOptions {
+    /// Extra CA certificates trusted in addition to the system roots.
+    pub root_certs: Vec<reqwest::Certificate>,
+    /// Client certificate presented for mutual TLS.
+    pub identity: Option<reqwest::Identity>,
+    pub min_version: Option<reqwest::tls::Version>,
+}
+
 pub fn create_http_client(
     proxy_mode: &str,
     proxy_url: Option<String>,
     ssl: Option<bool>,
     request_timeout: Option<Duration>,
     dns_override: Option<(String, SocketAddr)>,
-    extra_root_certs: Option<Vec<reqwest::Certificate>>,
+    tls: HttpTlsOptions,
 ) -> reqwest::Client {
     let mut builder = reqwest::ClientBuilder::new()
         .danger_accept_invalid_certs(!ssl.unwrap_or(true))
@@ -169,10 +180,17 @@ pub fn create_http_client(
         builder = builder.resolve(&domain, addr);
     }
 
-    if let Some(certs) = extra_root_certs {
-        for cert in certs {
-            builder = builder.add_root_certificate(cert);
-        }
+    for cert in tls.root_certs {
+        builder = builder.add_root_certificate(cert);
+    }
+
+    // Mutual TLS: present a client certificate (ES/OpenSearch PKI realm).
+    if let Some(identity) = tls.identity {
+        builder = builder.identity(identity);
+    }
+
+    if let Some(version) = tls.min_version {
+        builder = builder.min_tls_version(version);
     }
 
     builder.build().expect("Failed to build HTTP client")
@@ -244,14 +262,15 @@ mod tests {
 
     #[test]
     fn test_create_http_client_default_mode() {
-        let client = create_http_client("system", None, None, None, None, None);
+        let client =
+            create_http_client("system", None, None, None, None, HttpTlsOptions::default());
         // Should return a valid client without panicking
         let _ = client;
     }
 
     #[test]
     fn test_create_http_client_no_proxy_mode() {
-        let client = create_http_client("none", None, None, None, None, None);
+        let client = create_http_client("none", None, None, None, None, HttpTlsOptions::default());
         let _ = client;
     }
 
@@ -263,7 +282,7 @@ mod tests {
             Some(true),
             None,
             None,
-            None,
+            HttpTlsOptions::default(),
         );
         let _ = client;
     }
@@ -276,8 +295,18 @@ mod tests {
             None,
             Some(Duration::from_secs(30)),
             None,
-            None,
+            HttpTlsOptions::default(),
         );
         let _ = client;
     }
+
+    #[test]
+    fn test_create_http_client_with_min_tls_version() {
+        let tls = HttpTlsOptions {
+            min_version: Some(reqwest::tls::Version::TLS_1_3),
+            ..Default::default()
+        };
+        let client = create_http_client("none", None, Some(true), None, None, tls);
+        let _ = client;
+    }
 }
@@ -6,6 +6,11 @@ pub mod es;
 pub mod format;
 pub mod http_client;
 pub mod mongo;
+pub mod mongo_advisor;
+pub mod mongo_dump;
+pub mod mongo_explain;
+pub mod mongo_gridfs;
+pub mod mongo_schema;
 pub mod response;
 pub mod ssh_bridge;
 pub mod validation;
@@ -1,4 +1,8 @@
-use mongodb::{options::ClientOptions, Client as MongoClient};
+use mongodb::bson::Document;
+use mongodb::change_stream::event::ResumeToken;
+use mongodb::change_stream::ChangeStream;
+use mongodb::options::{ChangeStreamOptions, ClientOptions, FullDocumentType};
+use mongodb::Client as MongoClient;
 use serde_json::Value;
 
 fn build_mongo_uri(config: &Value) -> Result<String, String> {
@@ -107,6 +111,7 @@ async fn build_client_options(config: &Value) -> Result<ClientOptions, String> {
         .map_err(|e| format!("Failed to parse MongoDB connection options: {}", e))?;
     let socks5_proxy = config.get("socks5Proxy").and_then(|v| v.as_str());
     apply_socks5_proxy_from_config(&mut client_options, socks5_proxy)?;
+    crate::common::tls::ConnectionTls::from_config(config)?.apply_to_mongo(&mut client_options)?;
     Ok(client_options)
 }
 
@@ -124,6 +129,120 @@ pub(crate) async fn create_mongo_client_from_config(
     Ok((client, database))
 }
 
+/// Scope of a change stream: one collection, one database, or the whole deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WatchTarget {
+    Collection {
+        database: String,
+        collection: String,
+    },
+    Database(String),
+    Deployment,
+}
+
+impl WatchTarget {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            WatchTarget::Collection {
+                database,
+                collection,
+            } => format!("{}.{}", database, collection),
+            WatchTarget::Database(database) => database.clone(),
+            WatchTarget::Deployment => "deployment".to_string(),
+        }
+    }
+}
+
+/// Builds change stream options from the `fullDocument` mode name and an
+/// optional resume token previously returned as Extended JSON.
+pub(crate) fn change_stream_options(
+    full_document: Option<&str>,
+    resume_after: Option<&Value>,
+) -> Result<ChangeStreamOptions, String> {
+    let full_document = match full_document.filter(|s| !s.is_empty()) {
+        None | Some("default") => None,
+        Some("updateLookup") => Some(FullDocumentType::UpdateLookup),
+        Some("whenAvailable") => Some(FullDocumentType::WhenAvailable),
+        Some("required") => Some(FullDocumentType::Required),
+        Some(other) => {
+            return Err(format!(
+                "Unsupported fullDocument '{}': expected default, updateLookup, whenAvailable or required",
+                other
+            ))
+        }
+    };
+    let resume_after: Option<ResumeToken> = match resume_after.filter(|v| !v.is_null()) {
+        Some(token) => {
+            let bson = crate::common::bson::value_to_bson(token)?;
+            Some(
+                mongodb::bson::from_bson(bson)
+                    .map_err(|e| format!("Invalid resume token: {}", e))?,
+            )
+        }
+        None => None,
+    };
+    Ok(ChangeStreamOptions::builder()
+        .full_document(full_document)
+        .resume_after(resume_after)
+        .build())
+}
+
+/// Opens a change stream yielding raw event documents, so every field the
+/// server sends (including ones the driver does not model) reaches the caller.
+pub(crate) async fn open_change_stream(
+    client: &MongoClient,
+    target: &WatchTarget,
+    pipeline: Vec<Document>,
+    options: ChangeStreamOptions,
+) -> Result<ChangeStream<Document>, String> {
+    let opened = match target {
+        WatchTarget::Collection {
+            database,
+            collection,
+        } => client
+            .database(database)
+            .collection::<Document>(collection)
+            .watch()
+            .pipeline(pipeline)
+            .with_options(options)
+            .await
+            .map(|s| s.with_type::<Document>()),
+        WatchTarget::Database(database) => client
+            .database(database)
+            .watch()
+            .pipeline(pipeline)
+            .with_options(options)
+            .await
+            .map(|s| s.with_type::<Document>()),
+        WatchTarget::Deployment => client
+            .watch()
+            .pipeline(pipeline)
+            .with_options(options)
+            .await
+            .map(|s| s.with_type::<Document>()),
+    };
+    opened.map_err(|e| {
+        let msg = e.to_string();
+        if msg.contains("replica set") || msg.contains("40573") {
+            format!(
+                "Change streams require a replica set or sharded cluster: {}",
+                msg
+            )
+        } else {
+            format!("Failed to open change stream: {}", msg)
+        }
+    })
+}
+
+/// Latest resume token of a stream as Extended JSON, or null before the first batch.
+pub(crate) fn change_stream_resume_token(stream: &ChangeStream<Document>) -> Value {
+    stream
+        .resume_token()
+        .and_then(|token| mongodb::bson::to_bson(&token).ok())
+        .map(|bson| crate::common::bson::bson_to_value(&bson))
+        .unwrap_or(Value::Null)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,6 +407,30 @@ mod tests {
         assert!(options.direct_connection.is_none());
     }
 
+    #[tokio::test]
+    async fn test_build_client_options_applies_ca_bundle() {
+        let ca = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----";
+        let options = build_client_options(&json!({
+            "host": "127.0.0.1", "port": 27018, "tls": true, "caBundle": ca,
+        }))
+        .await
+        .expect("options should build");
+        match options.tls {
+            Some(mongodb::options::Tls::Enabled(opts)) => {
+                let path = opts.ca_file_path.expect("ca_file_path must be set");
+                assert_eq!(std::fs::read_to_string(path).unwrap(), ca);
+            }
+            _ => panic!("TLS must be enabled"),
+        }
+
+        let err = build_client_options(&json!({
+            "host": "127.0.0.1", "port": 27018, "tlsServerName": "db.internal",
+        }))
+        .await
+        .unwrap_err();
+        assert!(err.contains("tlsServerName"));
+    }
+
     #[tokio::test]
     async fn test_build_client_options_uri_auth_with_socks5() {
         let options = build_client_options(&json!({
@@ -325,4 +468,39 @@ mod tests {
         let err = result.unwrap_err();
         assert!(err.contains("no port separator"), "got: {}", err);
     }
+
+    #[test]
+    fn test_change_stream_options_full_document_modes() {
+        let options = change_stream_options(Some("updateLookup"), None).unwrap();
+        assert!(matches!(
+            options.full_document,
+            Some(FullDocumentType::UpdateLookup)
+        ));
+        assert!(change_stream_options(None, None)
+            .unwrap()
+            .full_document
+            .is_none());
+        let err = change_stream_options(Some("everything"), None).unwrap_err();
+        assert!(err.contains("Unsupported fullDocument"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_change_stream_options_resume_token_round_trip() {
+        let token = json!({"_data": "8263A1B2C3000000012B022C0100296E5A1004"});
+        let options = change_stream_options(None, Some(&token)).unwrap();
+        let resume = options.resume_after.expect("resume_after must be set");
+        let back = crate::common::bson::bson_to_value(&mongodb::bson::to_bson(&resume).unwrap());
+        assert_eq!(back, token);
+    }
+
+    #[test]
+    fn test_watch_target_describe() {
+        let target = WatchTarget::Collection {
+            database: "app".into(),
+            collection: "orders".into(),
+        };
+        assert_eq!(target.describe(), "app.orders");
+        assert_eq!(WatchTarget::Database("app".into()).describe(), "app");
+        assert_eq!(WatchTarget::Deployment.describe(), "deployment");
+    }
 }
@@ -0,0 +1,590 @@
+//! Index advice from profiled query shapes and `$indexStats`.
+//!
+//! Profiler entries are reduced to query shapes: the namespace plus the
+//! fields a query filters by equality, sorts by, and filters by range.
+//! A shape is served by an index whose keys list the equality fields first
+//! (in any order), then the sort fields in order, then the range fields; the
+//! same ESR (equality, sort, range) ordering is used for new proposals.
+//! Index keys are kept as ordered `(field, direction)` pairs throughout,
+//! because a JSON object would not preserve their order.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use mongodb::bson::{Bson, Document};
+use serde_json::Value;
+
+/// Operators that match a single value or set of values; anything else on a
+/// field is treated as a range predicate.
+const EQUALITY_OPERATORS: [&str; 4] = ["$eq", "$in", "$all", "$elemMatch"];
+/// Predicates that need a special index type and cannot be advised here.
+const SPECIAL_OPERATORS: [&str; 5] = [
+    "$near",
+    "$nearSphere",
+    "$geoWithin",
+    "$geoIntersects",
+    "$text",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct QueryShape {
+    pub(crate) namespace: String,
+    pub(crate) equality: Vec<String>,
+    pub(crate) sort: Vec<(String, i32)>,
+    pub(crate) range: Vec<String>,
+}
+
+impl QueryShape {
+    /// Index keys in ESR order.
+    pub(crate) fn esr_keys(&self) -> Vec<(String, i32)> {
+        self.equality
+            .iter()
+            .map(|f| (f.clone(), 1))
+            .chain(self.sort.iter().cloned())
+            .chain(self.range.iter().map(|f| (f.clone(), 1)))
+            .collect()
+    }
+
+    pub(crate) fn collection(&self) -> &str {
+        self.namespace
+            .split_once('.')
+            .map(|(_, coll)| coll)
+            .unwrap_or(&self.namespace)
+    }
+
+    fn to_value(&self) -> Value {
+        serde_json::json!({
+            "equality": self.equality,
+            "sort": self.sort.iter().map(|(f, d)| serde_json::json!([f, d])).collect::<Vec<_>>(),
+            "range": self.range,
+        })
+    }
+}
+
+/// The filter and sort behind a profiled operation.
+fn profiled_filter_and_sort(entry: &Document) -> Option<(Document, Document)> {
+    let command = match entry.get_str("op").ok()? {
+        "getmore" => entry.get_document("originatingCommand").ok()?,
+        "query" | "command" | "update" | "remove" => entry.get_document("command").ok()?,
+        _ => return None,
+    };
+    if let Ok(pipeline) = command.get_array("pipeline") {
+        // Only the leading $match/$sort stages can use an index.
+        let mut filter = Document::new();
+        let mut sort = Document::new();
+        for stage in pipeline.iter().filter_map(Bson::as_document) {
+            if let Ok(matched) = stage.get_document("$match") {
+                if !sort.is_empty() {
+                    break;
+                }
+                filter.extend(matched.clone());
+            } else if let Ok(sorted) = stage.get_document("$sort") {
+                sort = sorted.clone();
+                break;
+            } else {
+                break;
+            }
+        }
+        return Some((filter, sort));
+    }
+    let filter = ["filter", "query", "q"]
+        .iter()
+        .find_map(|key| command.get_document(key).ok())
+        .cloned()
+        .unwrap_or_default();
+    let sort = command.get_document("sort").cloned().unwrap_or_default();
+    Some((filter, sort))
+}
+
+fn push_unique(fields: &mut Vec<String>, field: &str) {
+    if !fields.iter().any(|f| f == field) {
+        fields.push(field.to_string());
+    }
+}
+
+/// Splits a filter into equality and range fields. Returns false when the
+/// filter uses a predicate no regular compound index can serve.
+fn classify_filter(filter: &Document, equality: &mut Vec<String>, range: &mut Vec<String>) -> bool {
+    for (field, value) in filter {
+        if field == "$and" {
+            for clause in value.as_array().into_iter().flatten() {
+                if let Some(clause) = clause.as_document() {
+                    if !classify_filter(clause, equality, range) {
+                        return false;
+                    }
+                }
+            }
+            continue;
+        }
+        if field.starts_with('$') {
+            // $or, $nor, $expr, $where and $text each need their own plan.
+            if field == "$text" {
+                return false;
+            }
+            continue;
+        }
+        match value {
+            Bson::Document(ops) if ops.keys().any(|k| k.starts_with('$')) => {
+                if ops.keys().any(|k| SPECIAL_OPERATORS.contains(&k.as_str())) {
+                    return false;
+                }
+                if ops.keys().all(|k| EQUALITY_OPERATORS.contains(&k.as_str())) {
+                    push_unique(equality, field);
+                } else {
+                    push_unique(range, field);
+                }
+            }
+            Bson::RegularExpression(_) => push_unique(range, field),
+            _ => push_unique(equality, field),
+        }
+    }
+    true
+}
+
+fn direction(value: &Bson) -> Option<i32> {
+    let n = match value {
+        Bson::Int32(n) => f64::from(*n),
+        Bson::Int64(n) => *n as f64,
+        Bson::Double(n) => *n,
+        _ => return None,
+    };
+    Some(if n < 0.0 { -1 } else { 1 })
+}
+
+/// Ordered `(field, direction)` pairs of an index key pattern; `None` for
+/// text, geo, hashed and wildcard indexes.
+pub(crate) fn index_keys(key: &Document) -> Option<Vec<(String, i32)>> {
+    key.iter()
+        .map(|(field, value)| direction(value).map(|d| (field.clone(), d)))
+        .collect()
+}
+
+/// Extracts the query shape of one `system.profile` entry.
+pub(crate) fn shape_from_profile(entry: &Document) -> Option<QueryShape> {
+    let namespace = entry.get_str("ns").ok()?;
+    let (_, collection) = namespace.split_once('.')?;
+    if collection.is_empty() || collection == "$cmd" || collection.starts_with("system.") {
+        return None;
+    }
+    let (filter, sort_doc) = profiled_filter_and_sort(entry)?;
+    let mut equality = Vec::new();
+    let mut range = Vec::new();
+    if !classify_filter(&filter, &mut equality, &mut range) {
+        return None;
+    }
+    range.retain(|f| !equality.contains(f));
+    // Sorting on a field pinned by equality does not constrain the order.
+    let sort: Vec<(String, i32)> = sort_doc
+        .iter()
+        .filter(|(field, _)| !equality.contains(field))
+        .filter_map(|(field, value)| direction(value).map(|d| (field.clone(), d)))
+        .collect();
+    range.retain(|f| !sort.iter().any(|(s, _)| s == f));
+    if equality.is_empty() && sort.is_empty() && range.is_empty() {
+        return None;
+    }
+    Some(QueryShape {
+        namespace: namespace.to_string(),
+        equality,
+        sort,
+        range,
+    })
+}
+
+/// True when an index with these keys supports the shape without a
+/// collection scan or an in-memory sort.
+pub(crate) fn index_serves(keys: &[(String, i32)], shape: &QueryShape) -> bool {
+    let e = shape.equality.len();
+    let s = shape.sort.len();
+    let r = shape.range.len();
+    if keys.len() < e + s + r {
+        return false;
+    }
+    let prefix: HashSet<&str> = keys[..e].iter().map(|(f, _)| f.as_str()).collect();
+    if prefix
+        != shape
+            .equality
+            .iter()
+            .map(String::as_str)
+            .collect::<HashSet<_>>()
+    {
+        return false;
+    }
+    // The sort may walk the index forwards or backwards, but consistently.
+    let mut reversed: Option<bool> = None;
+    for ((field, dir), (key, key_dir)) in shape.sort.iter().zip(&keys[e..e + s]) {
+        if field != key || *reversed.get_or_insert(dir != key_dir) != (dir != key_dir) {
+            return false;
+        }
+    }
+    let tail: HashSet<&str> = keys[e + s..e + s + r]
+        .iter()
+        .map(|(f, _)| f.as_str())
+        .collect();
+    tail == shape
+        .range
+        .iter()
+        .map(String::as_str)
+        .collect::<HashSet<_>>()
+}
+
+/// Profiled operations sharing one query shape.
+#[derive(Debug, Clone)]
+pub(crate) struct ShapeStats {
+    pub(crate) shape: QueryShape,
+    pub(crate) count: u64,
+    pub(crate) total_millis: i64,
+    pub(crate) max_millis: i64,
+    pub(crate) docs_examined: i64,
+    pub(crate) n_returned: i64,
+    pub(crate) plans: BTreeSet<String>,
+}
+
+fn number(doc: &Document, key: &str) -> i64 {
+    match doc.get(key) {
+        Some(Bson::Int32(n)) => i64::from(*n),
+        Some(Bson::Int64(n)) => *n,
+        Some(Bson::Double(n)) => *n as i64,
+        _ => 0,
+    }
+}
+
+/// Groups profiler entries by query shape, most expensive shapes first.
+pub(crate) fn group_profiled_shapes(entries: &[Document]) -> Vec<ShapeStats> {
+    let mut groups: HashMap<QueryShape, ShapeStats> = HashMap::new();
+    for entry in entries {
+        let Some(shape) = shape_from_profile(entry) else {
+            continue;
+        };
+        let stats = groups.entry(shape.clone()).or_insert_with(|| ShapeStats {
+            shape,
+            count: 0,
+            total_millis: 0,
+            max_millis: 0,
+            docs_examined: 0,
+            n_returned: 0,
+            plans: BTreeSet::new(),
+        });
+        let millis = number(entry, "millis");
+        stats.count += 1;
+        stats.total_millis += millis;
+        stats.max_millis = stats.max_millis.max(millis);
+        stats.docs_examined += number(entry, "docsExamined");
+        stats.n_returned += number(entry, "nreturned");
+        if let Ok(plan) = entry.get_str("planSummary") {
+            stats.plans.insert(plan.to_string());
+        }
+    }
+    let mut shapes: Vec<ShapeStats> = groups.into_values().collect();
+    shapes.sort_by(|a, b| {
+        b.total_millis
+            .cmp(&a.total_millis)
+            .then(b.count.cmp(&a.count))
+            .then(a.shape.namespace.cmp(&b.shape.namespace))
+    });
+    shapes
+}
+
+fn render_keys(keys: &[(String, i32)]) -> String {
+    keys.iter()
+        .map(|(f, d)| format!("{}: {}", serde_json::to_string(f).unwrap_or_default(), d))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn shape_value(stats: &ShapeStats) -> Value {
+    let mut value = stats.shape.to_value();
+    value["count"] = Value::from(stats.count);
+    value["total_millis"] = Value::from(stats.total_millis);
+    value["plans"] = serde_json::json!(stats.plans);
+    value
+}
+
+/// Proposes ESR compound indexes for shapes no existing index serves.
+///
+/// `existing` maps a namespace to `(index name, keys)` pairs. Shapes with
+/// fewer keys are folded into a longer proposal that already serves them,
+/// so one proposal can cover several shapes.
+pub(crate) fn propose_indexes(
+    shapes: &[ShapeStats],
+    existing: &HashMap<String, Vec<(String, Vec<(String, i32)>)>>,
+) -> (Vec<Value>, Vec<Value>) {
+    let mut already_indexed = Vec::new();
+    let mut pending: Vec<&ShapeStats> = Vec::new();
+    for stats in shapes {
+        let served_by = existing
+            .get(&stats.shape.namespace)
+            .into_iter()
+            .flatten()
+            .find(|(_, keys)| index_serves(keys, &stats.shape));
+        match served_by {
+            Some((name, _)) => {
+                let mut value = shape_value(stats);
+                value["namespace"] = Value::from(stats.shape.namespace.clone());
+                value["index"] = Value::from(name.clone());
+                already_indexed.push(value);
+            }
+            None => pending.push(stats),
+        }
+    }
+    pending.sort_by_key(|stats| std::cmp::Reverse(stats.shape.esr_keys().len()));
+
+    struct Proposal<'a> {
+        namespace: String,
+        keys: Vec<(String, i32)>,
+        shapes: Vec<&'a ShapeStats>,
+    }
+    let mut proposals: Vec<Proposal> = Vec::new();
+    for stats in pending {
+        let target = proposals
+            .iter_mut()
+            .find(|p| p.namespace == stats.shape.namespace && index_serves(&p.keys, &stats.shape));
+        match target {
+            Some(proposal) => proposal.shapes.push(stats),
+            None => proposals.push(Proposal {
+                namespace: stats.shape.namespace.clone(),
+                keys: stats.shape.esr_keys(),
+                shapes: vec![stats],
+            }),
+        }
+    }
+
+    let mut proposals: Vec<Value> = proposals
+        .into_iter()
+        .map(|p| {
+            let collection = p.shapes[0].shape.collection().to_string();
+            let affected: u64 = p.shapes.iter().map(|s| s.count).sum();
+            let total_millis: i64 = p.shapes.iter().map(|s| s.total_millis).sum();
+            let docs_examined: i64 = p.shapes.iter().map(|s| s.docs_examined).sum();
+            let n_returned: i64 = p.shapes.iter().map(|s| s.n_returned).sum();
+            let plans: BTreeSet<&String> = p.shapes.iter().flat_map(|s| &s.plans).collect();
+            let first = &p.shapes[0].shape;
+            serde_json::json!({
+                "namespace": p.namespace,
+                "collection": collection,
+                "keys": p.keys.iter().map(|(f, d)| serde_json::json!([f, d])).collect::<Vec<_>>(),
+                "create_index": format!(
+                    "db.getCollection({}).createIndex({{ {} }})",
+                    serde_json::to_string(&collection).unwrap_or_default(),
+                    render_keys(&p.keys)
+                ),
+                "rationale": format!(
+                    "equality: [{}]; sort: [{}]; range: [{}]",
+                    first.equality.join(", "),
+                    first.sort.iter().map(|(f, _)| f.as_str()).collect::<Vec<_>>().join(", "),
+                    first.range.join(", ")
+                ),
+                "estimated_affected_queries": affected,
+                "total_millis": total_millis,
+                "docs_examined": docs_examined,
+                "n_returned": n_returned,
+                "plans_seen": plans,
+                "shapes": p.shapes.iter().map(|s| shape_value(s)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    proposals.sort_by_key(|p| std::cmp::Reverse(p["total_millis"].as_i64().unwrap_or(0)));
+    (proposals, already_indexed)
+}
+
+/// An index definition as reported by `$indexStats` or `listIndexes`.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexInfo {
+    pub(crate) name: String,
+    pub(crate) key: Document,
+    pub(crate) spec: Document,
+}
+
+/// Indexes whose keys are a strict prefix of another index with the same
+/// directions, so every query they serve is also served by the longer one.
+/// Unique, partial, sparse, hidden and TTL indexes are kept, and an index is
+/// only covered by one with the same collation.
+pub(crate) fn redundant_indexes(indexes: &[IndexInfo]) -> Vec<(String, String)> {
+    let special = |spec: &Document| {
+        spec.get_bool("unique").unwrap_or(false)
+            || spec.get_bool("sparse").unwrap_or(false)
+            || spec.contains_key("partialFilterExpression")
+            || spec.contains_key("expireAfterSeconds")
+            || spec.get_bool("hidden").unwrap_or(false)
+    };
+    let mut redundant = Vec::new();
+    for index in indexes {
+        if index.name == "_id_" || special(&index.spec) {
+            continue;
+        }
+        let Some(keys) = index_keys(&index.key) else {
+            continue;
+        };
+        let covered_by = indexes.iter().find(|other| {
+            other.name != index.name
+                && !other.spec.get_bool("sparse").unwrap_or(false)
+                && !other.spec.contains_key("partialFilterExpression")
+                && !other.spec.get_bool("hidden").unwrap_or(false)
+                && other.spec.get("collation") == index.spec.get("collation")
+                && index_keys(&other.key).is_some_and(|other_keys| {
+                    other_keys.len() > keys.len() && other_keys[..keys.len()] == keys[..]
+                })
+        });
+        if let Some(other) = covered_by {
+            redundant.push((index.name.clone(), other.name.clone()));
+        }
+    }
+    redundant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    fn profile_find(filter: Document, sort: Document, millis: i32) -> Document {
+        doc! {
+            "op": "query",
+            "ns": "shop.orders",
+            "command": { "find": "orders", "filter": filter, "sort": sort },
+            "millis": millis,
+            "docsExamined": 5000,
+            "nreturned": 10,
+            "planSummary": "COLLSCAN",
+        }
+    }
+
+    #[test]
+    fn test_shape_classifies_equality_sort_range() {
+        let entry = profile_find(
+            doc! { "status": "A", "qty": { "$gt": 5 }, "tags": { "$in": ["x"] } },
+            doc! { "created": -1 },
+            30,
+        );
+        let shape = shape_from_profile(&entry).unwrap();
+        assert_eq!(shape.equality, vec!["status", "tags"]);
+        assert_eq!(shape.sort, vec![("created".to_string(), -1)]);
+        assert_eq!(shape.range, vec!["qty"]);
+        assert_eq!(
+            shape.esr_keys(),
+            vec![
+                ("status".to_string(), 1),
+                ("tags".to_string(), 1),
+                ("created".to_string(), -1),
+                ("qty".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shape_from_aggregate_and_update() {
+        let aggregate = doc! {
+            "op": "command",
+            "ns": "shop.orders",
+            "command": {
+                "aggregate": "orders",
+                "pipeline": [
+                    { "$match": { "customer": 7 } },
+                    { "$sort": { "created": 1 } },
+                    { "$group": { "_id": "$status" } },
+                ],
+            },
+        };
+        let shape = shape_from_profile(&aggregate).unwrap();
+        assert_eq!(shape.equality, vec!["customer"]);
+        assert_eq!(shape.sort, vec![("created".to_string(), 1)]);
+
+        let update = doc! {
+            "op": "update",
+            "ns": "shop.orders",
+            "command": { "q": { "sku": "abc" }, "u": { "$set": { "qty": 1 } } },
+        };
+        assert_eq!(shape_from_profile(&update).unwrap().equality, vec!["sku"]);
+
+        let system = doc! {
+            "op": "query",
+            "ns": "shop.system.profile",
+            "command": { "filter": { "a": 1 } },
+        };
+        assert!(shape_from_profile(&system).is_none());
+        let text = profile_find(doc! { "$text": { "$search": "x" } }, doc! {}, 1);
+        assert!(shape_from_profile(&text).is_none());
+    }
+
+    #[test]
+    fn test_index_serves_follows_esr_order() {
+        let shape = shape_from_profile(&profile_find(
+            doc! { "status": "A", "qty": { "$gt": 5 } },
+            doc! { "created": -1 },
+            1,
+        ))
+        .unwrap();
+        let keys = |pairs: &[(&str, i32)]| -> Vec<(String, i32)> {
+            pairs.iter().map(|(f, d)| (f.to_string(), *d)).collect()
+        };
+        assert!(index_serves(
+            &keys(&[("status", 1), ("created", -1), ("qty", 1)]),
+            &shape
+        ));
+        // Walking the whole index backwards still yields the sort order.
+        assert!(index_serves(
+            &keys(&[("status", -1), ("created", 1), ("qty", -1), ("extra", 1)]),
+            &shape
+        ));
+        assert!(!index_serves(
+            &keys(&[("status", 1), ("qty", 1), ("created", -1)]),
+            &shape
+        ));
+        assert!(!index_serves(&keys(&[("status", 1)]), &shape));
+    }
+
+    #[test]
+    fn test_propose_indexes_merges_prefix_shapes_and_skips_indexed() {
+        let entries = vec![
+            profile_find(doc! { "status": "A" }, doc! { "created": -1 }, 40),
+            profile_find(doc! { "status": "B" }, doc! { "created": -1 }, 20),
+            profile_find(doc! { "status": "A" }, doc! {}, 15),
+            profile_find(doc! { "sku": "x" }, doc! {}, 5),
+        ];
+        let shapes = group_profiled_shapes(&entries);
+        assert_eq!(shapes.len(), 3);
+        assert_eq!(shapes[0].count, 2);
+
+        let mut existing = HashMap::new();
+        existing.insert(
+            "shop.orders".to_string(),
+            vec![("sku_1".to_string(), vec![("sku".to_string(), 1)])],
+        );
+        let (proposals, already_indexed) = propose_indexes(&shapes, &existing);
+        assert_eq!(already_indexed.len(), 1);
+        assert_eq!(already_indexed[0]["index"], "sku_1");
+        assert_eq!(proposals.len(), 1, "got: {:?}", proposals);
+        let proposal = &proposals[0];
+        assert_eq!(
+            proposal["keys"],
+            serde_json::json!([["status", 1], ["created", -1]])
+        );
+        assert_eq!(proposal["estimated_affected_queries"], 3);
+        assert_eq!(proposal["total_millis"], 75);
+        assert_eq!(
+            proposal["create_index"],
+            "db.getCollection(\"orders\").createIndex({ \"status\": 1, \"created\": -1 })"
+        );
+    }
+
+    #[test]
+    fn test_redundant_indexes_respect_options() {
+        let info = |name: &str, key: Document, spec: Document| IndexInfo {
+            name: name.to_string(),
+            key,
+            spec,
+        };
+        let indexes = vec![
+            info("_id_", doc! { "_id": 1 }, doc! {}),
+            info("a_1", doc! { "a": 1 }, doc! {}),
+            info("a_1_b_1", doc! { "a": 1, "b": 1 }, doc! {}),
+            info("a_-1", doc! { "a": -1 }, doc! {}),
+            info("b_1", doc! { "b": 1 }, doc! { "unique": true }),
+            info("b_1_c_1", doc! { "b": 1, "c": 1 }, doc! {}),
+            info("c_text", doc! { "_fts": "text", "_ftsx": 1 }, doc! {}),
+        ];
+        assert_eq!(
+            redundant_indexes(&indexes),
+            vec![("a_1".to_string(), "a_1_b_1".to_string())]
+        );
+    }
+}
@@ -0,0 +1,793 @@
+//! Dumps and restores in the mongodump directory layout, so the official
+//! tools can read what DocKit writes and the other way round.
+//!
+//! A dump of database `db` is a `db/` directory holding, per collection,
+//! `<name>.bson` (the documents as concatenated raw BSON) and
+//! `<name>.metadata.json` (collection options, type, uuid and index
+//! definitions as canonical Extended JSON). With gzip every file gets a
+//! `.gz` suffix, as with `mongodump --gzip`. Views only have metadata.
+//!
+//! Documents are streamed as raw bytes in both directions and never
+//! decoded, so BSON types survive unchanged and memory use stays at one
+//! insert batch. Metadata is written and parsed with its key order kept,
+//! because the order of an index's keys is part of its definition.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document, RawDocumentBuf};
+use mongodb::error::ErrorKind;
+use mongodb::options::InsertManyOptions;
+use mongodb::Database;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+
+use crate::common::bson::value_to_bson;
+
+const BSON_SUFFIX: &str = ".bson";
+const METADATA_SUFFIX: &str = ".metadata.json";
+const GZIP_SUFFIX: &str = ".gz";
+const BATCH_DOCUMENTS: usize = 1000;
+const BATCH_BYTES: usize = 16 * 1024 * 1024;
+/// Largest document mongod accepts plus the headroom it allows internally.
+const MAX_DOCUMENT_BYTES: usize = 16 * 1024 * 1024 + 16 * 1024;
+const MAX_REPORTED_ERRORS: usize = 20;
+
+// ---------------------------------------------------------------------------
+// Metadata
+// ---------------------------------------------------------------------------
+
+/// Canonical Extended JSON text for `value`, keeping document key order.
+pub(crate) fn canonical_json(value: &Bson) -> String {
+    match value {
+        Bson::Document(doc) => {
+            let fields: Vec<String> = doc
+                .iter()
+                .map(|(k, v)| format!("{}:{}", Value::from(k.as_str()), canonical_json(v)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Bson::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.clone().into_canonical_extjson().to_string(),
+    }
+}
+
+/// The `.metadata.json` body for a `listCollections` entry and its indexes.
+pub(crate) fn metadata_json(info: &Document, indexes: &[Document]) -> String {
+    let indexes: Vec<Bson> = indexes
+        .iter()
+        .map(|index| {
+            let mut index = index.clone();
+            index.remove("ns");
+            Bson::Document(index)
+        })
+        .collect();
+    let mut metadata = doc! { "indexes": indexes };
+    if let Some(Bson::Binary(uuid)) = info.get_document("info").ok().and_then(|i| i.get("uuid")) {
+        let hex: String = uuid.bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        metadata.insert("uuid", hex);
+    }
+    metadata.insert("collectionName", info.get_str("name").unwrap_or_default());
+    metadata.insert("type", info.get_str("type").unwrap_or("collection"));
+    metadata.insert(
+        "options",
+        info.get_document("options").cloned().unwrap_or_default(),
+    );
+    canonical_json(&Bson::Document(metadata))
+}
+
+/// JSON with object key order kept as written. Document field order matters
+/// (index keys, `$sort` stages), and `Value` only keeps it when serde_json's
+/// `preserve_order` feature happens to be enabled by another crate in the
+/// build, so the order is tracked here instead of relied on.
+enum OrderedJson {
+    Scalar(Value),
+    Array(Vec<OrderedJson>),
+    Object(Vec<(String, OrderedJson)>),
+}
+
+impl OrderedJson {
+    fn into_value(self) -> Value {
+        match self {
+            OrderedJson::Scalar(v) => v,
+            OrderedJson::Array(items) => {
+                Value::Array(items.into_iter().map(Self::into_value).collect())
+            }
+            OrderedJson::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k, v.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn into_bson(self) -> Result<Bson, String> {
+        match self {
+            OrderedJson::Scalar(v) => value_to_bson(&v),
+            OrderedJson::Array(items) => items
+                .into_iter()
+                .map(Self::into_bson)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Bson::Array),
+            // `{"$numberInt": ...}` and friends are single values; their own
+            // key order does not matter.
+            OrderedJson::Object(fields)
+                if fields.first().is_some_and(|(k, _)| k.starts_with('$')) =>
+            {
+                value_to_bson(&OrderedJson::Object(fields).into_value())
+            }
+            OrderedJson::Object(fields) => {
+                let mut doc = Document::new();
+                for (k, v) in fields {
+                    doc.insert(k, v.into_bson()?);
+                }
+                Ok(Bson::Document(doc))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderedJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedVisitor;
+
+        impl<'de> Visitor<'de> for OrderedVisitor {
+            type Value = OrderedJson;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::from(v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::from(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::from(v)))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::from(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::from(v)))
+            }
+
+            fn visit_unit<E>(self) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Scalar(Value::Null))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OrderedJson, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(OrderedJson::Array(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedJson, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    fields.push(entry);
+                }
+                Ok(OrderedJson::Object(fields))
+            }
+        }
+
+        deserializer.deserialize_any(OrderedVisitor)
+    }
+}
+
+/// Parses a `.metadata.json` body, keeping the key order of index keys.
+pub(crate) fn parse_metadata(json: &str) -> Result<Document, String> {
+    let parsed: OrderedJson =
+        serde_json::from_str(json).map_err(|e| format!("Invalid metadata JSON: {}", e))?;
+    match parsed.into_bson()? {
+        Bson::Document(doc) => Ok(doc),
+        _ => Err("Invalid metadata JSON: expected an object".to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Files
+// ---------------------------------------------------------------------------
+
+/// A dump file being written, gzip-compressed or not.
+pub(crate) enum DumpWriter {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl DumpWriter {
+    pub(crate) fn create(path: &Path, gzip: bool) -> Result<Self, String> {
+        let file = BufWriter::new(
+            File::create(path)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?,
+        );
+        Ok(if gzip {
+            DumpWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            DumpWriter::Plain(file)
+        })
+    }
+
+    /// Flushes buffered output and writes the gzip trailer.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        match self {
+            DumpWriter::Plain(mut file) => file.flush(),
+            DumpWriter::Gzip(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for DumpWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            DumpWriter::Plain(file) => file.write(buf),
+            DumpWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            DumpWriter::Plain(file) => file.flush(),
+            DumpWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Opens a dump file for reading, decompressing it when it ends in `.gz`.
+pub(crate) fn open_dump_file(path: &Path) -> Result<Box<dyn Read + Send>, String> {
+    let file = BufReader::new(
+        File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?,
+    );
+    if path.to_string_lossy().ends_with(GZIP_SUFFIX) {
+        Ok(Box::new(GzDecoder::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Reads the next document of a `.bson` file; `None` at a clean end of file.
+pub(crate) fn read_raw_document(reader: &mut impl Read) -> Result<Option<RawDocumentBuf>, String> {
+    let mut len_bytes = [0u8; 4];
+    let mut filled = 0;
+    while filled < len_bytes.len() {
+        match reader.read(&mut len_bytes[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err("BSON file ends inside a document".to_string()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("Failed to read BSON file: {}", e)),
+        }
+    }
+    let len = i32::from_le_bytes(len_bytes);
+    if len < 5 || len as usize > MAX_DOCUMENT_BYTES {
+        return Err(format!("Invalid BSON document length {}", len));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    bytes[..4].copy_from_slice(&len_bytes);
+    reader.read_exact(&mut bytes[4..]).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            "BSON file ends inside a document".to_string()
+        } else {
+            format!("Failed to read BSON file: {}", e)
+        }
+    })?;
+    RawDocumentBuf::from_bytes(bytes)
+        .map(Some)
+        .map_err(|e| format!("Invalid BSON document: {}", e))
+}
+
+/// One collection found in a dump directory.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DumpEntry {
+    pub(crate) name: String,
+    pub(crate) metadata: Option<PathBuf>,
+    pub(crate) data: Option<PathBuf>,
+}
+
+/// The collections in a dump directory, by name.
+pub(crate) fn dump_entries(dir: &Path) -> Result<Vec<DumpEntry>, String> {
+    let listing =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut entries: Vec<DumpEntry> = Vec::new();
+    for item in listing {
+        let path = item
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .path();
+        let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        let plain = file_name.strip_suffix(GZIP_SUFFIX).unwrap_or(&file_name);
+        let (name, is_metadata) = if let Some(name) = plain.strip_suffix(METADATA_SUFFIX) {
+            (name.to_string(), true)
+        } else if let Some(name) = plain.strip_suffix(BSON_SUFFIX) {
+            (name.to_string(), false)
+        } else {
+            continue;
+        };
+        let index = match entries.iter().position(|e| e.name == name) {
+            Some(index) => index,
+            None => {
+                entries.push(DumpEntry {
+                    name,
+                    metadata: None,
+                    data: None,
+                });
+                entries.len() - 1
+            }
+        };
+        if is_metadata {
+            entries[index].metadata = Some(path);
+        } else {
+            entries[index].data = Some(path);
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Dump
+// ---------------------------------------------------------------------------
+
+async fn cursor_documents(db: &Database, command: Document) -> Result<Vec<Document>, String> {
+    db.run_cursor_command(command)
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn dump_path(dir: &Path, name: &str, suffix: &str, gzip: bool) -> PathBuf {
+    let gz = if gzip { GZIP_SUFFIX } else { "" };
+    dir.join(format!("{}{}{}", name, suffix, gz))
+}
+
+/// Dumps `db` (or only `collection`) into `out_dir/<db>/`, returning one
+/// summary per collection.
+pub(crate) async fn dump_database(
+    db: &Database,
+    out_dir: &Path,
+    collection: Option<&str>,
+    gzip: bool,
+) -> Result<Vec<Value>, String> {
+    let filter = match collection {
+        Some(name) => doc! { "name": name },
+        None => doc! {},
+    };
+    let infos = cursor_documents(db, doc! { "listCollections": 1, "filter": filter })
+        .await
+        .map_err(|e| format!("listCollections failed: {}", e))?;
+    if let (Some(name), true) = (collection, infos.is_empty()) {
+        return Err(format!("Collection '{}' not found", name));
+    }
+    let dir = out_dir.join(db.name());
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut summaries = Vec::new();
+    for info in infos {
+        let name = info.get_str("name").unwrap_or_default().to_string();
+        if name.starts_with("system.") {
+            continue;
+        }
+        let is_view = info.get_str("type").ok() == Some("view");
+        let indexes = if is_view {
+            Vec::new()
+        } else {
+            cursor_documents(db, doc! { "listIndexes": &name })
+                .await
+                .map_err(|e| format!("listIndexes on '{}' failed: {}", name, e))?
+        };
+
+        let metadata_path = dump_path(&dir, &name, METADATA_SUFFIX, gzip);
+        let mut metadata = DumpWriter::create(&metadata_path, gzip)?;
+        metadata
+            .write_all(metadata_json(&info, &indexes).as_bytes())
+            .and_then(|_| metadata.finish())
+            .map_err(|e| format!("Failed to write {}: {}", metadata_path.display(), e))?;
+
+        let mut documents = 0u64;
+        if !is_view {
+            let data_path = dump_path(&dir, &name, BSON_SUFFIX, gzip);
+            let mut data = DumpWriter::create(&data_path, gzip)?;
+            let mut cursor = db
+                .collection::<RawDocumentBuf>(&name)
+                .find(doc! {})
+                .await
+                .map_err(|e| format!("Failed to read '{}': {}", name, e))?;
+            while let Some(raw) = cursor
+                .try_next()
+                .await
+                .map_err(|e| format!("Failed to read '{}': {}", name, e))?
+            {
+                data.write_all(raw.as_bytes())
+                    .map_err(|e| format!("Failed to write {}: {}", data_path.display(), e))?;
+                documents += 1;
+            }
+            data.finish()
+                .map_err(|e| format!("Failed to write {}: {}", data_path.display(), e))?;
+        }
+
+        summaries.push(serde_json::json!({
+            "collection": name,
+            "type": info.get_str("type").unwrap_or("collection"),
+            "documents": documents,
+            "indexes": indexes.len(),
+        }));
+    }
+    Ok(summaries)
+}
+
+// ---------------------------------------------------------------------------
+// Restore
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RestoreOptions {
+    /// Drop each collection before restoring it, like `mongorestore --drop`.
+    pub(crate) drop: bool,
+    pub(crate) bypass_document_validation: bool,
+}
+
+/// The `create` command for a collection or view as described by its
+/// metadata.
+pub(crate) fn create_command(name: &str, metadata: &Document) -> Document {
+    let mut command = doc! { "create": name };
+    if let Ok(options) = metadata.get_document("options") {
+        command.extend(options.clone());
+    }
+    command
+}
+
+/// Index specs from metadata to pass to `createIndexes`; the `_id` index is
+/// created with the collection.
+pub(crate) fn index_specs(metadata: &Document) -> Vec<Document> {
+    metadata
+        .get_array("indexes")
+        .map(|indexes| {
+            indexes
+                .iter()
+                .filter_map(Bson::as_document)
+                .filter(|index| index.get_str("name").ok() != Some("_id_"))
+                .map(|index| {
+                    let mut index = index.clone();
+                    index.remove("ns");
+                    index
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_metadata(path: &Path) -> Result<Document, String> {
+    let mut json = String::new();
+    open_dump_file(path)?
+        .read_to_string(&mut json)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_metadata(&json).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Inserts one batch, counting documents rejected by the server (for
+/// example duplicate keys) instead of failing the restore on them.
+async fn insert_batch(
+    coll: &mongodb::Collection<RawDocumentBuf>,
+    batch: &mut Vec<RawDocumentBuf>,
+    options: &RestoreOptions,
+    errors: &mut Vec<String>,
+) -> Result<(u64, u64), String> {
+    let opts = InsertManyOptions::builder()
+        .ordered(false)
+        .bypass_document_validation(options.bypass_document_validation)
+        .build();
+    let total = batch.len() as u64;
+    let result = coll.insert_many(batch.drain(..)).with_options(opts).await;
+    match result {
+        Ok(_) => Ok((total, 0)),
+        Err(e) => match *e.kind {
+            ErrorKind::InsertMany(ref failure) if failure.write_concern_error.is_none() => {
+                let write_errors = failure.write_errors.as_deref().unwrap_or_default();
+                for error in write_errors {
+                    if errors.len() < MAX_REPORTED_ERRORS {
+                        errors.push(error.message.clone());
+                    }
+                }
+                let failed = write_errors.len() as u64;
+                Ok((total - failed, failed))
+            }
+            _ => Err(format!("Insert failed: {}", e)),
+        },
+    }
+}
+
+/// Restores one dumped collection into `db`. Collections that already exist
+/// keep their options; documents are inserted into them alongside the
+/// existing ones.
+pub(crate) async fn restore_collection(
+    db: &Database,
+    entry: &DumpEntry,
+    existing: &[String],
+    options: &RestoreOptions,
+) -> Result<Value, String> {
+    let name = entry.name.as_str();
+    let metadata = entry.metadata.as_deref().map(read_metadata).transpose()?;
+    let collection_type = metadata
+        .as_ref()
+        .and_then(|m| m.get_str("type").ok())
+        .unwrap_or("collection")
+        .to_string();
+
+    if options.drop {
+        db.collection::<Document>(name)
+            .drop()
+            .await
+            .map_err(|e| format!("Failed to drop '{}': {}", name, e))?;
+    }
+    let exists = !options.drop && existing.iter().any(|c| c == name);
+    let mut created = false;
+    if let (Some(metadata), false) = (&metadata, exists) {
+        db.run_command(create_command(name, metadata))
+            .await
+            .map_err(|e| format!("Failed to create '{}': {}", name, e))?;
+        created = true;
+    }
+
+    let mut errors = Vec::new();
+    let (mut restored, mut failed) = (0u64, 0u64);
+    if let Some(data_path) = &entry.data {
+        let coll = db.collection::<RawDocumentBuf>(name);
+        let mut reader = open_dump_file(data_path)?;
+        let mut batch = Vec::new();
+        let mut batch_bytes = 0usize;
+        loop {
+            let next = read_raw_document(&mut reader)
+                .map_err(|e| format!("{}: {}", data_path.display(), e))?;
+            let done = next.is_none();
+            if let Some(raw) = next {
+                batch_bytes += raw.as_bytes().len();
+                batch.push(raw);
+            }
+            let full = batch.len() >= BATCH_DOCUMENTS || batch_bytes >= BATCH_BYTES;
+            if !batch.is_empty() && (full || done) {
+                let (ok, bad) = insert_batch(&coll, &mut batch, options, &mut errors).await?;
+                restored += ok;
+                failed += bad;
+                batch_bytes = 0;
+            }
+            if done {
+                break;
+            }
+        }
+    }
+
+    let indexes = metadata.as_ref().map(index_specs).unwrap_or_default();
+    let index_count = indexes.len();
+    if !indexes.is_empty() {
+        if let Err(e) = db
+            .run_command(doc! { "createIndexes": name, "indexes": indexes })
+            .await
+        {
+            errors.push(format!("createIndexes failed: {}", e));
+        }
+    }
+
+    Ok(serde_json::json!({
+        "collection": name,
+        "type": collection_type,
+        "created": created,
+        "documents_restored": restored,
+        "documents_failed": failed,
+        "indexes": index_count,
+        "errors": errors,
+    }))
+}
+
+/// Restores every collection of a dump directory (or only `collection`)
+/// into `db`. Views are restored last, after the collections they read.
+pub(crate) async fn restore_database(
+    db: &Database,
+    dir: &Path,
+    collection: Option<&str>,
+    options: &RestoreOptions,
+) -> Result<Vec<Value>, String> {
+    let entries: Vec<DumpEntry> = dump_entries(dir)?
+        .into_iter()
+        .filter(|e| collection.is_none_or(|name| e.name == name))
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("No dump files found in {}", dir.display()));
+    }
+    let existing = db
+        .list_collection_names()
+        .await
+        .map_err(|e| format!("Failed to list collections: {}", e))?;
+
+    let mut views = Vec::new();
+    let mut summaries = Vec::new();
+    for entry in &entries {
+        // A view has metadata only.
+        if entry.data.is_none() && entry.metadata.is_some() {
+            views.push(entry);
+            continue;
+        }
+        summaries.push(restore_collection(db, entry, &existing, options).await?);
+    }
+    for entry in views {
+        summaries.push(restore_collection(db, entry, &existing, options).await?);
+    }
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{oid::ObjectId, spec::BinarySubtype, Binary};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mongo-dump-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_canonical_json_keeps_key_order() {
+        let value = Bson::Document(doc! { "z": 1, "a": -1_i64, "m": [1.5, "x"] });
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"z":{"$numberInt":"1"},"a":{"$numberLong":"-1"},"m":[{"$numberDouble":"1.5"},"x"]}"#
+        );
+    }
+
+    #[test]
+    fn test_metadata_round_trip_keeps_index_key_order() {
+        let info = doc! {
+            "name": "orders",
+            "type": "collection",
+            "options": { "validator": { "$jsonSchema": { "required": ["status"] } } },
+            "info": {
+                "uuid": Binary { subtype: BinarySubtype::Uuid, bytes: vec![0xab; 16] },
+            },
+        };
+        let indexes = vec![
+            doc! { "v": 2, "key": { "_id": 1 }, "name": "_id_" },
+            doc! {
+                "v": 2,
+                "key": { "status": 1, "createdAt": -1, "amount": 1 },
+                "name": "status_1_createdAt_-1_amount_1",
+                "ns": "app.orders",
+            },
+        ];
+        let json = metadata_json(&info, &indexes);
+        assert!(json.contains(&format!(r#""uuid":"{}""#, "ab".repeat(16))));
+
+        let metadata = parse_metadata(&json).unwrap();
+        assert_eq!(metadata.get_str("collectionName").unwrap(), "orders");
+        assert_eq!(metadata.get_str("type").unwrap(), "collection");
+        assert!(metadata
+            .get_document("options")
+            .unwrap()
+            .get_document("validator")
+            .is_ok());
+
+        let specs = index_specs(&metadata);
+        assert_eq!(specs.len(), 1);
+        let keys: Vec<&String> = specs[0].get_document("key").unwrap().keys().collect();
+        assert_eq!(keys, vec!["status", "createdAt", "amount"]);
+        assert_eq!(
+            specs[0]
+                .get_document("key")
+                .unwrap()
+                .get_i32("createdAt")
+                .ok(),
+            Some(-1)
+        );
+        assert!(!specs[0].contains_key("ns"));
+    }
+
+    #[test]
+    fn test_parse_metadata_accepts_relaxed_json() {
+        let metadata = parse_metadata(
+            r#"{"indexes":[{"v":2,"key":{"b":1,"a":1},"name":"b_1_a_1"}],"options":{}}"#,
+        )
+        .unwrap();
+        let specs = index_specs(&metadata);
+        let keys: Vec<&String> = specs[0].get_document("key").unwrap().keys().collect();
+        assert_eq!(keys, vec!["b", "a"]);
+        assert!(parse_metadata("[1]").is_err());
+    }
+
+    #[test]
+    fn test_create_command_splices_options() {
+        let metadata = doc! {
+            "type": "view",
+            "options": { "viewOn": "orders", "pipeline": [{ "$match": { "paid": true } }] },
+        };
+        let command = create_command("paid_orders", &metadata);
+        assert_eq!(command.keys().next().unwrap(), "create");
+        assert_eq!(command.get_str("create").unwrap(), "paid_orders");
+        assert_eq!(command.get_str("viewOn").unwrap(), "orders");
+        assert_eq!(
+            create_command("plain", &doc! {}),
+            doc! { "create": "plain" }
+        );
+    }
+
+    #[test]
+    fn test_bson_file_round_trip() {
+        let dir = temp_dir("round-trip");
+        let docs = vec![
+            doc! { "_id": ObjectId::new(), "n": 1_i64, "tags": ["a", "b"] },
+            doc! { "_id": 2, "price": 9.5, "nested": { "ok": true } },
+        ];
+        for gzip in [false, true] {
+            let path = dump_path(&dir, "orders", BSON_SUFFIX, gzip);
+            let mut writer = DumpWriter::create(&path, gzip).unwrap();
+            for doc in &docs {
+                let raw = RawDocumentBuf::from_document(doc).unwrap();
+                writer.write_all(raw.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let mut reader = open_dump_file(&path).unwrap();
+            let mut read_back = Vec::new();
+            while let Some(raw) = read_raw_document(&mut reader).unwrap() {
+                read_back.push(raw.to_document().unwrap());
+            }
+            assert_eq!(read_back, docs, "gzip: {}", gzip);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_raw_document_rejects_truncated_input() {
+        let raw = RawDocumentBuf::from_document(&doc! { "a": 1 }).unwrap();
+        let bytes = raw.as_bytes();
+        let mut truncated = &bytes[..bytes.len() - 2];
+        assert!(read_raw_document(&mut truncated)
+            .unwrap_err()
+            .contains("ends inside a document"));
+        let mut bad_length: &[u8] = &[1, 0, 0, 0, 0];
+        assert!(read_raw_document(&mut bad_length).is_err());
+        let mut empty: &[u8] = &[];
+        assert_eq!(read_raw_document(&mut empty).unwrap(), None);
+    }
+
+    #[test]
+    fn test_dump_entries_pairs_files_by_collection() {
+        let dir = temp_dir("entries");
+        for file in [
+            "orders.bson.gz",
+            "orders.metadata.json.gz",
+            "paid_orders.metadata.json",
+            "users.bson",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        let entries = dump_entries(&dir).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["orders", "paid_orders", "users"]);
+        assert!(entries[0].data.is_some() && entries[0].metadata.is_some());
+        assert!(entries[1].data.is_none() && entries[1].metadata.is_some());
+        assert!(entries[2].data.is_some() && entries[2].metadata.is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
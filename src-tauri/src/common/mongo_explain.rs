@@ -0,0 +1,431 @@
+//! Summaries of MongoDB `explain` output.
+//!
+//! The raw plan differs between find, aggregate and count, between the
+//! classic and slot-based engines, and between standalone and sharded
+//! deployments. [`summarize_explain`] reduces all of them to the handful of
+//! facts a DBA looks at first: the winning stage tree, whether anything scans
+//! the whole collection, which indexes were used, and how many documents and
+//! keys were examined for the documents returned.
+
+use mongodb::bson::{Bson, Document};
+use serde_json::{Map, Value};
+
+pub(crate) const EXPLAIN_VERBOSITIES: [&str; 3] =
+    ["queryPlanner", "executionStats", "allPlansExecution"];
+
+/// Examined/returned ratio above which a plan is flagged as unselective.
+const UNSELECTIVE_RATIO: f64 = 10.0;
+
+pub(crate) fn validate_verbosity(verbosity: &str) -> Result<&str, String> {
+    EXPLAIN_VERBOSITIES
+        .iter()
+        .find(|v| **v == verbosity)
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "Unknown explain verbosity '{}': expected {}",
+                verbosity,
+                EXPLAIN_VERBOSITIES.join(", ")
+            )
+        })
+}
+
+/// Wraps a find, aggregate or count command for `runCommand`.
+pub(crate) fn explain_command(command: Document, verbosity: &str) -> Document {
+    mongodb::bson::doc! { "explain": command, "verbosity": verbosity }
+}
+
+/// One query plan inside an explain result, with the shard it came from.
+struct PlanSource<'a> {
+    shard: Option<String>,
+    query_planner: &'a Document,
+    execution_stats: Option<&'a Document>,
+}
+
+fn plan_sources<'a>(explain: &'a Document, shard: Option<String>, out: &mut Vec<PlanSource<'a>>) {
+    if let Ok(query_planner) = explain.get_document("queryPlanner") {
+        out.push(PlanSource {
+            shard,
+            query_planner,
+            execution_stats: explain.get_document("executionStats").ok(),
+        });
+        return;
+    }
+    // Aggregations that are not fully pushed down wrap the query in `$cursor`.
+    if let Ok(stages) = explain.get_array("stages") {
+        if let Some(cursor) = stages
+            .first()
+            .and_then(Bson::as_document)
+            .and_then(|stage| stage.get_document("$cursor").ok())
+        {
+            plan_sources(cursor, shard, out);
+            return;
+        }
+    }
+    // Sharded aggregations report one explain per shard.
+    if let Ok(shards) = explain.get_document("shards") {
+        for (name, shard_explain) in shards {
+            if let Some(shard_explain) = shard_explain.as_document() {
+                plan_sources(shard_explain, Some(name.clone()), out);
+            }
+        }
+    }
+}
+
+fn number(doc: &Document, key: &str) -> Option<i64> {
+    match doc.get(key)? {
+        Bson::Int32(n) => Some(i64::from(*n)),
+        Bson::Int64(n) => Some(*n),
+        Bson::Double(n) => Some(*n as i64),
+        _ => None,
+    }
+}
+
+/// Compact stage tree: the stage name, its index details, and its children.
+pub(crate) fn stage_tree(plan: &Document) -> Value {
+    // The slot-based engine nests the classic-looking tree under `queryPlan`.
+    if !plan.contains_key("stage") {
+        if let Ok(query_plan) = plan.get_document("queryPlan") {
+            return stage_tree(query_plan);
+        }
+    }
+    let mut node = Map::new();
+    node.insert(
+        "stage".to_string(),
+        Value::from(plan.get_str("stage").unwrap_or("UNKNOWN")),
+    );
+    let stage = plan.get_str("stage").unwrap_or_default();
+    if stage == "IDHACK" && !plan.contains_key("indexName") {
+        node.insert("indexName".to_string(), Value::from("_id_"));
+    }
+    for key in [
+        "indexName",
+        "keyPattern",
+        "direction",
+        "isMultiKey",
+        "filter",
+    ] {
+        if let Some(value) = plan.get(key) {
+            node.insert(key.to_string(), crate::common::bson::bson_to_value(value));
+        }
+    }
+
+    let mut children: Vec<Value> = Vec::new();
+    if let Ok(input) = plan.get_document("inputStage") {
+        children.push(stage_tree(input));
+    }
+    if let Ok(inputs) = plan.get_array("inputStages") {
+        children.extend(inputs.iter().filter_map(Bson::as_document).map(stage_tree));
+    }
+    if let Ok(shards) = plan.get_array("shards") {
+        for shard in shards.iter().filter_map(Bson::as_document) {
+            let plan = shard
+                .get_document("winningPlan")
+                .or_else(|_| shard.get_document("executionStages"));
+            if let Ok(plan) = plan {
+                let mut child = stage_tree(plan);
+                if let (Some(obj), Ok(name)) = (child.as_object_mut(), shard.get_str("shardName")) {
+                    obj.insert("shard".to_string(), Value::from(name));
+                }
+                children.push(child);
+            }
+        }
+    }
+    if !children.is_empty() {
+        node.insert("children".to_string(), Value::Array(children));
+    }
+    Value::Object(node)
+}
+
+/// One-line rendering such as `FETCH(IXSCAN a_1)` or `SORT(COLLSCAN)`.
+pub(crate) fn describe_tree(tree: &Value) -> String {
+    let stage = tree["stage"].as_str().unwrap_or("UNKNOWN");
+    let mut label = match tree["indexName"].as_str() {
+        Some(index) => format!("{} {}", stage, index),
+        None => stage.to_string(),
+    };
+    if let Some(shard) = tree["shard"].as_str() {
+        label = format!("{}: {}", shard, label);
+    }
+    match tree["children"].as_array() {
+        Some(children) if !children.is_empty() => format!(
+            "{}({})",
+            label,
+            children
+                .iter()
+                .map(describe_tree)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => label,
+    }
+}
+
+fn collect_stages(tree: &Value, stages: &mut Vec<String>, indexes: &mut Vec<String>) {
+    if let Some(stage) = tree["stage"].as_str() {
+        stages.push(stage.to_string());
+    }
+    if let Some(index) = tree["indexName"].as_str() {
+        if !indexes.iter().any(|i| i == index) {
+            indexes.push(index.to_string());
+        }
+    }
+    for child in tree["children"].as_array().into_iter().flatten() {
+        collect_stages(child, stages, indexes);
+    }
+}
+
+/// Names of the aggregation stages that run after the query layer.
+fn pipeline_stages(explain: &Document) -> Vec<String> {
+    explain
+        .get_array("stages")
+        .map(|stages| {
+            stages
+                .iter()
+                .filter_map(Bson::as_document)
+                .filter_map(|stage| stage.keys().find(|k| k.starts_with('$')).cloned())
+                .filter(|name| name != "$cursor")
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reduces a raw explain result to the winning plan and its key metrics.
+pub(crate) fn summarize_explain(explain: &Document) -> Value {
+    let mut sources = Vec::new();
+    plan_sources(explain, None, &mut sources);
+    if sources.is_empty() {
+        return serde_json::json!({ "note": "explain output contained no query plan" });
+    }
+
+    let mut trees: Vec<Value> = Vec::new();
+    let mut stages: Vec<String> = Vec::new();
+    let mut indexes: Vec<String> = Vec::new();
+    let mut rejected_plans = 0usize;
+    let mut totals: Option<(i64, i64, i64, i64)> = None;
+    for source in &sources {
+        if let Ok(plan) = source.query_planner.get_document("winningPlan") {
+            let mut tree = stage_tree(plan);
+            if let (Some(obj), Some(shard)) = (tree.as_object_mut(), &source.shard) {
+                obj.insert("shard".to_string(), Value::from(shard.clone()));
+            }
+            collect_stages(&tree, &mut stages, &mut indexes);
+            trees.push(tree);
+        }
+        rejected_plans += source
+            .query_planner
+            .get_array("rejectedPlans")
+            .map(|plans| plans.len())
+            .unwrap_or(0);
+        if let Some(stats) = source.execution_stats {
+            let (returned, docs, keys, millis) = totals.unwrap_or_default();
+            totals = Some((
+                returned + number(stats, "nReturned").unwrap_or(0),
+                docs + number(stats, "totalDocsExamined").unwrap_or(0),
+                keys + number(stats, "totalKeysExamined").unwrap_or(0),
+                millis.max(number(stats, "executionTimeMillis").unwrap_or(0)),
+            ));
+        }
+    }
+
+    let collection_scan = stages.iter().any(|s| s == "COLLSCAN");
+    let in_memory_sort = stages.iter().any(|s| s == "SORT");
+    let covered = !indexes.is_empty()
+        && !collection_scan
+        && !stages.iter().any(|s| s == "FETCH" || s == "IDHACK");
+
+    let mut warnings: Vec<String> = Vec::new();
+    if collection_scan {
+        warnings.push(
+            "COLLSCAN: the query reads every document; no index supports its filter".to_string(),
+        );
+    }
+    if in_memory_sort {
+        warnings.push(
+            "In-memory SORT: an index whose keys match the sort would avoid the blocking sort"
+                .to_string(),
+        );
+    }
+
+    let mut summary = Map::new();
+    summary.insert(
+        "winning_plan".to_string(),
+        Value::from(
+            trees
+                .iter()
+                .map(describe_tree)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ),
+    );
+    summary.insert(
+        "stage_tree".to_string(),
+        if trees.len() == 1 {
+            trees.remove(0)
+        } else {
+            Value::Array(trees)
+        },
+    );
+    summary.insert("collection_scan".to_string(), Value::from(collection_scan));
+    summary.insert("indexes_used".to_string(), serde_json::json!(indexes));
+    summary.insert("covered".to_string(), Value::from(covered));
+    summary.insert("in_memory_sort".to_string(), Value::from(in_memory_sort));
+    summary.insert("rejected_plans".to_string(), Value::from(rejected_plans));
+    if let Some((returned, docs, keys, millis)) = totals {
+        summary.insert("n_returned".to_string(), Value::from(returned));
+        summary.insert("docs_examined".to_string(), Value::from(docs));
+        summary.insert("keys_examined".to_string(), Value::from(keys));
+        summary.insert("execution_time_ms".to_string(), Value::from(millis));
+        let ratio = docs.max(keys) as f64 / returned.max(1) as f64;
+        summary.insert(
+            "examined_per_returned".to_string(),
+            Value::from((ratio * 100.0).round() / 100.0),
+        );
+        if ratio >= UNSELECTIVE_RATIO && docs.max(keys) > 100 {
+            warnings.push(format!(
+                "Unselective: examined {} documents and {} keys to return {}",
+                docs, keys, returned
+            ));
+        }
+    }
+    let pipeline = pipeline_stages(explain);
+    if !pipeline.is_empty() {
+        summary.insert("pipeline_stages".to_string(), serde_json::json!(pipeline));
+    }
+    summary.insert("warnings".to_string(), serde_json::json!(warnings));
+    Value::Object(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn test_summarize_collscan_with_execution_stats() {
+        let explain = doc! {
+            "queryPlanner": {
+                "winningPlan": {
+                    "stage": "SORT",
+                    "inputStage": { "stage": "COLLSCAN", "filter": { "status": { "$eq": "A" } } },
+                },
+                "rejectedPlans": [],
+            },
+            "executionStats": {
+                "nReturned": 5,
+                "totalDocsExamined": 10_000,
+                "totalKeysExamined": 0,
+                "executionTimeMillis": 42,
+            },
+        };
+        let summary = summarize_explain(&explain);
+        assert_eq!(summary["winning_plan"], "SORT(COLLSCAN)");
+        assert_eq!(summary["collection_scan"], true);
+        assert_eq!(summary["in_memory_sort"], true);
+        assert_eq!(summary["docs_examined"], 10_000);
+        assert_eq!(summary["n_returned"], 5);
+        assert_eq!(summary["examined_per_returned"], 2000.0);
+        let warnings = summary["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 3, "got: {:?}", warnings);
+    }
+
+    #[test]
+    fn test_summarize_index_scan_and_covered_query() {
+        let explain = doc! {
+            "queryPlanner": {
+                "winningPlan": {
+                    "stage": "PROJECTION_COVERED",
+                    "inputStage": {
+                        "stage": "IXSCAN",
+                        "indexName": "status_1_qty_1",
+                        "keyPattern": { "status": 1, "qty": 1 },
+                        "direction": "forward",
+                    },
+                },
+                "rejectedPlans": [{ "stage": "COLLSCAN" }],
+            },
+        };
+        let summary = summarize_explain(&explain);
+        assert_eq!(
+            summary["winning_plan"],
+            "PROJECTION_COVERED(IXSCAN status_1_qty_1)"
+        );
+        assert_eq!(
+            summary["indexes_used"],
+            serde_json::json!(["status_1_qty_1"])
+        );
+        assert_eq!(summary["covered"], true);
+        assert_eq!(summary["collection_scan"], false);
+        assert_eq!(summary["rejected_plans"], 1);
+        assert!(summary.get("docs_examined").is_none());
+    }
+
+    #[test]
+    fn test_summarize_aggregate_cursor_and_sbe_query_plan() {
+        let explain = doc! {
+            "stages": [
+                {
+                    "$cursor": {
+                        "queryPlanner": {
+                            "winningPlan": {
+                                "queryPlan": {
+                                    "stage": "FETCH",
+                                    "inputStage": { "stage": "IXSCAN", "indexName": "a_1" },
+                                },
+                                "slotBasedPlan": { "stages": "..." },
+                            },
+                        },
+                        "executionStats": {
+                            "nReturned": 3_i64,
+                            "totalDocsExamined": 3_i64,
+                            "totalKeysExamined": 3_i64,
+                        },
+                    },
+                },
+                { "$group": { "_id": "$a" } },
+                { "$sort": { "_id": 1 } },
+            ],
+        };
+        let summary = summarize_explain(&explain);
+        assert_eq!(summary["winning_plan"], "FETCH(IXSCAN a_1)");
+        assert_eq!(summary["covered"], false);
+        assert_eq!(
+            summary["pipeline_stages"],
+            serde_json::json!(["$group", "$sort"])
+        );
+        assert_eq!(summary["warnings"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_summarize_sharded_find() {
+        let explain = doc! {
+            "queryPlanner": {
+                "winningPlan": {
+                    "stage": "SHARD_MERGE",
+                    "shards": [
+                        { "shardName": "rs0", "winningPlan": { "stage": "IDHACK" } },
+                        { "shardName": "rs1", "winningPlan": { "stage": "COLLSCAN" } },
+                    ],
+                },
+            },
+        };
+        let summary = summarize_explain(&explain);
+        assert_eq!(
+            summary["winning_plan"],
+            "SHARD_MERGE(rs0: IDHACK _id_, rs1: COLLSCAN)"
+        );
+        assert_eq!(summary["collection_scan"], true);
+        assert_eq!(summary["indexes_used"], serde_json::json!(["_id_"]));
+    }
+
+    #[test]
+    fn test_validate_verbosity() {
+        assert_eq!(
+            validate_verbosity("executionStats").unwrap(),
+            "executionStats"
+        );
+        assert!(validate_verbosity("verbose")
+            .unwrap_err()
+            .contains("queryPlanner"));
+    }
+}
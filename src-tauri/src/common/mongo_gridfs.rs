@@ -0,0 +1,325 @@
+//! GridFS helpers shared by the agent capabilities and the file browser
+//! commands.
+//!
+//! A bucket is a `<name>.files` / `<name>.chunks` collection pair. Listing
+//! reads the files collection directly so callers can filter and page on any
+//! field; transfers go through `GridFsBucket` and are streamed in
+//! `TRANSFER_BUFFER_BYTES` pieces, so a file is never held in memory whole.
+
+use std::path::Path;
+
+use futures::{AsyncReadExt, AsyncWriteExt, TryStreamExt};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::gridfs::GridFsBucket;
+use mongodb::options::{FindOptions, GridFsBucketOptions};
+use mongodb::Database;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+use crate::common::bson::{bson_to_value, doc_to_value, value_to_bson};
+
+pub(crate) const DEFAULT_BUCKET: &str = "fs";
+const TRANSFER_BUFFER_BYTES: usize = 256 * 1024;
+
+/// Bucket names among `collections`: every `<name>.files` that has a
+/// matching `<name>.chunks`, sorted.
+pub(crate) fn bucket_names(collections: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = collections
+        .iter()
+        .filter_map(|c| c.strip_suffix(".files"))
+        .filter(|name| collections.iter().any(|c| *c == format!("{}.chunks", name)))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+fn as_i64(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(n) => Some(i64::from(*n)),
+        Bson::Int64(n) => Some(*n),
+        Bson::Double(n) => Some(*n as i64),
+        _ => None,
+    }
+}
+
+/// A file id given as JSON: a 24-character hex string is an ObjectId (the
+/// driver's default id), any other value is read as Extended JSON.
+pub(crate) fn file_id_from_json(id: &Value) -> Result<Bson, String> {
+    match id {
+        Value::Null => Err("Missing file_id".to_string()),
+        Value::String(s) => Ok(ObjectId::parse_str(s)
+            .map(Bson::ObjectId)
+            .unwrap_or_else(|_| Bson::String(s.clone()))),
+        other => value_to_bson(other),
+    }
+}
+
+/// A files collection entry in the shape the file browser shows.
+pub(crate) fn file_summary(file: &Document) -> Value {
+    serde_json::json!({
+        "id": file.get("_id").map(bson_to_value).unwrap_or(Value::Null),
+        "filename": file.get_str("filename").ok(),
+        "length": file.get("length").and_then(as_i64),
+        "chunk_size": file.get("chunkSize").and_then(Bson::as_i32),
+        "upload_date": file
+            .get_datetime("uploadDate")
+            .ok()
+            .and_then(|d| d.try_to_rfc3339_string().ok()),
+        // Written by older drivers; kept for files that still carry it.
+        "content_type": file.get_str("contentType").ok(),
+        "metadata": file.get_document("metadata").map(doc_to_value).ok(),
+    })
+}
+
+pub(crate) fn bucket(db: &Database, name: &str) -> GridFsBucket {
+    db.gridfs_bucket(
+        GridFsBucketOptions::builder()
+            .bucket_name(name.to_string())
+            .build(),
+    )
+}
+
+/// Buckets in `db` with their file counts and total stored bytes.
+pub(crate) async fn list_buckets(db: &Database) -> Result<Vec<Value>, String> {
+    let collections = db
+        .list_collection_names()
+        .await
+        .map_err(|e| format!("Failed to list collections: {}", e))?;
+    let mut buckets = Vec::new();
+    for name in bucket_names(&collections) {
+        let files = db.collection::<Document>(&format!("{}.files", name));
+        let totals = files
+            .aggregate(vec![doc! {
+                "$group": { "_id": null, "count": { "$sum": 1 }, "bytes": { "$sum": "$length" } }
+            }])
+            .await
+            .map_err(|e| format!("Failed to read bucket '{}': {}", name, e))?
+            .try_next()
+            .await
+            .map_err(|e| format!("Failed to read bucket '{}': {}", name, e))?
+            .unwrap_or_default();
+        buckets.push(serde_json::json!({
+            "name": name,
+            "file_count": totals.get("count").and_then(as_i64).unwrap_or(0),
+            "total_bytes": totals.get("bytes").and_then(as_i64).unwrap_or(0),
+        }));
+    }
+    Ok(buckets)
+}
+
+/// One page of a bucket's files, newest first unless `sort` says otherwise.
+pub(crate) async fn list_files(
+    db: &Database,
+    bucket_name: &str,
+    filter: Document,
+    sort: Option<Document>,
+    skip: u64,
+    limit: i64,
+) -> Result<Value, String> {
+    let files = db.collection::<Document>(&format!("{}.files", bucket_name));
+    let total = files
+        .count_documents(filter.clone())
+        .await
+        .map_err(|e| format!("Failed to count files: {}", e))?;
+    let mut opts = FindOptions::default();
+    opts.sort = Some(sort.unwrap_or_else(|| doc! { "uploadDate": -1 }));
+    opts.skip = Some(skip);
+    opts.limit = Some(limit);
+    let page: Vec<Document> = files
+        .find(filter)
+        .with_options(opts)
+        .await
+        .map_err(|e| format!("Failed to list files: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Failed to list files: {}", e))?;
+    Ok(serde_json::json!({
+        "bucket": bucket_name,
+        "total": total,
+        "files": page.iter().map(file_summary).collect::<Vec<_>>(),
+        "has_more": skip + (page.len() as u64) < total,
+    }))
+}
+
+/// Hidden sibling of `path` that a download is written to before it is
+/// renamed into place.
+fn partial_download_path(path: &Path) -> Result<std::path::PathBuf, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Not a file path: {}", path.display()))?;
+    let mut partial = std::ffi::OsString::from(".");
+    partial.push(name);
+    partial.push(".part");
+    Ok(path.with_file_name(partial))
+}
+
+/// Streams the file `id` into `path`, returning the number of bytes written.
+/// The bytes go to a sibling file that replaces `path` only once the whole
+/// file has arrived, so a failed download leaves an existing file untouched.
+pub(crate) async fn download_to_path(
+    bucket: &GridFsBucket,
+    id: Bson,
+    path: &Path,
+) -> Result<u64, String> {
+    let mut source = bucket
+        .open_download_stream(id)
+        .await
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let partial = partial_download_path(path)?;
+    let mut target = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+
+    let copy = async {
+        let mut buf = vec![0u8; TRANSFER_BUFFER_BYTES];
+        let mut written = 0u64;
+        loop {
+            let n = source
+                .read(&mut buf)
+                .await
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            target
+                .write_all(&buf[..n])
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+            written += n as u64;
+        }
+        target
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        target
+            .sync_all()
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        drop(target);
+        tokio::fs::rename(&partial, path)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok::<u64, String>(written)
+    };
+    let result = copy.await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&partial).await;
+    }
+    result
+}
+
+/// Streams the local file at `path` into the bucket as `filename`, returning
+/// the new file's id and length. The upload is aborted on failure, so no
+/// orphaned chunks are left behind.
+pub(crate) async fn upload_from_path(
+    bucket: &GridFsBucket,
+    path: &Path,
+    filename: &str,
+    metadata: Option<Document>,
+) -> Result<(Bson, u64), String> {
+    let mut source = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut upload = bucket.open_upload_stream(filename);
+    if let Some(metadata) = metadata {
+        upload = upload.metadata(metadata);
+    }
+    let mut target = upload
+        .await
+        .map_err(|e| format!("Failed to start upload: {}", e))?;
+
+    let mut buf = vec![0u8; TRANSFER_BUFFER_BYTES];
+    let mut written = 0u64;
+    let copied = loop {
+        let n = match source.read(&mut buf).await {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) => break Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        if let Err(e) = target.write_all(&buf[..n]).await {
+            break Err(format!("Failed to upload: {}", e));
+        }
+        written += n as u64;
+    };
+    if let Err(e) = copied {
+        let _ = target.abort().await;
+        return Err(e);
+    }
+    target
+        .close()
+        .await
+        .map_err(|e| format!("Failed to finish upload: {}", e))?;
+    Ok((target.id().clone(), written))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bucket_names_requires_both_collections() {
+        let collections: Vec<String> = [
+            "fs.files",
+            "fs.chunks",
+            "avatars.files",
+            "avatars.chunks",
+            "orphan.files",
+            "users",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(bucket_names(&collections), vec!["avatars", "fs"]);
+    }
+
+    #[test]
+    fn test_file_id_from_json() {
+        let hex = "65a1b2c3d4e5f60718293a4b";
+        assert_eq!(
+            file_id_from_json(&json!(hex)).unwrap(),
+            Bson::ObjectId(ObjectId::parse_str(hex).unwrap())
+        );
+        assert_eq!(
+            file_id_from_json(&json!("report.pdf")).unwrap(),
+            Bson::String("report.pdf".to_string())
+        );
+        assert_eq!(
+            file_id_from_json(&json!({ "$oid": hex })).unwrap(),
+            Bson::ObjectId(ObjectId::parse_str(hex).unwrap())
+        );
+        assert_eq!(file_id_from_json(&json!(7)).unwrap(), Bson::Int32(7));
+        assert!(file_id_from_json(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn test_partial_download_path_is_a_hidden_sibling() {
+        assert_eq!(
+            partial_download_path(Path::new("/tmp/exports/report.pdf")).unwrap(),
+            Path::new("/tmp/exports/.report.pdf.part")
+        );
+        assert!(partial_download_path(Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn test_file_summary() {
+        let oid = ObjectId::new();
+        let file = doc! {
+            "_id": oid,
+            "length": 1_048_576_i64,
+            "chunkSize": 261_120,
+            "uploadDate": mongodb::bson::DateTime::from_millis(0),
+            "filename": "invoice.pdf",
+            "metadata": { "owner": "billing" },
+        };
+        let summary = file_summary(&file);
+        assert_eq!(summary["id"], json!({ "$oid": oid.to_hex() }));
+        assert_eq!(summary["filename"], "invoice.pdf");
+        assert_eq!(summary["length"], 1_048_576);
+        assert_eq!(summary["chunk_size"], 261_120);
+        assert_eq!(summary["upload_date"], "1970-01-01T00:00:00Z");
+        assert_eq!(summary["metadata"]["owner"], "billing");
+        assert!(summary["content_type"].is_null());
+    }
+}
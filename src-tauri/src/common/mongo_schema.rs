@@ -0,0 +1,484 @@
+//! Schema inference over sampled MongoDB documents.
+//!
+//! Every field is tracked by its dot-notation path (array elements that are
+//! documents contribute to `items.sku`, as in MongoDB queries and indexes).
+//! Per path the analyzer records the BSON type distribution, how many
+//! documents contain it, array lengths, and up to [`CARDINALITY_LIMIT`]
+//! distinct scalar values; past that the field counts as high-cardinality
+//! and only a few examples are kept.
+
+use std::collections::{BTreeMap, HashSet};
+
+use mongodb::bson::{Bson, Document};
+use serde_json::{Map, Value};
+
+/// Distinct scalar values kept per field before it counts as high-cardinality.
+pub(crate) const CARDINALITY_LIMIT: usize = 20;
+const EXAMPLE_LIMIT: usize = 3;
+const MAX_VALUE_CHARS: usize = 120;
+/// Upper bound on tracked paths, so documents with generated keys stay cheap.
+const MAX_PATHS: usize = 1000;
+
+/// The `$type` / `bsonType` alias of a value.
+pub(crate) fn bson_type_name(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Document(_) => "object",
+        Bson::Array(_) => "array",
+        Bson::Binary(_) => "binData",
+        Bson::Undefined => "undefined",
+        Bson::ObjectId(_) => "objectId",
+        Bson::Boolean(_) => "bool",
+        Bson::DateTime(_) => "date",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::DbPointer(_) => "dbPointer",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::Symbol(_) => "symbol",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::Int32(_) => "int",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Int64(_) => "long",
+        Bson::Decimal128(_) => "decimal",
+        Bson::MinKey => "minKey",
+        Bson::MaxKey => "maxKey",
+    }
+}
+
+#[derive(Default)]
+struct FieldStats {
+    /// Times the path appeared, counting every array element document.
+    occurrences: u64,
+    /// Sampled documents containing the path at least once.
+    documents: u64,
+    types: BTreeMap<&'static str, u64>,
+    item_types: BTreeMap<&'static str, u64>,
+    arrays: u64,
+    array_min: usize,
+    array_max: usize,
+    array_total: usize,
+    /// Times the value, or an element of it, was a document.
+    objects: u64,
+    values: BTreeMap<String, (Value, u64)>,
+    high_cardinality: bool,
+    examples: Vec<Value>,
+}
+
+impl FieldStats {
+    fn record(&mut self, value: &Bson) {
+        self.occurrences += 1;
+        *self.types.entry(bson_type_name(value)).or_default() += 1;
+        match value {
+            Bson::Array(items) => {
+                self.array_min = if self.arrays == 0 {
+                    items.len()
+                } else {
+                    self.array_min.min(items.len())
+                };
+                self.array_max = self.array_max.max(items.len());
+                self.array_total += items.len();
+                self.arrays += 1;
+                for item in items {
+                    *self.item_types.entry(bson_type_name(item)).or_default() += 1;
+                    self.record_scalar(item);
+                }
+            }
+            _ => self.record_scalar(value),
+        }
+    }
+
+    fn record_scalar(&mut self, value: &Bson) {
+        let key = match value {
+            Bson::String(s) => s.chars().take(MAX_VALUE_CHARS).collect(),
+            Bson::Int32(_)
+            | Bson::Int64(_)
+            | Bson::Double(_)
+            | Bson::Decimal128(_)
+            | Bson::Boolean(_)
+            | Bson::ObjectId(_)
+            | Bson::DateTime(_) => crate::common::bson::bson_to_value(value).to_string(),
+            _ => return,
+        };
+        if let Some((_, count)) = self.values.get_mut(&key) {
+            *count += 1;
+            return;
+        }
+        let shown = match value {
+            Bson::String(_) => Value::from(key.clone()),
+            _ => crate::common::bson::bson_to_value(value),
+        };
+        if self.examples.len() < EXAMPLE_LIMIT {
+            self.examples.push(shown.clone());
+        }
+        if self.high_cardinality {
+            return;
+        }
+        if self.values.len() >= CARDINALITY_LIMIT {
+            self.high_cardinality = true;
+            self.values.clear();
+            return;
+        }
+        self.values.insert(key, (shown, 1));
+    }
+
+    fn mixed(types: &BTreeMap<&'static str, u64>) -> bool {
+        types.keys().filter(|t| **t != "null").count() > 1
+    }
+}
+
+/// Accumulates field statistics over sampled documents.
+pub(crate) struct SchemaAnalyzer {
+    max_depth: usize,
+    documents: u64,
+    fields: BTreeMap<String, FieldStats>,
+    truncated: bool,
+}
+
+impl SchemaAnalyzer {
+    pub(crate) fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth: max_depth.max(1),
+            documents: 0,
+            fields: BTreeMap::new(),
+            truncated: false,
+        }
+    }
+
+    pub(crate) fn documents(&self) -> u64 {
+        self.documents
+    }
+
+    pub(crate) fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub(crate) fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub(crate) fn add_document(&mut self, doc: &Document) {
+        self.documents += 1;
+        let mut seen = HashSet::new();
+        self.walk(doc, "", 1, &mut seen);
+        for path in seen {
+            if let Some(stats) = self.fields.get_mut(&path) {
+                stats.documents += 1;
+            }
+        }
+    }
+
+    fn walk(&mut self, doc: &Document, prefix: &str, depth: usize, seen: &mut HashSet<String>) {
+        for (key, value) in doc {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            if !self.fields.contains_key(&path) && self.fields.len() >= MAX_PATHS {
+                self.truncated = true;
+                continue;
+            }
+            let stats = self.fields.entry(path.clone()).or_default();
+            stats.record(value);
+            let children: Vec<&Document> = match value {
+                Bson::Document(child) => vec![child],
+                Bson::Array(items) => items.iter().filter_map(Bson::as_document).collect(),
+                _ => Vec::new(),
+            };
+            stats.objects += children.len() as u64;
+            if depth < self.max_depth {
+                for child in children {
+                    self.walk(child, &path, depth + 1, seen);
+                }
+            }
+            seen.insert(path);
+        }
+    }
+
+    /// Direct children of `path` (top-level fields for the empty path).
+    fn children(&self, path: &str) -> Vec<(&str, &FieldStats)> {
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}.", path)
+        };
+        self.fields
+            .range(prefix.clone()..)
+            .take_while(|(p, _)| p.starts_with(&prefix))
+            .filter_map(|(p, stats)| {
+                let name = &p[prefix.len()..];
+                (!name.contains('.')).then_some((name, stats))
+            })
+            .collect()
+    }
+
+    fn percent(&self, n: u64) -> f64 {
+        if self.documents == 0 {
+            return 0.0;
+        }
+        (n as f64 * 1000.0 / self.documents as f64).round() / 10.0
+    }
+
+    fn field_value(&self, path: &str, name: &str, stats: &FieldStats) -> Value {
+        let mut field = Map::new();
+        field.insert("name".to_string(), Value::from(name));
+        field.insert("path".to_string(), Value::from(path));
+        field.insert(
+            "presence_pct".to_string(),
+            Value::from(self.percent(stats.documents)),
+        );
+        field.insert("types".to_string(), serde_json::json!(stats.types));
+        if FieldStats::mixed(&stats.types) {
+            field.insert("mixed_types".to_string(), Value::from(true));
+        }
+        if stats.arrays > 0 {
+            field.insert(
+                "array_length".to_string(),
+                serde_json::json!({
+                    "min": stats.array_min,
+                    "max": stats.array_max,
+                    "avg": (stats.array_total as f64 * 10.0 / stats.arrays as f64).round() / 10.0,
+                }),
+            );
+            field.insert(
+                "item_types".to_string(),
+                serde_json::json!(stats.item_types),
+            );
+        }
+        if stats.high_cardinality {
+            field.insert("high_cardinality".to_string(), Value::from(true));
+            field.insert("examples".to_string(), Value::from(stats.examples.clone()));
+        } else if !stats.values.is_empty() {
+            let mut values: Vec<&(Value, u64)> = stats.values.values().collect();
+            values.sort_by(|a, b| b.1.cmp(&a.1));
+            field.insert("cardinality".to_string(), Value::from(values.len()));
+            field.insert(
+                "values".to_string(),
+                values
+                    .into_iter()
+                    .map(|(value, count)| serde_json::json!({ "value": value, "count": count }))
+                    .collect(),
+            );
+        }
+        let children = self.children(path);
+        if !children.is_empty() {
+            field.insert(
+                "fields".to_string(),
+                children
+                    .into_iter()
+                    .map(|(child, child_stats)| {
+                        self.field_value(&format!("{}.{}", path, child), child, child_stats)
+                    })
+                    .collect(),
+            );
+        }
+        Value::Object(field)
+    }
+
+    /// The inferred field tree, top-level fields first.
+    pub(crate) fn field_tree(&self) -> Vec<Value> {
+        self.children("")
+            .into_iter()
+            .map(|(name, stats)| self.field_value(name, name, stats))
+            .collect()
+    }
+
+    /// Fields whose values (or array elements) have more than one non-null type.
+    pub(crate) fn mixed_type_fields(&self) -> Vec<Value> {
+        self.fields
+            .iter()
+            .filter_map(|(path, stats)| {
+                if FieldStats::mixed(&stats.types) {
+                    Some(serde_json::json!({ "path": path, "types": stats.types }))
+                } else if FieldStats::mixed(&stats.item_types) {
+                    Some(serde_json::json!({ "path": path, "item_types": stats.item_types }))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn bson_type(types: &BTreeMap<&'static str, u64>) -> Value {
+        match types.keys().collect::<Vec<_>>().as_slice() {
+            [only] => Value::from(**only),
+            many => serde_json::json!(many),
+        }
+    }
+
+    /// `bsonType`, `required` and `properties` for the documents under `path`.
+    fn object_schema(&self, path: &str, objects: u64) -> Value {
+        let mut properties = Map::new();
+        let mut required: Vec<&str> = Vec::new();
+        for (name, stats) in self.children(path) {
+            let child = if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", path, name)
+            };
+            if stats.occurrences >= objects {
+                required.push(name);
+            }
+            properties.insert(name.to_string(), self.property_schema(&child, stats));
+        }
+        let mut schema = serde_json::json!({ "bsonType": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = serde_json::json!(required);
+        }
+        schema
+    }
+
+    fn property_schema(&self, path: &str, stats: &FieldStats) -> Value {
+        let mut schema = serde_json::json!({ "bsonType": Self::bson_type(&stats.types) });
+        let has_children = !self.children(path).is_empty();
+        if has_children && stats.types.contains_key("object") {
+            let nested = self.object_schema(path, stats.objects);
+            schema["properties"] = nested["properties"].clone();
+            if let Some(required) = nested.get("required") {
+                schema["required"] = required.clone();
+            }
+        }
+        if stats.arrays > 0 && !stats.item_types.is_empty() {
+            let mut items = serde_json::json!({ "bsonType": Self::bson_type(&stats.item_types) });
+            if has_children && stats.item_types.contains_key("object") {
+                let nested = self.object_schema(path, stats.objects);
+                items["properties"] = nested["properties"].clone();
+            }
+            schema["items"] = items;
+        }
+        schema
+    }
+
+    /// A draft `$jsonSchema` validator: observed types per field, and every
+    /// field present in all sampled documents marked as required.
+    pub(crate) fn json_schema(&self) -> Value {
+        serde_json::json!({ "$jsonSchema": self.object_schema("", self.documents) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    fn analyzer() -> SchemaAnalyzer {
+        let mut analyzer = SchemaAnalyzer::new(8);
+        analyzer.add_document(&doc! {
+            "_id": 1,
+            "status": "A",
+            "qty": 5,
+            "address": { "city": "Paris", "zip": "75001" },
+            "items": [{ "sku": "x", "n": 1 }, { "sku": "y", "n": 2 }],
+        });
+        analyzer.add_document(&doc! {
+            "_id": 2,
+            "status": "B",
+            "qty": "five",
+            "address": { "city": "Lyon" },
+            "items": [],
+        });
+        analyzer.add_document(&doc! { "_id": 3, "status": "A", "qty": null });
+        analyzer
+    }
+
+    fn field<'a>(fields: &'a [Value], name: &str) -> &'a Value {
+        fields
+            .iter()
+            .find(|f| f["name"] == name)
+            .unwrap_or_else(|| panic!("missing field {}", name))
+    }
+
+    #[test]
+    fn test_field_tree_types_presence_and_cardinality() {
+        let analyzer = analyzer();
+        assert_eq!(analyzer.documents(), 3);
+        let tree = analyzer.field_tree();
+        let names: Vec<&str> = tree.iter().filter_map(|f| f["name"].as_str()).collect();
+        assert_eq!(names, vec!["_id", "address", "items", "qty", "status"]);
+
+        let status = field(&tree, "status");
+        assert_eq!(status["presence_pct"], 100.0);
+        assert_eq!(status["cardinality"], 2);
+        assert_eq!(
+            status["values"][0],
+            serde_json::json!({"value": "A", "count": 2})
+        );
+
+        let qty = field(&tree, "qty");
+        assert_eq!(qty["mixed_types"], true);
+        assert_eq!(
+            qty["types"],
+            serde_json::json!({"int": 1, "string": 1, "null": 1})
+        );
+
+        let address = field(&tree, "address");
+        assert_eq!(
+            (address["presence_pct"].as_f64().unwrap() * 10.0).round(),
+            667.0
+        );
+        let zip = field(address["fields"].as_array().unwrap(), "zip");
+        assert_eq!(zip["path"], "address.zip");
+        assert_eq!(
+            (zip["presence_pct"].as_f64().unwrap() * 10.0).round(),
+            333.0
+        );
+
+        let items = field(&tree, "items");
+        assert_eq!(
+            items["array_length"],
+            serde_json::json!({"min": 0, "max": 2, "avg": 1.0})
+        );
+        assert_eq!(items["item_types"], serde_json::json!({"object": 2}));
+        let sku = field(items["fields"].as_array().unwrap(), "sku");
+        assert_eq!(sku["path"], "items.sku");
+        assert_eq!(sku["cardinality"], 2);
+    }
+
+    #[test]
+    fn test_high_cardinality_keeps_examples_only() {
+        let mut analyzer = SchemaAnalyzer::new(8);
+        for i in 0..(CARDINALITY_LIMIT as i32 + 5) {
+            analyzer.add_document(&doc! { "n": i });
+        }
+        let tree = analyzer.field_tree();
+        let n = field(&tree, "n");
+        assert_eq!(n["high_cardinality"], true);
+        assert_eq!(n["examples"], serde_json::json!([0, 1, 2]));
+        assert!(n.get("values").is_none());
+    }
+
+    #[test]
+    fn test_mixed_type_fields_and_json_schema() {
+        let analyzer = analyzer();
+        let mixed = analyzer.mixed_type_fields();
+        assert_eq!(mixed.len(), 1);
+        assert_eq!(mixed[0]["path"], "qty");
+
+        let schema = &analyzer.json_schema()["$jsonSchema"];
+        assert_eq!(schema["bsonType"], "object");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["_id", "qty", "status"])
+        );
+        assert_eq!(schema["properties"]["_id"]["bsonType"], "int");
+        assert_eq!(
+            schema["properties"]["qty"]["bsonType"],
+            serde_json::json!(["int", "null", "string"])
+        );
+        let address = &schema["properties"]["address"];
+        assert_eq!(address["required"], serde_json::json!(["city"]));
+        let items = &schema["properties"]["items"];
+        assert_eq!(items["bsonType"], "array");
+        assert_eq!(items["items"]["bsonType"], "object");
+        assert_eq!(items["items"]["properties"]["sku"]["bsonType"], "string");
+    }
+
+    #[test]
+    fn test_max_depth_stops_recursion() {
+        let mut analyzer = SchemaAnalyzer::new(1);
+        analyzer.add_document(&doc! { "a": { "b": { "c": 1 } } });
+        assert_eq!(analyzer.field_count(), 1);
+        assert!(!analyzer.truncated());
+    }
+}
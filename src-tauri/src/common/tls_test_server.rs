@@ -136,6 +136,12 @@ pub fn test_root_certificate() -> reqwest::Certificate {
         .expect("embedded test certificate is valid PEM")
 }
 
+/// The embedded self-signed certificate as PEM text, for tests that pass it
+/// through a connection's `caBundle` setting.
+pub fn test_root_certificate_pem() -> &'static str {
+    TEST_CERT_PEM
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
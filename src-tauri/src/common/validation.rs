@@ -24,6 +24,46 @@ pub(crate) fn validate_index_name(name: &str, allow_wildcard: bool) -> Result<()
     Ok(())
 }
 
+/// Validate an Elasticsearch task ID (`<node_id>:<task_number>`). Task IDs
+/// are inserted into paths unencoded, so the node part is restricted to the
+/// characters ES generates.
+pub(crate) fn validate_task_id(task_id: &str) -> Result<(), String> {
+    let valid = match task_id.split_once(':') {
+        Some((node, number)) => {
+            !node.is_empty()
+                && node
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(format!(
+            "Invalid task ID '{}': expected <node_id>:<task_number>",
+            task_id
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a comma-separated list of search targets (indices, aliases or
+/// data streams). Entries after the first may be `-` exclusions, e.g.
+/// `logs-*,-logs-debug-*`.
+pub(crate) fn validate_search_targets(targets: &str) -> Result<(), String> {
+    for (i, target) in targets.split(',').enumerate() {
+        let target = target.trim();
+        let name = if i > 0 {
+            target.strip_prefix('-').unwrap_or(target)
+        } else {
+            target
+        };
+        validate_index_name(name, true)?;
+    }
+    Ok(())
+}
+
 pub(crate) fn url_encode_segment(segment: &str) -> String {
     form_urlencoded::byte_serialize(segment.as_bytes()).collect()
 }
@@ -131,6 +171,27 @@ mod tests {
         assert!(validate_index_name("logstash-*", true).is_ok());
     }
 
+    #[test]
+    fn test_validate_task_id() {
+        assert!(validate_task_id("oTUltX4IQMOUUVeiohTt8A:12345").is_ok());
+        assert!(validate_task_id("node-1_a:1").is_ok());
+        assert!(validate_task_id("12345").is_err());
+        assert!(validate_task_id("node:").is_err());
+        assert!(validate_task_id(":1").is_err());
+        assert!(validate_task_id("node/../x:1").is_err());
+        assert!(validate_task_id("node:12a").is_err());
+    }
+
+    #[test]
+    fn test_validate_search_targets() {
+        assert!(validate_search_targets("logs-nginx-default").is_ok());
+        assert!(validate_search_targets("logs-*,metrics-*").is_ok());
+        assert!(validate_search_targets("logs-*,-logs-debug-*").is_ok());
+        assert!(validate_search_targets("-logs-*").is_err());
+        assert!(validate_search_targets("logs-*,").is_err());
+        assert!(validate_search_targets("logs,../x").is_err());
+    }
+
     #[test]
     fn test_url_encode_segment_normal() {
         assert_eq!(url_encode_segment("hello"), "hello");
@@ -77,19 +77,19 @@ pub struct DynamoOptions {
     pub connection_id: Option<String>,
 }
 
-fn build_config_builder(
-    credentials: &DynamoCredentials,
-    tunnel_port: Option<u16>,
-) -> aws_config::ConfigLoader {
-    let effective_region = if credentials.region.is_empty() {
-        match &credentials.auth {
+/// Region and credentials for an AWS auth selection. Shared by the DynamoDB
+/// client and by SigV4 signing of Amazon OpenSearch Service requests, so
+/// both resolve profiles and SSO/assumed-role sessions the same way.
+pub(crate) fn aws_config_loader(region: &str, auth: &DynamoAuth) -> aws_config::ConfigLoader {
+    let effective_region = if region.is_empty() {
+        match auth {
             DynamoAuth::Sso { region, .. } | DynamoAuth::AssumeRole { region, .. } => {
                 region.clone()
             }
-            _ => credentials.region.clone(),
+            _ => region.to_string(),
         }
     } else {
-        credentials.region.clone()
+        region.to_string()
     };
 
     let region_provider = RegionProviderChain::first_try(Region::new(effective_region))
@@ -99,7 +99,7 @@ fn build_config_builder(
     let mut config_builder =
         aws_config::defaults(aws_config::BehaviorVersion::latest()).region(region_provider);
 
-    match &credentials.auth {
+    match auth {
         DynamoAuth::AccessKey {
             access_key_id,
             secret_access_key,
@@ -142,6 +142,15 @@ fn build_config_builder(
         }
     }
 
+    config_builder
+}
+
+fn build_config_builder(
+    credentials: &DynamoCredentials,
+    tunnel_port: Option<u16>,
+) -> aws_config::ConfigLoader {
+    let mut config_builder = aws_config_loader(&credentials.region, &credentials.auth);
+
     // Apply tunnel endpoint override before the configured endpoint_url
     if let Some(local_port) = tunnel_port {
         config_builder = config_builder.endpoint_url(format!("http://127.0.0.1:{}", local_port));
@@ -527,6 +536,8 @@ pub async fn dynamo_test_connection(
         }
     }
 
+    crate::common::connection_resolver::copy_tls_settings(&config, &mut normalized);
+
     let (remote_host, remote_port) = crate::common::ssh_bridge::extract_remote_target(&config);
     let endpoint_is_http = config
         .get("endpointUrl")
@@ -23,6 +23,15 @@ pub struct FetchApiOptions {
     headers: HashMap<String, String>,
     body: Option<String>,
     agent: Agent,
+    /// Connection auth settings (`authType`, `token`, `clientCert`/`clientKey`,
+    /// `awsRegion`/`awsService`/`awsAuth`) for auth methods that cannot be
+    /// expressed as a static header: client certificates and SigV4 signing.
+    #[serde(default)]
+    auth: Option<Value>,
+    /// Per-connection TLS settings (`caBundle`, `clientCert`/`clientKey`,
+    /// `tlsServerName`, `tlsMinVersion`), see `common::tls`.
+    #[serde(default)]
+    tls: Option<Value>,
 }
 
 /// Tunnel routing info for a request: keep the original hostname in the URL
@@ -158,28 +167,80 @@ async fn fetch_raw(
         options.agent.http_proxy.clone()
     };
     let has_explicit_proxy = proxy_url.as_deref().is_some_and(|p| !p.is_empty());
+    let to_error =
+        |e: String| json!({"status": 500, "message": e, "data": Option::<Value>::None}).to_string();
+    // TLS keys may arrive in either object: PKI auth carries the client
+    // certificate, the TLS settings carry everything else.
+    let mut tls_config = options.tls.clone().unwrap_or_else(|| json!({}));
+    if let (Some(merged), Some(auth)) = (
+        tls_config.as_object_mut(),
+        options.auth.as_ref().and_then(|v| v.as_object()),
+    ) {
+        for (key, value) in auth {
+            merged.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    let connection_tls = crate::common::es::es_connection_tls(&tls_config).map_err(to_error)?;
+    let mut tls = connection_tls.http_options().map_err(to_error)?;
+    tls.root_certs.extend(extra_root_certs.unwrap_or_default());
+    let has_tls_settings = !connection_tls.is_empty() || !tls.root_certs.is_empty();
+    let mut url = url.to_string();
     let client = if let Some(t) = tunnel {
         // Tunneled requests keep the original hostname for TLS. Socks5 mode
         // routes through the explicit proxy; port-forward mode uses a DNS
         // override to the local tunnel port.
-        match t.socks5_proxy {
-            Some(proxy) => create_http_client(
+        match (t.socks5_proxy, connection_tls.server_name.as_deref()) {
+            (Some(_), Some(_)) => {
+                return Err(to_error(
+                    "tlsServerName is not supported with SOCKS5 tunnels".to_string(),
+                ))
+            }
+            (Some(proxy), None) => create_http_client(
                 "manual",
                 Some(format!("socks5h://{}", proxy)),
                 Some(options.agent.ssl),
                 None,
                 None,
-                extra_root_certs,
-            ),
-            None => create_http_client(
-                "none",
-                None,
-                Some(options.agent.ssl),
-                None,
-                Some((t.hostname, SocketAddr::from(([127, 0, 0, 1], t.local_port)))),
-                extra_root_certs,
+                tls,
             ),
+            (None, server_name) => {
+                let hostname = match server_name {
+                    Some(name) => {
+                        url = crate::common::tls::with_url_host(&url, name).map_err(to_error)?;
+                        name.to_string()
+                    }
+                    None => t.hostname,
+                };
+                create_http_client(
+                    "none",
+                    None,
+                    Some(options.agent.ssl),
+                    None,
+                    Some((hostname, SocketAddr::from(([127, 0, 0, 1], t.local_port)))),
+                    tls,
+                )
+            }
+        }
+    } else if let Some(name) = connection_tls.server_name.as_deref() {
+        // The DNS override only takes effect on direct connections; a proxy
+        // would resolve the override name itself.
+        if has_explicit_proxy {
+            return Err(to_error(
+                "tlsServerName is not supported together with an HTTP proxy".to_string(),
+            ));
         }
+        let (rewritten, dns_override) = crate::common::tls::server_name_override(&url, name)
+            .await
+            .map_err(to_error)?;
+        url = rewritten;
+        create_http_client(
+            "none",
+            None,
+            Some(options.agent.ssl),
+            None,
+            Some(dns_override),
+            tls,
+        )
     } else if has_explicit_proxy {
         create_http_client(
             "manual",
@@ -187,26 +248,59 @@ async fn fetch_raw(
             Some(options.agent.ssl),
             None,
             None,
-            None,
+            tls,
         )
     } else if is_local {
-        create_http_client("none", None, Some(options.agent.ssl), None, None, None)
+        create_http_client("none", None, Some(options.agent.ssl), None, None, tls)
+    } else if has_tls_settings {
+        // CA bundles and client certificates are per connection; never
+        // cache them in the shared clients.
+        create_http_client("system", None, Some(options.agent.ssl), None, None, tls)
     } else if options.agent.ssl {
         SECURE_CLIENT
-            .get_or_init(|| create_http_client("system", None, Some(true), None, None, None))
+            .get_or_init(|| {
+                create_http_client("system", None, Some(true), None, None, Default::default())
+            })
             .clone()
     } else {
         INSECURE_CLIENT
-            .get_or_init(|| create_http_client("system", None, Some(false), None, None, None))
+            .get_or_init(|| {
+                create_http_client("system", None, Some(false), None, None, Default::default())
+            })
             .clone()
     };
+    let url = url.as_str();
+
+    let mut headers = headermap_from_hashmap(options.headers.iter());
+    if let Some(auth) = options.auth.as_ref() {
+        // Basic and ApiKey headers come from the frontend; bearer tokens are
+        // applied from the connection auth like the agent path does.
+        if auth.get("authType").and_then(|v| v.as_str()) == Some("bearer") {
+            if let Some(value) = crate::common::es::build_es_headers(auth).get("Authorization") {
+                headers.insert("Authorization", value.clone());
+            }
+        }
+        if crate::common::es::es_uses_sigv4(auth) {
+            crate::common::es::sign_es_request(
+                auth,
+                &options.method,
+                url,
+                &mut headers,
+                options.body.as_deref().unwrap_or("").as_bytes(),
+            )
+            .await
+            .map_err(|e| {
+                json!({"status": 500, "message": e, "data": Option::<Value>::None}).to_string()
+            })?;
+        }
+    }
 
     let response = client
         .request(
             reqwest::Method::from_bytes(options.method.as_bytes()).unwrap(),
             url,
         )
-        .headers(headermap_from_hashmap(options.headers.iter()))
+        .headers(headers)
         .body(options.body.clone().unwrap_or_default())
         .send()
         .await;
@@ -335,6 +429,8 @@ mod tests {
             method: "GET".to_string(),
             headers: HashMap::new(),
             body: None,
+            auth: None,
+            tls: None,
             agent: Agent {
                 ssl: true,
                 http_proxy: None,
@@ -357,6 +453,46 @@ mod tests {
         assert!(result.unwrap().contains("\"status\":200"));
     }
 
+    /// A connection CA bundle plus a `tlsServerName` override lets a direct
+    /// request to the bare IP validate the certificate issued for the name.
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_fetch_raw_applies_ca_bundle_and_server_name() {
+        use crate::common::tls_test_server::{spawn_tls_server, test_root_certificate_pem};
+
+        let addr = spawn_tls_server().await;
+        let options = FetchApiOptions {
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            auth: None,
+            tls: Some(json!({
+                "caBundle": test_root_certificate_pem(),
+                "tlsServerName": "es.example.com",
+                "tlsMinVersion": "1.2"
+            })),
+            agent: Agent {
+                ssl: true,
+                http_proxy: None,
+            },
+        };
+
+        let result = fetch_raw(
+            &format!("https://127.0.0.1:{}/", addr.port()),
+            &options,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(
+            result.is_ok(),
+            "CA bundle + server name fetch must succeed, got: {:?}",
+            result.err()
+        );
+        assert!(result.unwrap().contains("\"status\":200"));
+    }
+
     /// S4: Socks5 mode routes through a real local SOCKS5 proxy while the
     /// URL keeps the real hostname (wiremock as the target server).
     #[tokio::test]
@@ -398,6 +534,8 @@ mod tests {
             method: "GET".to_string(),
             headers: HashMap::new(),
             body: None,
+            auth: None,
+            tls: None,
             agent: Agent {
                 ssl: false,
                 http_proxy: None,
@@ -442,6 +580,8 @@ mod tests {
             method: "GET".to_string(),
             headers: HashMap::new(),
             body: None,
+            auth: None,
+            tls: None,
             agent: Agent {
                 ssl: false,
                 http_proxy: None,
@@ -463,4 +603,45 @@ mod tests {
         );
         assert!(result.unwrap().contains("hello"));
     }
+
+    #[tokio::test]
+    async fn test_fetch_raw_applies_bearer_and_sigv4_auth() {
+        use wiremock::matchers::{header, header_exists, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("Authorization", "Bearer svc-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"auth":"bearer"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(header_exists("x-amz-date"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"auth":"sigv4"}"#))
+            .mount(&server)
+            .await;
+
+        let url = format!("http://127.0.0.1:{}/", server.address().port());
+        let mut options = FetchApiOptions {
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            auth: Some(json!({"authType": "bearer", "token": "svc-token"})),
+            tls: None,
+            agent: Agent {
+                ssl: false,
+                http_proxy: None,
+            },
+        };
+        let result = fetch_raw(&url, &options, None, None).await.unwrap();
+        assert!(result.contains("bearer"), "got: {}", result);
+
+        options.auth = Some(json!({
+            "authType": "awsSigV4",
+            "awsRegion": "us-east-1",
+            "awsAuth": {"kind": "accessKey", "access_key_id": "AKID", "secret_access_key": "secret"}
+        }));
+        let result = fetch_raw(&url, &options, None, None).await.unwrap();
+        assert!(result.contains("sigv4"), "got: {}", result);
+    }
 }
@@ -48,7 +48,9 @@ use dynamo_client::{
 use fetch_client::fetch_api;
 use file_api::{get_file_info, read_file_batch};
 use mongo_client::{
-    mongo_execute_query, mongo_export_documents, mongo_import_documents, mongo_test_connection,
+    mongo_dump, mongo_execute_query, mongo_export_documents, mongo_gridfs_download,
+    mongo_gridfs_upload, mongo_import_documents, mongo_restore, mongo_test_connection,
+    mongo_watch_start, mongo_watch_stop,
 };
 use tauri::Emitter;
 
@@ -123,6 +125,12 @@ pub fn run() {
             mongo_execute_query,
             mongo_export_documents,
             mongo_import_documents,
+            mongo_gridfs_download,
+            mongo_gridfs_upload,
+            mongo_dump,
+            mongo_restore,
+            mongo_watch_start,
+            mongo_watch_stop,
             run_agent_step,
             validate_llm_config,
             list_llm_models,
@@ -193,6 +201,7 @@ pub fn run() {
             app.manage(agent_db);
             app.manage(crate::ssh::TunnelManager::new());
             app.manage(crate::mcp_bridge::McpServerHandle::new());
+            app.manage(crate::mongo_client::MongoWatches::default());
 
             use std::collections::HashMap;
             use std::sync::{Arc, Mutex};
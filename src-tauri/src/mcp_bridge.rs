@@ -433,6 +433,8 @@ fn to_safe_connection_summary(connection: &Value) -> Value {
         // First discovery tool an agent should call for this connection type
         // to learn its objects before querying.
         "discover": json!(discover_tool_for(&type_str(connection))),
+        // Follow-up tool that describes one object's structure.
+        "structure": json!(structure_tool_for(&type_str(connection))),
     })
 }
 
@@ -466,6 +468,17 @@ fn discover_tool_for(conn_type: &str) -> &'static str {
     }
 }
 
+/// The structure tool for one discovered object: a field-level schema for
+/// MongoDB collections, the mapping for indices, the key schema for tables.
+fn structure_tool_for(conn_type: &str) -> Option<&'static str> {
+    match conn_type.to_ascii_lowercase().as_str() {
+        t if t.contains("elastic") || t.contains("open") => Some("es__get_mapping"),
+        t if t.contains("mongo") => Some("mongo__analyze_schema"),
+        t if t.contains("dynamo") => Some("dynamo__describe_table"),
+        _ => None,
+    }
+}
+
 fn type_str(c: &serde_json::Value) -> String {
     c.get("type")
         .and_then(|v| v.as_str())
@@ -839,6 +852,19 @@ mod tests {
         assert!(summary.get("password").is_none());
     }
 
+    #[test]
+    fn test_to_safe_connection_summary_recommends_discovery_tools() {
+        let summary = to_safe_connection_summary(&json!({"id": 1, "type": "MONGODB"}));
+        assert_eq!(summary["discover"], "mongo__list_databases");
+        assert_eq!(summary["structure"], "mongo__analyze_schema");
+
+        let summary = to_safe_connection_summary(&json!({"id": 2, "type": "ELASTICSEARCH"}));
+        assert_eq!(summary["structure"], "es__get_mapping");
+
+        let summary = to_safe_connection_summary(&json!({"id": 3, "type": "UNKNOWN"}));
+        assert_eq!(summary["structure"], Value::Null);
+    }
+
     #[test]
     fn test_health_payload_shape() {
         let v = health_payload("dockit", "1.2.3", 9120);
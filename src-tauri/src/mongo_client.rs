@@ -33,6 +33,10 @@ pub struct MongoConnectionConfig {
     pub tls: Option<bool>,
     #[serde(default)]
     pub tunnel_port: Option<u16>,
+    /// CA bundle and client certificate settings (`caBundle`, `clientCert`,
+    /// `clientKey`, `tlsServerName`, `tlsMinVersion`), see `common::tls`.
+    #[serde(default, rename = "tlsSettings")]
+    pub tls_settings: Option<Value>,
 }
 
 fn encode_component(s: &str) -> String {
@@ -69,6 +73,18 @@ fn apply_tunnel_tls_skip(client_options: &mut ClientOptions, tunnel_port: Option
     }
 }
 
+fn apply_connection_tls(
+    client_options: &mut ClientOptions,
+    config: &MongoConnectionConfig,
+) -> Result<(), String> {
+    match &config.tls_settings {
+        Some(settings) => {
+            crate::common::tls::ConnectionTls::from_config(settings)?.apply_to_mongo(client_options)
+        }
+        None => Ok(()),
+    }
+}
+
 fn build_uri_tunneled(
     config: &MongoConnectionConfig,
     tunnel_port: Option<u16>,
@@ -191,6 +207,9 @@ pub async fn mongo_test_connection(
             ));
         }
     };
+    if let Err(e) = apply_connection_tls(&mut client_options, &config) {
+        return Ok(ApiResponse::err(400, e));
+    }
     if endpoint.socks5_port.is_some() {
         apply_socks5_proxy(&mut client_options, endpoint.socks5_port);
     } else {
@@ -232,57 +251,12 @@ pub async fn mongo_test_connection(
     ))
 }
 
-fn bson_to_json(bson: &Bson) -> Value {
-    match bson {
-        Bson::Double(v) => Value::from(*v),
-        Bson::String(v) => Value::from(v.as_str()),
-        Bson::Array(arr) => Value::Array(arr.iter().map(bson_to_json).collect()),
-        Bson::Document(d) => {
-            let map: serde_json::Map<String, Value> = d
-                .iter()
-                .map(|(k, v)| (k.clone(), bson_to_json(v)))
-                .collect();
-            Value::Object(map)
-        }
-        Bson::Boolean(v) => Value::from(*v),
-        Bson::Null => Value::Null,
-        Bson::Int32(v) => Value::from(*v),
-        Bson::Int64(v) => Value::from(*v),
-        Bson::ObjectId(oid) => serde_json::json!({"$oid": oid.to_string()}),
-        Bson::DateTime(dt) => {
-            serde_json::json!({"$date": {"$numberLong": dt.timestamp_millis().to_string()}})
-        }
-        Bson::Decimal128(d) => serde_json::json!({"$numberDecimal": d.to_string()}),
-        Bson::Binary(b) => {
-            let encoded = base64::engine::general_purpose::STANDARD.encode(&b.bytes);
-            let sub_type = format!("{:02X}", u8::from(b.subtype));
-            serde_json::json!({"$binary": {"base64": encoded, "subType": sub_type}})
-        }
-        Bson::RegularExpression(re) => {
-            serde_json::json!({"$regularExpression": {"pattern": re.pattern, "options": re.options}})
-        }
-        Bson::Timestamp(ts) => serde_json::json!({"$timestamp": {"t": ts.time, "i": ts.increment}}),
-        Bson::JavaScriptCode(code) => serde_json::json!({"$code": code}),
-        Bson::JavaScriptCodeWithScope(code_scope) => {
-            serde_json::json!({"$code": code_scope.code, "$scope": code_scope.scope})
-        }
-        Bson::Symbol(sym) => serde_json::json!({"$symbol": sym}),
-        Bson::MaxKey => serde_json::json!({"$maxKey": 1}),
-        Bson::MinKey => serde_json::json!({"$minKey": 1}),
-        Bson::Undefined => serde_json::json!({"$undefined": true}),
-        Bson::DbPointer(db_pointer) => serde_json::to_value(db_pointer).unwrap(),
-    }
-}
-
 fn doc_to_json(d: Document) -> Value {
-    let map: serde_json::Map<String, Value> =
-        d.into_iter().map(|(k, v)| (k, bson_to_json(&v))).collect();
-    Value::Object(map)
+    crate::common::bson::doc_to_value(&d)
 }
 
 fn json_to_bson_doc(val: Value) -> Result<Document, String> {
-    mongodb::bson::to_document(&val)
-        .map_err(|e| format!("Failed to convert to BSON document: {}", e))
+    crate::common::bson::json_to_bson_doc(&val)
 }
 
 // Convert JS-style object literals (single quotes, unquoted keys, ObjectId(), new Date()) to valid JSON.
@@ -378,39 +352,8 @@ fn js_to_json(s: &str) -> String {
                                 k += 1;
                             }
                             let inner: String = chars[j + 1..k.saturating_sub(1)].iter().collect();
-                            let inner = inner.trim().trim_matches('"').trim_matches('\'');
                             // Emit Extended JSON for known BSON types so the driver decodes them correctly
-                            match word.as_str() {
-                                "ObjectId" => {
-                                    out.push_str("{\"$oid\":\"");
-                                    out.push_str(inner);
-                                    out.push_str("\"}");
-                                }
-                                "ISODate" | "Date" => {
-                                    out.push_str("{\"$date\":\"");
-                                    out.push_str(inner);
-                                    out.push_str("\"}");
-                                }
-                                "NumberLong" | "Long" => {
-                                    out.push_str("{\"$numberLong\":\"");
-                                    out.push_str(inner);
-                                    out.push_str("\"}");
-                                }
-                                "NumberDecimal" | "Decimal128" => {
-                                    out.push_str("{\"$numberDecimal\":\"");
-                                    out.push_str(inner);
-                                    out.push_str("\"}");
-                                }
-                                _ => {
-                                    if inner.is_empty() {
-                                        out.push_str("null");
-                                    } else {
-                                        out.push('"');
-                                        out.push_str(inner);
-                                        out.push('"');
-                                    }
-                                }
-                            }
+                            out.push_str(&shell_constructor_to_json(&word, &inner));
                             i = k;
                         } else {
                             out.push_str(&word);
@@ -428,6 +371,96 @@ fn js_to_json(s: &str) -> String {
     out
 }
 
+fn unquote(s: &str) -> &str {
+    s.trim().trim_matches('"').trim_matches('\'')
+}
+
+/// Extended JSON for a mongosh type constructor call such as
+/// `ISODate("2024-01-01")` or `BinData(0, "...")`. Unknown constructors
+/// become their argument as a string. Malformed arguments are passed
+/// through so the Extended JSON parser reports them.
+fn shell_constructor_to_json(name: &str, raw_args: &str) -> String {
+    let args = split_top_level_args(raw_args);
+    let arg = |i: usize| args.get(i).map(|a| unquote(a)).unwrap_or("");
+    let quoted = |s: &str| Value::from(s).to_string();
+    let binary = |data: &str, sub_type: &str| {
+        serde_json::json!({ "$binary": { "base64": data, "subType": sub_type } }).to_string()
+    };
+    match name {
+        "ObjectId" if arg(0).is_empty() => {
+            format!(
+                "{{\"$oid\":\"{}\"}}",
+                mongodb::bson::oid::ObjectId::new().to_hex()
+            )
+        }
+        "ObjectId" => format!("{{\"$oid\":{}}}", quoted(arg(0))),
+        "ISODate" | "Date" => shell_date_to_json(arg(0)),
+        "NumberLong" | "Long" => format!("{{\"$numberLong\":{}}}", quoted(arg(0))),
+        "NumberInt" | "Int32" => format!("{{\"$numberInt\":{}}}", quoted(arg(0))),
+        "NumberDecimal" | "Decimal128" => format!("{{\"$numberDecimal\":{}}}", quoted(arg(0))),
+        "UUID" => {
+            let uuid = match arg(0) {
+                "" => uuid::Uuid::new_v4(),
+                text => match uuid::Uuid::parse_str(text) {
+                    Ok(uuid) => uuid,
+                    Err(_) => return binary(text, "04"),
+                },
+            };
+            binary(
+                &base64::engine::general_purpose::STANDARD.encode(uuid.as_bytes()),
+                "04",
+            )
+        }
+        "BinData" => {
+            let sub_type = match arg(0).parse::<u8>() {
+                Ok(sub_type) => format!("{:02x}", sub_type),
+                Err(_) => arg(0).to_string(),
+            };
+            binary(arg(1), &sub_type)
+        }
+        "Timestamp" => {
+            // Timestamp(t, i) or Timestamp({t: .., i: ..})
+            let (t, i) = match args.first().map(|a| a.trim_start().starts_with('{')) {
+                Some(true) => {
+                    let fields =
+                        serde_json::from_str::<Value>(&js_to_json(&args[0])).unwrap_or(Value::Null);
+                    (fields.get("t").cloned(), fields.get("i").cloned())
+                }
+                _ => (
+                    arg(0).parse::<u32>().ok().map(Value::from),
+                    arg(1).parse::<u32>().ok().map(Value::from),
+                ),
+            };
+            serde_json::json!({ "$timestamp": { "t": t, "i": i } }).to_string()
+        }
+        _ if arg(0).is_empty() => "null".to_string(),
+        _ => quoted(arg(0)),
+    }
+}
+
+/// `{"$date": ...}` for `ISODate(...)` / `new Date(...)`: no argument is
+/// now, a number is epoch millis, and the shell's shorthand date strings
+/// (date only, no time zone, space separator) are completed to RFC 3339.
+fn shell_date_to_json(arg: &str) -> String {
+    if arg.is_empty() {
+        let now = mongodb::bson::DateTime::now().timestamp_millis();
+        return format!("{{\"$date\":{{\"$numberLong\":\"{}\"}}}}", now);
+    }
+    if let Ok(millis) = arg.parse::<i64>() {
+        return format!("{{\"$date\":{{\"$numberLong\":\"{}\"}}}}", millis);
+    }
+    let mut date = arg.replacen(' ', "T", 1);
+    let has_zone = date
+        .split_once('T')
+        .map(|(_, time)| time.contains(['Z', 'z', '+', '-']));
+    match has_zone {
+        None => date.push_str("T00:00:00Z"),
+        Some(false) => date.push('Z'),
+        Some(true) => {}
+    }
+    format!("{{\"$date\":{}}}", Value::from(date))
+}
+
 fn parse_json_arg(s: &str) -> Result<Value, String> {
     let s = s.trim();
     if s.is_empty() {
@@ -541,10 +574,64 @@ fn extract_balanced(s: &str) -> Result<(String, &str), String> {
     Err("Unmatched '(' in statement".to_string())
 }
 
-fn parse_chain(s: &str) -> Result<(Option<i64>, Option<u64>, Option<Document>), String> {
-    let mut limit: Option<i64> = None;
-    let mut skip: Option<u64> = None;
-    let mut sort_doc: Option<Document> = None;
+/// Cursor methods chained after `find(...)` or `aggregate(...)`.
+#[derive(Default)]
+struct CursorChain {
+    limit: Option<i64>,
+    skip: Option<u64>,
+    sort: Option<Document>,
+    projection: Option<Document>,
+    /// Index name (string) or key pattern (document).
+    hint: Option<Bson>,
+    collation: Option<Document>,
+    max_time: Option<std::time::Duration>,
+    /// `explain()` verbosity.
+    explain: Option<String>,
+    count: bool,
+}
+
+impl CursorChain {
+    fn driver_hint(&self) -> Option<mongodb::options::Hint> {
+        match self.hint.clone()? {
+            Bson::String(name) => Some(mongodb::options::Hint::Name(name)),
+            Bson::Document(keys) => Some(mongodb::options::Hint::Keys(keys)),
+            _ => None,
+        }
+    }
+
+    fn driver_collation(&self) -> Result<Option<mongodb::options::Collation>, String> {
+        self.collation
+            .clone()
+            .map(mongodb::bson::from_document)
+            .transpose()
+            .map_err(|e| format!("Invalid collation: {}", e))
+    }
+
+    /// The hint, collation and time limit as fields of an explained command.
+    fn insert_command_options(&self, command: &mut Document) {
+        if let Some(hint) = &self.hint {
+            command.insert("hint", hint.clone());
+        }
+        if let Some(collation) = &self.collation {
+            command.insert("collation", collation.clone());
+        }
+        if let Some(max_time) = self.max_time {
+            command.insert("maxTimeMS", max_time.as_millis() as i64);
+        }
+    }
+}
+
+/// The verbosity named by an `explain(...)` argument, following mongosh.
+fn explain_verbosity(arg: &str) -> Result<&str, String> {
+    match unquote(arg) {
+        "" | "false" => Ok("queryPlanner"),
+        "true" => Ok("allPlansExecution"),
+        other => crate::common::mongo_explain::validate_verbosity(other),
+    }
+}
+
+fn parse_chain(s: &str) -> Result<CursorChain, String> {
+    let mut chain = CursorChain::default();
     let mut s = s.trim();
 
     while s.starts_with('.') {
@@ -555,33 +642,64 @@ fn parse_chain(s: &str) -> Result<(Option<i64>, Option<u64>, Option<Document>),
         };
         let method = s[..paren_pos].trim();
         let (arg_str, rest) = extract_balanced(&s[paren_pos..])?;
+        let arg = arg_str.trim();
         match method {
             "limit" => {
-                limit = arg_str.trim().parse::<i64>().ok();
+                chain.limit = arg.parse::<i64>().ok();
             }
             "skip" => {
-                skip = arg_str.trim().parse::<u64>().ok();
+                chain.skip = arg.parse::<u64>().ok();
             }
             "sort" => {
-                if let Ok(val) = parse_json_arg(&arg_str) {
-                    sort_doc = json_to_bson_doc(val).ok();
+                if let Ok(val) = parse_json_arg(arg) {
+                    chain.sort = json_to_bson_doc(val).ok();
                 }
             }
-            _ => {}
+            "projection" | "project" => {
+                chain.projection = Some(parse_json_arg(arg).and_then(json_to_bson_doc)?);
+            }
+            "hint" => {
+                chain.hint = Some(if arg.starts_with(['"', '\'']) {
+                    Bson::String(unquote(arg).to_string())
+                } else {
+                    Bson::Document(parse_json_arg(arg).and_then(json_to_bson_doc)?)
+                });
+            }
+            "collation" => {
+                chain.collation = Some(parse_json_arg(arg).and_then(json_to_bson_doc)?);
+                chain.driver_collation()?;
+            }
+            "maxTimeMS" => {
+                let millis = arg
+                    .parse::<u64>()
+                    .map_err(|_| format!("maxTimeMS expects milliseconds, got '{}'", arg))?;
+                chain.max_time = Some(std::time::Duration::from_millis(millis));
+            }
+            "explain" => chain.explain = Some(explain_verbosity(arg)?.to_string()),
+            "count" => chain.count = true,
+            // Shell display helpers; results always come back as an array.
+            "toArray" | "pretty" => {}
+            other => {
+                return Err(format!(
+                    "Unsupported cursor method '{}'. Supported: limit, skip, sort, projection, project, hint, collation, maxTimeMS, explain, count, toArray",
+                    other
+                ))
+            }
         }
         s = rest.trim();
     }
 
-    Ok((limit, skip, sort_doc))
+    Ok(chain)
 }
 
 struct ParsedStatement {
-    collection: String,
+    /// Set by `db.getSiblingDB('x')`; the connection's database otherwise.
+    database: Option<String>,
+    /// `None` for database methods such as `db.runCommand(...)`.
+    collection: Option<String>,
     method: String,
     args: Vec<String>,
-    chain_limit: Option<i64>,
-    chain_skip: Option<u64>,
-    chain_sort: Option<Document>,
+    chain: CursorChain,
 }
 
 fn parse_statement(code: &str) -> Result<ParsedStatement, String> {
@@ -594,36 +712,39 @@ fn parse_statement(code: &str) -> Result<ParsedStatement, String> {
         ));
     }
 
-    let after_db = &code[2..];
+    let mut after_db = &code[2..];
+    let mut database = None;
+    if let Some(rest) = after_db.strip_prefix(".getSiblingDB") {
+        let (name, rest) = extract_balanced(rest.trim_start())?;
+        let name = unquote(&name);
+        if name.is_empty() {
+            return Err("getSiblingDB requires a database name".to_string());
+        }
+        database = Some(name.to_string());
+        after_db = rest;
+    }
 
     let (collection, rest) = if after_db.starts_with('[') {
         let end = after_db
             .find(']')
             .ok_or("Unmatched '[' in collection accessor")?;
-        let name = after_db[1..end]
-            .trim()
-            .trim_matches('"')
-            .trim_matches('\'')
-            .to_string();
-        (name, &after_db[end + 1..])
+        let name = unquote(&after_db[1..end]).to_string();
+        (Some(name), &after_db[end + 1..])
     } else if after_db.starts_with(".getCollection(") {
         let start = ".getCollection(".len();
         let end = after_db[start..]
             .find(')')
             .map(|i| i + start)
             .ok_or("Unmatched '(' in getCollection")?;
-        let name = after_db[start..end]
-            .trim()
-            .trim_matches('"')
-            .trim_matches('\'')
-            .to_string();
-        (name, &after_db[end + 1..])
-    } else if after_db.starts_with('.') {
-        let rest = &after_db[1..];
-        let dot_pos = rest
-            .find('.')
-            .ok_or(format!("Missing method on collection: {}", code))?;
-        (rest[..dot_pos].to_string(), &rest[dot_pos..])
+        let name = unquote(&after_db[start..end]).to_string();
+        (Some(name), &after_db[end + 1..])
+    } else if let Some(rest) = after_db.strip_prefix('.') {
+        match (rest.find('.'), rest.find('(')) {
+            // `db.method(...)` calls a database method, not a collection's.
+            (dot, Some(paren)) if dot.map_or(true, |dot| paren < dot) => (None, after_db),
+            (Some(dot_pos), _) => (Some(rest[..dot_pos].to_string()), &rest[dot_pos..]),
+            _ => return Err(format!("Missing method on collection: {}", code)),
+        }
     } else {
         return Err(format!(
             "Cannot parse collection from: {}",
@@ -647,15 +768,33 @@ fn parse_statement(code: &str) -> Result<ParsedStatement, String> {
 
     let (main_args_str, chain_str) = extract_balanced(&rest[paren_pos..])?;
     let args = split_top_level_args(&main_args_str);
-    let (chain_limit, chain_skip, chain_sort) = parse_chain(chain_str)?;
+
+    // `db.coll.explain('executionStats').find(...)` names the verbosity first.
+    if method == "explain" && collection.is_some() {
+        let verbosity = explain_verbosity(args.first().map(String::as_str).unwrap_or(""))?;
+        let mut explained = parse_statement(&format!("db.explained{}", chain_str))?;
+        if !matches!(
+            explained.method.as_str(),
+            "find" | "aggregate" | "count" | "countDocuments"
+        ) {
+            return Err(format!(
+                "explain() supports find, aggregate, count and countDocuments, not '{}'",
+                explained.method
+            ));
+        }
+        explained.database = database;
+        explained.collection = collection;
+        explained.chain.explain = Some(verbosity.to_string());
+        return Ok(explained);
+    }
+    let chain = parse_chain(chain_str)?;
 
     Ok(ParsedStatement {
+        database,
         collection,
         method,
         args,
-        chain_limit,
-        chain_skip,
-        chain_sort,
+        chain,
     })
 }
 
@@ -753,8 +892,12 @@ async fn execute_statement(
     db_name: &str,
     stmt: ParsedStatement,
 ) -> Result<Value, String> {
-    let db = client.database(db_name);
-    let coll = db.collection::<Document>(&stmt.collection);
+    let db = client.database(stmt.database.as_deref().unwrap_or(db_name));
+    let Some(collection) = stmt.collection.clone() else {
+        return execute_db_statement(&db, stmt).await;
+    };
+    let coll = db.collection::<Document>(&collection);
+    let chain = stmt.chain;
 
     match stmt.method.as_str() {
         "find" => {
@@ -771,9 +914,51 @@ async fn execute_statement(
                     }
                 }
             }
-            opts.limit = Some(stmt.chain_limit.unwrap_or(100).min(1000));
-            opts.skip = stmt.chain_skip;
-            opts.sort = stmt.chain_sort;
+            if chain.projection.is_some() {
+                opts.projection = chain.projection.clone();
+            }
+            opts.hint = chain.driver_hint();
+            opts.collation = chain.driver_collation()?;
+            opts.max_time = chain.max_time;
+            if chain.count {
+                if let Some(verbosity) = &chain.explain {
+                    let mut command = doc! { "count": &collection, "query": filter };
+                    if let Some(limit) = chain.limit {
+                        command.insert("limit", limit);
+                    }
+                    if let Some(skip) = chain.skip {
+                        command.insert("skip", skip as i64);
+                    }
+                    chain.insert_command_options(&mut command);
+                    return run_explain(&db, command, verbosity).await;
+                }
+                let mut count_opts = mongodb::options::CountOptions::default();
+                count_opts.hint = opts.hint;
+                count_opts.collation = opts.collation;
+                count_opts.max_time = opts.max_time;
+                let count = coll.count_documents(filter).with_options(count_opts).await.map_err(|e| e.to_string())?;
+                return Ok(Value::from(count));
+            }
+            if let Some(verbosity) = &chain.explain {
+                let mut command = doc! { "find": &collection, "filter": filter };
+                if let Some(projection) = opts.projection {
+                    command.insert("projection", projection);
+                }
+                if let Some(sort) = &chain.sort {
+                    command.insert("sort", sort.clone());
+                }
+                if let Some(limit) = chain.limit {
+                    command.insert("limit", limit);
+                }
+                if let Some(skip) = chain.skip {
+                    command.insert("skip", skip as i64);
+                }
+                chain.insert_command_options(&mut command);
+                return run_explain(&db, command, verbosity).await;
+            }
+            opts.limit = Some(chain.limit.unwrap_or(100).min(1000));
+            opts.skip = chain.skip;
+            opts.sort = chain.sort;
             let mut cursor = coll.find(filter).with_options(opts).await.map_err(|e| e.to_string())?;
             let mut docs: Vec<Value> = vec![];
             while let Some(d) = cursor.try_next().await.map_err(|e| e.to_string())? {
@@ -787,15 +972,68 @@ async fn execute_statement(
             } else {
                 parse_json_arg(&stmt.args[0]).and_then(json_to_bson_doc)?
             };
-            let result = coll.find_one(filter).await.map_err(|e| e.to_string())?;
+            let mut opts = mongodb::options::FindOneOptions::default();
+            if let Some(proj_raw) = stmt.args.get(1).filter(|raw| !raw.is_empty()) {
+                opts.projection = Some(parse_json_arg(proj_raw).and_then(json_to_bson_doc)?);
+            }
+            let result = coll.find_one(filter).with_options(opts).await.map_err(|e| e.to_string())?;
+            Ok(result.map(doc_to_json).unwrap_or(Value::Null))
+        }
+        "findOneAndReplace" => {
+            let filter = parse_json_arg(stmt.args.first().ok_or("findOneAndReplace requires filter")?).and_then(json_to_bson_doc)?;
+            let replacement = parse_json_arg(stmt.args.get(1).ok_or("findOneAndReplace requires replacement")?).and_then(json_to_bson_doc)?;
+            let options = statement_options(stmt.args.get(2))?;
+            let mut opts = mongodb::options::FindOneAndReplaceOptions::default();
+            opts.projection = option_doc(&options, "projection")?;
+            opts.sort = option_doc(&options, "sort")?;
+            opts.upsert = options.get("upsert").and_then(|v| v.as_bool());
+            opts.return_document = Some(return_document(&options));
+            let result = coll.find_one_and_replace(filter, replacement).with_options(opts).await.map_err(|e| e.to_string())?;
+            Ok(result.map(doc_to_json).unwrap_or(Value::Null))
+        }
+        "findOneAndDelete" => {
+            let filter = parse_json_arg(stmt.args.first().ok_or("findOneAndDelete requires a filter")?).and_then(json_to_bson_doc)?;
+            let options = statement_options(stmt.args.get(1))?;
+            let mut opts = mongodb::options::FindOneAndDeleteOptions::default();
+            opts.projection = option_doc(&options, "projection")?;
+            opts.sort = option_doc(&options, "sort")?;
+            let result = coll.find_one_and_delete(filter).with_options(opts).await.map_err(|e| e.to_string())?;
             Ok(result.map(doc_to_json).unwrap_or(Value::Null))
         }
-        "countDocuments" => {
+        "getIndexes" => {
+            let result = db.run_command(doc! { "listIndexes": &collection }).await.map_err(|e| e.to_string())?;
+            let indexes = result
+                .get_document("cursor")
+                .and_then(|cursor| cursor.get_array("firstBatch"))
+                .map_err(|e| e.to_string())?;
+            Ok(Value::Array(indexes.iter().map(crate::common::bson::bson_to_value).collect()))
+        }
+        "stats" => {
+            let mut command = doc! { "collStats": &collection };
+            // stats(1024) or stats({scale: 1024})
+            if let Some(raw) = stmt.args.first() {
+                let scale = match raw.parse::<i64>() {
+                    Ok(scale) => Some(scale),
+                    Err(_) => parse_json_arg(raw)?.get("scale").and_then(|v| v.as_i64()),
+                };
+                if let Some(scale) = scale {
+                    command.insert("scale", scale);
+                }
+            }
+            let result = db.run_command(command).await.map_err(|e| e.to_string())?;
+            Ok(doc_to_json(result))
+        }
+        "countDocuments" | "count" => {
             let filter = if stmt.args.is_empty() || stmt.args[0].is_empty() {
                 doc! {}
             } else {
                 parse_json_arg(&stmt.args[0]).and_then(json_to_bson_doc)?
             };
+            if let Some(verbosity) = &chain.explain {
+                let mut command = doc! { "count": &collection, "query": filter };
+                chain.insert_command_options(&mut command);
+                return run_explain(&db, command, verbosity).await;
+            }
             let count = coll.count_documents(filter).await.map_err(|e| e.to_string())?;
             Ok(Value::from(count))
         }
@@ -851,7 +1089,16 @@ async fn execute_statement(
             let pipeline_val = parse_json_arg(pipeline_raw)?;
             let stages = pipeline_val.as_array().ok_or("aggregate pipeline must be an array")?;
             let pipeline: Vec<Document> = stages.iter().map(|v| json_to_bson_doc(v.clone())).collect::<Result<Vec<_>, _>>()?;
-            let mut cursor = coll.aggregate(pipeline).await.map_err(|e| e.to_string())?;
+            if let Some(verbosity) = &chain.explain {
+                let mut command = doc! { "aggregate": &collection, "pipeline": pipeline, "cursor": {} };
+                chain.insert_command_options(&mut command);
+                return run_explain(&db, command, verbosity).await;
+            }
+            let mut opts = mongodb::options::AggregateOptions::default();
+            opts.hint = chain.driver_hint();
+            opts.collation = chain.driver_collation()?;
+            opts.max_time = chain.max_time;
+            let mut cursor = coll.aggregate(pipeline).with_options(opts).await.map_err(|e| e.to_string())?;
             let mut docs: Vec<Value> = vec![];
             while let Some(d) = cursor.try_next().await.map_err(|e| e.to_string())? {
                 docs.push(doc_to_json(d));
@@ -865,7 +1112,9 @@ async fn execute_statement(
                 .and_then(|r| parse_json_arg(r).and_then(json_to_bson_doc).ok())
                 .unwrap_or(doc! {});
             let result = coll.distinct(&field, filter).await.map_err(|e| e.to_string())?;
-            Ok(Value::Array(result.iter().map(bson_to_json).collect()))
+            Ok(Value::Array(
+                result.iter().map(crate::common::bson::bson_to_value).collect(),
+            ))
         }
         "createIndex" => {
             use mongodb::IndexModel;
@@ -894,7 +1143,7 @@ async fn execute_statement(
             let ops_raw = stmt.args.first().ok_or("bulkWrite requires an operations array")?;
             let ops_val = parse_json_arg(ops_raw)?;
             let ops_arr = ops_val.as_array().ok_or("bulkWrite argument must be an array")?;
-            let ns = Namespace::new(db_name, &stmt.collection);
+            let ns = Namespace::new(db.name(), &collection);
 
             let mut models: Vec<WriteModel> = Vec::with_capacity(ops_arr.len());
             for op in ops_arr {
@@ -959,12 +1208,85 @@ async fn execute_statement(
             }))
         }
         other => Err(format!(
-            "Unsupported method '{}'. Supported: find, findOne, countDocuments, estimatedDocumentCount, insertOne, insertMany, updateOne, updateMany, replaceOne, deleteOne, deleteMany, aggregate, distinct, createIndex, dropIndex, drop, bulkWrite",
+            "Unsupported method '{}'. Supported: find, findOne, findOneAndReplace, findOneAndDelete, countDocuments, estimatedDocumentCount, insertOne, insertMany, updateOne, updateMany, replaceOne, deleteOne, deleteMany, aggregate, distinct, createIndex, dropIndex, getIndexes, stats, drop, bulkWrite",
+            other
+        )),
+    }
+}
+
+/// Database-level statements: `db.getCollectionNames()`, `db.runCommand(...)`.
+async fn execute_db_statement(
+    db: &mongodb::Database,
+    stmt: ParsedStatement,
+) -> Result<Value, String> {
+    match stmt.method.as_str() {
+        "getCollectionNames" => {
+            let mut names = db.list_collection_names().await.map_err(|e| e.to_string())?;
+            names.sort();
+            Ok(Value::from(names))
+        }
+        "runCommand" => {
+            let raw = stmt.args.first().ok_or("runCommand requires a command document")?;
+            // runCommand('ping') is shorthand for runCommand({ping: 1}).
+            let command = if raw.starts_with(['"', '\'']) {
+                doc! { unquote(raw): 1 }
+            } else {
+                parse_json_arg(raw).and_then(json_to_bson_doc)?
+            };
+            let result = db.run_command(command).await.map_err(|e| e.to_string())?;
+            Ok(doc_to_json(result))
+        }
+        other => Err(format!(
+            "Unsupported database method '{}'. Supported: getCollectionNames, runCommand, getSiblingDB, getCollection",
             other
         )),
     }
 }
 
+/// Options document passed as a trailing argument, e.g. the third argument
+/// of `findOneAndReplace(filter, replacement, {upsert: true})`.
+fn statement_options(raw: Option<&String>) -> Result<Value, String> {
+    match raw {
+        Some(raw) if !raw.is_empty() => parse_json_arg(raw),
+        _ => Ok(Value::Object(serde_json::Map::new())),
+    }
+}
+
+fn option_doc(options: &Value, key: &str) -> Result<Option<Document>, String> {
+    options
+        .get(key)
+        .map(|v| json_to_bson_doc(v.clone()))
+        .transpose()
+}
+
+/// mongosh takes `returnDocument: "after"` or the legacy `returnNewDocument: true`.
+fn return_document(options: &Value) -> mongodb::options::ReturnDocument {
+    let after = options.get("returnDocument").and_then(|v| v.as_str()) == Some("after")
+        || options.get("returnNewDocument").and_then(|v| v.as_bool()) == Some(true);
+    if after {
+        mongodb::options::ReturnDocument::After
+    } else {
+        mongodb::options::ReturnDocument::Before
+    }
+}
+
+async fn run_explain(
+    db: &mongodb::Database,
+    command: Document,
+    verbosity: &str,
+) -> Result<Value, String> {
+    let result = db
+        .run_command(crate::common::mongo_explain::explain_command(
+            command, verbosity,
+        ))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({
+        "summary": crate::common::mongo_explain::summarize_explain(&result),
+        "explain": doc_to_json(result),
+    }))
+}
+
 #[tauri::command]
 pub async fn mongo_execute_query(
     app: tauri::AppHandle,
@@ -1031,6 +1353,7 @@ async fn build_client_tunneled(
     let mut client_options = ClientOptions::parse(&uri)
         .await
         .map_err(|e| format!("Failed to parse connection options: {}", e))?;
+    apply_connection_tls(&mut client_options, config)?;
     if socks5_port.is_some() {
         apply_socks5_proxy(&mut client_options, socks5_port);
     } else {
@@ -1129,18 +1452,474 @@ pub async fn mongo_export_documents(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Import writes
+// ---------------------------------------------------------------------------
+
+/// How an imported document is written when it matches an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportMode {
+    /// Always insert; a duplicate key is reported as a failure.
+    Insert,
+    /// Replace the matching document, or insert when none matches.
+    Replace,
+    /// `$set` the document's fields on the matching document, or insert.
+    Merge,
+    /// Insert only when nothing matches; matching documents are left as is.
+    InsertIfAbsent,
+}
+
+impl ImportMode {
+    /// `upsert` is the older boolean flag, kept for callers that do not pass
+    /// a mode.
+    fn parse(mode: Option<&str>, upsert: bool) -> Result<Self, String> {
+        match mode.filter(|m| !m.is_empty()) {
+            None if upsert => Ok(ImportMode::Merge),
+            None | Some("insert") => Ok(ImportMode::Insert),
+            Some("replace") => Ok(ImportMode::Replace),
+            Some("merge") => Ok(ImportMode::Merge),
+            Some("insert_if_absent") => Ok(ImportMode::InsertIfAbsent),
+            Some(other) => Err(format!(
+                "Unknown import mode '{}': expected insert, replace, merge or insert_if_absent",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ImportWrite {
+    Insert(Document),
+    Replace {
+        filter: Document,
+        replacement: Document,
+    },
+    Update {
+        filter: Document,
+        update: Document,
+    },
+}
+
+impl ImportWrite {
+    fn into_model(self, ns: &mongodb::Namespace) -> mongodb::options::WriteModel {
+        use mongodb::options::{InsertOneModel, ReplaceOneModel, UpdateOneModel, WriteModel};
+
+        match self {
+            ImportWrite::Insert(document) => WriteModel::InsertOne(
+                InsertOneModel::builder()
+                    .namespace(ns.clone())
+                    .document(document)
+                    .build(),
+            ),
+            ImportWrite::Replace {
+                filter,
+                replacement,
+            } => WriteModel::ReplaceOne(
+                ReplaceOneModel::builder()
+                    .namespace(ns.clone())
+                    .filter(filter)
+                    .replacement(replacement)
+                    .upsert(true)
+                    .build(),
+            ),
+            ImportWrite::Update { filter, update } => WriteModel::UpdateOne(
+                UpdateOneModel::builder()
+                    .namespace(ns.clone())
+                    .filter(filter)
+                    .update(update)
+                    .upsert(true)
+                    .build(),
+            ),
+        }
+    }
+}
+
+/// The value at a dotted `path` in `doc`.
+fn doc_path<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut value = doc.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// The write for one imported document. The filter matches on
+/// `match_fields` with whatever BSON type the values have; a document
+/// without an `_id` is simply inserted when matching on `_id`.
+fn import_write(
+    mut doc: Document,
+    mode: ImportMode,
+    match_fields: &[String],
+) -> Result<ImportWrite, String> {
+    if mode == ImportMode::Insert {
+        return Ok(ImportWrite::Insert(doc));
+    }
+    let matches_id = match_fields.iter().any(|f| f == "_id");
+    let mut filter = Document::new();
+    for field in match_fields {
+        match doc_path(&doc, field).filter(|v| !matches!(v, Bson::Null)) {
+            Some(value) => {
+                filter.insert(field.clone(), value.clone());
+            }
+            None if matches_id && match_fields.len() == 1 => {
+                return Ok(ImportWrite::Insert(doc));
+            }
+            None => return Err(format!("missing match field '{}'", field)),
+        }
+    }
+
+    Ok(match mode {
+        ImportMode::Insert => ImportWrite::Insert(doc),
+        ImportMode::Replace => ImportWrite::Replace {
+            filter,
+            replacement: doc,
+        },
+        ImportMode::Merge => {
+            // `_id` is immutable, so it may only be set when inserting.
+            let id = doc.remove("_id");
+            let mut update = Document::new();
+            if !doc.is_empty() {
+                update.insert("$set", doc);
+            }
+            if let Some(id) = id {
+                if !matches_id || update.is_empty() {
+                    update.insert("$setOnInsert", doc! { "_id": id });
+                }
+            }
+            ImportWrite::Update { filter, update }
+        }
+        ImportMode::InsertIfAbsent => ImportWrite::Update {
+            filter,
+            update: doc! { "$setOnInsert": doc },
+        },
+    })
+}
+
+#[derive(Debug, Default)]
+struct ImportTally {
+    inserted: i64,
+    updated: i64,
+    skipped: i64,
+    errors: Vec<Value>,
+}
+
+impl ImportTally {
+    fn failed(&mut self, index: usize, error: impl ToString) {
+        self.skipped += 1;
+        self.errors.push(serde_json::json!({
+            "index": index,
+            "error": error.to_string(),
+        }));
+    }
+
+    fn upserted(&mut self, mode: ImportMode, inserted: bool) {
+        if inserted {
+            self.inserted += 1;
+        } else if mode == ImportMode::InsertIfAbsent {
+            self.skipped += 1;
+        } else {
+            self.updated += 1;
+        }
+    }
+
+    fn not_written(&mut self, count: usize) {
+        if count > 0 {
+            self.skipped += count as i64;
+            self.errors.push(serde_json::json!({
+                "index": null,
+                "error": format!("{} documents were not written after an earlier error", count),
+            }));
+        }
+    }
+
+    /// A request that failed as a whole, so none of its `count` documents
+    /// is confirmed written.
+    fn batch_failed(&mut self, count: usize, error: impl std::fmt::Display) {
+        self.skipped += count as i64;
+        self.errors.push(serde_json::json!({
+            "index": null,
+            "error": format!("{} documents not confirmed: {}", count, error),
+        }));
+    }
+}
+
+/// Sends the writes as one client `bulkWrite`. `writes` pairs each write with
+/// the index of its document in the import batch. Returns `false`, having
+/// written nothing, when the server predates `bulkWrite` (MongoDB 8.0).
+async fn bulk_write_import(
+    client: &Client,
+    ns: &mongodb::Namespace,
+    writes: &[(usize, ImportWrite)],
+    mode: ImportMode,
+    ordered: bool,
+    tally: &mut ImportTally,
+) -> bool {
+    use mongodb::error::{ErrorKind, PartialBulkWriteResult};
+
+    let models: Vec<_> = writes
+        .iter()
+        .map(|(_, write)| write.clone().into_model(ns))
+        .collect();
+    let (results, write_errors) = match client
+        .bulk_write(models)
+        .ordered(ordered)
+        .verbose_results()
+        .await
+    {
+        Ok(results) => (Some(results), Default::default()),
+        Err(e) => match *e.kind {
+            ErrorKind::ClientBulkWrite(failure) => {
+                for error in &failure.write_concern_errors {
+                    tally.errors.push(serde_json::json!({
+                        "index": null,
+                        "error": error.message,
+                    }));
+                }
+                let results = match failure.partial_result {
+                    Some(PartialBulkWriteResult::Verbose(results)) => Some(results),
+                    _ => None,
+                };
+                (results, failure.write_errors)
+            }
+            ErrorKind::IncompatibleServer { .. } => return false,
+            other => {
+                tally.batch_failed(writes.len(), other);
+                return true;
+            }
+        },
+    };
+
+    let mut unwritten = 0;
+    for (model_index, (doc_index, _)) in writes.iter().enumerate() {
+        if let Some(error) = write_errors.get(&model_index) {
+            tally.failed(*doc_index, &error.message);
+        } else if let Some(update) = results
+            .as_ref()
+            .and_then(|r| r.update_results.get(&model_index))
+        {
+            tally.upserted(mode, update.upserted_id.is_some());
+        } else if results
+            .as_ref()
+            .is_some_and(|r| r.insert_results.contains_key(&model_index))
+        {
+            tally.inserted += 1;
+        } else {
+            unwritten += 1;
+        }
+    }
+    tally.not_written(unwritten);
+    true
+}
+
+/// Limits for one fallback `insert`/`update` command, well below the
+/// server's 100,000 writes and 48 MB message size.
+const IMPORT_COMMAND_MAX_WRITES: usize = 1000;
+const IMPORT_COMMAND_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// A collection-level `insert` or `update` command and, per statement, the
+/// index of its document in the import batch.
+#[derive(Debug, PartialEq)]
+struct ImportCommand {
+    command: Document,
+    doc_indexes: Vec<usize>,
+}
+
+impl ImportCommand {
+    fn is_update(&self) -> bool {
+        self.command.contains_key("update")
+    }
+}
+
+/// Batches the writes into `insert` and `update` commands for servers
+/// without client `bulkWrite`. Consecutive writes of one kind share a
+/// command, so an ordered import keeps its order.
+fn import_commands(
+    collection: &str,
+    writes: Vec<(usize, ImportWrite)>,
+    ordered: bool,
+) -> Vec<ImportCommand> {
+    let mut commands: Vec<ImportCommand> = Vec::new();
+    let mut statements: Vec<Bson> = Vec::new();
+    let mut doc_indexes: Vec<usize> = Vec::new();
+    let mut batch_bytes = 0;
+    let mut batch_is_update = false;
+
+    let mut flush = |statements: &mut Vec<Bson>, doc_indexes: &mut Vec<usize>, is_update: bool| {
+        if statements.is_empty() {
+            return;
+        }
+        let (verb, field) = if is_update {
+            ("update", "updates")
+        } else {
+            ("insert", "documents")
+        };
+        let mut command = Document::new();
+        command.insert(verb, collection);
+        command.insert(field, std::mem::take(statements));
+        command.insert("ordered", ordered);
+        commands.push(ImportCommand {
+            command,
+            doc_indexes: std::mem::take(doc_indexes),
+        });
+    };
+
+    for (index, write) in writes {
+        let (is_update, statement) = match write {
+            ImportWrite::Insert(document) => (false, document),
+            ImportWrite::Replace {
+                filter,
+                replacement,
+            } => (true, doc! { "q": filter, "u": replacement, "upsert": true }),
+            ImportWrite::Update { filter, update } => {
+                (true, doc! { "q": filter, "u": update, "upsert": true })
+            }
+        };
+        let bytes = mongodb::bson::to_vec(&statement).map_or(0, |b| b.len());
+        if is_update != batch_is_update
+            || statements.len() >= IMPORT_COMMAND_MAX_WRITES
+            || batch_bytes + bytes > IMPORT_COMMAND_MAX_BYTES
+        {
+            flush(&mut statements, &mut doc_indexes, batch_is_update);
+            batch_bytes = 0;
+        }
+        batch_is_update = is_update;
+        batch_bytes += bytes;
+        statements.push(Bson::Document(statement));
+        doc_indexes.push(index);
+    }
+    flush(&mut statements, &mut doc_indexes, batch_is_update);
+    commands
+}
+
+/// Statement indexes listed under `key` (`writeErrors` or `upserted`) in an
+/// `insert`/`update` reply, with the entries themselves.
+fn reply_entries<'a>(reply: &'a Document, key: &str) -> Vec<(usize, &'a Document)> {
+    reply
+        .get_array(key)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(Bson::as_document)
+                .filter_map(|entry| {
+                    let index = match entry.get("index")? {
+                        Bson::Int32(n) => usize::try_from(*n).ok()?,
+                        Bson::Int64(n) => usize::try_from(*n).ok()?,
+                        _ => return None,
+                    };
+                    Some((index, entry))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Tallies one `insert`/`update` reply, mapping statement indexes back to
+/// document indexes. Returns whether any statement failed.
+fn tally_command_reply(
+    command: &ImportCommand,
+    reply: &Document,
+    mode: ImportMode,
+    ordered: bool,
+    tally: &mut ImportTally,
+) -> bool {
+    let write_errors = reply_entries(reply, "writeErrors");
+    let upserted: Vec<usize> = reply_entries(reply, "upserted")
+        .into_iter()
+        .map(|(index, _)| index)
+        .collect();
+    if let Ok(error) = reply.get_document("writeConcernError") {
+        tally.errors.push(serde_json::json!({
+            "index": null,
+            "error": error.get_str("errmsg").unwrap_or("write concern error"),
+        }));
+    }
+    // An ordered command stops at its first error.
+    let stopped_at = if ordered {
+        write_errors.iter().map(|(index, _)| *index).min()
+    } else {
+        None
+    };
+
+    let mut unwritten = 0;
+    for (statement, doc_index) in command.doc_indexes.iter().enumerate() {
+        if let Some((_, error)) = write_errors.iter().find(|(index, _)| *index == statement) {
+            tally.failed(*doc_index, error.get_str("errmsg").unwrap_or("write error"));
+        } else if stopped_at.is_some_and(|stop| statement > stop) {
+            unwritten += 1;
+        } else if command.is_update() {
+            tally.upserted(mode, upserted.contains(&statement));
+        } else {
+            tally.inserted += 1;
+        }
+    }
+    tally.not_written(unwritten);
+    !write_errors.is_empty()
+}
+
+/// Writes through batched collection-level `insert` and `update` commands,
+/// for servers older than MongoDB 8.0 that have no client `bulkWrite`.
+async fn command_write_import(
+    db: &mongodb::Database,
+    collection: &str,
+    writes: Vec<(usize, ImportWrite)>,
+    mode: ImportMode,
+    ordered: bool,
+    tally: &mut ImportTally,
+) {
+    let commands = import_commands(collection, writes, ordered);
+    for (position, command) in commands.iter().enumerate() {
+        let failed = match db.run_command(command.command.clone()).await {
+            Ok(reply) => tally_command_reply(command, &reply, mode, ordered, tally),
+            Err(e) => {
+                tally.batch_failed(command.doc_indexes.len(), e);
+                true
+            }
+        };
+        if failed && ordered {
+            let remaining = commands[position + 1..]
+                .iter()
+                .map(|c| c.doc_indexes.len())
+                .sum();
+            tally.not_written(remaining);
+            break;
+        }
+    }
+}
+
+/// Imports a batch of Extended JSON documents. `mode` picks insert, replace,
+/// merge (`$set`) or insert_if_absent; all but insert match existing
+/// documents on `match_fields` (default `_id`). Writes go through one
+/// unordered `bulkWrite` unless `ordered` is set (batched `insert`/`update`
+/// commands before MongoDB 8.0), and every document that fails is reported
+/// with its index in the batch. A request that fails as a whole is reported
+/// as an error without an index alongside what was already written.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn mongo_import_documents(
     app: tauri::AppHandle,
     config: MongoConnectionConfig,
     collection: String,
     documents: Vec<String>,
     upsert: Option<bool>,
+    mode: Option<String>,
+    match_fields: Option<Vec<String>>,
+    ordered: Option<bool>,
     ssh_tunnel: Option<serde_json::Value>,
 ) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
     use crate::common::response::ApiResponse;
     use crate::common::ssh_bridge::resolve_ssh_tunnel;
 
+    let mode = match ImportMode::parse(mode.as_deref(), upsert.unwrap_or(false)) {
+        Ok(mode) => mode,
+        Err(e) => return Ok(ApiResponse::err(400, e)),
+    };
+    let match_fields: Vec<String> = match_fields
+        .map(|fields| fields.into_iter().filter(|f| !f.is_empty()).collect())
+        .filter(|fields: &Vec<String>| !fields.is_empty())
+        .unwrap_or_else(|| vec!["_id".to_string()]);
+    let ordered = ordered.unwrap_or(false);
+
     let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
     let endpoint =
         resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
@@ -1160,96 +1939,537 @@ pub async fn mongo_import_documents(
     let db = client.database(&db_name);
     let coll = db.collection::<Document>(&collection);
 
-    let upsert_mode = upsert.unwrap_or(false);
-    let mut inserted = 0i64;
-    let mut updated = 0i64;
-    let mut skipped = 0i64;
-    let mut errors: Vec<String> = vec![];
-
-    if upsert_mode {
-        for (idx, doc_str) in documents.iter().enumerate() {
-            let bson_doc = match parse_json_arg(doc_str).and_then(json_to_bson_doc) {
-                Ok(d) => d,
-                Err(e) => {
-                    skipped += 1;
-                    errors.push(format!("Doc {}: {}", idx, e));
-                    continue;
-                }
-            };
-
-            let id = bson_doc.get_object_id("_id").ok().map(|id| id.clone());
-            if let Some(oid) = id {
-                let filter = doc! { "_id": oid };
-                let update = doc! { "$set": &bson_doc };
-                let opts = mongodb::options::UpdateOptions::builder()
-                    .upsert(true)
-                    .build();
-                match coll.update_one(filter, update).with_options(opts).await {
-                    Ok(result) => {
-                        if result.upserted_id.is_some() {
-                            inserted += 1;
-                        } else {
-                            updated += 1;
-                        }
-                    }
-                    Err(e) => {
-                        skipped += 1;
-                        errors.push(format!("Doc {}: {}", idx, e));
-                    }
-                }
-            } else {
-                match coll.insert_one(bson_doc).await {
-                    Ok(_) => {
-                        inserted += 1;
-                    }
-                    Err(e) => {
-                        skipped += 1;
-                        errors.push(format!("Doc {}: {}", idx, e));
-                    }
-                }
-            }
-        }
-    } else {
-        let mut docs_vec: Vec<Document> = Vec::new();
-        for (idx, doc_str) in documents.iter().enumerate() {
-            match parse_json_arg(doc_str).and_then(json_to_bson_doc) {
-                Ok(bson_doc) => {
-                    docs_vec.push(bson_doc);
-                }
-                Err(e) => {
-                    skipped += 1;
-                    errors.push(format!("Doc {}: {}", idx, e));
-                }
-            }
+    let mut tally = ImportTally::default();
+    let mut writes: Vec<(usize, ImportWrite)> = Vec::new();
+    for (idx, doc_str) in documents.iter().enumerate() {
+        match parse_json_arg(doc_str)
+            .and_then(json_to_bson_doc)
+            .and_then(|doc| import_write(doc, mode, &match_fields))
+        {
+            Ok(write) => writes.push((idx, write)),
+            Err(e) => tally.failed(idx, e),
         }
+    }
 
-        if !docs_vec.is_empty() {
-            match coll.insert_many(docs_vec).await {
-                Ok(result) => {
-                    inserted = result.inserted_ids.len() as i64;
-                }
-                Err(e) => {
-                    return Ok(ApiResponse::err(500, format!("Bulk insert failed: {}", e)));
-                }
-            }
+    if !writes.is_empty() {
+        let ns = coll.namespace();
+        if !bulk_write_import(&client, &ns, &writes, mode, ordered, &mut tally).await {
+            command_write_import(&db, &collection, writes, mode, ordered, &mut tally).await;
         }
     }
 
     let data = serde_json::json!({
-        "inserted": inserted,
-        "updated": updated,
-        "skipped": skipped,
-        "errors": if errors.is_empty() { serde_json::Value::Null } else { serde_json::json!(errors) },
+        "inserted": tally.inserted,
+        "updated": tally.updated,
+        "skipped": tally.skipped,
+        "errors": if tally.errors.is_empty() { Value::Null } else { Value::from(tally.errors) },
     });
     Ok(ApiResponse::ok(data))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// ---------------------------------------------------------------------------
+// GridFS transfers
+// ---------------------------------------------------------------------------
 
-    fn uri_config(uri: &str) -> MongoConnectionConfig {
-        MongoConnectionConfig {
+fn gridfs_target(
+    config: &MongoConnectionConfig,
+    database: Option<String>,
+    bucket: Option<String>,
+) -> (String, String) {
+    let database = database
+        .filter(|s| !s.is_empty())
+        .or_else(|| config.database.clone().filter(|s| !s.is_empty()))
+        .unwrap_or_else(|| "test".to_string());
+    let bucket = bucket
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| crate::common::mongo_gridfs::DEFAULT_BUCKET.to_string());
+    (database, bucket)
+}
+
+/// Streams a GridFS file to `path` on the local disk.
+#[tauri::command]
+pub async fn mongo_gridfs_download(
+    app: tauri::AppHandle,
+    config: MongoConnectionConfig,
+    database: Option<String>,
+    bucket: Option<String>,
+    file_id: Value,
+    path: String,
+    ssh_tunnel: Option<serde_json::Value>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::mongo_gridfs;
+    use crate::common::response::ApiResponse;
+    use crate::common::ssh_bridge::resolve_ssh_tunnel;
+
+    let id = match mongo_gridfs::file_id_from_json(&file_id) {
+        Ok(id) => id,
+        Err(e) => return Ok(ApiResponse::err(400, e)),
+    };
+    let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
+    let endpoint =
+        resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
+    let client = match build_client_tunneled(
+        &config,
+        tunnel_port(ssh_enabled, &endpoint.host, endpoint.port),
+        endpoint.socks5_port,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => return Ok(ApiResponse::err(500, e)),
+    };
+    let (database, bucket) = gridfs_target(&config, database, bucket);
+    let bucket = mongo_gridfs::bucket(&client.database(&database), &bucket);
+
+    match mongo_gridfs::download_to_path(&bucket, id, std::path::Path::new(&path)).await {
+        Ok(length) => Ok(ApiResponse::ok(serde_json::json!({
+            "path": path,
+            "length": length,
+        }))),
+        Err(e) => Ok(ApiResponse::err(500, e)),
+    }
+}
+
+/// Streams the local file at `path` into a GridFS bucket. The stored
+/// filename defaults to the file's own name.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn mongo_gridfs_upload(
+    app: tauri::AppHandle,
+    config: MongoConnectionConfig,
+    database: Option<String>,
+    bucket: Option<String>,
+    path: String,
+    filename: Option<String>,
+    metadata: Option<Value>,
+    ssh_tunnel: Option<serde_json::Value>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::mongo_gridfs;
+    use crate::common::response::ApiResponse;
+    use crate::common::ssh_bridge::resolve_ssh_tunnel;
+
+    let local_path = std::path::PathBuf::from(&path);
+    let filename = match filename.filter(|s| !s.is_empty()).or_else(|| {
+        local_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }) {
+        Some(name) => name,
+        None => return Ok(ApiResponse::err(400, format!("No file name in {}", path))),
+    };
+    let metadata = match metadata.filter(|m| !m.is_null()).map(json_to_bson_doc) {
+        Some(Ok(doc)) => Some(doc),
+        Some(Err(e)) => return Ok(ApiResponse::err(400, e)),
+        None => None,
+    };
+    let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
+    let endpoint =
+        resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
+    let client = match build_client_tunneled(
+        &config,
+        tunnel_port(ssh_enabled, &endpoint.host, endpoint.port),
+        endpoint.socks5_port,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => return Ok(ApiResponse::err(500, e)),
+    };
+    let (database, bucket) = gridfs_target(&config, database, bucket);
+    let bucket = mongo_gridfs::bucket(&client.database(&database), &bucket);
+
+    match mongo_gridfs::upload_from_path(&bucket, &local_path, &filename, metadata).await {
+        Ok((id, length)) => Ok(ApiResponse::ok(serde_json::json!({
+            "id": crate::common::bson::bson_to_value(&id),
+            "filename": filename,
+            "length": length,
+        }))),
+        Err(e) => Ok(ApiResponse::err(500, e)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// mongodump-compatible dump and restore
+// ---------------------------------------------------------------------------
+
+/// Dumps a database, or one collection of it, into `path/<database>/` in the
+/// mongodump directory layout.
+#[tauri::command]
+pub async fn mongo_dump(
+    app: tauri::AppHandle,
+    config: MongoConnectionConfig,
+    database: Option<String>,
+    collection: Option<String>,
+    path: String,
+    gzip: Option<bool>,
+    ssh_tunnel: Option<serde_json::Value>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::mongo_dump;
+    use crate::common::response::ApiResponse;
+    use crate::common::ssh_bridge::resolve_ssh_tunnel;
+
+    let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
+    let endpoint =
+        resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
+    let client = match build_client_tunneled(
+        &config,
+        tunnel_port(ssh_enabled, &endpoint.host, endpoint.port),
+        endpoint.socks5_port,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => return Ok(ApiResponse::err(500, e)),
+    };
+    let db_name = database
+        .filter(|s| !s.is_empty())
+        .or_else(|| config.database.clone().filter(|s| !s.is_empty()))
+        .unwrap_or_else(|| "test".to_string());
+    let db = client.database(&db_name);
+    let out_dir = std::path::Path::new(&path);
+
+    match mongo_dump::dump_database(
+        &db,
+        out_dir,
+        collection.as_deref().filter(|s| !s.is_empty()),
+        gzip.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(collections) => Ok(ApiResponse::ok(serde_json::json!({
+            "path": out_dir.join(&db_name).to_string_lossy(),
+            "collections": collections,
+        }))),
+        Err(e) => Ok(ApiResponse::err(500, e)),
+    }
+}
+
+/// Restores a mongodump directory (one database's `.bson` and
+/// `.metadata.json` files, plain or gzipped) with its collection options,
+/// validators and indexes. The target database defaults to the directory's
+/// name.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn mongo_restore(
+    app: tauri::AppHandle,
+    config: MongoConnectionConfig,
+    database: Option<String>,
+    collection: Option<String>,
+    path: String,
+    drop: Option<bool>,
+    bypass_document_validation: Option<bool>,
+    ssh_tunnel: Option<serde_json::Value>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::mongo_dump::{self, RestoreOptions};
+    use crate::common::response::ApiResponse;
+    use crate::common::ssh_bridge::resolve_ssh_tunnel;
+
+    let dump_dir = std::path::PathBuf::from(&path);
+    let db_name = match database.filter(|s| !s.is_empty()).or_else(|| {
+        dump_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }) {
+        Some(name) => name,
+        None => return Ok(ApiResponse::err(400, "Missing database".to_string())),
+    };
+    let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
+    let endpoint =
+        resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
+    let client = match build_client_tunneled(
+        &config,
+        tunnel_port(ssh_enabled, &endpoint.host, endpoint.port),
+        endpoint.socks5_port,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => return Ok(ApiResponse::err(500, e)),
+    };
+    let options = RestoreOptions {
+        drop: drop.unwrap_or(false),
+        bypass_document_validation: bypass_document_validation.unwrap_or(false),
+    };
+
+    match mongo_dump::restore_database(
+        &client.database(&db_name),
+        &dump_dir,
+        collection.as_deref().filter(|s| !s.is_empty()),
+        &options,
+    )
+    .await
+    {
+        Ok(collections) => Ok(ApiResponse::ok(serde_json::json!({
+            "database": db_name,
+            "collections": collections,
+        }))),
+        Err(e) => Ok(ApiResponse::err(500, e)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Live change stream feed
+// ---------------------------------------------------------------------------
+
+/// Change stream feeds started from the UI, keyed by the caller's watch id.
+/// Each entry carries a generation so a finished feed only removes itself,
+/// never a newer feed started under the same id.
+#[derive(Default)]
+pub struct MongoWatches(
+    std::sync::Mutex<std::collections::HashMap<String, (u64, tokio::task::AbortHandle)>>,
+);
+
+/// Watch ids name the resume-token file, so they are used verbatim and
+/// anything outside a safe character set is rejected rather than rewritten
+/// (rewriting would let two ids share one token file).
+fn validate_watch_id(watch_id: &str) -> Result<(), String> {
+    if watch_id.is_empty() || watch_id.len() > 128 {
+        return Err("watch_id must be 1 to 128 characters".to_string());
+    }
+    if !watch_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "watch_id '{}' may only contain letters, digits, '-' and '_'",
+            watch_id
+        ));
+    }
+    Ok(())
+}
+
+fn watch_token_path(app: &tauri::AppHandle, watch_id: &str) -> Result<std::path::PathBuf, String> {
+    use tauri::Manager;
+
+    validate_watch_id(watch_id)?;
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("mongo-watch");
+    Ok(dir.join(format!("{}.json", watch_id)))
+}
+
+fn load_resume_token(path: &std::path::Path) -> Option<Value> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    let saved: Value = serde_json::from_str(&raw).ok()?;
+    saved.get("resumeToken").filter(|t| !t.is_null()).cloned()
+}
+
+fn save_resume_token(path: &std::path::Path, token: &Value) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let body = serde_json::json!({ "resumeToken": token });
+    std::fs::write(path, body.to_string())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn watch_target_for(
+    config: &MongoConnectionConfig,
+    database: Option<String>,
+    collection: Option<String>,
+) -> crate::common::mongo::WatchTarget {
+    use crate::common::mongo::WatchTarget;
+
+    let database = database
+        .filter(|s| !s.is_empty())
+        .or_else(|| config.database.clone().filter(|s| !s.is_empty()));
+    match (database, collection.filter(|s| !s.is_empty())) {
+        (database, Some(collection)) => WatchTarget::Collection {
+            database: database.unwrap_or_else(|| "test".to_string()),
+            collection,
+        },
+        (Some(database), None) => WatchTarget::Database(database),
+        (None, None) => WatchTarget::Deployment,
+    }
+}
+
+/// Opens a change stream and forwards every event to the UI as
+/// `mongo-watch-event`. The resume token is saved after each batch, so a
+/// later start with the same `watch_id` continues where this one stopped.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn mongo_watch_start(
+    app: tauri::AppHandle,
+    watches: tauri::State<'_, MongoWatches>,
+    config: MongoConnectionConfig,
+    watch_id: String,
+    database: Option<String>,
+    collection: Option<String>,
+    pipeline: Option<Value>,
+    full_document: Option<String>,
+    resume: Option<bool>,
+    ssh_tunnel: Option<serde_json::Value>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::response::ApiResponse;
+    use crate::common::ssh_bridge::resolve_ssh_tunnel;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tauri::{Emitter, Manager};
+
+    static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+    if let Err(e) = validate_watch_id(&watch_id) {
+        return Ok(ApiResponse::err(400, e));
+    }
+    let pipeline: Vec<Document> = match pipeline {
+        Some(Value::Array(stages)) => match stages
+            .into_iter()
+            .map(json_to_bson_doc)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(p) => p,
+            Err(e) => return Ok(ApiResponse::err(400, e)),
+        },
+        Some(Value::Null) | None => Vec::new(),
+        Some(_) => return Ok(ApiResponse::err(400, "pipeline must be an array of stages")),
+    };
+    let token_path = watch_token_path(&app, &watch_id)?;
+    let resume_after = if resume.unwrap_or(true) {
+        load_resume_token(&token_path)
+    } else {
+        let _ = std::fs::remove_file(&token_path);
+        None
+    };
+    let options = match crate::common::mongo::change_stream_options(
+        full_document.as_deref(),
+        resume_after.as_ref(),
+    ) {
+        Ok(o) => o,
+        Err(e) => return Ok(ApiResponse::err(400, e)),
+    };
+
+    let ssh_enabled = is_ssh_enabled(ssh_tunnel.as_ref());
+    let endpoint =
+        resolve_ssh_tunnel(&app, ssh_tunnel.as_ref(), &config.host, config.port, false).await?;
+    let client = match build_client_tunneled(
+        &config,
+        tunnel_port(ssh_enabled, &endpoint.host, endpoint.port),
+        endpoint.socks5_port,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => return Ok(ApiResponse::err(500, e)),
+    };
+
+    let target = watch_target_for(&config, database, collection);
+    let mut stream =
+        match crate::common::mongo::open_change_stream(&client, &target, pipeline, options).await {
+            Ok(s) => s,
+            Err(e) => return Ok(ApiResponse::err(500, e)),
+        };
+
+    let resumed = resume_after.is_some();
+    let task_app = app.clone();
+    let task_id = watch_id.clone();
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    // Held until the new feed is registered, so a feed that ends at once
+    // still finds its own entry to remove.
+    let mut running = watches.0.lock().map_err(|e| e.to_string())?;
+    let task = tokio::spawn(async move {
+        let mut saved = resume_after.unwrap_or(Value::Null);
+        loop {
+            let next = stream.next_if_any().await;
+            let token = crate::common::mongo::change_stream_resume_token(&stream);
+            if !token.is_null() && token != saved {
+                if let Err(e) = save_resume_token(&token_path, &token) {
+                    let _ = task_app.emit(
+                        "mongo-watch-error",
+                        serde_json::json!({ "watchId": task_id, "error": e }),
+                    );
+                }
+                saved = token.clone();
+            }
+            match next {
+                Ok(Some(event)) => {
+                    let _ = task_app.emit(
+                        "mongo-watch-event",
+                        serde_json::json!({
+                            "watchId": task_id,
+                            "event": crate::common::bson::doc_to_value(&event),
+                            "resumeToken": token,
+                        }),
+                    );
+                }
+                Ok(None) if stream.is_alive() => {}
+                Ok(None) => break,
+                Err(e) => {
+                    let _ = task_app.emit(
+                        "mongo-watch-error",
+                        serde_json::json!({
+                            "watchId": task_id,
+                            "error": format!("change stream error: {}", e),
+                        }),
+                    );
+                    break;
+                }
+            }
+        }
+        let _ = task_app.emit(
+            "mongo-watch-end",
+            serde_json::json!({ "watchId": task_id, "invalidated": !stream.is_alive() }),
+        );
+        if let Ok(mut running) = task_app.state::<MongoWatches>().0.lock() {
+            if running.get(&task_id).is_some_and(|(g, _)| *g == generation) {
+                running.remove(&task_id);
+            }
+        }
+    });
+
+    let previous = running.insert(watch_id.clone(), (generation, task.abort_handle()));
+    drop(running);
+    if let Some((_, previous)) = previous {
+        previous.abort();
+    }
+
+    Ok(ApiResponse::ok(serde_json::json!({
+        "watchId": watch_id,
+        "target": target.describe(),
+        "resumed": resumed,
+    })))
+}
+
+/// Stops a feed started by `mongo_watch_start`. With `forget`, the saved
+/// resume token is deleted too, so the next start begins at the current time.
+#[tauri::command]
+pub async fn mongo_watch_stop(
+    app: tauri::AppHandle,
+    watches: tauri::State<'_, MongoWatches>,
+    watch_id: String,
+    forget: Option<bool>,
+) -> Result<crate::common::response::ApiResponse<serde_json::Value>, String> {
+    use crate::common::response::ApiResponse;
+
+    let running = watches
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&watch_id);
+    let stopped = match running {
+        Some((_, handle)) => {
+            let was_running = !handle.is_finished();
+            handle.abort();
+            was_running
+        }
+        None => false,
+    };
+    if forget.unwrap_or(false) {
+        let _ = std::fs::remove_file(watch_token_path(&app, &watch_id)?);
+    }
+    Ok(ApiResponse::ok(serde_json::json!({
+        "watchId": watch_id,
+        "stopped": stopped,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri_config(uri: &str) -> MongoConnectionConfig {
+        MongoConnectionConfig {
             host: "mongo.example.com".to_string(),
             port: 27017,
             auth: MongoAuth::Uri {
@@ -1258,6 +2478,7 @@ mod tests {
             database: None,
             tls: None,
             tunnel_port: None,
+            tls_settings: None,
         }
     }
 
@@ -1311,6 +2532,7 @@ mod tests {
             database: Some("app".to_string()),
             tls: None,
             tunnel_port: None,
+            tls_settings: None,
         };
         assert_eq!(
             build_uri_tunneled(&config, None).unwrap(),
@@ -1328,6 +2550,7 @@ mod tests {
             database: None,
             tls,
             tunnel_port: None,
+            tls_settings: None,
         }
     }
 
@@ -1426,4 +2649,368 @@ mod tests {
         assert_eq!(tunnel_port(true, "mongo.example.com", 27017), None);
         assert_eq!(tunnel_port(false, "127.0.0.1", 51234), None);
     }
+
+    #[test]
+    fn watch_target_prefers_collection_then_database() {
+        use crate::common::mongo::WatchTarget;
+
+        let config: MongoConnectionConfig = serde_json::from_value(serde_json::json!({
+            "host": "localhost", "port": 27017, "auth": {"kind": "none"}, "database": "app",
+        }))
+        .unwrap();
+        assert_eq!(
+            watch_target_for(&config, None, Some("orders".into())),
+            WatchTarget::Collection {
+                database: "app".into(),
+                collection: "orders".into()
+            }
+        );
+        assert_eq!(
+            watch_target_for(&config, Some("logs".into()), None),
+            WatchTarget::Database("logs".into())
+        );
+        let no_db: MongoConnectionConfig = serde_json::from_value(serde_json::json!({
+            "host": "localhost", "port": 27017, "auth": {"kind": "none"},
+        }))
+        .unwrap();
+        assert_eq!(
+            watch_target_for(&no_db, None, None),
+            WatchTarget::Deployment
+        );
+    }
+
+    #[test]
+    fn validate_watch_id_rejects_ids_that_would_need_rewriting() {
+        assert!(validate_watch_id("orders-feed_1").is_ok());
+        assert!(validate_watch_id("a/b").is_err());
+        assert!(validate_watch_id("../token").is_err());
+        assert!(validate_watch_id("").is_err());
+        assert!(validate_watch_id(&"x".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn gridfs_target_defaults_to_config_database_and_fs_bucket() {
+        let config: MongoConnectionConfig = serde_json::from_value(serde_json::json!({
+            "host": "localhost", "port": 27017, "auth": {"kind": "none"}, "database": "app",
+        }))
+        .unwrap();
+        assert_eq!(
+            gridfs_target(&config, None, None),
+            ("app".to_string(), "fs".to_string())
+        );
+        assert_eq!(
+            gridfs_target(&config, Some("files".into()), Some("avatars".into())),
+            ("files".to_string(), "avatars".to_string())
+        );
+    }
+
+    #[test]
+    fn import_mode_defaults_follow_the_upsert_flag() {
+        assert_eq!(ImportMode::parse(None, false), Ok(ImportMode::Insert));
+        assert_eq!(ImportMode::parse(None, true), Ok(ImportMode::Merge));
+        assert_eq!(ImportMode::parse(Some(""), true), Ok(ImportMode::Merge));
+        assert_eq!(
+            ImportMode::parse(Some("replace"), false),
+            Ok(ImportMode::Replace)
+        );
+        assert_eq!(
+            ImportMode::parse(Some("insert_if_absent"), true),
+            Ok(ImportMode::InsertIfAbsent)
+        );
+        assert!(ImportMode::parse(Some("upsert"), false).is_err());
+    }
+
+    #[test]
+    fn import_write_matches_any_id_type() {
+        let id_only = vec!["_id".to_string()];
+        for id in [
+            Bson::String("user-1".into()),
+            Bson::Int64(42),
+            Bson::Document(doc! { "tenant": "a", "n": 1 }),
+        ] {
+            let write = import_write(
+                doc! { "_id": id.clone(), "name": "Ada" },
+                ImportMode::Replace,
+                &id_only,
+            )
+            .unwrap();
+            assert_eq!(
+                write,
+                ImportWrite::Replace {
+                    filter: doc! { "_id": id.clone() },
+                    replacement: doc! { "_id": id, "name": "Ada" },
+                }
+            );
+        }
+        assert_eq!(
+            import_write(doc! { "name": "Ada" }, ImportMode::Merge, &id_only).unwrap(),
+            ImportWrite::Insert(doc! { "name": "Ada" })
+        );
+    }
+
+    #[test]
+    fn import_write_merge_by_business_key_sets_id_only_on_insert() {
+        let by_email = vec!["email".to_string()];
+        let write = import_write(
+            doc! { "_id": 7, "email": "ada@example.com", "name": "Ada" },
+            ImportMode::Merge,
+            &by_email,
+        )
+        .unwrap();
+        assert_eq!(
+            write,
+            ImportWrite::Update {
+                filter: doc! { "email": "ada@example.com" },
+                update: doc! {
+                    "$set": { "email": "ada@example.com", "name": "Ada" },
+                    "$setOnInsert": { "_id": 7 },
+                },
+            }
+        );
+
+        let by_id = vec!["_id".to_string()];
+        let write = import_write(doc! { "_id": 7 }, ImportMode::Merge, &by_id).unwrap();
+        assert_eq!(
+            write,
+            ImportWrite::Update {
+                filter: doc! { "_id": 7 },
+                update: doc! { "$setOnInsert": { "_id": 7 } },
+            }
+        );
+    }
+
+    #[test]
+    fn import_write_insert_if_absent_and_missing_keys() {
+        let keys = vec!["account.id".to_string(), "sku".to_string()];
+        let doc = doc! { "account": { "id": 3 }, "sku": "X1", "qty": 2 };
+        assert_eq!(
+            import_write(doc.clone(), ImportMode::InsertIfAbsent, &keys).unwrap(),
+            ImportWrite::Update {
+                filter: doc! { "account.id": 3, "sku": "X1" },
+                update: doc! { "$setOnInsert": doc },
+            }
+        );
+        assert!(import_write(doc! { "sku": "X1" }, ImportMode::Merge, &keys)
+            .unwrap_err()
+            .contains("account.id"));
+        assert!(import_write(
+            doc! { "account": { "id": Bson::Null }, "sku": "X1" },
+            ImportMode::Replace,
+            &keys
+        )
+        .is_err());
+        assert_eq!(
+            import_write(doc! { "sku": "X1" }, ImportMode::Insert, &keys).unwrap(),
+            ImportWrite::Insert(doc! { "sku": "X1" })
+        );
+    }
+
+    #[test]
+    fn import_commands_batch_runs_of_inserts_and_updates() {
+        let writes = vec![
+            (0, ImportWrite::Insert(doc! { "_id": 1 })),
+            (2, ImportWrite::Insert(doc! { "_id": 2 })),
+            (
+                3,
+                ImportWrite::Update {
+                    filter: doc! { "_id": 3 },
+                    update: doc! { "$set": { "n": 3 } },
+                },
+            ),
+            (
+                4,
+                ImportWrite::Replace {
+                    filter: doc! { "_id": 4 },
+                    replacement: doc! { "_id": 4, "n": 4 },
+                },
+            ),
+            (5, ImportWrite::Insert(doc! { "_id": 5 })),
+        ];
+        let commands = import_commands("orders", writes, true);
+        assert_eq!(
+            commands,
+            vec![
+                ImportCommand {
+                    command: doc! {
+                        "insert": "orders",
+                        "documents": [{ "_id": 1 }, { "_id": 2 }],
+                        "ordered": true,
+                    },
+                    doc_indexes: vec![0, 2],
+                },
+                ImportCommand {
+                    command: doc! {
+                        "update": "orders",
+                        "updates": [
+                            { "q": { "_id": 3 }, "u": { "$set": { "n": 3 } }, "upsert": true },
+                            { "q": { "_id": 4 }, "u": { "_id": 4, "n": 4 }, "upsert": true },
+                        ],
+                        "ordered": true,
+                    },
+                    doc_indexes: vec![3, 4],
+                },
+                ImportCommand {
+                    command: doc! {
+                        "insert": "orders",
+                        "documents": [{ "_id": 5 }],
+                        "ordered": true,
+                    },
+                    doc_indexes: vec![5],
+                },
+            ]
+        );
+
+        let many = (0..IMPORT_COMMAND_MAX_WRITES + 1)
+            .map(|i| (i, ImportWrite::Insert(doc! { "_id": i as i64 })))
+            .collect();
+        let commands = import_commands("orders", many, false);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[1].doc_indexes, vec![IMPORT_COMMAND_MAX_WRITES]);
+    }
+
+    #[test]
+    fn tally_command_reply_maps_statement_indexes_to_documents() {
+        let update = ImportCommand {
+            command: doc! { "update": "orders" },
+            doc_indexes: vec![10, 11, 12],
+        };
+        let reply = doc! {
+            "ok": 1,
+            "n": 2,
+            "upserted": [{ "index": 0, "_id": 1 }],
+            "writeErrors": [{ "index": 1, "code": 11000, "errmsg": "E11000 duplicate key" }],
+        };
+        let mut tally = ImportTally::default();
+        assert!(tally_command_reply(
+            &update,
+            &reply,
+            ImportMode::Merge,
+            false,
+            &mut tally
+        ));
+        assert_eq!((tally.inserted, tally.updated, tally.skipped), (1, 1, 1));
+        assert_eq!(tally.errors[0]["index"], 11);
+
+        // An ordered command stops at the first error.
+        let mut tally = ImportTally::default();
+        tally_command_reply(&update, &reply, ImportMode::Merge, true, &mut tally);
+        assert_eq!((tally.inserted, tally.updated, tally.skipped), (1, 0, 2));
+        assert_eq!(tally.errors.len(), 2);
+    }
+
+    #[test]
+    fn resume_token_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("mongo-watch-test-{}", std::process::id()));
+        let path = dir.join("orders.json");
+        assert_eq!(load_resume_token(&path), None);
+        let token = serde_json::json!({"_data": "8263A1B2C3000000012B"});
+        save_resume_token(&path, &token).unwrap();
+        assert_eq!(load_resume_token(&path), Some(token));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parses_sibling_db_and_cursor_chain() {
+        let stmt = parse_statement(
+            "db.getSiblingDB('analytics').events.find({a: 1}).projection({a: 1}).hint('a_1').collation({locale: 'fr'}).maxTimeMS(500).sort({a: -1}).limit(5).explain('executionStats');",
+        )
+        .unwrap();
+        assert_eq!(stmt.database.as_deref(), Some("analytics"));
+        assert_eq!(stmt.collection.as_deref(), Some("events"));
+        assert_eq!(stmt.method, "find");
+        assert_eq!(stmt.chain.projection, Some(doc! { "a": 1 }));
+        assert_eq!(stmt.chain.hint, Some(Bson::String("a_1".into())));
+        assert_eq!(stmt.chain.collation, Some(doc! { "locale": "fr" }));
+        assert_eq!(
+            stmt.chain.max_time,
+            Some(std::time::Duration::from_millis(500))
+        );
+        assert_eq!(stmt.chain.sort, Some(doc! { "a": -1 }));
+        assert_eq!(stmt.chain.limit, Some(5));
+        assert_eq!(stmt.chain.explain.as_deref(), Some("executionStats"));
+
+        let stmt = parse_statement("db.users.find().hint({email: 1}).count()").unwrap();
+        assert_eq!(stmt.chain.hint, Some(Bson::Document(doc! { "email": 1 })));
+        assert!(stmt.chain.count);
+        assert!(parse_statement("db.users.find().toArray()").is_ok());
+        assert!(parse_statement("db.users.find().batchSize(10)").is_err());
+        assert!(parse_statement("db.users.find().explain('verbose')").is_err());
+
+        let stmt = parse_statement(
+            "db.getSiblingDB('shop')['orders'].explain('executionStats').find({status: 'A'}).sort({qty: -1})",
+        )
+        .unwrap();
+        assert_eq!(stmt.database.as_deref(), Some("shop"));
+        assert_eq!(stmt.collection.as_deref(), Some("orders"));
+        assert_eq!(stmt.method, "find");
+        assert_eq!(stmt.chain.explain.as_deref(), Some("executionStats"));
+        assert!(stmt.chain.sort.is_some());
+
+        let stmt = parse_statement("db.orders.explain().count({status: 'A'})").unwrap();
+        assert_eq!(stmt.method, "count");
+        assert_eq!(stmt.chain.explain.as_deref(), Some("queryPlanner"));
+        assert!(parse_statement("db.orders.explain().insertOne({a: 1})").is_err());
+    }
+
+    #[test]
+    fn parses_database_methods() {
+        let stmt = parse_statement("db.runCommand({ping: 1})").unwrap();
+        assert_eq!(stmt.collection, None);
+        assert_eq!(stmt.method, "runCommand");
+        assert_eq!(stmt.args, vec!["{ping: 1}".to_string()]);
+
+        let stmt = parse_statement("db.getSiblingDB(\"admin\").getCollectionNames()").unwrap();
+        assert_eq!(stmt.database.as_deref(), Some("admin"));
+        assert_eq!(stmt.collection, None);
+        assert_eq!(stmt.method, "getCollectionNames");
+
+        let stmt = parse_statement("db.orders.findOneAndDelete({'a.b': 1})").unwrap();
+        assert_eq!(stmt.collection.as_deref(), Some("orders"));
+        assert_eq!(stmt.method, "findOneAndDelete");
+        let stmt = parse_statement("db.getCollection('x.y').getIndexes()").unwrap();
+        assert_eq!(stmt.collection.as_deref(), Some("x.y"));
+    }
+
+    #[test]
+    fn js_to_json_handles_shell_constructors() {
+        let doc = parse_json_arg(
+            r#"{
+                d: ISODate("2024-01-01"), t: new Date("2024-01-01T10:00:00"), ms: new Date(86400000),
+                n: NumberLong(5), i: NumberInt("7"), m: NumberDecimal("1.5"),
+                u: UUID("123e4567-e89b-12d3-a456-426614174000"), b: BinData(0, "AQID"),
+                ts: Timestamp(1700000000, 3), ts2: Timestamp({t: 1, i: 2})
+            }"#,
+        )
+        .and_then(json_to_bson_doc)
+        .unwrap();
+        let date = |ms| Bson::DateTime(mongodb::bson::DateTime::from_millis(ms));
+        assert_eq!(doc.get("d"), Some(&date(1_704_067_200_000)));
+        assert_eq!(doc.get("t"), Some(&date(1_704_103_200_000)));
+        assert_eq!(doc.get("ms"), Some(&date(86_400_000)));
+        assert_eq!(doc.get("n"), Some(&Bson::Int64(5)));
+        assert_eq!(doc.get("i"), Some(&Bson::Int32(7)));
+        assert_eq!(
+            doc.get("m").map(|v| v.element_type()),
+            Some(mongodb::bson::spec::ElementType::Decimal128)
+        );
+        match doc.get("u") {
+            Some(Bson::Binary(b)) => {
+                assert_eq!(b.subtype, mongodb::bson::spec::BinarySubtype::Uuid);
+                assert_eq!(b.bytes.len(), 16);
+                assert_eq!(b.bytes[0], 0x12);
+            }
+            other => panic!("expected UUID binary, got {:?}", other),
+        }
+        match doc.get("b") {
+            Some(Bson::Binary(b)) => assert_eq!(b.bytes, vec![1, 2, 3]),
+            other => panic!("expected binary, got {:?}", other),
+        }
+        let ts = |time, increment| Bson::Timestamp(mongodb::bson::Timestamp { time, increment });
+        assert_eq!(doc.get("ts"), Some(&ts(1_700_000_000, 3)));
+        assert_eq!(doc.get("ts2"), Some(&ts(1, 2)));
+
+        assert!(parse_json_arg("{u: UUID('not-a-uuid!')}")
+            .and_then(json_to_bson_doc)
+            .is_err());
+    }
 }
@@ -1,15 +1,23 @@
 import { base64Encode } from './base64.ts';
 
 export const buildAuthHeader = (
-  authType: 'basic' | 'apiKey' | undefined,
+  authType: 'basic' | 'apiKey' | 'bearer' | 'pki' | 'awsSigV4' | undefined,
   username: string | undefined,
   password: string | undefined,
   apiKey?: string | undefined,
+  token?: string | undefined,
 ) => {
   if (authType === 'apiKey') {
     const authorization = apiKey ? `ApiKey ${apiKey}` : undefined;
     return authorization ? { authorization } : undefined;
   }
+  if (authType === 'bearer') {
+    return token ? { authorization: `Bearer ${token}` } : undefined;
+  }
+  // Client certificates and SigV4 signatures are applied by the Rust side.
+  if (authType === 'pki' || authType === 'awsSigV4') {
+    return undefined;
+  }
   const authorization =
     username || password
       ? `Basic ${base64Encode(`${username ?? ''}:${password ?? ''}`)}`
@@ -3,7 +3,17 @@ import { lang } from '../lang';
 import { invoke } from '@tauri-apps/api/core';
 import { get } from 'lodash';
 
-import type { SshConnectionConfig } from '@/store';
+import type { SearchConnectionBase, SshConnectionConfig } from '@/store';
+
+type SearchAuth = Pick<
+  SearchConnectionBase,
+  'authType' | 'token' | 'clientCert' | 'clientKey' | 'awsRegion' | 'awsService' | 'awsAuth'
+>;
+
+type SearchTls = Pick<
+  SearchConnectionBase,
+  'caBundle' | 'clientCert' | 'clientKey' | 'tlsServerName' | 'tlsMinVersion'
+>;
 
 type FetchApiOptions = {
   method: string;
@@ -13,6 +23,10 @@ type FetchApiOptions = {
   agent: { ssl: boolean } | undefined;
   payload?: string;
   sshTunnel?: SshConnectionConfig | null;
+  // Auth the Rust side applies itself: bearer tokens, client certificates, SigV4
+  auth?: SearchAuth;
+  // Per-connection CA bundle, client certificate, server name and TLS version
+  tls?: SearchTls;
 };
 
 const extractEsError = (data: unknown): string | null => {
@@ -70,16 +84,24 @@ const fetchWrapper = async ({
   password,
   authType,
   apiKey,
+  token,
+  clientCert,
+  clientKey,
+  awsRegion,
+  awsService,
+  awsAuth,
+  caBundle,
+  tlsServerName,
+  tlsMinVersion,
   ssl,
   sshTunnel,
-}: {
+}: SearchAuth & SearchTls & {
   method: string;
   path?: string;
   queryParameters?: string;
   payload?: string;
   username?: string;
   password?: string;
-  authType?: 'basic' | 'apiKey';
   apiKey?: string;
   host: string;
   port: number;
@@ -88,19 +110,37 @@ const fetchWrapper = async ({
 }) => {
   const url = buildURL(host, port, path, queryParameters);
   const authHeader = buildAuthHeader(authType, username, password, apiKey);
+  const auth =
+    authType === 'bearer' || authType === 'pki' || authType === 'awsSigV4'
+      ? { authType, token, clientCert, clientKey, awsRegion, awsService, awsAuth }
+      : undefined;
+  const tls =
+    caBundle || clientCert || tlsServerName || tlsMinVersion
+      ? { caBundle, clientCert, clientKey, tlsServerName, tlsMinVersion }
+      : undefined;
   const { data, status, details, errorType } = await fetchRequest(url, {
     method,
     headers: { ...authHeader },
     payload,
     agent: { ssl },
     sshTunnel,
+    auth,
+    tls,
   });
   return handleFetch({ data, status, details, errorType });
 };
 
 const fetchRequest = async (
   url: string,
-  { method, headers: inputHeaders, payload, agent: agentSslConf, sshTunnel }: FetchApiOptions,
+  {
+    method,
+    headers: inputHeaders,
+    payload,
+    agent: agentSslConf,
+    sshTunnel,
+    auth,
+    tls,
+  }: FetchApiOptions,
 ) => {
   const agent = { ssl: url.startsWith('https') && agentSslConf?.ssl };
 
@@ -110,7 +150,7 @@ const fetchRequest = async (
   try {
     const response = await invoke<string>('fetch_api', {
       url,
-      options: { method, headers, body: payload ?? undefined, agent },
+      options: { method, headers, body: payload ?? undefined, agent, auth, tls },
       sshTunnel: sshTunnel || null,
     });
 
@@ -155,16 +195,17 @@ const fetchRequest = async (
   }
 };
 
-const loadHttpClient = (con: {
-  host: string;
-  port: number;
-  username?: string;
-  password?: string;
-  authType?: 'basic' | 'apiKey';
-  apiKey?: string;
-  sslCertVerification: boolean;
-  sshTunnel?: SshConnectionConfig | null;
-}) => ({
+const loadHttpClient = (
+  con: SearchAuth & SearchTls & {
+    host: string;
+    port: number;
+    username?: string;
+    password?: string;
+    apiKey?: string;
+    sslCertVerification: boolean;
+    sshTunnel?: SshConnectionConfig | null;
+  },
+) => ({
   get: async <T = unknown>(path?: string, queryParameters?: string, payload?: string): Promise<T> =>
     fetchWrapper({
       ...con,
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type { MongoDBConnection, MongoDBAuth, SshConnectionConfig } from '../store';
 import {
   invokeCapability,
@@ -211,11 +212,116 @@ export type MongoExportResult = {
   error?: string;
 };
 
+export type MongoImportMode = 'insert' | 'replace' | 'merge' | 'insert_if_absent';
+
+export type MongoImportOptions = {
+  mode?: MongoImportMode;
+  // Fields that identify an existing document; defaults to _id.
+  matchFields?: string[];
+  ordered?: boolean;
+};
+
 export type MongoImportResult = {
   inserted: number;
   updated: number;
   skipped: number;
-  errors?: string[];
+  // index is the document's position in the batch, or null for batch-wide errors.
+  errors?: { index: number | null; error: string }[];
+  error?: string;
+};
+
+export type MongoDumpCollectionSummary = {
+  collection: string;
+  type: string;
+  documents: number;
+  indexes: number;
+};
+
+export type MongoDumpResult = {
+  path?: string;
+  collections?: MongoDumpCollectionSummary[];
+  error?: string;
+};
+
+export type MongoRestoreOptions = {
+  database?: string;
+  collection?: string;
+  drop?: boolean;
+  bypassDocumentValidation?: boolean;
+};
+
+export type MongoRestoreCollectionSummary = {
+  collection: string;
+  type: string;
+  created: boolean;
+  documents_restored: number;
+  documents_failed: number;
+  indexes: number;
+  errors: string[];
+};
+
+export type MongoRestoreResult = {
+  database?: string;
+  collections?: MongoRestoreCollectionSummary[];
+  error?: string;
+};
+
+export type MongoWatchOptions = {
+  database?: string;
+  collection?: string;
+  pipeline?: Record<string, unknown>[];
+  fullDocument?: 'default' | 'updateLookup' | 'whenAvailable' | 'required';
+  resume?: boolean;
+};
+
+export type MongoWatchStartResult = {
+  watchId?: string;
+  target?: string;
+  resumed?: boolean;
+  error?: string;
+};
+
+export type MongoWatchHandlers = {
+  onEvent: (event: Record<string, unknown>, resumeToken: unknown) => void;
+  onError?: (error: string) => void;
+  onEnd?: (invalidated: boolean) => void;
+};
+
+export type MongoGridfsBucket = {
+  name: string;
+  file_count: number;
+  total_bytes: number;
+};
+
+export type MongoGridfsFile = {
+  id: unknown;
+  filename?: string;
+  length?: number;
+  chunk_size?: number;
+  upload_date?: string;
+  content_type?: string;
+  metadata?: Record<string, unknown>;
+};
+
+export type MongoGridfsListFilesOptions = {
+  filter?: Record<string, unknown>;
+  sort?: Record<string, unknown>;
+  skip?: number;
+  limit?: number;
+};
+
+export type MongoGridfsListFilesResult = {
+  files?: MongoGridfsFile[];
+  total?: number;
+  has_more?: boolean;
+  error?: string;
+};
+
+export type MongoGridfsTransferResult = {
+  id?: unknown;
+  filename?: string;
+  path?: string;
+  length?: number;
   error?: string;
 };
 
@@ -225,6 +331,13 @@ const buildConfig = (con: MongoDBConnection) => ({
   auth: con.auth,
   database: con.activeDatabase || con.database,
   tls: con.tls,
+  tlsSettings: {
+    caBundle: con.caBundle,
+    clientCert: con.clientCert,
+    clientKey: con.clientKey,
+    tlsServerName: con.tlsServerName,
+    tlsMinVersion: con.tlsMinVersion,
+  },
 });
 
 export const mongoApi = {
@@ -716,6 +829,7 @@ export const mongoApi = {
     documents: string[],
     upsert?: boolean,
     sshTunnel?: SshConnectionConfig | null,
+    options: MongoImportOptions = {},
   ): Promise<MongoImportResult> => {
     try {
       const raw = await invoke<ApiResponse<MongoImportResult>>('mongo_import_documents', {
@@ -723,6 +837,9 @@ export const mongoApi = {
         collection,
         documents,
         upsert,
+        mode: options.mode ?? null,
+        matchFields: options.matchFields ?? null,
+        ordered: options.ordered ?? null,
         sshTunnel: sshTunnel ?? null,
       });
       if (raw.status >= 400) {
@@ -739,6 +856,230 @@ export const mongoApi = {
     }
   },
 
+  dump: async (
+    con: MongoDBConnection,
+    path: string,
+    options: { database?: string; collection?: string; gzip?: boolean } = {},
+  ): Promise<MongoDumpResult> => {
+    try {
+      const raw = await invoke<ApiResponse<MongoDumpResult>>('mongo_dump', {
+        config: buildConfig(con),
+        database: options.database ?? null,
+        collection: options.collection ?? null,
+        path,
+        gzip: options.gzip ?? null,
+        sshTunnel: con.sshTunnel ?? null,
+      });
+      if (raw.status >= 400) {
+        return { error: raw.message || 'Request failed' };
+      }
+      return raw.data ?? {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  restore: async (
+    con: MongoDBConnection,
+    path: string,
+    options: MongoRestoreOptions = {},
+  ): Promise<MongoRestoreResult> => {
+    try {
+      const raw = await invoke<ApiResponse<MongoRestoreResult>>('mongo_restore', {
+        config: buildConfig(con),
+        database: options.database ?? null,
+        collection: options.collection ?? null,
+        path,
+        drop: options.drop ?? null,
+        bypassDocumentValidation: options.bypassDocumentValidation ?? null,
+        sshTunnel: con.sshTunnel ?? null,
+      });
+      if (raw.status >= 400) {
+        return { error: raw.message || 'Request failed' };
+      }
+      return raw.data ?? {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  // ==================== GridFS ====================
+
+  gridfsListBuckets: async (
+    con: MongoDBConnection,
+    database: string,
+  ): Promise<{ buckets?: MongoGridfsBucket[]; error?: string }> => {
+    try {
+      const raw = await invokeCapability(
+        'mongo__gridfs_list_buckets',
+        { database },
+        String(con.id),
+      );
+      const data = parseCapabilityResponse<{ buckets: MongoGridfsBucket[] }>(raw);
+      return { buckets: data.buckets };
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  gridfsListFiles: async (
+    con: MongoDBConnection,
+    database: string,
+    bucket: string,
+    options: MongoGridfsListFilesOptions = {},
+  ): Promise<MongoGridfsListFilesResult> => {
+    try {
+      const raw = await invokeCapability(
+        'mongo__gridfs_list_files',
+        { database, bucket, ...options },
+        String(con.id),
+      );
+      const data = parseCapabilityResponse<MongoGridfsListFilesResult>(raw);
+      return { files: data.files, total: data.total, has_more: data.has_more };
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  gridfsDeleteFile: async (
+    con: MongoDBConnection,
+    database: string,
+    bucket: string,
+    fileId: unknown,
+  ): Promise<MongoOperationResult> => {
+    try {
+      const raw = await invokeCapability(
+        'mongo__gridfs_delete_file',
+        { database, bucket, file_id: fileId },
+        String(con.id),
+      );
+      parseCapabilityResponse<{ deleted: boolean }>(raw);
+      return {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  gridfsRenameFile: async (
+    con: MongoDBConnection,
+    database: string,
+    bucket: string,
+    fileId: unknown,
+    newFilename: string,
+  ): Promise<MongoOperationResult> => {
+    try {
+      const raw = await invokeCapability(
+        'mongo__gridfs_rename_file',
+        { database, bucket, file_id: fileId, new_filename: newFilename },
+        String(con.id),
+      );
+      parseCapabilityResponse<{ filename: string }>(raw);
+      return {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  gridfsDownload: async (
+    con: MongoDBConnection,
+    database: string,
+    bucket: string,
+    fileId: unknown,
+    path: string,
+  ): Promise<MongoGridfsTransferResult> => {
+    try {
+      const raw = await invoke<ApiResponse<MongoGridfsTransferResult>>('mongo_gridfs_download', {
+        config: buildConfig(con),
+        database,
+        bucket,
+        fileId,
+        path,
+        sshTunnel: con.sshTunnel ?? null,
+      });
+      if (raw.status >= 400) {
+        return { error: raw.message || 'Request failed' };
+      }
+      return raw.data ?? {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  gridfsUpload: async (
+    con: MongoDBConnection,
+    database: string,
+    bucket: string,
+    path: string,
+    options: { filename?: string; metadata?: Record<string, unknown> } = {},
+  ): Promise<MongoGridfsTransferResult> => {
+    try {
+      const raw = await invoke<ApiResponse<MongoGridfsTransferResult>>('mongo_gridfs_upload', {
+        config: buildConfig(con),
+        database,
+        bucket,
+        path,
+        filename: options.filename ?? null,
+        metadata: options.metadata ?? null,
+        sshTunnel: con.sshTunnel ?? null,
+      });
+      if (raw.status >= 400) {
+        return { error: raw.message || 'Request failed' };
+      }
+      return raw.data ?? {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  // ==================== Change Streams ====================
+
+  watchStart: async (
+    con: MongoDBConnection,
+    watchId: string,
+    options: MongoWatchOptions = {},
+  ): Promise<MongoWatchStartResult> => {
+    try {
+      const raw = await invoke<ApiResponse<MongoWatchStartResult>>('mongo_watch_start', {
+        config: buildConfig(con),
+        watchId,
+        database: options.database ?? null,
+        collection: options.collection ?? null,
+        pipeline: options.pipeline ?? null,
+        fullDocument: options.fullDocument ?? null,
+        resume: options.resume ?? null,
+        sshTunnel: con.sshTunnel ?? null,
+      });
+      if (raw.status >= 400) {
+        return { error: raw.message || 'Request failed' };
+      }
+      return raw.data ?? {};
+    } catch (e) {
+      return { error: e instanceof Error ? e.message : String(e) };
+    }
+  },
+
+  watchStop: async (watchId: string, forget = false): Promise<void> => {
+    await invoke('mongo_watch_stop', { watchId, forget });
+  },
+
+  onWatchEvent: async (watchId: string, handlers: MongoWatchHandlers): Promise<UnlistenFn> => {
+    const unlisteners = await Promise.all([
+      listen<{ watchId: string; event: Record<string, unknown>; resumeToken: unknown }>(
+        'mongo-watch-event',
+        ({ payload }) => {
+          if (payload.watchId === watchId) handlers.onEvent(payload.event, payload.resumeToken);
+        },
+      ),
+      listen<{ watchId: string; error: string }>('mongo-watch-error', ({ payload }) => {
+        if (payload.watchId === watchId) handlers.onError?.(payload.error);
+      }),
+      listen<{ watchId: string; invalidated: boolean }>('mongo-watch-end', ({ payload }) => {
+        if (payload.watchId === watchId) handlers.onEnd?.(payload.invalidated);
+      }),
+    ]);
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  },
+
   sampleDocuments: async (
     con: MongoDBConnection,
     collection: string,
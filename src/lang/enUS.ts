@@ -917,6 +917,9 @@ export const enUS = {
       profileNameRequired: 'Please select a profile',
       endpointUrlRequired: 'Endpoint URL is required for local connections',
       apiKeyRequired: 'API Key is required',
+      tokenRequired: 'Token is required',
+      clientCertRequired: 'Client certificate is required',
+      clientKeyRequired: 'Client private key is required',
       invalidUri: 'URI must start with mongodb:// or mongodb+srv://',
       uriRequired: 'MongoDB URI is required',
       usernameRequired: 'Username is required',
@@ -959,6 +962,26 @@ export const enUS = {
     authTypeApiKey: 'API Key',
     apiKey: 'API Key',
     apiKeyPlaceholder: 'Enter base64-encoded id:api_key',
+    authTypeBearer: 'Token',
+    authTypePki: 'Client Cert',
+    authTypeAwsSigV4: 'AWS SigV4',
+    token: 'Token',
+    tokenPlaceholder: 'Service account token or OAuth access token',
+    clientCert: 'Client Certificate',
+    clientKey: 'Client Private Key',
+    pemPlaceholder: 'Path to a PEM file',
+    awsService: 'Service',
+    awsServiceEs: 'OpenSearch Service',
+    awsServiceAoss: 'OpenSearch Serverless',
+    awsCredentials: 'AWS Credentials',
+    tls: {
+      label: 'TLS',
+      caBundle: 'CA Bundle',
+      serverName: 'Server Name',
+      serverNamePlaceholder: 'Name on the server certificate, if not the host',
+      minVersion: 'Minimum Version',
+      minVersionDefault: 'Default',
+    },
     emptyState: {
       noConnections: 'No connections available',
       pleaseSelect: 'Please select a connection',
@@ -1640,6 +1663,17 @@ export const enUS = {
     appendRecordsDesc: 'Merge with existing data',
     replaceCollection: 'Replace Collection',
     replaceCollectionDesc: 'Overwrite existing data',
+    mongoWriteOptions: 'MongoDB Write Options',
+    mongoImportMode: 'Write Mode',
+    mongoModeAuto: 'Follow import strategy',
+    mongoModeInsert: 'Insert',
+    mongoModeReplace: 'Replace matching documents',
+    mongoModeMerge: 'Merge into matching documents',
+    mongoModeInsertIfAbsent: 'Insert only if absent',
+    mongoMatchFields: 'Match Fields',
+    mongoMatchFieldsDesc: 'Comma-separated fields that identify an existing document',
+    mongoOrdered: 'Ordered Writes',
+    mongoOrderedDesc: 'Stop at the first failed document',
     startImportTask: 'Start Import Task',
     importNote: 'Task will run in the background. You will be notified upon completion.',
     parseErrors: 'Some rows could not be parsed',
@@ -881,6 +881,9 @@ export const zhCN = {
       profileNameRequired: '请选择配置文件',
       endpointUrlRequired: '本地连接需要输入端点 URL',
       apiKeyRequired: '请输入 API 密钥',
+      tokenRequired: '请输入令牌',
+      clientCertRequired: '请输入客户端证书',
+      clientKeyRequired: '请输入客户端私钥',
       invalidUri: 'URI 必须以 mongodb:// 或 mongodb+srv:// 开头',
       uriRequired: '请输入 MongoDB URI',
       usernameRequired: '请输入用户名',
@@ -923,6 +926,26 @@ export const zhCN = {
     authTypeApiKey: 'API 密钥',
     apiKey: 'API 密钥',
     apiKeyPlaceholder: '输入 Base64 编码的 id:api_key',
+    authTypeBearer: '令牌',
+    authTypePki: '客户端证书',
+    authTypeAwsSigV4: 'AWS SigV4',
+    token: '令牌',
+    tokenPlaceholder: '服务账号令牌或 OAuth 访问令牌',
+    clientCert: '客户端证书',
+    clientKey: '客户端私钥',
+    pemPlaceholder: 'PEM 文件路径',
+    awsService: '服务',
+    awsServiceEs: 'OpenSearch Service',
+    awsServiceAoss: 'OpenSearch Serverless',
+    awsCredentials: 'AWS 凭证',
+    tls: {
+      label: 'TLS',
+      caBundle: 'CA 证书',
+      serverName: '服务器名称',
+      serverNamePlaceholder: '服务器证书上的名称（与主机不同时填写）',
+      minVersion: '最低版本',
+      minVersionDefault: '默认',
+    },
     emptyState: {
       noConnections: '暂无可用连接',
       pleaseSelect: '请选择一个连接',
@@ -1599,6 +1622,17 @@ export const zhCN = {
     appendRecordsDesc: '与现有数据合并',
     replaceCollection: '替换集合',
     replaceCollectionDesc: '覆盖现有数据',
+    mongoWriteOptions: 'MongoDB 写入选项',
+    mongoImportMode: '写入模式',
+    mongoModeAuto: '跟随导入策略',
+    mongoModeInsert: '插入',
+    mongoModeReplace: '替换匹配的文档',
+    mongoModeMerge: '合并到匹配的文档',
+    mongoModeInsertIfAbsent: '仅在不存在时插入',
+    mongoMatchFields: '匹配字段',
+    mongoMatchFieldsDesc: '用于识别已有文档的字段，以逗号分隔',
+    mongoOrdered: '有序写入',
+    mongoOrderedDesc: '遇到第一个失败的文档时停止',
     startImportTask: '开始导入任务',
     importNote: '任务将在后台运行，完成后会通知您。',
     parseErrors: '部分行无法解析',
@@ -206,6 +206,18 @@ export type DynamoDBAuth =
       expirationTimestamp?: number;
     };
 
+// Per-connection TLS settings; PEM values may be inline text or file paths
+export type ConnectionTlsSettings = {
+  // extra CA certificates trusted in addition to the system roots
+  caBundle?: string;
+  // client certificate and key for mutual TLS
+  clientCert?: string;
+  clientKey?: string;
+  // name to validate the server certificate against, e.g. when connecting by IP
+  tlsServerName?: string;
+  tlsMinVersion?: '1.2' | '1.3';
+};
+
 export type DynamoDBConnection = {
   id?: number | string;
   name: string;
@@ -219,20 +231,31 @@ export type DynamoDBConnection = {
   favoriteTables?: Array<string>;
   sshTunnel?: SshConnectionConfig;
   prompt?: string;
+  // custom endpoints only; the AWS SDK accepts just a CA bundle
+  caBundle?: string;
 };
 
+export type SearchAuthType = 'basic' | 'apiKey' | 'bearer' | 'pki' | 'awsSigV4';
+
 // Shared base for HTTP-API-compatible search engines
-export type SearchConnectionBase = {
+export type SearchConnectionBase = ConnectionTlsSettings & {
   id?: number;
   name: string;
   indices: Array<ElasticSearchIndex>;
   host: string;
   port: number;
-  authType?: 'basic' | 'apiKey';
+  authType?: SearchAuthType;
   username?: string;
   sslCertVerification: boolean;
   password?: string;
   apiKey?: string;
+  // bearer: Elastic service account token or OAuth access token
+  token?: string;
+  // pki: authenticates with clientCert/clientKey from the TLS settings
+  // awsSigV4: Amazon OpenSearch Service ('es') or OpenSearch Serverless ('aoss')
+  awsRegion?: string;
+  awsService?: 'es' | 'aoss';
+  awsAuth?: DynamoDBAuth;
   queryParameters?: string;
   activeIndex: ElasticSearchIndex | undefined;
   version: string;
@@ -289,7 +312,7 @@ export type MongoDBAuth =
     }
   | { kind: 'uri'; uri: string };
 
-export type MongoDBConnection = {
+export type MongoDBConnection = ConnectionTlsSettings & {
   id?: number | string;
   name: string;
   type: DatabaseType.MONGODB;
@@ -1046,7 +1069,7 @@ export const useConnectionStore = defineStore('connectionStore', {
       if (!isSearchConnection(connection)) {
         throw new Error('Operation only supported for Elasticsearch/OpenSearch connections');
       }
-      const { username, password, host, port, sslCertVerification, authType, apiKey } =
+      const { username, password, host, port, sslCertVerification, authType, apiKey, token } =
         connection ?? {
           host: 'http://localhost',
           port: 9200,
@@ -1056,7 +1079,7 @@ export const useConnectionStore = defineStore('connectionStore', {
         };
       const url = buildURL(host, port, buildPath(index, path, connection), queryParams);
 
-      const authHeader = buildAuthHeader(authType, username, password, apiKey);
+      const authHeader = buildAuthHeader(authType, username, password, apiKey, token);
 
       const headers = {
         ...authHeader,
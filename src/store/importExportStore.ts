@@ -11,6 +11,7 @@ import {
   sourceFileApi,
   type DynamoDBTableInfo,
   type MongoConnectionConfig,
+  type MongoImportOptions,
 } from '../datasources';
 import { buildEsMappingBody } from '../views/import-export/utils/schemaMapping';
 import {
@@ -101,6 +102,7 @@ export type ImportTaskConfig = {
   metadataFile: string;
   strategy: ImportStrategy;
   isNewCollection: boolean;
+  mongoOptions?: MongoImportOptions;
 };
 
 export type TaskRuntime = {
@@ -184,6 +186,8 @@ export const useImportExportStore = defineStore('importExportStore', {
       readCapacity: number;
       writeCapacity: number;
     };
+    // Write options for MongoDB targets; an unset mode follows importStrategy.
+    importMongoOptions: MongoImportOptions;
     importCreationPhase: 'idle' | 'creating' | 'importing' | 'done' | 'error';
     importCreationError: string | null;
     importParseErrors: string[];
@@ -242,6 +246,7 @@ export const useImportExportStore = defineStore('importExportStore', {
         readCapacity: 5,
         writeCapacity: 5,
       },
+      importMongoOptions: {},
       importCreationPhase: 'idle' as const,
       importCreationError: null,
       importParseErrors: [],
@@ -968,6 +973,7 @@ export const useImportExportStore = defineStore('importExportStore', {
         this.syncImportProgressToTask();
 
         const upsert = this.importStrategy !== 'append';
+        const importOptions: MongoImportOptions = { ...this.importMongoOptions };
 
         if (fileType === 'json') {
           const data = await sourceFileApi.readFile(input.restoreFile);
@@ -988,6 +994,7 @@ export const useImportExportStore = defineStore('importExportStore', {
               jsonStrings,
               upsert,
               mongoConnection.sshTunnel,
+              importOptions,
             );
 
             if (this.restoreProgress) {
@@ -1082,6 +1089,7 @@ export const useImportExportStore = defineStore('importExportStore', {
                 sendBatch,
                 upsert,
                 mongoConnection.sshTunnel,
+                importOptions,
               );
 
               if (this.restoreProgress) {
@@ -1106,6 +1114,7 @@ export const useImportExportStore = defineStore('importExportStore', {
               accumulatedDocs,
               upsert,
               mongoConnection.sshTunnel,
+              importOptions,
             );
 
             if (this.restoreProgress) {
@@ -1477,10 +1486,10 @@ export const useImportExportStore = defineStore('importExportStore', {
           [index: string]: { mappings: { properties?: Record<string, { type?: string }> } };
         }>(`/${this.importTargetIndex}/_mapping`);
 
-        const indexData = response[this.importTargetIndex];
-        if (indexData?.mappings?.properties) {
+        const { properties } = mergedMappings(response, this.importTargetIndex);
+        if (Object.keys(properties).length) {
           const schema: Record<string, string> = {};
-          for (const [fieldName, fieldInfo] of Object.entries(indexData.mappings.properties)) {
+          for (const [fieldName, fieldInfo] of Object.entries(properties)) {
             schema[fieldName] = fieldInfo.type || 'unknown';
           }
           return schema;
@@ -1706,6 +1715,10 @@ export const useImportExportStore = defineStore('importExportStore', {
       this.restoreProgress = null;
     },
 
+    setImportMongoOptions(options: Partial<MongoImportOptions>) {
+      this.importMongoOptions = { ...this.importMongoOptions, ...options };
+    },
+
     toggleSchemaFieldExclude(fieldName: string) {
       const field = this.importSchemaFields.find(f => f.name === fieldName);
       if (field) {
@@ -1804,6 +1817,7 @@ export const useImportExportStore = defineStore('importExportStore', {
         readCapacity: 5,
         writeCapacity: 5,
       };
+      this.importMongoOptions = {};
       this.importCreationPhase = 'idle';
       this.importCreationError = null;
       this.importParseErrors = [];
@@ -2102,7 +2116,7 @@ export const useImportExportStore = defineStore('importExportStore', {
           };
         }>(`/${this.selectedIndex}/_mapping`);
 
-        const { mappings = { properties: {} } } = mappingResponse[this.selectedIndex];
+        const mappings = mergedMappings(mappingResponse, this.selectedIndex);
 
         // Get sample documents
         const sampleResponse = await client.get<{
@@ -2442,7 +2456,7 @@ export const useImportExportStore = defineStore('importExportStore', {
           };
         }>(`/${input.index}/_mapping`);
 
-        const indexMapping = mappingResponse[input.index];
+        const indexMapping = mergedMappings(mappingResponse, input.index);
 
         // Get aliases
         let aliases = {};
@@ -2483,7 +2497,7 @@ export const useImportExportStore = defineStore('importExportStore', {
             rowCount: countResponse.count,
             includedFields: input.fields.filter(f => f.includeInExport).map(f => f.name),
           },
-          schema: indexMapping?.mappings || {},
+          schema: indexMapping,
           indexes: {},
           aliases: aliases,
           stats: stats,
@@ -3134,6 +3148,7 @@ export const useImportExportStore = defineStore('importExportStore', {
         this.importMetadataFile = cfg.metadataFile;
         this.importStrategy = cfg.strategy;
         this.importIsNewCollection = cfg.isNewCollection;
+        this.importMongoOptions = cfg.mongoOptions ?? {};
         this.activeImportTaskId = taskId;
         this.activeMode = 'import';
 
@@ -3239,6 +3254,24 @@ const formatBytes = (bytes: number): string => {
   return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i];
 };
 
+// A `_mapping` response for a data stream or alias is keyed by the backing
+// indices (`.ds-*`), not by the requested name, so merge their properties.
+// Backing indices sort by generation, so the newest mapping wins a conflict.
+const mergedMappings = <P>(
+  response: { [key: string]: { mappings?: { properties?: { [key: string]: P } } } },
+  name: string,
+): { properties: { [key: string]: P } } => {
+  const direct = response[name]?.mappings;
+  if (direct) {
+    return { ...direct, properties: direct.properties ?? {} };
+  }
+  const properties: { [key: string]: P } = {};
+  Object.keys(response)
+    .sort()
+    .forEach(index => Object.assign(properties, response[index]?.mappings?.properties ?? {}));
+  return { properties };
+};
+
 const convertToCsv = (
   headers: string[],
   data: Array<{ _id?: string; _source?: { [key: string]: unknown }; [key: string]: unknown }>,
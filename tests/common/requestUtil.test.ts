@@ -63,4 +63,20 @@ describe('Unit tests for requestUtil', () => {
       expect(result?.authorization).toBe('ApiKey myApiKey');
     });
   });
+
+  describe('buildAuthHeader - bearer, PKI and SigV4', () => {
+    it('should return Bearer authorization header when token is provided', () => {
+      const result = buildAuthHeader('bearer', undefined, undefined, undefined, 'svc-token');
+      expect(result?.authorization).toBe('Bearer svc-token');
+    });
+
+    it('should return undefined when bearer token is missing', () => {
+      expect(buildAuthHeader('bearer', 'user', 'pass')).toBeUndefined();
+    });
+
+    it('should leave PKI and SigV4 auth to the backend', () => {
+      expect(buildAuthHeader('pki', 'user', 'pass')).toBeUndefined();
+      expect(buildAuthHeader('awsSigV4', 'user', 'pass')).toBeUndefined();
+    });
+  });
 });